@@ -1,10 +1,12 @@
 #![forbid(missing_docs)]
 //! Binary `hc-dna` command executable.
 
-use holochain_types::prelude::{AppManifest, DnaManifest, ValidatedDnaManifest};
+use holochain_types::prelude::{
+    AppManifest, DnaManifest, DnaModifiersOpt, ValidatedDnaManifest, YamlProperties,
+};
 use holochain_types::web_app::WebAppManifest;
 use holochain_util::ffs;
-use mr_bundle::{Location, Manifest};
+use mr_bundle::{Location, Manifest, PublisherSecret};
 use std::path::Path;
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -51,6 +53,11 @@ pub enum HcDnaBundle {
         /// provided working directory.
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// Sign the bundle with the publisher secret key in this file,
+        /// as created by `hc app keygen`
+        #[structopt(long)]
+        sign: Option<PathBuf>,
     },
 
     /// Unpack parts of the `.dna` bundle file into a specific directory.
@@ -83,6 +90,43 @@ pub enum HcDnaBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print the manifest of a `.dna` bundle, the size and SHA-256 hash of
+    /// each bundled resource, and the DNA it defines: its hash, modifiers and zomes,
+    /// with the names of the entry types and the number of link types of each
+    /// integrity zome.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc dna inspect ./some/dir/my-bundle.dna
+    Inspect {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+
+        /// Compute DNA hashes with this network seed, as when installing
+        /// with a network seed
+        #[structopt(long)]
+        network_seed: Option<String>,
+
+        /// Compute DNA hashes with the properties in this YAML file, as when
+        /// installing with properties
+        #[structopt(long)]
+        properties: Option<PathBuf>,
+    },
+
+    /// Compare two `.dna` bundles, reporting which changes alter DNA hashes
+    /// and which only change coordinator zomes.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc dna diff ./old.dna ./new.dna
+    Diff {
+        /// The path to the old bundle
+        old: std::path::PathBuf,
+
+        /// The path to the new bundle
+        new: std::path::PathBuf,
+    },
 }
 
 /// Work with Holochain hApp bundles
@@ -116,6 +160,11 @@ pub enum HcAppBundle {
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
 
+        /// Sign the bundle with the publisher secret key in this file,
+        /// as created by `hc app keygen`
+        #[structopt(long)]
+        sign: Option<PathBuf>,
+
         /// Also run `dna pack` on all DNAs manifests
         /// that have their location bundled
         #[structopt(short, long)]
@@ -152,6 +201,58 @@ pub enum HcAppBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Generate a new publisher key for signing bundles.
+    ///
+    /// The secret key is written to the given file, which must not already
+    /// exist, and the public key is printed. Conductors can be configured to
+    /// only install hApps signed by the publisher keys listed in their
+    /// `trusted_publishers`.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc app keygen ./publisher.key
+    ///
+    /// $ hc app pack ./some/directory/foo --sign ./publisher.key
+    Keygen {
+        /// The path of the secret key file to create
+        path: PathBuf,
+    },
+
+    /// Print the manifest of a `.happ` bundle, the size and SHA-256 hash of
+    /// each bundled resource, and the DNA of each role which the bundle includes.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc app inspect ./some/dir/my-bundle.happ
+    Inspect {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+
+        /// Compute DNA hashes with this network seed, as when installing
+        /// with a network seed
+        #[structopt(long)]
+        network_seed: Option<String>,
+
+        /// Compute DNA hashes with the properties in this YAML file, as when
+        /// installing with properties
+        #[structopt(long)]
+        properties: Option<PathBuf>,
+    },
+
+    /// Compare two `.happ` bundles, reporting which changes alter DNA hashes
+    /// and which only change coordinator zomes.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc app diff ./old.happ ./new.happ
+    Diff {
+        /// The path to the old bundle
+        old: std::path::PathBuf,
+
+        /// The path to the new bundle
+        new: std::path::PathBuf,
+    },
 }
 
 /// Work with Holochain Web-hApp bundles
@@ -185,6 +286,11 @@ pub enum HcWebAppBundle {
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
 
+        /// Sign the bundle with the publisher secret key in this file,
+        /// as created by `hc app keygen`
+        #[structopt(long)]
+        sign: Option<PathBuf>,
+
         /// Also run `app pack` and `dna pack` on all App and DNAs manifests
         /// that have their location bundled
         #[structopt(short, long)]
@@ -221,6 +327,41 @@ pub enum HcWebAppBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print the manifest of a `.webhapp` bundle, the size and SHA-256 hash of
+    /// each bundled resource, and the hApp bundle inside it.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc web-app inspect ./some/dir/my-bundle.webhapp
+    Inspect {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+
+        /// Compute DNA hashes with this network seed, as when installing
+        /// with a network seed
+        #[structopt(long)]
+        network_seed: Option<String>,
+
+        /// Compute DNA hashes with the properties in this YAML file, as when
+        /// installing with properties
+        #[structopt(long)]
+        properties: Option<PathBuf>,
+    },
+
+    /// Compare two `.webhapp` bundles, reporting which changes alter DNA hashes
+    /// and which only change coordinator zomes.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc web-app diff ./old.webhapp ./new.webhapp
+    Diff {
+        /// The path to the old bundle
+        old: std::path::PathBuf,
+
+        /// The path to the new bundle
+        new: std::path::PathBuf,
+    },
 }
 
 impl HcDnaBundle {
@@ -230,10 +371,16 @@ impl HcDnaBundle {
             Self::Init { path } => {
                 crate::init::init_dna(path).await?;
             }
-            Self::Pack { path, output } => {
+            Self::Pack { path, output, sign } => {
                 let name = get_dna_name(&path).await?;
-                let (bundle_path, _) =
-                    crate::packing::pack::<ValidatedDnaManifest>(&path, output, name).await?;
+                let publisher = read_publisher_secret(sign).await?;
+                let (bundle_path, _) = crate::packing::pack::<ValidatedDnaManifest>(
+                    &path,
+                    output,
+                    name,
+                    publisher.as_ref(),
+                )
+                .await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
             }
             Self::Unpack {
@@ -262,6 +409,18 @@ impl HcDnaBundle {
                 };
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Inspect {
+                path,
+                network_seed,
+                properties,
+            } => {
+                let modifiers = inspect_modifiers(network_seed, properties)?;
+                let inspection = crate::inspect::inspect_dna(&path, modifiers).await?;
+                print!("{}", serde_yaml::to_string(&inspection)?);
+            }
+            Self::Diff { old, new } => {
+                print!("{}", crate::inspect::diff_dna(&old, &new).await?);
+            }
         }
         Ok(())
     }
@@ -277,16 +436,19 @@ impl HcAppBundle {
             Self::Pack {
                 path,
                 output,
+                sign,
                 recursive,
             } => {
                 let name = get_app_name(&path).await?;
+                let publisher = read_publisher_secret(sign).await?;
 
                 if recursive {
                     app_pack_recursive(&path).await?;
                 }
 
                 let (bundle_path, _) =
-                    crate::packing::pack::<AppManifest>(&path, output, name).await?;
+                    crate::packing::pack::<AppManifest>(&path, output, name, publisher.as_ref())
+                        .await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
             }
             Self::Unpack {
@@ -310,6 +472,24 @@ impl HcAppBundle {
                 };
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Keygen { path } => {
+                let secret = PublisherSecret::generate()?;
+                write_publisher_secret(&path, &secret)?;
+                println!("Wrote secret key to {}", path.to_string_lossy());
+                println!("Publisher key: {}", secret.public_key());
+            }
+            Self::Inspect {
+                path,
+                network_seed,
+                properties,
+            } => {
+                let modifiers = inspect_modifiers(network_seed, properties)?;
+                let inspection = crate::inspect::inspect_app(&path, modifiers).await?;
+                print!("{}", serde_yaml::to_string(&inspection)?);
+            }
+            Self::Diff { old, new } => {
+                print!("{}", crate::inspect::diff_app(&old, &new).await?);
+            }
         }
         Ok(())
     }
@@ -325,16 +505,19 @@ impl HcWebAppBundle {
             Self::Pack {
                 path,
                 output,
+                sign,
                 recursive,
             } => {
                 let name = get_web_app_name(&path).await?;
+                let publisher = read_publisher_secret(sign).await?;
 
                 if recursive {
                     web_app_pack_recursive(&path).await?;
                 }
 
                 let (bundle_path, _) =
-                    crate::packing::pack::<WebAppManifest>(&path, output, name).await?;
+                    crate::packing::pack::<WebAppManifest>(&path, output, name, publisher.as_ref())
+                        .await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
             }
             Self::Unpack {
@@ -363,11 +546,70 @@ impl HcWebAppBundle {
                 };
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Inspect {
+                path,
+                network_seed,
+                properties,
+            } => {
+                let modifiers = inspect_modifiers(network_seed, properties)?;
+                let inspection = crate::inspect::inspect_web_app(&path, modifiers).await?;
+                print!("{}", serde_yaml::to_string(&inspection)?);
+            }
+            Self::Diff { old, new } => {
+                print!("{}", crate::inspect::diff_web_app(&old, &new).await?);
+            }
         }
         Ok(())
     }
 }
 
+async fn read_publisher_secret(path: Option<PathBuf>) -> HcBundleResult<Option<PublisherSecret>> {
+    match path {
+        Some(path) => Ok(Some(PublisherSecret::from_hex(
+            &ffs::read_to_string(&path).await?,
+        )?)),
+        None => Ok(None),
+    }
+}
+
+/// The DNA modifiers to override when inspecting a bundle, with the
+/// properties read from a YAML file.
+fn inspect_modifiers(
+    network_seed: Option<String>,
+    properties: Option<PathBuf>,
+) -> HcBundleResult<DnaModifiersOpt<YamlProperties>> {
+    let properties = match properties {
+        Some(path) => Some(YamlProperties::new(serde_yaml::from_str(
+            &std::fs::read_to_string(path)?,
+        )?)),
+        None => None,
+    };
+    Ok(DnaModifiersOpt {
+        network_seed,
+        properties,
+        ..DnaModifiersOpt::none()
+    })
+}
+
+/// Write a new secret key file, readable only by its owner, failing rather
+/// than overwriting an existing file.
+fn write_publisher_secret(path: &Path, secret: &PublisherSecret) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = match options.open(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            anyhow::bail!("Refusing to overwrite existing file {}", path.display())
+        }
+        result => result?,
+    };
+    file.write_all(secret.to_hex().as_bytes())?;
+    Ok(())
+}
+
 async fn get_dna_name(manifest_path: &Path) -> HcBundleResult<String> {
     let manifest_path = manifest_path.to_path_buf();
     let manifest_path = manifest_path.join(ValidatedDnaManifest::path());
@@ -416,6 +658,7 @@ async fn web_app_pack_recursive(web_app_workdir_path: &PathBuf) -> anyhow::Resul
         HcAppBundle::Pack {
             path: ffs::canonicalize(app_workdir_location).await?,
             output: None,
+            sign: None,
             recursive: true,
         }
         .run()
@@ -441,6 +684,7 @@ async fn app_pack_recursive(app_workdir_path: &PathBuf) -> anyhow::Result<()> {
         HcDnaBundle::Pack {
             path: dna_workdir_location,
             output: None,
+            sign: None,
         }
         .run()
         .await?;
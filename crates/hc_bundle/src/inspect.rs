@@ -0,0 +1,777 @@
+#![forbid(missing_docs)]
+
+//! Defines the CLI commands for inspecting and comparing DNA, hApp and Web-hApp bundles
+
+use crate::error::HcBundleResult;
+use holochain_types::prelude::*;
+use holochain_types::web_app::{WebAppBundle, WebAppManifest};
+use mr_bundle::{Bundle, Location, Manifest, PublisherKey, ResourceHash};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The most wasm operations an `entry_defs` callback may run while
+/// its zome is inspected.
+const ENTRY_DEFS_FUEL: u64 = 10_000_000;
+
+/// A resource included in a bundle
+#[derive(Debug, serde::Serialize)]
+pub struct ResourceSummary {
+    /// The path of the resource within the bundle
+    pub path: PathBuf,
+    /// The size of the resource in bytes
+    pub size: usize,
+    /// The SHA-256 hash of the resource
+    pub sha256: ResourceHash,
+}
+
+/// Whether a bundle is signed, and by whom
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureSummary {
+    /// The bundle is not signed
+    Unsigned,
+    /// The bundle is signed and its signature matches its contents
+    Valid {
+        /// The publisher who signed the bundle
+        publisher: PublisherKey,
+    },
+    /// The bundle claims to be signed but its signature doesn't match
+    /// its contents, so it would not be installed
+    Invalid {
+        /// The publisher the bundle claims to be signed by
+        publisher: PublisherKey,
+        /// Why the signature is invalid
+        error: String,
+    },
+}
+
+/// What is common to all bundles: the manifest, signature and resources
+#[derive(Debug, serde::Serialize)]
+pub struct BundleSummary<M> {
+    /// The bundle's manifest
+    pub manifest: M,
+    /// The publisher's signature of the bundle
+    pub signature: SignatureSummary,
+    /// The resources included in the bundle
+    pub resources: Vec<ResourceSummary>,
+}
+
+impl<M: Manifest> BundleSummary<M> {
+    fn new(bundle: &Bundle<M>) -> Self {
+        let signature = match (bundle.signature(), bundle.verify_signature()) {
+            (None, _) => SignatureSummary::Unsigned,
+            (Some(signature), Ok(_)) => SignatureSummary::Valid {
+                publisher: signature.publisher,
+            },
+            (Some(signature), Err(e)) => SignatureSummary::Invalid {
+                publisher: signature.publisher,
+                error: e.to_string(),
+            },
+        };
+        Self {
+            manifest: bundle.manifest().clone(),
+            signature,
+            resources: bundle
+                .bundled_resources()
+                .iter()
+                .map(|(path, bytes)| ResourceSummary {
+                    path: path.clone(),
+                    size: bytes.len(),
+                    sha256: ResourceHash::of(bytes.inner()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A zome of a DNA
+#[derive(Debug, serde::Serialize)]
+pub struct ZomeSummary {
+    /// The name of the zome
+    pub name: String,
+    /// The hash of the zome's wasm
+    pub wasm_hash: String,
+    /// The integrity zomes which a coordinator zome depends on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    /// The names of the entry types an integrity zome defines, in order,
+    /// if its `entry_defs` callback can be run without the host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_types: Option<Vec<String>>,
+    /// The number of link types an integrity zome defines, if it can be
+    /// read from the wasm. Link type names are not compiled into the wasm.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_types: Option<u8>,
+}
+
+/// A DNA as it would be installed, with its modifiers applied
+#[derive(Debug, serde::Serialize)]
+pub struct DnaSummary {
+    /// The hash of the DNA
+    pub dna_hash: String,
+    /// The name of the DNA
+    pub name: String,
+    /// The network seed of the DNA
+    pub network_seed: String,
+    /// The properties of the DNA
+    pub properties: serde_yaml::Value,
+    /// The origin time of the DNA
+    pub origin_time: String,
+    /// The integrity zomes, in order
+    pub integrity_zomes: Vec<ZomeSummary>,
+    /// The coordinator zomes
+    pub coordinator_zomes: Vec<ZomeSummary>,
+}
+
+impl DnaSummary {
+    fn new(dna_file: &DnaFile) -> HcBundleResult<Self> {
+        let dna_def = dna_file.dna_def();
+        let integrity_zomes = dna_def
+            .integrity_zomes
+            .iter()
+            .map(|(name, def)| -> HcBundleResult<ZomeSummary> {
+                let wasm = dna_file.get_wasm_for_zome(name)?;
+                Ok(ZomeSummary {
+                    name: name.to_string(),
+                    wasm_hash: def.wasm_hash(name).map_err(DnaError::from)?.to_string(),
+                    dependencies: vec![],
+                    entry_types: entry_type_names(&wasm.code),
+                    link_types: const_export(&wasm.code, "__num_link_types"),
+                })
+            })
+            .collect::<HcBundleResult<_>>()?;
+        let coordinator_zomes = dna_def
+            .coordinator_zomes
+            .iter()
+            .map(|(name, def)| -> HcBundleResult<ZomeSummary> {
+                Ok(ZomeSummary {
+                    name: name.to_string(),
+                    wasm_hash: def.wasm_hash(name).map_err(DnaError::from)?.to_string(),
+                    dependencies: def
+                        .as_any_zome_def()
+                        .dependencies()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                    entry_types: None,
+                    link_types: None,
+                })
+            })
+            .collect::<HcBundleResult<_>>()?;
+        Ok(Self {
+            dna_hash: dna_file.dna_hash().to_string(),
+            name: dna_def.name.clone(),
+            network_seed: dna_def.modifiers.network_seed.clone(),
+            properties: YamlProperties::try_from(dna_def.modifiers.properties.clone())
+                .map(YamlProperties::into_inner)
+                .unwrap_or(serde_yaml::Value::Null),
+            origin_time: dna_def.modifiers.origin_time.to_string(),
+            integrity_zomes,
+            coordinator_zomes,
+        })
+    }
+}
+
+/// The contents of a DNA bundle
+#[derive(Debug, serde::Serialize)]
+pub struct DnaInspection {
+    /// The manifest and resources of the bundle
+    #[serde(flatten)]
+    pub bundle: BundleSummary<ValidatedDnaManifest>,
+    /// The DNA the bundle defines
+    pub dna: DnaSummary,
+}
+
+/// The contents of a hApp bundle
+#[derive(Debug, serde::Serialize)]
+pub struct AppInspection {
+    /// The manifest and resources of the bundle
+    #[serde(flatten)]
+    pub bundle: BundleSummary<AppManifest>,
+    /// The DNAs of the roles whose DNA is included in the bundle
+    pub roles: BTreeMap<RoleName, DnaSummary>,
+    /// The roles whose DNA is at a URL or path outside the bundle, which is
+    /// not read when inspecting, so their DNA hashes are unknown
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub unresolved_roles: BTreeMap<RoleName, Location>,
+}
+
+/// The contents of a Web-hApp bundle
+#[derive(Debug, serde::Serialize)]
+pub struct WebAppInspection {
+    /// The manifest and resources of the bundle
+    #[serde(flatten)]
+    pub bundle: BundleSummary<WebAppManifest>,
+    /// The hApp included in the bundle
+    pub happ: AppInspection,
+}
+
+/// Describe a DNA bundle file. The modifiers which are set override
+/// those in the manifest, as when installing.
+pub async fn inspect_dna(
+    path: &Path,
+    modifiers: DnaModifiersOpt<YamlProperties>,
+) -> HcBundleResult<DnaInspection> {
+    let bundle = DnaBundle::read_from_file(path).await?;
+    let summary = BundleSummary::<ValidatedDnaManifest>::new(&bundle);
+    let dna_file = dna_file(bundle, modifiers).await?;
+    Ok(DnaInspection {
+        bundle: summary,
+        dna: DnaSummary::new(&dna_file)?,
+    })
+}
+
+/// Describe a hApp bundle file. The modifiers which are set override
+/// those given for every role, as when installing.
+pub async fn inspect_app(
+    path: &Path,
+    modifiers: DnaModifiersOpt<YamlProperties>,
+) -> HcBundleResult<AppInspection> {
+    let bundle = AppBundle::from(Bundle::<AppManifest>::read_from_file(path).await?);
+    app_inspection(&bundle, &modifiers).await
+}
+
+/// Describe a Web-hApp bundle file, including the hApp inside it
+pub async fn inspect_web_app(
+    path: &Path,
+    modifiers: DnaModifiersOpt<YamlProperties>,
+) -> HcBundleResult<WebAppInspection> {
+    let bundle = WebAppBundle::from(Bundle::<WebAppManifest>::read_from_file(path).await?);
+    Ok(WebAppInspection {
+        bundle: BundleSummary::<WebAppManifest>::new(&bundle),
+        happ: app_inspection(&bundle.happ_bundle().await?, &modifiers).await?,
+    })
+}
+
+async fn app_inspection(
+    bundle: &AppBundle,
+    modifiers: &DnaModifiersOpt<YamlProperties>,
+) -> HcBundleResult<AppInspection> {
+    let (dna_files, unresolved_roles) = role_dna_files(bundle, modifiers).await?;
+    Ok(AppInspection {
+        bundle: BundleSummary::<AppManifest>::new(bundle),
+        roles: dna_files
+            .iter()
+            .map(|(role_name, dna_file)| -> HcBundleResult<_> {
+                Ok((role_name.clone(), DnaSummary::new(dna_file)?))
+            })
+            .collect::<HcBundleResult<_>>()?,
+        unresolved_roles,
+    })
+}
+
+async fn dna_file(
+    bundle: DnaBundle,
+    modifiers: DnaModifiersOpt<YamlProperties>,
+) -> HcBundleResult<DnaFile> {
+    Ok(bundle.into_dna_file(modifiers.serialized()?).await?.0)
+}
+
+/// The DNAs of the roles whose DNA is included in the bundle, with the
+/// modifiers given for each role and then the overrides which are set,
+/// along with the locations of the roles whose DNA is not included.
+async fn role_dna_files(
+    bundle: &AppBundle,
+    overrides: &DnaModifiersOpt<YamlProperties>,
+) -> HcBundleResult<(BTreeMap<RoleName, DnaFile>, BTreeMap<RoleName, Location>)> {
+    let mut dna_files = BTreeMap::new();
+    let mut unresolved = BTreeMap::new();
+    for role in bundle.manifest().app_roles() {
+        match &role.dna.location {
+            Some(location @ Location::Bundled(_)) => {
+                let mut modifiers = role.dna.modifiers.clone();
+                if let Some(network_seed) = &overrides.network_seed {
+                    modifiers.network_seed = Some(network_seed.clone());
+                }
+                if let Some(properties) = &overrides.properties {
+                    modifiers.properties = Some(properties.clone());
+                }
+                if let Some(origin_time) = overrides.origin_time {
+                    modifiers.origin_time = Some(origin_time);
+                }
+                if let Some(quantum_time) = overrides.quantum_time {
+                    modifiers.quantum_time = Some(quantum_time);
+                }
+                let dna_bundle = DnaBundle::decode(bundle.resolve(location).await?.inner())?;
+                let (dna_file, _) = dna_bundle.into_dna_file(modifiers.serialized()?).await?;
+                dna_files.insert(role.name, dna_file);
+            }
+            Some(location) => {
+                unresolved.insert(role.name, location.clone());
+            }
+            None => {}
+        }
+    }
+    Ok((dna_files, unresolved))
+}
+
+/// Run the `entry_defs` callback of an integrity zome's wasm to get the
+/// names of its entry types, in order.
+///
+/// No host functions are available to the callback, and it may only run a
+/// limited number of operations. None is returned if it fails.
+fn entry_type_names(wasm: &[u8]) -> Option<Vec<String>> {
+    use holochain_wasmer_host::prelude::*;
+    use wasmer_middlewares::Metering;
+
+    let metering = Arc::new(Metering::new(ENTRY_DEFS_FUEL, |_: &wasmparser::Operator| 1));
+    let mut compiler = Cranelift::default();
+    compiler.canonicalize_nans(true).push_middleware(metering);
+    let store = Store::new(&Universal::new(compiler).engine());
+    let module = Module::new(&store, wasm).ok()?;
+
+    let mut namespaces: BTreeMap<String, Exports> = BTreeMap::new();
+    for import in module.imports() {
+        if let ExternType::Function(ty) = import.ty() {
+            let function = Function::new(&store, ty.clone(), |_| {
+                Err(RuntimeError::new(
+                    "Host functions are not available while inspecting a bundle",
+                ))
+            });
+            namespaces
+                .entry(import.module().to_string())
+                .or_insert_with(Exports::new)
+                .insert(import.name(), function);
+        }
+    }
+    let mut imports = ImportObject::new();
+    for (name, namespace) in namespaces {
+        imports.register(name, namespace);
+    }
+    let instance = Instance::new(&module, &imports).ok()?;
+    // A zome without entry types need not have the callback.
+    if instance.exports.get_function("entry_defs").is_err() {
+        return Some(vec![]);
+    }
+
+    let output: ExternIO = holochain_wasmer_host::guest::call(
+        Arc::new(Mutex::new(instance)),
+        "entry_defs",
+        ExternIO::encode(()).ok()?,
+    )
+    .ok()?;
+    let EntryDefsCallbackResult::Defs(defs) = output.decode().ok()?;
+    Some(
+        defs.0
+            .into_iter()
+            .map(|def| match def.id {
+                EntryDefId::App(name) => name.to_string(),
+                EntryDefId::CapClaim => "CapClaim".to_string(),
+                EntryDefId::CapGrant => "CapGrant".to_string(),
+            })
+            .collect(),
+    )
+}
+
+/// Read the value returned by an exported wasm function which takes no
+/// arguments and only returns a constant, such as the `__num_entry_types`
+/// function written by `hdk_entry_defs`.
+///
+/// The function is not run. Its body is read, following calls to other such
+/// functions, and None is returned if it is anything other than a constant.
+fn const_export(wasm: &[u8], name: &str) -> Option<u8> {
+    use wasmparser::{ExternalKind, ImportSectionEntryType, Operator, Parser, Payload};
+
+    let mut imported_functions = 0;
+    let mut export = None;
+    let mut bodies = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.ok()? {
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    if let ImportSectionEntryType::Function(_) = import.ok()?.ty {
+                        imported_functions += 1;
+                    }
+                }
+            }
+            Payload::ExportSection(exports) => {
+                for e in exports {
+                    let e = e.ok()?;
+                    if e.field == name && e.kind == ExternalKind::Function {
+                        export = Some(e.index);
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => bodies.push(body),
+            _ => {}
+        }
+    }
+
+    // Function indexes count the imported functions before those defined
+    // in the code section.
+    let mut index = export?;
+    // Bound the calls followed, in case of recursion.
+    for _ in 0..8 {
+        let body = bodies.get(index.checked_sub(imported_functions)? as usize)?;
+        let mut reader = body.get_operators_reader().ok()?;
+        let mut operators = Vec::new();
+        while !reader.eof() {
+            match reader.read().ok()? {
+                Operator::Nop | Operator::Return | Operator::End => {}
+                op => operators.push(op),
+            }
+        }
+        match operators[..] {
+            [Operator::I32Const { value }] => return u8::try_from(value).ok(),
+            [Operator::Call { function_index }] => index = function_index,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// The differences between two versions of a DNA
+#[derive(Debug)]
+pub struct DnaDiff {
+    /// The hash of the old DNA
+    pub old_hash: DnaHash,
+    /// The hash of the new DNA
+    pub new_hash: DnaHash,
+    /// Changes which alter the DNA hash, so that the new DNA forms a
+    /// separate network from the old one
+    pub integrity: Vec<String>,
+    /// Changes to the coordinator zomes only, which can be applied to
+    /// installed cells of the old DNA
+    pub coordinator: Vec<String>,
+}
+
+impl DnaDiff {
+    /// Compare two DNAs
+    pub fn new(old: &DnaFile, new: &DnaFile) -> Self {
+        let (old_def, new_def) = (old.dna_def(), new.dna_def());
+        let mut integrity = Vec::new();
+        let mut changed = |what: &str, old: String, new: String| {
+            if old != new {
+                integrity.push(format!("{} changed from {} to {}", what, old, new));
+            }
+        };
+        changed("name", old_def.name.clone(), new_def.name.clone());
+        let (old_mods, new_mods) = (&old_def.modifiers, &new_def.modifiers);
+        changed(
+            "network seed",
+            format!("{:?}", old_mods.network_seed),
+            format!("{:?}", new_mods.network_seed),
+        );
+        changed(
+            "origin time",
+            old_mods.origin_time.to_string(),
+            new_mods.origin_time.to_string(),
+        );
+        changed(
+            "quantum time",
+            format!("{:?}", old_mods.quantum_time),
+            format!("{:?}", new_mods.quantum_time),
+        );
+        if old_mods.properties != new_mods.properties {
+            integrity.push("properties changed".to_string());
+        }
+
+        diff_zomes(
+            "integrity",
+            zome_defs(&old_def.integrity_zomes, |d| d.as_any_zome_def()),
+            zome_defs(&new_def.integrity_zomes, |d| d.as_any_zome_def()),
+            &mut integrity,
+        );
+        let mut coordinator = Vec::new();
+        diff_zomes(
+            "coordinator",
+            zome_defs(&old_def.coordinator_zomes, |d| d.as_any_zome_def()),
+            zome_defs(&new_def.coordinator_zomes, |d| d.as_any_zome_def()),
+            &mut coordinator,
+        );
+
+        Self {
+            old_hash: old.dna_hash().clone(),
+            new_hash: new.dna_hash().clone(),
+            integrity,
+            coordinator,
+        }
+    }
+
+    /// Whether the DNAs are the same
+    pub fn is_empty(&self) -> bool {
+        self.integrity.is_empty() && self.coordinator.is_empty()
+    }
+}
+
+fn zome_defs<'a, D>(
+    zomes: &'a [(ZomeName, D)],
+    as_any: impl Fn(&D) -> &ZomeDef,
+) -> Vec<(&'a ZomeName, &'a ZomeDef)> {
+    zomes
+        .iter()
+        .map(|(name, def)| (name, as_any(def)))
+        .collect()
+}
+
+fn diff_zomes(
+    kind: &str,
+    old: Vec<(&ZomeName, &ZomeDef)>,
+    new: Vec<(&ZomeName, &ZomeDef)>,
+    changes: &mut Vec<String>,
+) {
+    let old_map: BTreeMap<_, _> = old.iter().cloned().collect();
+    let new_map: BTreeMap<_, _> = new.iter().cloned().collect();
+    for (name, old_def) in &old {
+        match new_map.get(name) {
+            None => changes.push(format!("{} zome '{}' removed", kind, name)),
+            Some(new_def) => {
+                if old_def.wasm_hash(name).ok() != new_def.wasm_hash(name).ok() {
+                    changes.push(format!("{} zome '{}' wasm changed", kind, name));
+                }
+                if old_def.dependencies() != new_def.dependencies() {
+                    changes.push(format!(
+                        "{} zome '{}' dependencies changed from {:?} to {:?}",
+                        kind,
+                        name,
+                        old_def.dependencies(),
+                        new_def.dependencies()
+                    ));
+                }
+            }
+        }
+    }
+    for (name, _) in &new {
+        if !old_map.contains_key(name) {
+            changes.push(format!("{} zome '{}' added", kind, name));
+        }
+    }
+
+    // The position of an integrity zome is its zome index, which entries and
+    // links are stored under.
+    if kept_order(&old, &new_map) != kept_order(&new, &old_map) {
+        changes.push(format!("{} zomes reordered", kind));
+    }
+}
+
+/// The names of the zomes which are also in the other DNA, in order
+fn kept_order<'a>(
+    zomes: &[(&'a ZomeName, &ZomeDef)],
+    other: &BTreeMap<&ZomeName, &ZomeDef>,
+) -> Vec<&'a ZomeName> {
+    zomes
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| other.contains_key(name))
+        .collect()
+}
+
+impl fmt::Display for DnaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.old_hash == self.new_hash {
+            writeln!(f, "DNA hash unchanged: {}", self.old_hash)?;
+        } else {
+            writeln!(
+                f,
+                "DNA hash changed: {} -> {}",
+                self.old_hash, self.new_hash
+            )?;
+        }
+        if self.is_empty() {
+            writeln!(f, "No changes")?;
+        }
+        if !self.integrity.is_empty() {
+            writeln!(f, "Changes which alter the DNA hash:")?;
+            for change in &self.integrity {
+                writeln!(f, "  - {}", change)?;
+            }
+        }
+        if !self.coordinator.is_empty() {
+            writeln!(f, "Coordinator-only changes:")?;
+            for change in &self.coordinator {
+                writeln!(f, "  - {}", change)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The differences between two versions of a hApp
+#[derive(Debug)]
+pub struct AppDiff {
+    /// Changes to the hApp manifest
+    pub manifest: Vec<String>,
+    /// The differences between the DNAs of roles which both hApps include
+    pub roles: BTreeMap<RoleName, DnaDiff>,
+}
+
+impl AppDiff {
+    async fn new(old: &AppBundle, new: &AppBundle) -> HcBundleResult<Self> {
+        let (old_manifest, new_manifest) = (old.manifest(), new.manifest());
+        let mut manifest = Vec::new();
+        if old_manifest.app_name() != new_manifest.app_name() {
+            manifest.push(format!(
+                "name changed from {} to {}",
+                old_manifest.app_name(),
+                new_manifest.app_name()
+            ));
+        }
+
+        let old_roles: BTreeMap<_, _> = old_manifest
+            .app_roles()
+            .into_iter()
+            .map(|role| (role.name.clone(), role))
+            .collect();
+        let new_roles: BTreeMap<_, _> = new_manifest
+            .app_roles()
+            .into_iter()
+            .map(|role| (role.name.clone(), role))
+            .collect();
+        for (name, old_role) in &old_roles {
+            let new_role = match new_roles.get(name) {
+                Some(new_role) => new_role,
+                None => {
+                    manifest.push(format!("role '{}' removed", name));
+                    continue;
+                }
+            };
+            let (old_dna, new_dna) = (&old_role.dna, &new_role.dna);
+            if old_role.provisioning != new_role.provisioning {
+                manifest.push(format!("role '{}' provisioning changed", name));
+            }
+            if old_dna.location != new_dna.location {
+                manifest.push(format!("role '{}' DNA location changed", name));
+            }
+            if old_dna.modifiers != new_dna.modifiers {
+                manifest.push(format!("role '{}' DNA modifiers changed", name));
+            }
+            if old_dna.version != new_dna.version {
+                manifest.push(format!("role '{}' DNA version changed", name));
+            }
+            if old_dna.clone_limit != new_dna.clone_limit {
+                manifest.push(format!(
+                    "role '{}' clone limit changed from {} to {}",
+                    name, old_dna.clone_limit, new_dna.clone_limit
+                ));
+            }
+        }
+        for name in new_roles.keys() {
+            if !old_roles.contains_key(name) {
+                manifest.push(format!("role '{}' added", name));
+            }
+        }
+        if manifest.is_empty() && old_manifest != new_manifest {
+            manifest.push("other manifest fields changed".to_string());
+        }
+
+        let (old_dnas, _) = role_dna_files(old, &DnaModifiersOpt::none()).await?;
+        let (mut new_dnas, _) = role_dna_files(new, &DnaModifiersOpt::none()).await?;
+        let roles = old_dnas
+            .iter()
+            .filter_map(|(name, old_dna)| {
+                let new_dna = new_dnas.remove(name)?;
+                Some((name.clone(), DnaDiff::new(old_dna, &new_dna)))
+            })
+            .collect();
+
+        Ok(Self { manifest, roles })
+    }
+
+    /// Whether the hApps are the same
+    pub fn is_empty(&self) -> bool {
+        self.manifest.is_empty() && self.roles.values().all(DnaDiff::is_empty)
+    }
+}
+
+impl fmt::Display for AppDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.manifest.is_empty() {
+            writeln!(f, "Manifest changes:")?;
+            for change in &self.manifest {
+                writeln!(f, "  - {}", change)?;
+            }
+        }
+        for (name, diff) in &self.roles {
+            writeln!(f, "Role '{}':", name)?;
+            for line in diff.to_string().lines() {
+                writeln!(f, "  {}", line)?;
+            }
+        }
+        if self.manifest.is_empty() && self.roles.is_empty() {
+            writeln!(f, "No changes")?;
+        }
+        Ok(())
+    }
+}
+
+/// The differences between two versions of a Web-hApp
+#[derive(Debug)]
+pub struct WebAppDiff {
+    /// Whether the Web UI changed
+    pub ui_changed: bool,
+    /// The differences between the hApps
+    pub happ: AppDiff,
+}
+
+impl fmt::Display for WebAppDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ui_changed {
+            writeln!(f, "Web UI changed")?;
+        } else {
+            writeln!(f, "Web UI unchanged")?;
+        }
+        writeln!(f, "hApp:")?;
+        for line in self.happ.to_string().lines() {
+            writeln!(f, "  {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two DNA bundle files
+pub async fn diff_dna(old: &Path, new: &Path) -> HcBundleResult<DnaDiff> {
+    let old = dna_file(
+        DnaBundle::read_from_file(old).await?,
+        DnaModifiersOpt::none(),
+    )
+    .await?;
+    let new = dna_file(
+        DnaBundle::read_from_file(new).await?,
+        DnaModifiersOpt::none(),
+    )
+    .await?;
+    Ok(DnaDiff::new(&old, &new))
+}
+
+/// Compare two hApp bundle files
+pub async fn diff_app(old: &Path, new: &Path) -> HcBundleResult<AppDiff> {
+    let old = AppBundle::from(Bundle::<AppManifest>::read_from_file(old).await?);
+    let new = AppBundle::from(Bundle::<AppManifest>::read_from_file(new).await?);
+    AppDiff::new(&old, &new).await
+}
+
+/// Compare two Web-hApp bundle files
+pub async fn diff_web_app(old: &Path, new: &Path) -> HcBundleResult<WebAppDiff> {
+    let old = WebAppBundle::from(Bundle::<WebAppManifest>::read_from_file(old).await?);
+    let new = WebAppBundle::from(Bundle::<WebAppManifest>::read_from_file(new).await?);
+    Ok(WebAppDiff {
+        ui_changed: old.web_ui_zip_bytes().await? != new.web_ui_zip_bytes().await?,
+        happ: AppDiff::new(&old.happ_bundle().await?, &new.happ_bundle().await?).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_const_exports() {
+        let name = b"__num_entry_types";
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        // One function type, () -> i32, and one function of that type
+        wasm.extend([0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f]);
+        wasm.extend([0x03, 0x02, 0x01, 0x00]);
+        // The function is exported by name
+        wasm.extend([0x07, name.len() as u8 + 4, 0x01, name.len() as u8]);
+        wasm.extend(name);
+        wasm.extend([0x00, 0x00]);
+        // Its body is `i32.const 3`
+        wasm.extend([0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, 0x03, 0x0b]);
+
+        assert_eq!(const_export(&wasm, "__num_entry_types"), Some(3));
+        assert_eq!(const_export(&wasm, "__num_link_types"), None);
+        assert_eq!(const_export(b"not wasm", "__num_entry_types"), None);
+    }
+}
@@ -5,7 +5,7 @@
 use crate::error::{HcBundleError, HcBundleResult};
 use holochain_util::ffs;
 use mr_bundle::RawBundle;
-use mr_bundle::{Bundle, Manifest};
+use mr_bundle::{Bundle, Manifest, PublisherSecret};
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -71,15 +71,20 @@ fn bundle_path_to_dir(path: &Path, extension: &'static str) -> HcBundleResult<Pa
 }
 
 /// Pack a directory containing a DNA manifest into a DnaBundle, returning
-/// the path to which the bundle file was written
+/// the path to which the bundle file was written.
+/// The bundle is signed if a publisher secret is given.
 pub async fn pack<M: Manifest>(
     dir_path: &std::path::Path,
     target_path: Option<PathBuf>,
     name: String,
+    publisher: Option<&PublisherSecret>,
 ) -> HcBundleResult<(PathBuf, Bundle<M>)> {
     let dir_path = ffs::canonicalize(dir_path).await?;
     let manifest_path = dir_path.join(M::path());
-    let bundle: Bundle<M> = Bundle::pack_yaml(&manifest_path).await?;
+    let mut bundle: Bundle<M> = Bundle::pack_yaml(&manifest_path).await?;
+    if let Some(publisher) = publisher {
+        bundle = bundle.signed(publisher)?;
+    }
     let target_path = match target_path {
         Some(target_path) => {
             if target_path.is_dir() {
@@ -145,7 +150,7 @@ integrity:
         std::fs::write(tmpdir.path().join("zome-3.wasm"), &[7, 8, 9]).unwrap();
 
         let (bundle_path, bundle) =
-            pack::<ValidatedDnaManifest>(&dir, None, "test_dna".to_string())
+            pack::<ValidatedDnaManifest>(&dir, None, "test_dna".to_string(), None)
                 .await
                 .unwrap();
         // Ensure the bundle path was generated as expected
@@ -182,6 +187,7 @@ integrity:
             &dir,
             Some(dir.parent().unwrap().to_path_buf()),
             "test_dna".to_string(),
+            None,
         )
         .await
         .unwrap();
@@ -200,7 +206,23 @@ integrity:
         assert_eq!(dir.read_dir().unwrap().collect::<Vec<_>>().len(), 3);
 
         // Ensure that we get the same bundle after the roundtrip
-        let (_, bundle2) = pack(&dir, None, "test_dna".to_string()).await.unwrap();
+        let (_, bundle2) = pack(&dir, None, "test_dna".to_string(), None)
+            .await
+            .unwrap();
         assert_eq!(bundle, bundle2);
+
+        // A signed bundle is written with its signature
+        let publisher = PublisherSecret::generate().unwrap();
+        let (bundle_path, _) =
+            pack::<ValidatedDnaManifest>(&dir, None, "test_dna".to_string(), Some(&publisher))
+                .await
+                .unwrap();
+        let signed = Bundle::<ValidatedDnaManifest>::read_from_file(&bundle_path)
+            .await
+            .unwrap();
+        assert_eq!(
+            signed.verify_signature().unwrap(),
+            Some(publisher.public_key())
+        );
     }
 }
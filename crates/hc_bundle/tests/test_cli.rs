@@ -256,3 +256,66 @@ async fn test_multi_integrity() {
     );
     assert_eq!(*dna.dna_def(), expected);
 }
+
+#[tokio::test]
+async fn test_inspect_and_diff() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let pack_dna = |path: &str, name: &str| {
+        let output = tmpdir.path().join(name);
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        let cmd = cmd.args(&["pack", path, "-o"]).arg(&output);
+        cmd.assert().success();
+        output
+    };
+    let integrity = pack_dna("tests/fixtures/my-app/dnas/dna3", "integrity.dna");
+    let coordinator = pack_dna("tests/fixtures/my-app/dnas/dna4", "coordinator.dna");
+
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    let output = cmd.arg("inspect").arg(&coordinator).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("dna_hash:"));
+    assert!(stdout.contains("network_seed: 00000000-0000-0000-0000-000000000000"));
+    assert!(stdout.contains("name: zome2"));
+    assert!(stdout.contains("signature: unsigned"));
+
+    // Adding a coordinator zome leaves the DNA hash alone
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    let output = cmd.arg("diff").arg(&integrity).arg(&coordinator).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("DNA hash unchanged"));
+    assert!(stdout.contains("coordinator zome 'zome2' added"));
+    assert!(!stdout.contains("Changes which alter the DNA hash"));
+
+    // The network seed can be overridden, as when installing
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    let output = cmd
+        .args(&["inspect", "--network-seed", "other"])
+        .arg(&integrity)
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("network_seed: other"));
+
+    // So can the properties, which change the DNA hash
+    let dna_hash = |stdout: &str| {
+        stdout
+            .lines()
+            .find(|line| line.trim_start().starts_with("dna_hash:"))
+            .unwrap()
+            .to_string()
+    };
+    let properties = tmpdir.path().join("properties.yaml");
+    std::fs::write(&properties, "foo: bar\n").unwrap();
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    let without = cmd.arg("inspect").arg(&integrity).unwrap();
+    let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+    let with = cmd
+        .arg("inspect")
+        .arg("--properties")
+        .arg(&properties)
+        .arg(&integrity)
+        .unwrap();
+    assert_ne!(
+        dna_hash(&String::from_utf8(without.stdout).unwrap()),
+        dna_hash(&String::from_utf8(with.stdout).unwrap())
+    );
+}
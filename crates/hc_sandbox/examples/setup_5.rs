@@ -46,7 +46,9 @@ async fn main() -> anyhow::Result<()> {
         // Generate a new agent key using the simple calls api.
         let agent_key = hc_sandbox::calls::generate_agent_pub_key(&mut cmd).await?;
 
-        let bundle = AppBundleSource::Path(happ.clone()).resolve().await?;
+        let bundle = AppBundleSource::Path(happ.clone())
+            .resolve(&Default::default())
+            .await?;
 
         // Create the raw InstallAppPayload request.
         let payload = InstallAppPayload {
@@ -24,6 +24,8 @@ use holochain_types::prelude::YamlProperties;
 use holochain_types::prelude::{AgentPubKey, AppBundleSource};
 use holochain_types::prelude::{CellId, InstallAppPayload};
 use holochain_types::prelude::{DnaSource, NetworkSeed};
+use holochain_types::prelude::{ZomeFnInfo, ZomeName};
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 use crate::cmds::Existing;
@@ -69,6 +71,7 @@ pub enum AdminRequestCli {
     ListCells,
     /// Calls AdminRequest::ListApps.
     ListApps(ListApps),
+    ListZomeFns(ListZomeFns),
     EnableApp(EnableApp),
     DisableApp(DisableApp),
     DumpState(DumpState),
@@ -203,6 +206,16 @@ pub struct ListApps {
     pub status: Option<AppStatusFilter>,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ListZomeFunctions
+/// and prints the zome functions and callbacks
+/// of every zome in a Dna.
+pub struct ListZomeFns {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The hash of the Dna to list the zome functions of.
+    pub dna: DnaHash,
+}
+
 #[doc(hidden)]
 pub async fn call(holochain_path: &Path, req: Call) -> anyhow::Result<()> {
     let Call {
@@ -296,6 +309,24 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             let apps = list_apps(cmd, args).await?;
             msg!("List Apps: {:?}", apps);
         }
+        AdminRequestCli::ListZomeFns(args) => {
+            use std::fmt::Write;
+            let zome_fns = list_zome_fns(cmd, args).await?;
+            let mut out = String::new();
+            for (zome_name, fns) in zome_fns {
+                writeln!(out, "{}:", zome_name)?;
+                for f in fns {
+                    let kind = if f.is_callback { " (callback)" } else { "" };
+                    match f.signature {
+                        Some(sig) => {
+                            writeln!(out, "  {}({}) -> {}{}", f.name, sig.input, sig.output, kind)?
+                        }
+                        None => writeln!(out, "  {}{}", f.name, kind)?,
+                    }
+                }
+            }
+            msg!("Zome Functions:\n{}", out);
+        }
         AdminRequestCli::EnableApp(args) => {
             let app_id = args.app_id.clone();
             enable_app(cmd, args).await?;
@@ -511,6 +542,17 @@ pub async fn list_apps(cmd: &mut CmdRunner, args: ListApps) -> anyhow::Result<Ve
     Ok(expect_match!(resp => AdminResponse::AppsListed, "Failed to list apps"))
 }
 
+/// Calls [`AdminRequest::ListZomeFunctions`].
+pub async fn list_zome_fns(
+    cmd: &mut CmdRunner,
+    args: ListZomeFns,
+) -> anyhow::Result<BTreeMap<ZomeName, Vec<ZomeFnInfo>>> {
+    let resp = cmd
+        .command(AdminRequest::ListZomeFunctions { dna_hash: args.dna })
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::ZomeFunctionsListed, "Failed to list zome functions"))
+}
+
 /// Calls [`AdminRequest::EnableApp`] and activates the installed app.
 pub async fn enable_app(cmd: &mut CmdRunner, args: EnableApp) -> anyhow::Result<()> {
     let resp = cmd
@@ -105,8 +105,7 @@ pub fn create_cap_claim(cap_claim_entry: CapClaimEntry) -> ExternResult<ActionHa
 /// access. Special care is required for Unrestricted grants as several may apply to a single
 /// extern at one time, or may apply in addition to a stricter grant. In this case, revoking a
 /// stricter grant, or failing to revoke all Unrestricted grants will leave the function open.
-//
-// @todo administration functions to query active grants
+/// Use [`capability_grants`] to find the grants which are currently in effect.
 ///
 /// There is an apparent "chicken or the egg" situation where [`CapGrant`] are required for remote
 /// agents to call externs, so how does an agent request a grant in the first place?
@@ -154,6 +153,52 @@ where
     delete(delete_input)
 }
 
+/// Revoke the capability grant which requires the given secret.
+///
+/// Looks up the live grant with this secret as per [`capability_grants`] and deletes it as per
+/// [`delete_cap_grant`]. Returns the hash of the delete action, or `None` if no live grant
+/// requires the secret, e.g. because it was already revoked.
+pub fn revoke_cap_grant(secret: CapSecret) -> ExternResult<Option<ActionHash>> {
+    let grant = capability_grants(CapGrantQuery::default())?
+        .into_iter()
+        .find(|info| info.cap_grant.access.secret() == Some(&secret));
+    match grant {
+        Some(info) => delete_cap_grant(info.action_hash).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// List the capability grants of the local source chain which are currently in effect.
+///
+/// Grants which have been deleted, or replaced by an update, are not listed.
+/// This includes grants committed earlier in the current zome call.
+/// The query can restrict the listed grants to those with a given tag.
+///
+/// Each [`CapGrantInfo`] carries the hash of the action which committed the grant, which is
+/// what [`update_cap_grant`] and [`delete_cap_grant`] expect.
+pub fn capability_grants(query: CapGrantQuery) -> ExternResult<Vec<CapGrantInfo>> {
+    HDK.with(|h| h.borrow().capability_grants(query))
+}
+
+/// List the capability claims of the local source chain.
+///
+/// Deleted claims are not listed. The query can restrict the listed claims to those with
+/// a given tag and/or issued by a given grantor.
+///
+/// There is no guarantee that a listed claim is still honoured by its grantor, see
+/// [`create_cap_claim`].
+pub fn capability_claims(query: CapClaimQuery) -> ExternResult<Vec<CapClaim>> {
+    HDK.with(|h| h.borrow().capability_claims(query))
+}
+
+/// Get the capability which authorized the current zome call, along with the agent who made it.
+///
+/// Calls made by the chain author are authorized by [`CapGrant::ChainAuthor`], all others by
+/// the [`CapGrant::RemoteAgent`] grant which matched the call.
+pub fn capability_info() -> ExternResult<CapabilityInfo> {
+    HDK.with(|h| h.borrow().capability_info(()))
+}
+
 /// Generate secrets for capability grants.
 ///
 /// Wraps the [`random_bytes`] HDK function with appropriate parameters set.
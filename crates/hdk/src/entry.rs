@@ -294,6 +294,31 @@ pub fn get_details<H: Into<AnyDhtHash>>(
         .unwrap())
 }
 
+/// Get the compact metadata the DHT authorities hold for any [`AnyDhtHash`].
+///
+/// Unlike [`get_details`] this does not return any actions or entries, only
+/// the hashes and timestamps of the actions that created, updated, deleted or
+/// linked to the hash, along with the [`EntryDhtStatus`] of an entry.
+/// The [`MetadataRequest`] controls which of these are included in the
+/// returned [`MetadataSet`].
+///
+/// If the agent is an authority for the hash the metadata is read locally,
+/// otherwise the responses of the authorities are merged.
+/// Returns [`None`] if no authority could be reached.
+pub fn get_meta<H: Into<AnyDhtHash>>(
+    hash: H,
+    request: MetadataRequest,
+) -> ExternResult<Option<MetadataSet>> {
+    Ok(HDK
+        .with(|h| {
+            h.borrow()
+                .get_meta(vec![GetMetaInput::new(hash.into(), request)])
+        })?
+        .into_iter()
+        .next()
+        .unwrap())
+}
+
 /// Implements a whole lot of sane defaults for a struct or enum that should behave as an entry.
 /// All the entry def fields are available as dedicated methods on the type and matching From impls
 /// are provided for each. This allows for both Foo::entry_def() and EntryDef::from(Foo::new())
@@ -36,6 +36,8 @@ pub trait HdkT: HdiT {
     fn delete(&self, delete_input: DeleteInput) -> ExternResult<ActionHash>;
     fn get(&self, get_input: Vec<GetInput>) -> ExternResult<Vec<Option<Record>>>;
     fn get_details(&self, get_input: Vec<GetInput>) -> ExternResult<Vec<Option<Details>>>;
+    fn get_meta(&self, get_meta_input: Vec<GetMetaInput>)
+        -> ExternResult<Vec<Option<MetadataSet>>>;
     // CounterSigning
     fn accept_countersigning_preflight_request(
         &self,
@@ -44,6 +46,10 @@ pub trait HdkT: HdiT {
     // Info
     fn agent_info(&self, agent_info_input: ()) -> ExternResult<AgentInfo>;
     fn call_info(&self, call_info_input: ()) -> ExternResult<CallInfo>;
+    // Capability
+    fn capability_grants(&self, query: CapGrantQuery) -> ExternResult<Vec<CapGrantInfo>>;
+    fn capability_claims(&self, query: CapClaimQuery) -> ExternResult<Vec<CapClaim>>;
+    fn capability_info(&self, capability_info_input: ()) -> ExternResult<CapabilityInfo>;
     // Link
     fn create_link(&self, create_link_input: CreateLinkInput) -> ExternResult<ActionHash>;
     fn delete_link(&self, delete_link_input: DeleteLinkInput) -> ExternResult<ActionHash>;
@@ -106,6 +112,10 @@ mockall::mock! {
         fn delete(&self, delete_input: DeleteInput) -> ExternResult<ActionHash>;
         fn get(&self, get_input: Vec<GetInput>) -> ExternResult<Vec<Option<Record>>>;
         fn get_details(&self, get_input: Vec<GetInput>) -> ExternResult<Vec<Option<Details>>>;
+        fn get_meta(
+            &self,
+            get_meta_input: Vec<GetMetaInput>,
+        ) -> ExternResult<Vec<Option<MetadataSet>>>;
         // CounterSigning
         fn accept_countersigning_preflight_request(
             &self,
@@ -114,6 +124,10 @@ mockall::mock! {
         // Info
         fn agent_info(&self, agent_info_input: ()) -> ExternResult<AgentInfo>;
         fn call_info(&self, call_info_input: ()) -> ExternResult<CallInfo>;
+        // Capability
+        fn capability_grants(&self, query: CapGrantQuery) -> ExternResult<Vec<CapGrantInfo>>;
+        fn capability_claims(&self, query: CapClaimQuery) -> ExternResult<Vec<CapClaim>>;
+        fn capability_info(&self, capability_info_input: ()) -> ExternResult<CapabilityInfo>;
         // Link
         fn create_link(&self, create_link_input: CreateLinkInput) -> ExternResult<ActionHash>;
         fn delete_link(&self, delete_link_input: DeleteLinkInput) -> ExternResult<ActionHash>;
@@ -297,6 +311,9 @@ impl HdkT for ErrHdk {
     fn get_details(&self, _: Vec<GetInput>) -> ExternResult<Vec<Option<Details>>> {
         Self::err()
     }
+    fn get_meta(&self, _: Vec<GetMetaInput>) -> ExternResult<Vec<Option<MetadataSet>>> {
+        Self::err()
+    }
     // CounterSigning
     fn accept_countersigning_preflight_request(
         &self,
@@ -310,6 +327,16 @@ impl HdkT for ErrHdk {
     fn call_info(&self, _: ()) -> ExternResult<CallInfo> {
         Self::err()
     }
+    // Capability
+    fn capability_grants(&self, _: CapGrantQuery) -> ExternResult<Vec<CapGrantInfo>> {
+        Self::err()
+    }
+    fn capability_claims(&self, _: CapClaimQuery) -> ExternResult<Vec<CapClaim>> {
+        Self::err()
+    }
+    fn capability_info(&self, _: ()) -> ExternResult<CapabilityInfo> {
+        Self::err()
+    }
     // Link
     fn create_link(&self, _: CreateLinkInput) -> ExternResult<ActionHash> {
         Self::err()
@@ -488,6 +515,12 @@ impl HdkT for HostHdk {
     fn get_details(&self, get_inputs: Vec<GetInput>) -> ExternResult<Vec<Option<Details>>> {
         host_call::<Vec<GetInput>, Vec<Option<Details>>>(__hc__get_details_1, get_inputs)
     }
+    fn get_meta(
+        &self,
+        get_meta_inputs: Vec<GetMetaInput>,
+    ) -> ExternResult<Vec<Option<MetadataSet>>> {
+        host_call::<Vec<GetMetaInput>, Vec<Option<MetadataSet>>>(__hc__get_meta_1, get_meta_inputs)
+    }
     // CounterSigning
     fn accept_countersigning_preflight_request(
         &self,
@@ -504,6 +537,15 @@ impl HdkT for HostHdk {
     fn call_info(&self, _: ()) -> ExternResult<CallInfo> {
         host_call::<(), CallInfo>(__hc__call_info_1, ())
     }
+    fn capability_grants(&self, query: CapGrantQuery) -> ExternResult<Vec<CapGrantInfo>> {
+        host_call::<CapGrantQuery, Vec<CapGrantInfo>>(__hc__capability_grants_1, query)
+    }
+    fn capability_claims(&self, query: CapClaimQuery) -> ExternResult<Vec<CapClaim>> {
+        host_call::<CapClaimQuery, Vec<CapClaim>>(__hc__capability_claims_1, query)
+    }
+    fn capability_info(&self, _: ()) -> ExternResult<CapabilityInfo> {
+        host_call::<(), CapabilityInfo>(__hc__capability_info_1, ())
+    }
     fn create_link(&self, create_link_input: CreateLinkInput) -> ExternResult<ActionHash> {
         host_call::<CreateLinkInput, ActionHash>(__hc__create_link_1, create_link_input)
     }
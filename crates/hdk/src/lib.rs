@@ -602,9 +602,8 @@ pub mod trace;
 
 /// Everything related to inspecting or responding to time.
 ///
-/// Currently only fetching the host's opinion of the local time is supported.
-//
-// @todo implement scheduled execution and sleeping
+/// Fetching the host's opinion of the local time, scheduling functions and
+/// sleeping are supported.
 pub mod time;
 
 /// Generate cryptographic strength random data
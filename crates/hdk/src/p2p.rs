@@ -9,7 +9,9 @@ use crate::prelude::*;
 /// - to_cell: The cell you want to call (If None will call the current cell).
 /// - zome_name: The name of the zome you want to call.
 /// - fn_name: The name of the function in the zome you are calling.
-/// - cap_secret: The capability secret if required.
+/// - cap_secret: The capability secret if required. When calling a
+///   [`CallTargetCell::Dependency`] and no secret is given, the conductor
+///   uses the secret it obtained when the dependency was granted at install.
 /// - payload: The arguments to the function you are calling.
 pub fn call<I, Z>(
     to_cell: CallTargetCell,
@@ -1,7 +1,11 @@
+pub use crate::capability::capability_claims;
+pub use crate::capability::capability_grants;
+pub use crate::capability::capability_info;
 pub use crate::capability::create_cap_claim;
 pub use crate::capability::create_cap_grant;
 pub use crate::capability::delete_cap_grant;
 pub use crate::capability::generate_cap_secret;
+pub use crate::capability::revoke_cap_grant;
 pub use crate::capability::update_cap_grant;
 pub use crate::chain::get_agent_activity;
 pub use crate::chain::must_get_agent_activity;
@@ -20,6 +24,7 @@ pub use crate::entry::delete;
 pub use crate::entry::delete_entry;
 pub use crate::entry::get;
 pub use crate::entry::get_details;
+pub use crate::entry::get_meta;
 pub use crate::entry::must_get_action;
 pub use crate::entry::must_get_entry;
 pub use crate::entry::must_get_valid_record;
@@ -127,6 +132,7 @@ macro_rules! holochain_externs {
             capability_info:1,
             get:1,
             get_details:1,
+            get_meta:1,
             get_links:1,
             get_link_details:1,
             get_agent_activity:1,
@@ -136,7 +136,14 @@ pub fn schedule(scheduled_fn: &str) -> ExternResult<()> {
     HDK.with(|h| h.borrow().schedule(String::from(scheduled_fn)))
 }
 
-/// @todo Not implemented
+/// Pause the current zome call for at least the given duration.
+///
+/// This is useful for zome functions that poll for something to appear, e.g.
+/// waiting for a countersigning partner's entry, without busy-looping on `get`.
+///
+/// - Sleeping is non-deterministic so it is not allowed during validation.
+/// - A zome call cannot sleep past the time its caller set for it to expire,
+///   and will fail immediately rather than start such a sleep.
 pub fn sleep(wake_after: std::time::Duration) -> ExternResult<()> {
     HDK.with(|h| h.borrow().sleep(wake_after))
 }
@@ -173,21 +173,55 @@ pub fn hdk_extern(attrs: TokenStream, item: TokenStream) -> TokenStream {
 
     let internal_fn_ident = external_fn_ident.clone();
 
+    let signature = extern_signature(&external_fn_ident, &input_type, &output_type);
+
     if attrs.to_string() == "infallible" {
         (quote::quote! {
             map_extern_infallible!(#external_fn_ident, #internal_fn_ident, #input_type, #output_type);
+            #signature
             #item_fn
         })
         .into()
     } else {
         (quote::quote! {
             map_extern!(#external_fn_ident, #internal_fn_ident, #input_type, #output_type);
+            #signature
             #item_fn
         })
         .into()
     }
 }
 
+/// Write the signature of an extern into a custom section of the wasm
+/// so the host can list it along with the exported functions.
+fn extern_signature(
+    ident: &syn::Ident,
+    input_type: &syn::Type,
+    output_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    use heck::ToShoutySnakeCase;
+    let signature = format!(
+        "{}\t{}\t{}\n",
+        ident,
+        util::type_to_string(input_type),
+        util::type_to_string(output_type)
+    );
+    let len = signature.len();
+    let bytes = syn::LitByteStr::new(signature.as_bytes(), proc_macro2::Span::call_site());
+    let section = holochain_integrity_types::zome::EXTERN_SIGNATURES_SECTION;
+    let static_ident = quote::format_ident!(
+        "__HC_EXTERN_SIGNATURE_{}",
+        ident.to_string().to_shouty_snake_case()
+    );
+    quote::quote! {
+        #[cfg(target_arch = "wasm32")]
+        #[doc(hidden)]
+        #[used]
+        #[link_section = #section]
+        static #static_ident: [u8; #len] = *#bytes;
+    }
+}
+
 #[proc_macro_error]
 #[proc_macro_derive(EntryDefRegistration, attributes(entry_def))]
 pub fn derive_entry_def_registration(input: TokenStream) -> TokenStream {
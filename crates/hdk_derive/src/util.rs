@@ -67,3 +67,23 @@ pub fn get_single_tuple_variant<'a>(ident: &syn::Ident, fields: &'a syn::Fields)
             }),
     }
 }
+
+/// Render a type the way it would be written in source, without the spaces
+/// `to_string` puts between every token.
+pub fn type_to_string(ty: &syn::Type) -> String {
+    let tokens = quote::quote!(#ty).to_string();
+    let chars: Vec<char> = tokens.chars().collect();
+    let is_word = |c: Option<&char>| c.map_or(false, |c| c.is_alphanumeric() || *c == '_');
+    let mut out = String::with_capacity(tokens.len());
+    for (i, c) in chars.iter().enumerate() {
+        // Only keep the spaces between two words, e.g. `dyn Trait`.
+        if *c == ' ' && !(is_word(chars.get(i.wrapping_sub(1))) && is_word(chars.get(i + 1))) {
+            continue;
+        }
+        out.push(*c);
+        if *c == ',' {
+            out.push(' ');
+        }
+    }
+    out
+}
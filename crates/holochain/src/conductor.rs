@@ -35,6 +35,7 @@ pub mod paths;
 pub mod ribosome_store;
 pub mod space;
 pub mod state;
+pub mod zome_call_quota;
 
 pub use cell::error::CellError;
 pub use cell::Cell;
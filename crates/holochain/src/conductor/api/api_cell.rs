@@ -207,6 +207,14 @@ pub trait CellConductorReadHandleT: Send + Sync {
         role_name: &RoleName,
     ) -> ConductorResult<Option<CellId>>;
 
+    /// Resolve a dependency declared by the app the given cell id is in to
+    /// the depended-upon cell id, along with the secret granted for calling it.
+    async fn find_cell_with_app_dependency(
+        &self,
+        cell_id: &CellId,
+        dependency_name: &str,
+    ) -> ConductorResult<Option<(CellId, Option<CapSecret>)>>;
+
     /// Expose block functionality to zomes.
     async fn block(&self, block: Block) -> ConductorResult<()>;
 
@@ -264,6 +272,16 @@ impl CellConductorReadHandleT for CellConductorApi {
             .await
     }
 
+    async fn find_cell_with_app_dependency(
+        &self,
+        cell_id: &CellId,
+        dependency_name: &str,
+    ) -> ConductorResult<Option<(CellId, Option<CapSecret>)>> {
+        self.conductor_handle
+            .find_cell_with_app_dependency(cell_id, dependency_name)
+            .await
+    }
+
     async fn block(&self, block: Block) -> ConductorResult<()> {
         self.conductor_handle.block(block).await
     }
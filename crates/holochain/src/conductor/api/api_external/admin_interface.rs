@@ -123,6 +123,10 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .ok_or(ConductorApiError::DnaMissing(*dna_hash))?;
                 Ok(AdminResponse::DnaDefinitionReturned(dna_def))
             }
+            ListZomeFunctions { dna_hash } => {
+                let zome_fns = self.conductor_handle.list_zome_functions(&dna_hash)?;
+                Ok(AdminResponse::ZomeFunctionsListed(zome_fns))
+            }
             UpdateCoordinators(payload) => {
                 let UpdateCoordinatorsPayload { dna_hash, source } = *payload;
                 let (coordinator_zomes, wasms) = match source {
@@ -282,6 +286,18 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::CloneCellDeleted)
             }
+            ProvisionDeferredCell(payload) => {
+                let cell = self
+                    .conductor_handle
+                    .clone()
+                    .provision_deferred_cell(*payload)
+                    .await?;
+                Ok(AdminResponse::DeferredCellProvisioned(cell))
+            }
+            ClearWasmCache => {
+                self.conductor_handle.clear_wasm_cache()?;
+                Ok(AdminResponse::WasmCacheCleared)
+            }
         }
     }
 }
@@ -448,6 +464,60 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_zome_functions() -> Result<()> {
+        observability::test_run().ok();
+        let env_dir = test_db_dir();
+        let handle = Conductor::builder().test(env_dir.path(), &[]).await?;
+
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+        let dna = fake_dna_zomes(
+            &Uuid::new_v4().to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        handle.register_dna(dna).await?;
+
+        let response = admin_api
+            .handle_admin_request(AdminRequest::ListZomeFunctions { dna_hash })
+            .await;
+        let zome_fns = match response {
+            AdminResponse::ZomeFunctionsListed(zome_fns) => zome_fns,
+            r => panic!("unexpected response {:?}", r),
+        };
+        let zome_name: ZomeName = TestWasm::Foo.into();
+        let foo_fns = zome_fns.get(&zome_name).unwrap();
+        assert!(foo_fns.contains(&ZomeFnInfo {
+            name: "foo".into(),
+            is_callback: false,
+            signature: Some(ZomeFnSignature {
+                input: "()".into(),
+                output: "ExternResult<String>".into(),
+            }),
+        }));
+        assert!(foo_fns.contains(&ZomeFnInfo {
+            name: "init".into(),
+            is_callback: true,
+            signature: Some(ZomeFnSignature {
+                input: "()".into(),
+                output: "ExternResult<InitCallbackResult>".into(),
+            }),
+        }));
+
+        // An unknown dna is an error.
+        let response = admin_api
+            .handle_admin_request(AdminRequest::ListZomeFunctions {
+                dna_hash: DnaHash::from_raw_32(vec![0; 32]),
+            })
+            .await;
+        assert_matches!(response, AdminResponse::Error(_));
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), handle.shutdown())
+            .await
+            .ok();
+        Ok(())
+    }
+
     // @todo fix test by using new InstallApp call
     // #[tokio::test(flavor = "multi_thread")]
     // async fn install_list_dna_app() {
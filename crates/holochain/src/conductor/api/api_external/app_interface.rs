@@ -64,7 +64,7 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     .await?,
             )),
             AppRequest::CallZome(call) => {
-                match self.conductor_handle.call_zome(*call.clone()).await? {
+                match self.conductor_handle.call_zome_from_app_interface(*call.clone()).await? {
                     Ok(ZomeCallResponse::Ok(output)) => Ok(AppResponse::ZomeCalled(Box::new(output))),
                     Ok(ZomeCallResponse::Unauthorized(zome_call_authorization, _, zome_name, fn_name, _)) => Ok(AppResponse::Error(
                         ExternalApiWireError::ZomeCallUnauthorized(format!(
@@ -108,6 +108,14 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     .await?;
                 Ok(AppResponse::CloneCellEnabled(enabled_cell))
             }
+            AppRequest::ProvisionDeferredCell(payload) => {
+                let cell = self
+                    .conductor_handle
+                    .clone()
+                    .provision_deferred_cell(*payload)
+                    .await?;
+                Ok(AppResponse::DeferredCellProvisioned(cell))
+            }
             AppRequest::NetworkInfo(payload) => {
                 let info = self.conductor_handle.network_info(&payload.dnas).await?;
                 Ok(AppResponse::NetworkInfo(info))
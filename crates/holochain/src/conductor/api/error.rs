@@ -1,6 +1,7 @@
 //! Errors occurring during a [`CellConductorApi`](super::CellConductorApi) or [`InterfaceApi`](super::InterfaceApi) call
 use crate::conductor::error::ConductorError;
 use crate::conductor::interface::error::InterfaceError;
+use crate::conductor::zome_call_quota::ZomeCallQuotaError;
 use crate::conductor::CellError;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::workflow::error::WorkflowError;
@@ -107,6 +108,10 @@ pub enum ConductorApiError {
     #[error(transparent)]
     ChcError(#[from] ChcError),
 
+    /// A zome call was rejected or abandoned because of a quota
+    #[error(transparent)]
+    ZomeCallQuotaExceeded(#[from] ZomeCallQuotaError),
+
     /// Other
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -146,6 +151,10 @@ impl From<ConductorApiError> for ExternalApiWireError {
     fn from(err: ConductorApiError) -> Self {
         match err {
             ConductorApiError::DnaReadError(e) => ExternalApiWireError::DnaReadError(e),
+            ConductorApiError::ZomeCallQuotaExceeded(e)
+            | ConductorApiError::CellError(CellError::ZomeCallQuotaExceeded(e)) => {
+                ExternalApiWireError::ZomeCallQuotaExceeded(e.to_string())
+            }
             e => ExternalApiWireError::internal(e),
         }
     }
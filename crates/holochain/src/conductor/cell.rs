@@ -7,6 +7,7 @@
 use super::api::CellConductorHandle;
 use super::interface::SignalBroadcaster;
 use super::space::Space;
+use super::zome_call_quota::ZomeCallQuotaKey;
 use super::ConductorHandle;
 use crate::conductor::api::CellConductorApi;
 use crate::conductor::cell::error::CellResult;
@@ -14,6 +15,7 @@ use crate::core::queue_consumer::spawn_queue_consumer_tasks;
 use crate::core::queue_consumer::InitialQueueTriggers;
 use crate::core::queue_consumer::QueueTriggers;
 use crate::core::ribosome::guest_callback::init::InitResult;
+use crate::core::ribosome::guest_callback::post_commit::send_post_commit_attempt;
 use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::ZomeCallInvocation;
 use crate::core::workflow::call_zome_workflow;
@@ -32,6 +34,7 @@ use futures::future::FutureExt;
 use hash_type::AnyDht;
 use holo_hash::*;
 use holochain_cascade::authority;
+use holochain_conductor_api::conductor::ZomeCallQuota;
 use holochain_conductor_api::ZomeCall;
 use holochain_p2p::event::CountersigningSessionNegotiationMessage;
 use holochain_p2p::ChcImpl;
@@ -370,7 +373,7 @@ impl Cell {
     #[instrument(skip(self, evt))]
     /// Entry point for incoming messages from the network that need to be handled
     pub async fn handle_holochain_p2p_event(
-        &self,
+        self: &Arc<Self>,
         evt: holochain_p2p::event::HolochainP2pEvent,
     ) -> CellResult<()> {
         use holochain_p2p::event::HolochainP2pEvent::*;
@@ -648,14 +651,17 @@ impl Cell {
             .map_err(Into::into)
     }
 
-    #[instrument(skip(self, _dht_hash, _options))]
+    #[instrument(skip(self, options))]
     /// a remote node is asking us for metadata
     async fn handle_get_meta(
         &self,
-        _dht_hash: holo_hash::AnyDhtHash,
-        _options: holochain_p2p::event::GetMetaOptions,
+        dht_hash: holo_hash::AnyDhtHash,
+        options: holochain_p2p::event::GetMetaOptions,
     ) -> CellResult<MetadataSet> {
-        unimplemented!()
+        let db = self.space.dht_db.clone();
+        authority::handle_get_meta(db.into(), dht_hash, options)
+            .await
+            .map_err(Into::into)
     }
 
     #[instrument(skip(self, options))]
@@ -792,7 +798,7 @@ impl Cell {
     #[allow(clippy::too_many_arguments)]
     /// a remote agent is attempting a "call_remote" on this cell.
     async fn handle_call_remote(
-        &self,
+        self: &Arc<Self>,
         from_agent: AgentPubKey,
         from_signature: Signature,
         zome_name: ZomeName,
@@ -813,10 +819,20 @@ impl Cell {
             nonce,
             expires_at,
         };
-        // double ? because
-        // - ConductorApiResult
+        let permit = self
+            .conductor_handle
+            .zome_call_quotas()
+            .admit(ZomeCallQuotaKey::RemoteAgent(invocation.provenance.clone()))?;
+        let quota = permit.quota().clone();
+        let cell = self.clone();
+        // triple ? because
+        // - ZomeCallQuotaError
+        // - CellResult
         // - ZomeCallResult
-        Ok(self.call_zome(invocation, None).await??.try_into()?)
+        Ok(permit
+            .run(async move { cell.call_zome_with_quota(invocation, &quota).await })
+            .await???
+            .try_into()?)
     }
 
     /// Function called by the Conductor
@@ -825,6 +841,35 @@ impl Cell {
         &self,
         call: ZomeCall,
         workspace_lock: Option<SourceChainWorkspace>,
+    ) -> CellResult<ZomeCallResult> {
+        self.call_zome_with_limits(call, workspace_lock, self.holochain_p2p_cell.clone(), None)
+            .await
+    }
+
+    /// Call a zome function admitted under a quota, limiting the network
+    /// requests and fuel it may use accordingly.
+    pub(crate) async fn call_zome_with_quota(
+        &self,
+        call: ZomeCall,
+        quota: &ZomeCallQuota,
+    ) -> CellResult<ZomeCallResult> {
+        let network = match quota.max_network_requests_per_call {
+            Some(max_requests) => self
+                .holochain_p2p_cell
+                .clone()
+                .with_request_budget(max_requests),
+            None => self.holochain_p2p_cell.clone(),
+        };
+        self.call_zome_with_limits(call, None, network, quota.max_fuel_per_call)
+            .await
+    }
+
+    async fn call_zome_with_limits(
+        &self,
+        call: ZomeCall,
+        workspace_lock: Option<SourceChainWorkspace>,
+        network: HolochainP2pDna,
+        fuel_limit: Option<u64>,
     ) -> CellResult<ZomeCallResult> {
         // Only check if init has run if this call is not coming from
         // an already running init call.
@@ -871,10 +916,11 @@ impl Cell {
             signal_tx,
             conductor_handle,
             is_root_zome_call,
+            fuel_limit,
         };
         Ok(call_zome_workflow(
             workspace_lock,
-            self.holochain_p2p_cell.clone(),
+            network,
             keystore,
             args,
             self.queue_triggers.publish_dht_ops.clone(),
@@ -884,6 +930,43 @@ impl Cell {
         .map_err(Box::new)?)
     }
 
+    /// Run a zome's `post_commit` callback again for actions which were
+    /// already committed to this cell's chain.
+    pub(super) async fn retry_post_commit(
+        &self,
+        zome_name: &ZomeName,
+        actions: Vec<SignedActionHashed>,
+        attempt: u32,
+    ) -> CellResult<()> {
+        let keystore = self.conductor_api.keystore().clone();
+        let ribosome = self.get_ribosome()?;
+        let zome = ribosome
+            .dna_def()
+            .get_coordinator_zome(zome_name)
+            .map_err(DnaError::from)?;
+        let workspace = SourceChainWorkspace::new(
+            self.authored_db().clone(),
+            self.dht_db().clone(),
+            self.space.dht_query_cache.clone(),
+            self.cache().clone(),
+            keystore.clone(),
+            self.id.agent_pubkey().clone(),
+            Arc::new(ribosome.dna_def().as_content().clone()),
+        )
+        .await?;
+        send_post_commit_attempt(
+            &self.conductor_handle,
+            &workspace,
+            self.holochain_p2p_dna(),
+            &keystore,
+            actions,
+            zome,
+            attempt,
+        )
+        .await
+        .map_err(|e| CellError::WorkflowError(Box::new(e.into())))
+    }
+
     /// Check if each Zome's init callback has been run, and if not, run it.
     #[tracing::instrument(skip(self))]
     async fn check_or_run_zome_init(&self) -> CellResult<()> {
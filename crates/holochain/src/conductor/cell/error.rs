@@ -1,5 +1,6 @@
 use super::INIT_MUTEX_TIMEOUT_SECS;
 use crate::conductor::entry_def_store::error::EntryDefStoreError;
+use crate::conductor::zome_call_quota::ZomeCallQuotaError;
 use crate::conductor::{api::error::ConductorApiError, error::ConductorError};
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::guest_callback::init::InitResult;
@@ -79,6 +80,8 @@ pub enum CellError {
     StateMutationError(#[from] holochain_state::mutations::StateMutationError),
     #[error(transparent)]
     OneErr(#[from] one_err::OneErr),
+    #[error(transparent)]
+    ZomeCallQuotaExceeded(#[from] ZomeCallQuotaError),
 }
 
 pub type CellResult<T> = Result<T, CellError>;
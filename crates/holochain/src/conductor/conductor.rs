@@ -54,6 +54,8 @@ use super::space::Spaces;
 use super::state::AppInterfaceConfig;
 use super::state::AppInterfaceId;
 use super::state::ConductorState;
+use super::zome_call_quota::ZomeCallQuotaKey;
+use super::zome_call_quota::ZomeCallQuotas;
 use super::CellError;
 use super::{api::RealAdminInterfaceApi, manager::TaskManagerClient};
 use crate::conductor::cell::Cell;
@@ -67,6 +69,8 @@ use crate::core::queue_consumer::QueueConsumerMap;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitArgs;
 use crate::core::ribosome::guest_callback::post_commit::POST_COMMIT_CHANNEL_BOUND;
 use crate::core::ribosome::guest_callback::post_commit::POST_COMMIT_CONCURRENT_LIMIT;
+use crate::core::ribosome::module_cache::ModuleDiskCache;
+use crate::core::ribosome::module_cache::MODULE_CACHE_DIRECTORY;
 use crate::core::ribosome::RibosomeT;
 use crate::core::workflow::ZomeCallResult;
 use crate::{
@@ -79,6 +83,7 @@ use futures::future::TryFutureExt;
 use futures::stream::StreamExt;
 use holo_hash::DnaHash;
 use holochain_conductor_api::conductor::KeystoreConfig;
+use holochain_conductor_api::conductor::WasmLimits;
 use holochain_conductor_api::AppInfo;
 use holochain_conductor_api::AppStatusFilter;
 use holochain_conductor_api::ClonedCell;
@@ -107,7 +112,7 @@ use holochain_types::prelude::{test_keystore, wasm, *};
 use kitsune_p2p::agent_store::AgentInfoSigned;
 use kitsune_p2p_types::config::JOIN_NETWORK_TIMEOUT;
 use rusqlite::Transaction;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use tokio::sync::mpsc::error::SendError;
@@ -228,6 +233,12 @@ pub struct Conductor {
     post_commit: tokio::sync::mpsc::Sender<PostCommitArgs>,
 
     scheduler: Arc<parking_lot::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+
+    /// The quotas applied to zome calls from app interfaces and remote agents.
+    zome_call_quotas: ZomeCallQuotas,
+
+    /// Compiled wasm persisted across restarts.
+    module_disk_cache: Arc<ModuleDiskCache>,
 }
 
 impl Conductor {
@@ -257,6 +268,11 @@ mod startup_shutdown_impls {
             post_commit: tokio::sync::mpsc::Sender<PostCommitArgs>,
             outcome_sender: OutcomeSender,
         ) -> Self {
+            let zome_call_quotas =
+                ZomeCallQuotas::new(config.zome_call_quotas.clone().unwrap_or_default());
+            let db_dir: std::path::PathBuf = (*spaces.db_dir).clone().into();
+            let module_disk_cache =
+                Arc::new(ModuleDiskCache::open(db_dir.join(MODULE_CACHE_DIRECTORY)));
             Self {
                 spaces,
                 running_cells: RwShare::new(HashMap::new()),
@@ -272,6 +288,8 @@ mod startup_shutdown_impls {
                 keystore,
                 holochain_p2p,
                 post_commit,
+                zome_call_quotas,
+                module_disk_cache,
             }
         }
 
@@ -317,6 +335,7 @@ mod startup_shutdown_impls {
             admin_configs: Vec<AdminInterfaceConfig>,
         ) -> ConductorResult<CellStartupErrors> {
             self.load_dnas().await?;
+            self.release_interrupted_deferred_roles().await?;
 
             // Start the task manager
             self.outcomes_task.share_mut(|lock| {
@@ -497,6 +516,15 @@ mod dna_impls {
             self.ribosome_store().share_ref(|ds| ds.get_entry_def(key))
         }
 
+        /// List the zome functions and callbacks exported by every zome of a Dna
+        pub fn list_zome_functions(
+            &self,
+            hash: &DnaHash,
+        ) -> ConductorResult<BTreeMap<ZomeName, Vec<ZomeFnInfo>>> {
+            let externs = self.get_ribosome(hash)?.list_externs()?;
+            Ok(externs.as_ref().clone())
+        }
+
         /// Create a hash map of all existing DNA definitions, mapped to cell
         /// ids.
         pub fn get_dna_definitions(
@@ -504,7 +532,12 @@ mod dna_impls {
             app: &InstalledApp,
         ) -> ConductorResult<HashMap<CellId, DnaDefHashed>> {
             let mut dna_defs = HashMap::new();
-            for cell_id in app.all_cells() {
+            let deferred_cells = app
+                .roles()
+                .values()
+                .filter(|role| role.is_deferred())
+                .map(|role| role.cell_id());
+            for cell_id in app.all_cells().chain(deferred_cells) {
                 let ribosome = self.get_ribosome(cell_id.dna_hash())?;
                 let dna_def = ribosome.dna_def();
                 dna_defs.insert(cell_id.to_owned(), dna_def.to_owned());
@@ -598,7 +631,7 @@ mod dna_impls {
             // try to join all the tasks and return the list of dna files
             let wasms = wasms.into_iter().map(|(dna_def, wasms)| async move {
                 let dna_file = DnaFile::new(dna_def.into_content(), wasms).await;
-                let ribosome = RealRibosome::new(dna_file)?;
+                let ribosome = self.build_ribosome(dna_file)?;
                 ConductorResult::Ok((ribosome.dna_hash().clone(), ribosome))
             });
             let dnas = futures::future::try_join_all(wasms).await?;
@@ -718,18 +751,37 @@ mod dna_impls {
 
         /// Install a [`DnaFile`](holochain_types::dna::DnaFile) in this Conductor
         pub async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()> {
-            let ribosome = RealRibosome::new(dna)?;
+            let ribosome = self.build_ribosome(dna)?;
+            // Compile the coordinator zomes now rather than on their first
+            // call, so that they are in the disk cache for the next restart.
+            ribosome.precompile()?;
             let entry_defs = self.register_dna_wasm(ribosome.clone()).await?;
             self.register_dna_entry_defs(entry_defs);
             self.add_ribosome_to_store(ribosome);
             Ok(())
         }
+
+        /// Remove a DNA which was registered but is no longer needed.
+        /// Its wasms are kept, since other DNAs may share them.
+        pub(crate) async fn unregister_dna(&self, dna_hash: &DnaHash) -> ConductorResult<()> {
+            self.ribosome_store
+                .share_mut(|d| d.remove_ribosome(dna_hash));
+            let dna_hash = dna_hash.clone();
+            self.spaces
+                .wasm_db
+                .async_commit(move |txn| holochain_state::dna_def::delete(txn, &dna_hash))
+                .await?;
+            Ok(())
+        }
     }
 }
 
 /// Network-related methods
 mod network_impls {
+    use holochain_conductor_api::AgentPeerScore;
     use holochain_conductor_api::NetworkInfo;
+    use holochain_conductor_api::MAX_PENDING_FETCHES;
+    use holochain_p2p::AgentPubKeyExt;
     use holochain_p2p::HolochainP2pSender;
     use holochain_zome_types::block::Block;
 
@@ -789,8 +841,6 @@ mod network_impls {
         }
 
         pub(crate) async fn prune_p2p_agents_db(&self) -> ConductorResult<()> {
-            use holochain_p2p::AgentPubKeyExt;
-
             let mut space_to_agents = HashMap::new();
 
             for cell in self.running_cells.share_ref(|c| {
@@ -816,8 +866,24 @@ mod network_impls {
         ) -> ConductorResult<Vec<NetworkInfo>> {
             futures::future::join_all(dnas.iter().map(|dna| async move {
                 let d = self.holochain_p2p.get_diagnostics(dna.clone()).await?;
-                let fetch_pool_info = d.fetch_pool.info([dna.to_kitsune()].into_iter().collect());
-                ConductorResult::Ok(NetworkInfo { fetch_pool_info })
+                let spaces: HashSet<_> = [dna.to_kitsune()].into_iter().collect();
+                let fetch_pool_info = d.fetch_pool.info(spaces.clone());
+                let pending_fetches = d.fetch_pool.items(spaces, MAX_PENDING_FETCHES);
+                let peer_scores = d
+                    .metrics
+                    .read()
+                    .peer_scores()
+                    .into_iter()
+                    .map(|(agent, score)| AgentPeerScore {
+                        agent: AgentPubKey::from_kitsune(&agent),
+                        score,
+                    })
+                    .collect();
+                ConductorResult::Ok(NetworkInfo {
+                    fetch_pool_info,
+                    pending_fetches,
+                    peer_scores,
+                })
             }))
             .await
             .into_iter()
@@ -1021,6 +1087,47 @@ mod network_impls {
             Ok(cell.call_zome(call, None).await?)
         }
 
+        /// Invoke a zome function on a Cell on behalf of an app interface client.
+        /// The call is counted against the zome call quota of the app which
+        /// the Cell belongs to.
+        pub async fn call_zome_from_app_interface(
+            &self,
+            call: ZomeCall,
+        ) -> ConductorApiResult<ZomeCallResult> {
+            let cell = self.cell_by_id(&call.cell_id)?;
+            match self.quota_app_for_cell(&call.cell_id).await? {
+                Some(installed_app_id) => {
+                    let permit = self
+                        .zome_call_quotas
+                        .admit(ZomeCallQuotaKey::App(installed_app_id))?;
+                    let quota = permit.quota().clone();
+                    Ok(permit
+                        .run(async move { cell.call_zome_with_quota(call, &quota).await })
+                        .await??)
+                }
+                None => Ok(cell.call_zome(call, None).await?),
+            }
+        }
+
+        /// The running app whose zome call quota calls to this Cell count
+        /// against. A Cell shared by several apps belongs to the app which
+        /// provisioned it, if that app is running.
+        async fn quota_app_for_cell(
+            &self,
+            cell_id: &CellId,
+        ) -> ConductorResult<Option<InstalledAppId>> {
+            let state = self.get_state().await?;
+            let apps: Vec<_> = state
+                .running_apps()
+                .filter(|(_, app)| app.all_cells().any(|c| c == cell_id))
+                .collect();
+            Ok(apps
+                .iter()
+                .find(|(_, app)| app.owns_cell(cell_id))
+                .or_else(|| apps.iter().min_by_key(|(id, _)| *id))
+                .map(|(id, _)| (*id).clone()))
+        }
+
         pub(crate) async fn call_zome_with_workspace(
             &self,
             call: ZomeCall,
@@ -1035,8 +1142,19 @@ mod network_impls {
 
 /// Methods related to app installation and management
 mod app_impls {
+    use holochain_conductor_api::ProvisionedCell;
 
     use super::*;
+
+    /// A dependency of an app being installed, resolved to the cell which
+    /// the app will be granted access to.
+    struct ResolvedAppDependency {
+        name: String,
+        dependency: AppDependency,
+        cell_id: CellId,
+        functions: GrantedFunctions,
+    }
+
     impl Conductor {
         pub(crate) async fn install_app(
             self: Arc<Self>,
@@ -1061,6 +1179,24 @@ mod app_impls {
             Ok(())
         }
 
+        /// Reject a bundle whose signature is invalid, or which isn't signed
+        /// by one of the trusted publishers if the config lists any.
+        async fn check_publisher(&self, bundle: &AppBundle) -> ConductorResult<()> {
+            let publisher = bundle.verify_signature()?;
+            match &self.config.trusted_publishers {
+                Some(trusted) if !matches!(publisher, Some(key) if trusted.contains(&key)) => {
+                    Err(ConductorError::UntrustedPublisher(publisher))
+                }
+                // The signature only vouches for what the manifests pin,
+                // including the manifests of the DNAs in the bundle
+                Some(_) => match bundle.unpinned_locations_nested().await?.into_iter().next() {
+                    Some(location) => Err(ConductorError::UnpinnedLocation(location)),
+                    None => Ok(()),
+                },
+                None => Ok(()),
+            }
+        }
+
         /// Install DNAs and set up Cells as specified by an AppBundle
         pub async fn install_app_bundle(
             self: Arc<Self>,
@@ -1075,7 +1211,21 @@ mod app_impls {
             } = payload;
 
             let bundle = {
-                let original_bundle = source.resolve().await?;
+                let fetch_config = self
+                    .config
+                    .bundle_fetch
+                    .clone()
+                    .unwrap_or_else(mr_bundle::FetchConfig::from_env);
+                if let AppBundleSource::Url { url, sha256: None } = &source {
+                    tracing::warn!(%url, "Installing an app bundle from a URL which is not pinned by a sha256 hash");
+                }
+                let original_bundle = source.resolve(&fetch_config).await?;
+                self.check_publisher(&original_bundle).await?;
+                for location in original_bundle.unpinned_locations() {
+                    if let mr_bundle::Location::Url(url) = location {
+                        tracing::warn!(%url, "App bundle has a resource at a URL which is not pinned by a sha256 hash");
+                    }
+                }
                 if let Some(network_seed) = network_seed {
                     let mut manifest = original_bundle.manifest().to_owned();
                     manifest.set_network_seed(network_seed);
@@ -1087,14 +1237,25 @@ mod app_impls {
 
             let installed_app_id =
                 installed_app_id.unwrap_or_else(|| bundle.manifest().app_name().to_owned());
+            let dependency_manifests = bundle.manifest().app_dependencies();
+
+            // Existing cells may be shared with this app, and registered DNAs
+            // may be used by its disabled roles.
+            let state = self.get_state().await?;
+            let gamut = DnaGamut::new(
+                state
+                    .installed_apps()
+                    .values()
+                    .flat_map(|app| app.provisioned_cells().map(|(_, cell_id)| cell_id.clone())),
+            )
+            .with_dnas(self.list_dnas());
             let ops = bundle
-                .resolve_cells(agent_key.clone(), DnaGamut::placeholder(), membrane_proofs)
+                .resolve_cells(agent_key.clone(), gamut, membrane_proofs)
                 .await?;
 
             let cells_to_create = ops.cells_to_create();
 
             // check if cells_to_create contains a cell identical to an existing one
-            let state = self.get_state().await?;
             let all_cells: HashSet<_> = state
                 .installed_apps()
                 .values()
@@ -1109,21 +1270,333 @@ mod app_impls {
                 ));
             };
 
+            // Dependencies are resolved before anything is changed, but only
+            // granted once the app is installed, so a failed install leaves
+            // no grants behind.
+            let dependencies = self
+                .resolve_app_dependencies(&agent_key, dependency_manifests)
+                .await?;
+
             for (dna, _) in ops.dnas_to_register {
                 self.clone().register_dna(dna).await?;
             }
+            for dna in ops.dnas_to_register_without_cells {
+                self.clone().register_dna(dna).await?;
+            }
 
             crate::conductor::conductor::genesis_cells(self.clone(), cells_to_create).await?;
 
             let roles = ops.role_assignments;
-            let app = InstalledAppCommon::new(installed_app_id, agent_key, roles)?;
+            let app = InstalledAppCommon::new(installed_app_id.clone(), agent_key.clone(), roles)?
+                .with_dependencies(
+                    dependencies
+                        .iter()
+                        .map(|d| (d.name.clone(), d.dependency.clone())),
+                );
 
             // Update the db
             let stopped_app = self.add_disabled_app_to_db(app).await?;
 
+            if let Err(e) = self
+                .grant_app_dependencies(&installed_app_id, &agent_key, dependencies)
+                .await
+            {
+                // Uninstalling revokes any grants which were already made.
+                if let Err(cleanup_error) = self.clone().uninstall_app(&installed_app_id).await {
+                    error!(
+                        ?cleanup_error,
+                        app_id = %installed_app_id,
+                        "Failed to uninstall an app whose dependencies could not be granted"
+                    );
+                }
+                return Err(e);
+            }
+
             Ok(stopped_app)
         }
 
+        /// Create the cell of a role whose provisioning was deferred at install.
+        ///
+        /// If a network seed is given, the cell is created from a DNA with that
+        /// seed applied, which is registered in the process.
+        pub async fn provision_deferred_cell(
+            self: Arc<Self>,
+            payload: ProvisionDeferredCellPayload,
+        ) -> ConductorResult<ProvisionedCell> {
+            let ProvisionDeferredCellPayload {
+                app_id,
+                role_name,
+                membrane_proof,
+                network_seed,
+            } = payload;
+            let state = self.get_state().await?;
+            let role = state.get_app(&app_id)?.role(&role_name)?.clone();
+            if !role.is_deferred() {
+                return Err(AppError::RoleNotDeferred(role_name).into());
+            }
+
+            let mut dna_file = self
+                .get_dna_file(role.dna_hash())
+                .ok_or_else(|| DnaError::DnaMissing(role.dna_hash().clone()))?;
+            if let Some(network_seed) = network_seed {
+                dna_file = dna_file
+                    .update_modifiers(DnaModifiersOpt::none().with_network_seed(network_seed));
+            }
+            // Only a DNA which is registered for this role is removed
+            // if its cell can't be created.
+            let registered_dna = self.get_dna_file(dna_file.dna_hash()).is_none();
+            let cell_id = CellId::new(dna_file.dna_hash().clone(), role.agent_key().clone());
+
+            // Claim the role before registering the DNA and running genesis, so that
+            // concurrent requests can't both create a cell for it. The claim is
+            // persisted, so it is released on startup if the conductor stops
+            // before the cell is created.
+            self.update_state({
+                let app_id = app_id.clone();
+                let role_name = role_name.clone();
+                let cell_id = cell_id.clone();
+                move |mut state| {
+                    if state
+                        .installed_apps()
+                        .values()
+                        .any(|app| app.all_cells().any(|c| *c == cell_id))
+                    {
+                        return Err(ConductorError::CellAlreadyExists(cell_id));
+                    }
+                    state.get_app_mut(&app_id)?.claim_deferred_role(
+                        &role_name,
+                        cell_id,
+                        registered_dna,
+                    )?;
+                    Ok(state)
+                }
+            })
+            .await?;
+
+            let created = async {
+                if registered_dna {
+                    self.register_dna(dna_file.clone()).await?;
+                }
+                crate::conductor::conductor::genesis_cells(
+                    self.clone(),
+                    vec![(cell_id.clone(), membrane_proof)],
+                )
+                .await
+            }
+            .await;
+            if let Err(e) = created {
+                if let Err(release_error) = self.release_deferred_role(&app_id, &role_name).await {
+                    error!(
+                        ?release_error,
+                        app_id = %app_id,
+                        role_name = %role_name,
+                        "Failed to release the claim on a deferred role whose cell could not be created"
+                    );
+                }
+                return Err(e);
+            }
+            self.update_state({
+                let app_id = app_id.clone();
+                move |mut state| {
+                    state
+                        .get_app_mut(&app_id)?
+                        .provision_claimed_role(&role_name)?;
+                    Ok(state)
+                }
+            })
+            .await?;
+            self.create_and_add_initialized_cells_for_running_apps(Some(&app_id))
+                .await?;
+
+            let dna = dna_file.dna();
+            Ok(ProvisionedCell {
+                cell_id,
+                dna_modifiers: dna.modifiers.clone(),
+                name: dna.name.clone(),
+            })
+        }
+
+        /// Return a claimed role to deferred, removing the DNA registered for it.
+        async fn release_deferred_role(
+            &self,
+            app_id: &InstalledAppId,
+            role_name: &RoleName,
+        ) -> ConductorResult<()> {
+            let (_, released) = self
+                .update_state_prime({
+                    let app_id = app_id.clone();
+                    let role_name = role_name.clone();
+                    move |mut state| {
+                        let released = state
+                            .get_app_mut(&app_id)?
+                            .release_deferred_role(&role_name)?;
+                        Ok((state, vec![released]))
+                    }
+                })
+                .await?;
+            self.remove_released_claim_dnas(released).await
+        }
+
+        /// Release the claims on deferred roles whose cells were still being
+        /// created when the conductor last stopped.
+        pub(crate) async fn release_interrupted_deferred_roles(&self) -> ConductorResult<()> {
+            let (_, released) = self
+                .update_state_prime(|mut state| {
+                    let released = state
+                        .installed_apps_mut()
+                        .values_mut()
+                        .flat_map(|app| app.release_all_deferred_roles())
+                        .collect::<Vec<_>>();
+                    Ok((state, released))
+                })
+                .await?;
+            for (_, cell_id) in released.iter() {
+                warn!(
+                    ?cell_id,
+                    "Released the claim on a deferred role whose cell was not created"
+                );
+            }
+            self.remove_released_claim_dnas(released).await
+        }
+
+        /// Remove the DNAs which were registered for released claims,
+        /// unless another role now uses them.
+        async fn remove_released_claim_dnas(
+            &self,
+            released: Vec<(DeferredRoleClaim, CellId)>,
+        ) -> ConductorResult<()> {
+            let state = self.get_state().await?;
+            for (claim, cell_id) in released {
+                let dna_hash = cell_id.dna_hash();
+                let in_use = state.installed_apps().values().any(|app| {
+                    app.roles().values().any(|role| role.dna_hash() == dna_hash)
+                        || app.all_cells().any(|c| c.dna_hash() == dna_hash)
+                });
+                if claim.registered_dna && !in_use {
+                    self.unregister_dna(dna_hash).await?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Resolve the dependencies on other apps declared in an app manifest
+        /// to the cells they depend on, choosing the secret of each grant which
+        /// will be needed. Nothing is granted yet.
+        async fn resolve_app_dependencies(
+            &self,
+            agent_key: &AgentPubKey,
+            dependencies: Vec<AppDependencyManifest>,
+        ) -> ConductorResult<Vec<ResolvedAppDependency>> {
+            let state = self.get_state().await?;
+            let mut resolved = Vec::with_capacity(dependencies.len());
+            for AppDependencyManifest {
+                name,
+                installed_app_id: dependency_app_id,
+                role_name,
+                functions,
+            } in dependencies
+            {
+                let dependency_app = state.get_app(&dependency_app_id)?;
+                if !dependency_app.status().is_running() {
+                    return Err(ConductorError::AppNotRunning(dependency_app_id));
+                }
+                let cell_id = dependency_app
+                    .role(&role_name)?
+                    .provisioned_cell()
+                    .cloned()
+                    .ok_or_else(|| AppError::RoleNameMissing(role_name.clone()))?;
+
+                // A cell of the same agent is always callable through the
+                // author grant, so there is nothing to grant.
+                let cap_secret = if cell_id.agent_pubkey() == agent_key {
+                    None
+                } else {
+                    let mut secret: CapSecretBytes = [0; CAP_SECRET_BYTES];
+                    getrandom::getrandom(&mut secret).map_err(ConductorError::other)?;
+                    Some(CapSecret::from(secret))
+                };
+
+                resolved.push(ResolvedAppDependency {
+                    name,
+                    dependency: AppDependency {
+                        installed_app_id: dependency_app_id,
+                        role_name,
+                        cap_secret,
+                    },
+                    cell_id,
+                    functions: GrantedFunctions::Listed(functions.into_iter().collect()),
+                });
+            }
+            Ok(resolved)
+        }
+
+        /// Grant the installing agent a capability on each depended-upon cell
+        /// so that the dependent app can call it without a manual grant.
+        async fn grant_app_dependencies(
+            &self,
+            installed_app_id: &InstalledAppId,
+            agent_key: &AgentPubKey,
+            dependencies: Vec<ResolvedAppDependency>,
+        ) -> ConductorResult<()> {
+            for ResolvedAppDependency {
+                name,
+                dependency,
+                cell_id,
+                functions,
+            } in dependencies
+            {
+                let secret = match dependency.cap_secret {
+                    Some(secret) => secret,
+                    None => continue,
+                };
+                let cap_grant = ZomeCallCapGrant::new(
+                    format!("app-dependency:{}:{}", installed_app_id, name),
+                    (secret, agent_key.clone()).into(),
+                    functions,
+                );
+                self.grant_zome_call_capability(GrantZomeCallCapabilityPayload {
+                    cell_id,
+                    cap_grant,
+                })
+                .await
+                .map_err(ConductorError::other)?;
+            }
+            Ok(())
+        }
+
+        /// Revoke the capabilities granted for an app's dependencies. Every
+        /// depended-upon cell which still exists must be running, so that no
+        /// grant is left behind.
+        async fn revoke_app_dependencies(&self, app: &InstalledApp) -> ConductorResult<()> {
+            let state = self.get_state().await?;
+            let mut grants = Vec::new();
+            for dependency in app.dependencies().values() {
+                let secret = match dependency.cap_secret {
+                    Some(secret) => secret,
+                    None => continue,
+                };
+                // The cell may have gone with the app which provided it.
+                let cell_id = match state
+                    .get_app(&dependency.installed_app_id)
+                    .ok()
+                    .and_then(|app| app.role(&dependency.role_name).ok())
+                    .and_then(|role| role.provisioned_cell().cloned())
+                {
+                    Some(cell_id) => cell_id,
+                    None => continue,
+                };
+                // Check every cell before revoking anything.
+                self.cell_by_id(&cell_id)?;
+                grants.push((cell_id, secret));
+            }
+            for (cell_id, secret) in grants {
+                self.revoke_zome_call_capability(&cell_id, secret)
+                    .await
+                    .map_err(ConductorError::other)?;
+            }
+            Ok(())
+        }
+
         /// Uninstall an app
         #[tracing::instrument(skip(self))]
         pub async fn uninstall_app(
@@ -1131,9 +1604,26 @@ mod app_impls {
             installed_app_id: &InstalledAppId,
         ) -> ConductorResult<()> {
             let self_clone = self.clone();
+            // Revoke the dependency grants first, so that an app whose grants
+            // can't be revoked stays installed and can be uninstalled later.
+            let app = self.get_state().await?.get_app(installed_app_id)?.clone();
+            self.revoke_app_dependencies(&app).await?;
             let app = self.remove_app_from_db(installed_app_id).await?;
             tracing::debug!(msg = "Removed app from db.", app = ?app);
 
+            // Failed post_commit calls of the app's own cells can never be
+            // retried now.
+            let cell_ids = app
+                .all_cells()
+                .filter(|cell_id| app.owns_cell(cell_id))
+                .cloned()
+                .collect();
+            holochain_state::post_commit_retry::delete_post_commit_retries(
+                &self.spaces.conductor_db,
+                cell_ids,
+            )
+            .await?;
+
             // Remove cells which may now be dangling due to the removed app
             self_clone
                 .process_app_status_fx(AppStatusFx::SpinDown, None)
@@ -1211,6 +1701,35 @@ mod app_impls {
                 }))
         }
 
+        /// Resolve a dependency declared by the running app which uses this Cell
+        /// to the depended-upon Cell, along with the secret granted for calling it.
+        pub async fn find_cell_with_app_dependency(
+            &self,
+            cell_id: &CellId,
+            dependency_name: &str,
+        ) -> ConductorResult<Option<(CellId, Option<CapSecret>)>> {
+            let state = self.get_state().await?;
+            let dependency = match state
+                .running_apps()
+                .find(|(_, running_app)| running_app.all_cells().any(|i| i == cell_id))
+            {
+                Some((_, running_app)) => match running_app.dependency(dependency_name) {
+                    Ok(dependency) => dependency.clone(),
+                    Err(_) => return Ok(None),
+                },
+                None => return Ok(None),
+            };
+            let dependency_app = state.get_app(&dependency.installed_app_id)?;
+            if !dependency_app.status().is_running() {
+                return Err(ConductorError::AppNotRunning(dependency.installed_app_id));
+            }
+            Ok(dependency_app
+                .role(&dependency.role_name)
+                .ok()
+                .and_then(|role| role.provisioned_cell())
+                .map(|cell_id| (cell_id.clone(), dependency.cap_secret)))
+        }
+
         /// Get the IDs of all active installed Apps which use this Dna
         pub async fn list_running_apps_for_dependent_dna_hash(
             &self,
@@ -1815,10 +2334,9 @@ mod scheduler_impls {
                 let mut interval = tokio::time::interval(interval_period);
                 loop {
                     interval.tick().await;
-                    scheduler_handle
-                        .clone()
-                        .dispatch_scheduled_fns(Timestamp::now())
-                        .await;
+                    let now = Timestamp::now();
+                    scheduler_handle.clone().dispatch_scheduled_fns(now).await;
+                    scheduler_handle.dispatch_post_commit_retries(now).await;
                 }
             }));
         }
@@ -1843,6 +2361,115 @@ mod scheduler_impls {
     }
 }
 
+/// Methods related to the outcomes and retrying of `post_commit` callbacks
+mod post_commit_impls {
+    use super::*;
+    use holochain_state::post_commit_retry::*;
+
+    impl Conductor {
+        /// Handle the result of running a `post_commit` callback. Failures,
+        /// and successes of retries, are signalled to app interfaces, and
+        /// failures are scheduled to be retried if retries are configured.
+        pub(crate) async fn post_commit_outcome(
+            &self,
+            cell_id: CellId,
+            zome_name: ZomeName,
+            actions: Vec<SignedActionHashed>,
+            attempt: u32,
+            error: Option<String>,
+        ) {
+            if error.is_none() && attempt == 1 {
+                return;
+            }
+            let mut next_attempt = match (&error, &self.config.post_commit_retry) {
+                (Some(_), Some(retry)) => retry
+                    .backoff(attempt)
+                    .and_then(|delay| (Timestamp::now() + delay).ok()),
+                _ => None,
+            };
+            let action_hashes = actions.iter().map(|a| a.as_hash().clone()).collect();
+            if let Some(at) = next_attempt {
+                let pending = PendingPostCommit {
+                    cell_id: cell_id.clone(),
+                    zome_name: zome_name.clone(),
+                    actions,
+                    attempt,
+                    next_attempt: at,
+                };
+                if let Err(e) = schedule_post_commit_retry(&self.spaces.conductor_db, pending).await
+                {
+                    error!(?e, "Failed to schedule a post_commit retry");
+                    next_attempt = None;
+                }
+            }
+            let outcome = PostCommitOutcome {
+                cell_id,
+                zome_name,
+                action_hashes,
+                attempt,
+                error,
+                next_attempt,
+            };
+            if let Err(e) = self
+                .signal_broadcaster()
+                .send(SystemSignal::PostCommit(outcome).into())
+            {
+                debug!(?e, "Failed to signal a post_commit outcome");
+            }
+        }
+
+        /// Run again any failed `post_commit` callbacks which are due.
+        /// Retries for cells which are not running are kept until they are.
+        pub(crate) async fn dispatch_post_commit_retries(&self, now: Timestamp) {
+            let due = match take_due_post_commit_retries(
+                &self.spaces.conductor_db,
+                now,
+                self.running_cell_ids(),
+            )
+            .await
+            {
+                Ok(due) => due,
+                Err(e) => {
+                    error!(?e, "Failed to load post_commit retries");
+                    return;
+                }
+            };
+            for pending in due {
+                let cell = match self.cell_by_id(&pending.cell_id) {
+                    Ok(cell) => cell,
+                    Err(_) => {
+                        // The cell stopped since the retries were taken, so
+                        // put this one back to be retried once it runs again.
+                        if let Err(e) =
+                            schedule_post_commit_retry(&self.spaces.conductor_db, pending).await
+                        {
+                            error!(?e, "Failed to keep a post_commit retry");
+                        }
+                        continue;
+                    }
+                };
+                let attempt = pending.attempt + 1;
+                if let Err(e) = cell
+                    .retry_post_commit(&pending.zome_name, pending.actions.clone(), attempt)
+                    .await
+                {
+                    // The callback couldn't be run, so count this as a failed
+                    // attempt, which is retried again if attempts remain.
+                    error!(?e, "Failed to retry post_commit");
+                    self.post_commit_outcome(
+                        pending.cell_id,
+                        pending.zome_name,
+                        pending.actions,
+                        attempt,
+                        Some(e.to_string()),
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+}
+
 /// Miscellaneous methods
 mod misc_impls {
     use holochain_zome_types::builder;
@@ -1887,6 +2514,64 @@ mod misc_impls {
             Ok(())
         }
 
+        /// Revoke the zome call capabilities granted with this secret on a
+        /// cell, by deleting their grants from the cell's source chain.
+        pub(crate) async fn revoke_zome_call_capability(
+            &self,
+            cell_id: &CellId,
+            cap_secret: CapSecret,
+        ) -> ConductorApiResult<()> {
+            // The deletes can't be published without a running cell.
+            let cell = self.cell_by_id(cell_id)?;
+            let source_chain = SourceChain::new(
+                self.get_authored_db(cell_id.dna_hash())?,
+                self.get_dht_db(cell_id.dna_hash())?,
+                self.get_dht_db_cache(cell_id.dna_hash())?,
+                self.keystore.clone(),
+                cell_id.agent_pubkey().clone(),
+            )
+            .await?;
+
+            let grants = source_chain
+                .query(
+                    ChainQueryFilter::new()
+                        .entry_type(EntryType::CapGrant)
+                        .include_entries(true),
+                )
+                .await?;
+            let deleted: HashSet<ActionHash> = source_chain
+                .query(ChainQueryFilter::new().action_type(ActionType::Delete))
+                .await?
+                .into_iter()
+                .filter_map(|record| match record.action() {
+                    Action::Delete(delete) => Some(delete.deletes_address.clone()),
+                    _ => None,
+                })
+                .collect();
+            for record in grants {
+                if deleted.contains(record.action_address()) {
+                    continue;
+                }
+                let has_secret = matches!(
+                    record.entry().as_option(),
+                    Some(Entry::CapGrant(grant)) if grant.access.secret() == Some(&cap_secret)
+                );
+                if let (true, Some(entry_hash)) = (has_secret, record.action().entry_hash()) {
+                    let action_builder = builder::Delete {
+                        deletes_address: record.action_address().clone(),
+                        deletes_entry_address: entry_hash.clone(),
+                    };
+                    source_chain
+                        .put_weightless(action_builder, None, ChainTopOrdering::default())
+                        .await?;
+                }
+            }
+
+            source_chain.flush(cell.holochain_p2p_dna()).await?;
+
+            Ok(())
+        }
+
         /// Create a JSON dump of the cell's state
         pub async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String> {
             let cell = self.cell_by_id(cell_id)?;
@@ -1939,15 +2624,21 @@ mod misc_impls {
             Ok(out)
         }
 
-        /// JSON dump of network metrics
+        /// JSON dump of network metrics, with the wasm usage of each DNA
         pub async fn dump_network_metrics(
             &self,
             dna_hash: Option<DnaHash>,
         ) -> ConductorApiResult<String> {
             use holochain_p2p::HolochainP2pSender;
-            self.holochain_p2p()
+            let dump = self
+                .holochain_p2p()
                 .dump_network_metrics(dna_hash)
                 .await
+                .map_err(crate::conductor::api::error::ConductorApiError::other)?;
+            let mut dump: serde_json::Value = serde_json::from_str(&dump)
+                .map_err(crate::conductor::api::error::ConductorApiError::other)?;
+            crate::core::ribosome::wasm_metrics::add_to_network_dump(&mut dump);
+            serde_json::to_string_pretty(&dump)
                 .map_err(crate::conductor::api::error::ConductorApiError::other)
         }
 
@@ -2014,6 +2705,8 @@ mod misc_impls {
             )
             .await?;
 
+            ribosome.precompile()?;
+
             // Update RibosomeStore.
             self.ribosome_store()
                 .share_mut(|d| d.add_ribosome(ribosome));
@@ -2034,6 +2727,40 @@ mod accessor_impls {
             &self.ribosome_store
         }
 
+        /// The quotas applied to zome calls from app interfaces and remote agents
+        pub(crate) fn zome_call_quotas(&self) -> &ZomeCallQuotas {
+            &self.zome_call_quotas
+        }
+
+        /// The wasm fuel and memory limits configured for a DNA
+        pub(crate) fn wasm_limits_for_dna(&self, dna_hash: &DnaHash) -> WasmLimits {
+            self.config
+                .wasm_limits
+                .as_ref()
+                .map(|limits| limits.for_dna(dna_hash).clone())
+                .unwrap_or_default()
+        }
+
+        /// Create a ribosome for a DNA, using this conductor's wasm limits
+        /// and compiled wasm cache.
+        pub(crate) fn build_ribosome(&self, dna_file: DnaFile) -> ConductorResult<RealRibosome> {
+            let wasm_limits = self.wasm_limits_for_dna(dna_file.dna_hash());
+            Ok(
+                RealRibosome::new_with_module_cache(
+                    dna_file,
+                    Some(self.module_disk_cache.clone()),
+                )?
+                .with_wasm_limits(wasm_limits),
+            )
+        }
+
+        /// Remove all compiled wasm from the disk cache. Zomes will be
+        /// recompiled, and cached again, the next time they are loaded.
+        pub fn clear_wasm_cache(&self) -> ConductorResult<()> {
+            self.module_disk_cache.clear()?;
+            Ok(())
+        }
+
         pub(crate) fn get_queue_consumer_workflows(&self) -> QueueConsumerMap {
             self.spaces.queue_consumer_map.clone()
         }
@@ -129,6 +129,7 @@ impl ConductorBuilder {
             ribosome_store.clone(),
             network_config.tuning_params.clone(),
             strat,
+            config.traffic_weights.clone().unwrap_or_default(),
             Some(tag_ed),
             Some(keystore.lair_client()),
         );
@@ -202,23 +203,25 @@ impl ConductorBuilder {
                         host_access,
                         invocation,
                         cell_id,
+                        attempt,
                     } = post_commit_args;
-                    match conductor_handle.clone().get_ribosome(cell_id.dna_hash()) {
-                        Ok(ribosome) => {
-                            if let Err(e) = tokio::task::spawn_blocking(move || {
-                                if let Err(e) = ribosome.run_post_commit(host_access, invocation) {
-                                    tracing::error!(?e);
-                                }
-                            })
-                            .await
-                            {
-                                tracing::error!(?e);
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!(?e);
-                        }
+                    let zome_name = invocation.zome().zome_name().clone();
+                    let actions = invocation.actions().to_vec();
+                    let result = match conductor_handle.clone().get_ribosome(cell_id.dna_hash()) {
+                        Ok(ribosome) => tokio::task::spawn_blocking(move || {
+                            ribosome.run_post_commit(host_access, invocation)
+                        })
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|r| r.map_err(|e| e.to_string())),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    if let Err(e) = &result {
+                        tracing::error!(?e);
                     }
+                    conductor_handle
+                        .post_commit_outcome(cell_id, zome_name, actions, attempt, result.err())
+                        .await;
                 }
             })
             .await;
@@ -314,6 +317,7 @@ impl ConductorBuilder {
             ribosome_store.clone(),
             tuning_params,
             strat,
+            self.config.traffic_weights.clone().unwrap_or_default(),
             Some(tag.0),
             Some(keystore.lair_client()),
         );
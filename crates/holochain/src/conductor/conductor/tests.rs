@@ -1,3 +1,4 @@
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
 
@@ -221,7 +222,7 @@ async fn common_genesis_test_app(
 ) -> ConductorApiResult<SweetApp> {
     let hardcoded_zome = InlineIntegrityZome::new_unique(Vec::new(), 0);
 
-    // Just a strong reminder that we need to be careful once we start using existing Cells:
+    // Remember that existing Cells can be shared between apps:
     // When a Cell panics or fails validation in general, we want to disable all Apps touching that Cell.
     // However, if the panic/failure happens during Genesis, we want to completely
     // destroy the app which is attempting to Create that Cell, but *NOT* any other apps
@@ -231,8 +232,6 @@ async fn common_genesis_test_app(
     // no other app could be possibly referencing it, but just in case we have some kind of complex
     // behavior like installing two apps which reference each others' Cells at the same time,
     // we need to be aware of this distinction.
-    holochain_types::app::we_must_remember_to_rework_cell_panic_handling_after_implementing_use_existing_cell_resolution(
-    );
 
     // Create one DNA which always works, and another from a zome that gets passed in
     let (dna_hardcoded, _, _) = mk_dna(("hardcoded", hardcoded_zome)).await;
@@ -334,6 +333,183 @@ async fn test_uninstall_app() {
         .is_none());
 }
 
+/// Install "app2" with a single role which uses an existing cell of the DNA
+async fn install_using_existing(
+    conductor: &SweetConductor,
+    dna: &DnaFile,
+    agent: AgentPubKey,
+) -> ConductorResult<StoppedApp> {
+    let roles = vec![AppRoleManifest {
+        name: "shared".into(),
+        dna: AppRoleDnaManifest {
+            location: None,
+            sha256: None,
+            modifiers: DnaModifiersOpt::none(),
+            version: Some(DnaVersionSpec::from(vec![dna.dna_hash().clone().into()]).into()),
+            clone_limit: 0,
+        },
+        provisioning: Some(CellProvisioning::UseExisting { deferred: false }),
+    }];
+    let manifest = AppManifestCurrentBuilder::default()
+        .name("app2".into())
+        .description(None)
+        .roles(roles)
+        .build()
+        .unwrap();
+    let bundle = AppBundle::new(
+        manifest.into(),
+        Vec::<(std::path::PathBuf, DnaBundle)>::new(),
+        ".".into(),
+    )
+    .await
+    .unwrap();
+    conductor
+        .raw_handle()
+        .install_app_bundle(InstallAppPayload {
+            agent_key: agent,
+            source: AppBundleSource::Bundle(bundle),
+            installed_app_id: None,
+            network_seed: None,
+            membrane_proofs: Default::default(),
+        })
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_install_app_using_existing_cell() {
+    observability::test_run().ok();
+    let (dna, _, _) = mk_dna(simple_crud_zome()).await;
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let agent = SweetAgents::one(conductor.keystore()).await;
+
+    let app1 = conductor
+        .setup_app_for_agent("app1", agent.clone(), [&dna])
+        .await
+        .unwrap();
+    let cell_id = app1.cells()[0].cell_id().clone();
+
+    // - Another agent has no cell to share
+    let err = install_using_existing(&conductor, &dna, fixt!(AgentPubKey))
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ConductorError::AppBundleError(AppBundleError::NoExistingCell(_))
+    );
+
+    // - The same agent shares the cell of the first app
+    install_using_existing(&conductor, &dna, agent)
+        .await
+        .unwrap();
+    let app2 = conductor
+        .raw_handle()
+        .get_app_info(&"app2".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let app2_cell_id = match &app2.cell_info["shared"][0] {
+        CellInfo::Provisioned(cell) => cell.cell_id.clone(),
+        other => panic!("unexpected cell info {:?}", other),
+    };
+    assert_eq!(app2_cell_id, cell_id);
+
+    // - The shared cell keeps running while one of its apps is enabled
+    conductor
+        .raw_handle()
+        .enable_app("app2".to_string())
+        .await
+        .unwrap();
+    conductor
+        .raw_handle()
+        .uninstall_app(&"app1".to_string())
+        .await
+        .unwrap();
+    assert!(conductor
+        .raw_handle()
+        .list_cell_ids(None)
+        .contains(&cell_id));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn app_interface_calls_to_a_shared_cell_count_against_its_owner() {
+    use crate::conductor::api::{AppInterfaceApi, RealAppInterfaceApi};
+    use holochain_conductor_api::conductor::{ZomeCallQuota, ZomeCallQuotaConfig};
+    use holochain_conductor_api::{AppRequest, AppResponse, ExternalApiWireError, ZomeCall};
+    use holochain_state::nonce::fresh_nonce;
+
+    observability::test_run().ok();
+    let zome = InlineZomeSet::new_unique_single("integrity", "zome", vec![], 0).function(
+        "zome",
+        "noop",
+        |_, ()| Ok(()),
+    );
+    let (dna, _, _) = mk_dna(zome).await;
+
+    // - Only the owning app is limited, and "app2" sorts before it
+    let mut config = standard_config();
+    config.zome_call_quotas = Some(ZomeCallQuotaConfig {
+        apps: [(
+            "owner".to_string(),
+            ZomeCallQuota {
+                calls_per_second: Some(1),
+                ..Default::default()
+            },
+        )]
+        .into(),
+        ..Default::default()
+    });
+    let mut conductor = SweetConductor::from_config(config).await;
+    let agent = SweetAgents::one(conductor.keystore()).await;
+    let owner = conductor
+        .setup_app_for_agent("owner", agent.clone(), [&dna])
+        .await
+        .unwrap();
+    install_using_existing(&conductor, &dna, agent.clone())
+        .await
+        .unwrap();
+    conductor
+        .raw_handle()
+        .enable_app("app2".to_string())
+        .await
+        .unwrap();
+    let cell_id = owner.cells()[0].cell_id().clone();
+
+    let mut calls = Vec::new();
+    for _ in 0..2 {
+        let (nonce, expires_at) = fresh_nonce(Timestamp::now()).unwrap();
+        let call = ZomeCall::try_from_unsigned_zome_call(
+            &conductor.keystore(),
+            ZomeCallUnsigned {
+                cell_id: cell_id.clone(),
+                zome_name: "zome".into(),
+                fn_name: "noop".into(),
+                cap_secret: None,
+                provenance: agent.clone(),
+                payload: ExternIO::encode(()).unwrap(),
+                nonce,
+                expires_at,
+            },
+        )
+        .await
+        .unwrap();
+        calls.push(call);
+    }
+
+    let api = RealAppInterfaceApi::new(conductor.raw_handle());
+    let mut responses = Vec::new();
+    for call in calls {
+        responses.push(
+            api.handle_app_request(AppRequest::CallZome(Box::new(call)))
+                .await,
+        );
+    }
+    assert_matches!(responses[0], AppResponse::ZomeCalled(_));
+    assert_matches!(
+        &responses[1],
+        AppResponse::Error(ExternalApiWireError::ZomeCallQuotaExceeded(e)) if e.contains("app owner")
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_reconciliation_idempotency() {
     observability::test_run().ok();
@@ -1115,3 +1291,296 @@ async fn test_init_concurrency() {
     assert_eq!(num_calls_clone.fetch_add(0, Ordering::SeqCst), 100);
     assert_eq!(num_inits_clone.fetch_add(0, Ordering::SeqCst), 1);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn compiled_wasm_is_cached_on_disk() {
+    observability::test_run().ok();
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Foo]).await;
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let cache_dir = conductor
+        .db_path()
+        .join(crate::core::ribosome::module_cache::MODULE_CACHE_DIRECTORY);
+    // The cache holds one directory per engine, each holding one file per wasm
+    let cached_files = || {
+        std::fs::read_dir(&cache_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .flat_map(|engine_dir| std::fs::read_dir(engine_dir.path()).into_iter().flatten())
+            .count()
+    };
+
+    // Registering the DNA compiles all of its zomes into the cache
+    conductor.setup_app("app", [&dna]).await.unwrap();
+    assert!(cached_files() > 0);
+
+    conductor.clear_wasm_cache().unwrap();
+    assert_eq!(cached_files(), 0);
+}
+
+/// A zome whose `post_commit` fails the first time it is passed a create
+fn post_commit_fails_once_zome() -> InlineZomeSet {
+    use holochain_zome_types::inline_zome::error::InlineZomeError;
+
+    let failed_once = Arc::new(AtomicBool::new(false));
+    InlineZomeSet::new_unique_single("integrity", "zome", vec![EntryDef::from_id("unit")], 0)
+        .function("zome", "create", move |api, ()| {
+            let entry = Entry::app(().try_into().unwrap()).unwrap();
+            let hash = api.create(CreateInput::new(
+                InlineZomeSet::get_entry_location(&api, EntryDefIndex(0)),
+                EntryVisibility::Public,
+                entry,
+                ChainTopOrdering::default(),
+            ))?;
+            Ok(hash)
+        })
+        .function(
+            "zome",
+            "post_commit",
+            move |_, actions: Vec<SignedActionHashed>| {
+                // Fail the first time the created entry is passed in. Commits made
+                // by init are unaffected, and their success is not signalled.
+                let is_create = actions
+                    .iter()
+                    .any(|a| matches!(a.action(), Action::Create(_)));
+                if is_create && !failed_once.swap(true, Ordering::SeqCst) {
+                    Err(InlineZomeError::TestError("post_commit failed".into()))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_post_commit_is_signalled_and_retried() {
+    use holochain_conductor_api::conductor::PostCommitRetryConfig;
+    use tokio_stream::StreamExt;
+
+    observability::test_run().ok();
+    let dnas = [mk_dna(post_commit_fails_once_zome()).await.0];
+
+    let mut config = standard_config();
+    config.post_commit_retry = Some(PostCommitRetryConfig {
+        max_attempts: 3,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 10,
+    });
+    let mut conductor = SweetConductor::from_config(config).await;
+    let app = conductor.setup_app("app", &dnas).await.unwrap();
+    let (cell,) = app.into_tuple();
+    let mut outcomes = Box::pin(conductor.post_commit_outcomes().await);
+
+    let hash: ActionHash = conductor.call(&cell.zome("zome"), "create", ()).await;
+
+    let failed = tokio::time::timeout(std::time::Duration::from_secs(10), outcomes.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(failed.cell_id, *cell.cell_id());
+    assert_eq!(failed.attempt, 1);
+    assert!(!failed.is_ok());
+    assert!(failed.next_attempt.is_some());
+    assert!(failed.action_hashes.contains(&hash));
+
+    let retried = tokio::time::timeout(std::time::Duration::from_secs(10), outcomes.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(retried.attempt, 2);
+    assert!(retried.is_ok());
+    assert_eq!(retried.action_hashes, failed.action_hashes);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn post_commit_retry_waits_for_its_cell_across_a_restart() {
+    use holochain_conductor_api::conductor::PostCommitRetryConfig;
+    use tokio_stream::StreamExt;
+
+    observability::test_run().ok();
+    let dnas = [mk_dna(post_commit_fails_once_zome()).await.0];
+
+    // Retry far enough in the future that only the test dispatches retries
+    let mut config = standard_config();
+    config.post_commit_retry = Some(PostCommitRetryConfig {
+        max_attempts: 3,
+        initial_backoff_ms: 60_000,
+        max_backoff_ms: 60_000,
+    });
+    let mut conductor = SweetConductor::from_config(config).await;
+    let app = conductor.setup_app("app", &dnas).await.unwrap();
+    let (cell,) = app.into_tuple();
+    let mut outcomes = Box::pin(conductor.post_commit_outcomes().await);
+
+    let _: ActionHash = conductor.call(&cell.zome("zome"), "create", ()).await;
+    let failed = tokio::time::timeout(std::time::Duration::from_secs(10), outcomes.next())
+        .await
+        .unwrap()
+        .unwrap();
+    let due = failed.next_attempt.unwrap();
+
+    // The retry falls due while the cell is disabled, as it would before
+    // the cells have started after a restart
+    conductor
+        .disable_app("app".to_string(), DisabledAppReason::User)
+        .await
+        .unwrap();
+    conductor
+        .raw_handle()
+        .dispatch_post_commit_retries(due)
+        .await;
+    conductor.enable_app("app".to_string()).await.unwrap();
+
+    conductor.shutdown().await;
+    conductor.startup().await;
+    let mut outcomes = Box::pin(conductor.post_commit_outcomes().await);
+    conductor
+        .raw_handle()
+        .dispatch_post_commit_retries(due)
+        .await;
+
+    let retried = tokio::time::timeout(std::time::Duration::from_secs(10), outcomes.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(retried.cell_id, *cell.cell_id());
+    assert_eq!(retried.attempt, 2);
+    assert!(retried.is_ok());
+    assert_eq!(retried.action_hashes, failed.action_hashes);
+}
+
+/// Install an app bundle, signed by the given publisher if any
+async fn install_signed_by(
+    conductor: &SweetConductor,
+    name: &str,
+    publisher: Option<&mr_bundle::PublisherSecret>,
+    roles: Vec<AppRoleManifest>,
+    dnas: Vec<(std::path::PathBuf, DnaBundle)>,
+) -> ConductorResult<StoppedApp> {
+    let manifest = AppManifestCurrentBuilder::default()
+        .name(name.into())
+        .description(None)
+        .roles(roles)
+        .build()
+        .unwrap();
+    let mut bundle = AppBundle::new(manifest.into(), dnas, ".".into())
+        .await
+        .unwrap();
+    if let Some(publisher) = publisher {
+        bundle = bundle.into_inner().signed(publisher).unwrap().into();
+    }
+    conductor
+        .raw_handle()
+        .install_app_bundle(InstallAppPayload {
+            agent_key: fixt!(AgentPubKey),
+            source: AppBundleSource::Bundle(bundle),
+            installed_app_id: None,
+            network_seed: None,
+            membrane_proofs: Default::default(),
+        })
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn install_rejects_untrusted_publishers() {
+    observability::test_run().ok();
+    let trusted = mr_bundle::PublisherSecret::generate().unwrap();
+    let untrusted = mr_bundle::PublisherSecret::generate().unwrap();
+
+    let mut config = standard_config();
+    config.trusted_publishers = Some(vec![trusted.public_key()]);
+    let conductor = SweetConductor::from_config(config).await;
+
+    assert_matches!(
+        install_signed_by(&conductor, "unsigned", None, vec![], vec![]).await,
+        Err(ConductorError::UntrustedPublisher(None))
+    );
+    assert_matches!(
+        install_signed_by(&conductor, "untrusted", Some(&untrusted), vec![], vec![]).await,
+        Err(ConductorError::UntrustedPublisher(Some(key))) if key == untrusted.public_key()
+    );
+    install_signed_by(&conductor, "trusted", Some(&trusted), vec![], vec![])
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn install_rejects_unpinned_locations_from_trusted_publishers() {
+    observability::test_run().ok();
+    let trusted = mr_bundle::PublisherSecret::generate().unwrap();
+
+    let mut config = standard_config();
+    config.trusted_publishers = Some(vec![trusted.public_key()]);
+    let conductor = SweetConductor::from_config(config).await;
+
+    let location = mr_bundle::Location::Url("https://example.com/app.dna".into());
+    let roles = vec![AppRoleManifest {
+        name: "remote".into(),
+        dna: AppRoleDnaManifest {
+            location: Some(location.clone()),
+            sha256: None,
+            modifiers: DnaModifiersOpt::none(),
+            version: None,
+            clone_limit: 0,
+        },
+        provisioning: Some(CellProvisioning::Create { deferred: false }),
+    }];
+    assert_matches!(
+        install_signed_by(&conductor, "unpinned", Some(&trusted), roles, vec![]).await,
+        Err(ConductorError::UnpinnedLocation(l)) if l == location
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn install_rejects_unpinned_zomes_in_bundled_dnas_from_trusted_publishers() {
+    observability::test_run().ok();
+    let trusted = mr_bundle::PublisherSecret::generate().unwrap();
+
+    let mut config = standard_config();
+    config.trusted_publishers = Some(vec![trusted.public_key()]);
+    let conductor = SweetConductor::from_config(config).await;
+
+    let zome_location = mr_bundle::Location::Url("https://example.com/zome.wasm".into());
+    let dna_manifest = DnaManifestCurrent {
+        name: "nested".into(),
+        integrity: IntegrityManifest {
+            network_seed: None,
+            properties: None,
+            origin_time: Timestamp::HOLOCHAIN_EPOCH.into(),
+            zomes: vec![ZomeManifest {
+                name: "zome".into(),
+                hash: None,
+                location: zome_location.clone(),
+                sha256: None,
+                dependencies: Default::default(),
+            }],
+        },
+        coordinator: CoordinatorManifest { zomes: vec![] },
+    };
+    let dna_bundle: DnaBundle =
+        mr_bundle::Bundle::new_unchecked(dna_manifest.try_into().unwrap(), vec![])
+            .unwrap()
+            .into();
+    let roles = vec![AppRoleManifest {
+        name: "nested".into(),
+        dna: AppRoleDnaManifest {
+            location: Some(mr_bundle::Location::Bundled("nested.dna".into())),
+            sha256: None,
+            modifiers: DnaModifiersOpt::none(),
+            version: None,
+            clone_limit: 0,
+        },
+        provisioning: Some(CellProvisioning::Create { deferred: false }),
+    }];
+    assert_matches!(
+        install_signed_by(
+            &conductor,
+            "nested",
+            Some(&trusted),
+            roles,
+            vec![("nested.dna".into(), dna_bundle)],
+        )
+        .await,
+        Err(ConductorError::UnpinnedLocation(l)) if l == zome_location
+    );
+}
@@ -95,6 +95,12 @@ pub enum ConductorError {
     #[error("Tried to perform an operation on an app that was not running: {0}")]
     AppNotRunning(InstalledAppId),
 
+    #[error("The app bundle is not signed by a trusted publisher. Signed by: {0:?}")]
+    UntrustedPublisher(Option<mr_bundle::PublisherKey>),
+
+    #[error("The app bundle's resource at {0:?} is not pinned by a sha256 hash, so its publisher can't be trusted for it")]
+    UnpinnedLocation(mr_bundle::Location),
+
     #[error(transparent)]
     HolochainP2pError(#[from] holochain_p2p::HolochainP2pError),
 
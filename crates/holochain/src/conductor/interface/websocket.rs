@@ -479,7 +479,9 @@ pub mod test {
                     assert_eq!(
                         info,
                         vec![NetworkInfo {
-                            fetch_pool_info: FetchPoolInfo::default()
+                            fetch_pool_info: FetchPoolInfo::default(),
+                            pending_fetches: vec![],
+                            peer_scores: vec![],
                         }]
                     )
                 }
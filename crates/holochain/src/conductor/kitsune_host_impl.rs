@@ -9,6 +9,7 @@ use std::sync::Arc;
 use super::{ribosome_store::RibosomeStore, space::Spaces};
 use futures::FutureExt;
 use holo_hash::DnaHash;
+use holochain_conductor_api::conductor::TrafficWeightsConfig;
 use holochain_p2p::{
     dht::{spacetime::Topology, ArqStrat},
     DnaHashExt,
@@ -20,8 +21,11 @@ use holochain_types::{
     share::RwShare,
 };
 use kitsune_p2p::{
-    agent_store::AgentInfoSigned, dependencies::kitsune_p2p_fetch::OpHashSized,
-    event::GetAgentInfoSignedEvt, KitsuneHost, KitsuneHostResult,
+    agent_store::AgentInfoSigned,
+    dependencies::kitsune_p2p_fetch::{FetchPoolItemInfo, OpHashSized},
+    event::GetAgentInfoSignedEvt,
+    traffic::TrafficWeights,
+    KitsuneHost, KitsuneHostResult,
 };
 use kitsune_p2p_types::{
     config::KitsuneP2pTuningParams, dependencies::lair_keystore_api, KOpData, KOpHash,
@@ -34,6 +38,7 @@ pub struct KitsuneHostImpl {
     ribosome_store: RwShare<RibosomeStore>,
     tuning_params: KitsuneP2pTuningParams,
     strat: ArqStrat,
+    traffic_weights: TrafficWeightsConfig,
     lair_tag: Option<Arc<str>>,
     lair_client: Option<lair_keystore_api::LairClient>,
 }
@@ -45,6 +50,7 @@ impl KitsuneHostImpl {
         ribosome_store: RwShare<RibosomeStore>,
         tuning_params: KitsuneP2pTuningParams,
         strat: ArqStrat,
+        traffic_weights: TrafficWeightsConfig,
         lair_tag: Option<Arc<str>>,
         lair_client: Option<lair_keystore_api::LairClient>,
     ) -> Arc<Self> {
@@ -53,6 +59,7 @@ impl KitsuneHostImpl {
             ribosome_store,
             tuning_params,
             strat,
+            traffic_weights,
             lair_tag,
             lair_client,
         })
@@ -100,6 +107,64 @@ impl KitsuneHost for KitsuneHostImpl {
         .into()
     }
 
+    fn query_metrics(
+        &self,
+        space: std::sync::Arc<kitsune_p2p::KitsuneSpace>,
+    ) -> KitsuneHostResult<Vec<kitsune_p2p::event::MetricRecord>> {
+        async move {
+            let db = self.spaces.p2p_metrics_db(&DnaHash::from_kitsune(&space))?;
+            use holochain_sqlite::db::AsP2pMetricStoreConExt;
+            let permit = db.conn_permit().await;
+            let task = tokio::task::spawn_blocking(move || {
+                let mut conn = db.with_permit(permit)?;
+                conn.p2p_latest_metrics()
+            })
+            .await;
+            Ok(task??)
+        }
+        .boxed()
+        .into()
+    }
+
+    fn persist_fetch_pool(
+        &self,
+        space: std::sync::Arc<kitsune_p2p::KitsuneSpace>,
+        items: Vec<FetchPoolItemInfo>,
+    ) -> KitsuneHostResult<()> {
+        async move {
+            let db = self.spaces.p2p_metrics_db(&DnaHash::from_kitsune(&space))?;
+            use holochain_sqlite::db::AsP2pMetricStoreConExt;
+            let permit = db.conn_permit().await;
+            let task = tokio::task::spawn_blocking(move || {
+                let mut conn = db.with_permit(permit)?;
+                conn.p2p_replace_fetch_pool(items)
+            })
+            .await;
+            Ok(task??)
+        }
+        .boxed()
+        .into()
+    }
+
+    fn load_fetch_pool(
+        &self,
+        space: std::sync::Arc<kitsune_p2p::KitsuneSpace>,
+    ) -> KitsuneHostResult<Vec<FetchPoolItemInfo>> {
+        async move {
+            let db = self.spaces.p2p_metrics_db(&DnaHash::from_kitsune(&space))?;
+            use holochain_sqlite::db::AsP2pMetricStoreConExt;
+            let permit = db.conn_permit().await;
+            let task = tokio::task::spawn_blocking(move || {
+                let mut conn = db.with_permit(permit)?;
+                conn.p2p_fetch_pool()
+            })
+            .await;
+            Ok(task??)
+        }
+        .boxed()
+        .into()
+    }
+
     fn get_agent_info_signed(
         &self,
         GetAgentInfoSignedEvt { space, agent }: GetAgentInfoSignedEvt,
@@ -181,6 +246,10 @@ impl KitsuneHost for KitsuneHostImpl {
         .into()
     }
 
+    fn traffic_weights(&self, space: Arc<kitsune_p2p::KitsuneSpace>) -> TrafficWeights {
+        self.traffic_weights.for_dna(&DnaHash::from_kitsune(&space))
+    }
+
     fn get_topology(&self, space: Arc<kitsune_p2p::KitsuneSpace>) -> KitsuneHostResult<Topology> {
         let dna_hash = DnaHash::from_kitsune(&space);
         let dna_def = self
@@ -97,12 +97,14 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
+    use holochain_p2p::dht::test_utils::OpStore;
     use holochain_serialized_bytes::UnsafeBytes;
     use holochain_state::prelude::StateMutationResult;
     use holochain_state::{prelude::insert_op, test_utils::test_dht_db};
     use holochain_types::fixt::*;
-    use holochain_types::prelude::{DhtOp, DhtOpHashed, NewEntryAction};
+    use holochain_types::prelude::{DhtOp, DhtOpHashed, EntryHash, NewEntryAction};
     use holochain_zome_types::{AppEntryBytes, Entry};
+    use proptest::prelude::*;
 
     /// Ensure that the size reported by RegionData is "close enough" to the actual size of
     /// ops that get transferred over the wire.
@@ -170,4 +172,83 @@ mod tests {
             assert!(wire_bytes as u32 - sum.size < 32 * num as u32);
         }
     }
+
+    const DAY_SECS: u64 = 24 * 60 * 60;
+
+    /// Build the StoreEntry, StoreRecord and RegisterAgentActivity ops for a
+    /// Create action authored `secs` after the given origin.
+    fn mk_ops(origin: Timestamp, i: usize, secs: u64, byte: u8, len: usize) -> Vec<DhtOp> {
+        let entry = Entry::App(AppEntryBytes(
+            UnsafeBytes::from(vec![byte; len]).try_into().unwrap(),
+        ));
+        let sig = fixt::fixt!(Signature);
+        let mut create = fixt::fixt!(Create);
+        create.entry_hash = EntryHash::with_data_sync(&entry);
+        // Offset each action by its index so that no two ops share
+        // both a type and a timestamp.
+        create.timestamp =
+            (origin + (Duration::from_secs(secs) + Duration::from_micros(i as u64))).unwrap();
+        vec![
+            DhtOp::StoreEntry(
+                sig.clone(),
+                NewEntryAction::Create(create.clone()),
+                Box::new(entry.clone()),
+            ),
+            DhtOp::StoreRecord(sig.clone(), create.clone().into(), Some(Box::new(entry))),
+            DhtOp::RegisterAgentActivity(sig, create.into()),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        /// The RegionSet computed in-memory from the OpRegion impl of DhtOp
+        /// must match the one computed by the database for the same ops.
+        #[test]
+        fn region_set_in_memory_matches_sql(
+            specs in proptest::collection::vec(
+                (0..DAY_SECS, any::<u8>(), 0usize..1_000),
+                0..20,
+            )
+        ) {
+            let origin = (Timestamp::now() - Duration::from_secs(DAY_SECS)).unwrap();
+            let topo = Topology::standard(origin, Duration::ZERO);
+            let strat = ArqStrat::default();
+            let (arq_set, _) =
+                ArqBoundsSet::from_dht_arc_set_rounded(&topo, &strat, &DhtArcSet::Full);
+            let coords = RegionCoordSetLtcs::new(TelescopingTimes::historical(&topo), arq_set);
+
+            let ops: Vec<DhtOp> = specs
+                .into_iter()
+                .enumerate()
+                .flat_map(|(i, (secs, byte, len))| mk_ops(origin, i, secs, byte, len))
+                .collect();
+
+            let db = test_dht_db();
+            db.test_commit(|txn| {
+                for op in ops.iter() {
+                    insert_op(txn, &DhtOpHashed::from_content_sync(op.clone())).unwrap()
+                }
+                StateMutationResult::Ok(())
+            })
+            .unwrap();
+
+            let sql_topo = topo.clone();
+            let sql_coords = coords.clone();
+            let from_sql = tokio_helper::block_forever_on(db.to_db().async_reader(move |txn| {
+                let sql = holochain_sqlite::sql::sql_cell::FETCH_OP_REGION;
+                let mut stmt = txn.prepare_cached(sql).map_err(DatabaseError::from)?;
+                sql_coords
+                    .into_region_set(|(_, coords)| query_region_data(&mut stmt, &sql_topo, coords))
+            }))
+            .unwrap();
+
+            let mut store: OpStore<DhtOp> = OpStore::new(topo, GossipParams::zero());
+            store.integrate_ops(ops.into_iter().map(Arc::new));
+            let in_memory =
+                tokio_helper::block_forever_on(store.fetch_region_set(coords)).unwrap();
+
+            prop_assert_eq!(in_memory, from_sql);
+        }
+    }
 }
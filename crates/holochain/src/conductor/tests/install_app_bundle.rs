@@ -3,12 +3,16 @@ use std::{collections::HashMap, path::PathBuf};
 use crate::{conductor::error::ConductorError, sweettest::*};
 use futures::future::join_all;
 use holo_hash::DnaHash;
+use holochain_conductor_api::CellInfo;
+use holochain_state::source_chain::SourceChain;
+use holochain_types::app::error::AppError;
 use holochain_types::prelude::{
-    AppBundle, AppBundleSource, AppManifestCurrentBuilder, AppRoleDnaManifest, AppRoleManifest,
-    CellProvisioning, DnaBundle, DnaLocation, DnaVersionSpec, InstallAppPayload,
+    AgentPubKey, AppBundle, AppBundleSource, AppDependencyManifest, AppManifestCurrentBuilder,
+    AppRoleDnaManifest, AppRoleManifest, CapGrant, CapSecret, CellProvisioning, DnaBundle, DnaFile,
+    DnaLocation, DnaVersionSpec, InstallAppPayload, ProvisionDeferredCellPayload,
 };
 use holochain_wasm_test_utils::TestWasm;
-use holochain_zome_types::{CellId, DnaModifiersOpt};
+use holochain_zome_types::{AgentInfo, CellId, DnaModifiersOpt};
 use matches::assert_matches;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -33,6 +37,7 @@ async fn reject_duplicate_app_for_same_agent() {
         name: "name".into(),
         dna: AppRoleDnaManifest {
             location: Some(DnaLocation::Bundled(path.clone())),
+            sha256: None,
             modifiers: modifiers.clone(),
             version: Some(version),
             clone_limit: 0,
@@ -129,3 +134,314 @@ async fn reject_duplicate_app_for_same_agent() {
         .await;
     assert!(valid_install_of_second_app.is_ok());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn app_dependency_is_granted_and_callable() {
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let (alice, bob) = SweetAgents::two(conductor.keystore()).await;
+
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::WhoAmI]).await;
+    conductor
+        .setup_app_for_agent(
+            "profiles-app",
+            bob.clone(),
+            &[("profiles".to_string(), dna.clone())],
+        )
+        .await
+        .unwrap();
+
+    let dependent_dna = dna.clone().with_network_seed("dependent".into()).await;
+    let path = PathBuf::from(format!("{}", dependent_dna.dna_hash()));
+    let roles = vec![AppRoleManifest {
+        name: "main".into(),
+        dna: AppRoleDnaManifest {
+            location: Some(DnaLocation::Bundled(path.clone())),
+            sha256: None,
+            modifiers: DnaModifiersOpt::none(),
+            version: None,
+            clone_limit: 0,
+        },
+        provisioning: Some(CellProvisioning::Create { deferred: false }),
+    }];
+    let dependency = |installed_app_id: &str| AppDependencyManifest {
+        name: "profiles".into(),
+        installed_app_id: installed_app_id.into(),
+        role_name: "profiles".into(),
+        functions: vec![("whoami".into(), "whoami".into())],
+    };
+    let bundle = |installed_app_id: &'static str| {
+        let manifest = AppManifestCurrentBuilder::default()
+            .name("dependent_app".into())
+            .description(None)
+            .roles(roles.clone())
+            .dependencies(vec![dependency(installed_app_id)])
+            .build()
+            .unwrap();
+        let path = path.clone();
+        let dependent_dna = dependent_dna.clone();
+        async move {
+            let resources = vec![(path, DnaBundle::from_dna_file(dependent_dna).await.unwrap())];
+            AppBundle::new(manifest.into(), resources, PathBuf::from("."))
+                .await
+                .unwrap()
+        }
+    };
+
+    // A dependency on an app which is not installed is rejected
+    let result = conductor
+        .clone()
+        .install_app_bundle(InstallAppPayload {
+            source: AppBundleSource::Bundle(bundle("missing-app").await),
+            agent_key: alice.clone(),
+            installed_app_id: Some("dependent-app".into()),
+            membrane_proofs: HashMap::new(),
+            network_seed: None,
+        })
+        .await;
+    assert_matches!(
+        result.unwrap_err(),
+        ConductorError::AppNotInstalled(id) if id == "missing-app"
+    );
+
+    conductor
+        .clone()
+        .install_app_bundle(InstallAppPayload {
+            source: AppBundleSource::Bundle(bundle("profiles-app").await),
+            agent_key: alice.clone(),
+            installed_app_id: Some("dependent-app".into()),
+            membrane_proofs: HashMap::new(),
+            network_seed: None,
+        })
+        .await
+        .unwrap();
+    conductor.enable_app("dependent-app".into()).await.unwrap();
+    conductor
+        .raw_handle()
+        .reconcile_cell_status_with_app_status()
+        .await
+        .unwrap();
+
+    // Alice calls into Bob's cell through the dependency, using the
+    // capability granted by the conductor at install time.
+    let cell = conductor
+        .get_sweet_cell(CellId::new(dependent_dna.dna_hash().clone(), alice.clone()))
+        .unwrap();
+    let agent_info: AgentInfo = conductor
+        .call(
+            &cell.zome(TestWasm::WhoAmI),
+            "who_are_they_dependency",
+            "profiles",
+        )
+        .await;
+    assert_eq!(agent_info.agent_initial_pubkey, bob);
+
+    // Uninstalling the dependent app revokes its grant on Bob's cell
+    let secret = conductor
+        .get_state()
+        .await
+        .unwrap()
+        .get_app(&"dependent-app".to_string())
+        .unwrap()
+        .dependency("profiles")
+        .unwrap()
+        .cap_secret
+        .unwrap();
+    let bob_cell_id = CellId::new(dna.dna_hash().clone(), bob);
+    assert!(valid_grant(&conductor, &bob_cell_id, alice.clone(), secret)
+        .await
+        .is_some());
+    conductor
+        .clone()
+        .uninstall_app(&"dependent-app".to_string())
+        .await
+        .unwrap();
+    assert!(valid_grant(&conductor, &bob_cell_id, alice, secret)
+        .await
+        .is_none());
+}
+
+/// The grant on a cell which lets an agent call `whoami` with this secret
+async fn valid_grant(
+    conductor: &SweetConductor,
+    cell_id: &CellId,
+    agent: AgentPubKey,
+    secret: CapSecret,
+) -> Option<CapGrant> {
+    let handle = conductor.raw_handle();
+    let dna_hash = cell_id.dna_hash();
+    SourceChain::new(
+        handle.get_authored_db(dna_hash).unwrap(),
+        handle.get_dht_db(dna_hash).unwrap(),
+        handle.get_dht_db_cache(dna_hash).unwrap(),
+        handle.keystore().clone(),
+        cell_id.agent_pubkey().clone(),
+    )
+    .await
+    .unwrap()
+    .valid_cap_grant(("whoami".into(), "whoami".into()), agent, Some(secret))
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deferred_cell_is_provisioned_on_request() {
+    let conductor = SweetConductor::from_standard_config().await;
+    let alice = SweetAgents::one(conductor.keystore()).await;
+
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create]).await;
+    install_deferred_app(&conductor, &alice, &dna).await;
+    conductor.enable_app("app".into()).await.unwrap();
+
+    // - No cell exists yet, and the role is reported as a stem cell
+    assert!(conductor.running_cell_ids().is_empty());
+    let app_info = conductor
+        .get_app_info(&"app".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_matches!(
+        &app_info.cell_info["heavy"][..],
+        [CellInfo::Stem(stem)] if stem.original_dna_hash == *dna.dna_hash()
+    );
+
+    // - Provisioning with a network seed creates the cell on its own network
+    let payload = ProvisionDeferredCellPayload {
+        app_id: "app".into(),
+        role_name: "heavy".into(),
+        membrane_proof: None,
+        network_seed: Some("later".into()),
+    };
+    let cell = conductor
+        .clone()
+        .provision_deferred_cell(payload.clone())
+        .await
+        .unwrap();
+    assert_eq!(cell.dna_modifiers.network_seed, "later");
+    assert_ne!(cell.cell_id.dna_hash(), dna.dna_hash());
+    assert_eq!(cell.cell_id.agent_pubkey(), &alice);
+    assert!(conductor.running_cell_ids().contains(&cell.cell_id));
+
+    let app_info = conductor
+        .get_app_info(&"app".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_matches!(
+        &app_info.cell_info["heavy"][..],
+        [CellInfo::Provisioned(provisioned)] if provisioned.cell_id == cell.cell_id
+    );
+
+    // - The role can't be provisioned a second time
+    let err = conductor
+        .clone()
+        .provision_deferred_cell(payload)
+        .await
+        .unwrap_err();
+    assert_matches!(
+        err,
+        ConductorError::AppError(AppError::RoleNotDeferred(role)) if role == "heavy"
+    );
+}
+
+/// Install and enable an app called "app", whose "heavy" role is deferred.
+async fn install_deferred_app(conductor: &SweetConductor, alice: &AgentPubKey, dna: &DnaFile) {
+    let path = PathBuf::from(format!("{}", dna.dna_hash()));
+    let roles = vec![AppRoleManifest {
+        name: "heavy".into(),
+        dna: AppRoleDnaManifest {
+            location: Some(DnaLocation::Bundled(path.clone())),
+            sha256: None,
+            modifiers: DnaModifiersOpt::none(),
+            version: None,
+            clone_limit: 0,
+        },
+        provisioning: Some(CellProvisioning::Create { deferred: true }),
+    }];
+    let manifest = AppManifestCurrentBuilder::default()
+        .name("test_app".into())
+        .description(None)
+        .roles(roles)
+        .build()
+        .unwrap();
+    let resources = vec![(path, DnaBundle::from_dna_file(dna.clone()).await.unwrap())];
+    let bundle = AppBundle::new(manifest.into(), resources, PathBuf::from("."))
+        .await
+        .unwrap();
+
+    conductor
+        .raw_handle()
+        .install_app_bundle(InstallAppPayload {
+            source: AppBundleSource::Bundle(bundle),
+            agent_key: alice.clone(),
+            installed_app_id: Some("app".into()),
+            membrane_proofs: HashMap::new(),
+            network_seed: None,
+        })
+        .await
+        .unwrap();
+    conductor.enable_app("app".into()).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deferred_cell_is_provisioned_only_once_by_concurrent_requests() {
+    let conductor = SweetConductor::from_standard_config().await;
+    let alice = SweetAgents::one(conductor.keystore()).await;
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create]).await;
+    install_deferred_app(&conductor, &alice, &dna).await;
+
+    let payload = ProvisionDeferredCellPayload {
+        app_id: "app".into(),
+        role_name: "heavy".into(),
+        membrane_proof: None,
+        network_seed: None,
+    };
+    let results = join_all((0..2).map(|_| {
+        conductor
+            .raw_handle()
+            .provision_deferred_cell(payload.clone())
+    }))
+    .await;
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert_eq!(conductor.running_cell_ids().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deferred_cell_stays_deferred_when_genesis_fails() {
+    let conductor = SweetConductor::from_standard_config().await;
+    let alice = SweetAgents::one(conductor.keystore()).await;
+    let (dna, _, _) =
+        SweetDnaFile::unique_from_test_wasms(vec![TestWasm::GenesisSelfCheckInvalid]).await;
+    install_deferred_app(&conductor, &alice, &dna).await;
+
+    let payload = ProvisionDeferredCellPayload {
+        app_id: "app".into(),
+        role_name: "heavy".into(),
+        membrane_proof: None,
+        network_seed: Some("later".into()),
+    };
+    let err = conductor
+        .raw_handle()
+        .provision_deferred_cell(payload.clone())
+        .await
+        .unwrap_err();
+    assert_matches!(err, ConductorError::GenesisFailed { .. });
+
+    // - The claim was released, so the role is still a stem cell of the original DNA
+    let app_info = conductor
+        .get_app_info(&"app".into())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_matches!(
+        &app_info.cell_info["heavy"][..],
+        [CellInfo::Stem(stem)] if stem.original_dna_hash == *dna.dna_hash()
+    );
+
+    // - Provisioning can be retried, rather than being refused as already provisioned
+    let err = conductor
+        .raw_handle()
+        .provision_deferred_cell(payload)
+        .await
+        .unwrap_err();
+    assert_matches!(err, ConductorError::GenesisFailed { .. });
+}
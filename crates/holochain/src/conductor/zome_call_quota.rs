@@ -0,0 +1,367 @@
+//! Enforcement of the [`ZomeCallQuotaConfig`] for zome calls made through
+//! app interfaces and by remote agents.
+//!
+//! Rate and concurrency limits are checked when a call is admitted: a call
+//! which would exceed them is rejected immediately rather than queued, so a
+//! misbehaving caller cannot build up a backlog. The per-call limits on
+//! duration, fuel and network requests are applied while the call runs.
+//!
+//! Counters are only kept for keys with calls running or counted in the
+//! current window, so callers which have gone quiet cost nothing.
+
+use holochain_conductor_api::conductor::ZomeCallQuota;
+use holochain_conductor_api::conductor::ZomeCallQuotaConfig;
+use holochain_types::prelude::*;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Who a zome call is counted against
+#[derive(Clone, Debug, PartialEq, Eq, Hash, derive_more::Display)]
+pub enum ZomeCallQuotaKey {
+    /// A call made through an app interface to a cell of this app
+    #[display(fmt = "app {}", _0)]
+    App(InstalledAppId),
+    /// A call made by this agent via `call_remote`
+    #[display(fmt = "remote agent {}", _0)]
+    RemoteAgent(AgentPubKey),
+}
+
+/// Why a zome call was rejected or abandoned
+#[derive(Debug, thiserror::Error)]
+pub enum ZomeCallQuotaError {
+    /// Too many calls in the last second
+    #[error("Zome call quota exceeded for {0}: more than {1} calls per second")]
+    CallsPerSecond(ZomeCallQuotaKey, u32),
+
+    /// Too many calls running at once
+    #[error("Zome call quota exceeded for {0}: more than {1} concurrent calls")]
+    ConcurrentCalls(ZomeCallQuotaKey, u32),
+
+    /// The call ran for too long
+    #[error("Zome call quota exceeded for {0}: call took longer than {1:?}")]
+    CallDuration(ZomeCallQuotaKey, Duration),
+}
+
+/// Counters for one [`ZomeCallQuotaKey`]
+#[derive(Default)]
+struct QuotaUsage {
+    window_start: Option<Instant>,
+    calls_in_window: u32,
+    concurrent_calls: u32,
+}
+
+impl QuotaUsage {
+    /// No calls are running and none are counted in a current window
+    fn is_idle(&self, now: Instant) -> bool {
+        self.concurrent_calls == 0
+            && self
+                .window_start
+                .map_or(true, |start| now.duration_since(start) >= RATE_WINDOW)
+    }
+}
+
+/// The window over which `calls_per_second` is counted
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// The counters of every key which is currently in use
+#[derive(Default)]
+struct Usage {
+    by_key: HashMap<ZomeCallQuotaKey, QuotaUsage>,
+    /// When each current rate window ends, in the order the windows started.
+    /// Windows all have the same length, so this is also the order in which
+    /// they end.
+    window_ends: VecDeque<(Instant, ZomeCallQuotaKey)>,
+}
+
+impl Usage {
+    /// Drop the counters of keys whose rate window has ended and which have
+    /// no calls running. Keys with calls running are dropped by the last
+    /// permit instead.
+    fn prune(&mut self, now: Instant) {
+        while self
+            .window_ends
+            .front()
+            .map_or(false, |(end, _)| *end <= now)
+        {
+            if let Some((_, key)) = self.window_ends.pop_front() {
+                if self.by_key.get(&key).map_or(false, |u| u.is_idle(now)) {
+                    self.by_key.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// The runtime state of the zome call quotas of a conductor
+#[derive(Clone, Default)]
+pub struct ZomeCallQuotas {
+    config: Arc<ZomeCallQuotaConfig>,
+    usage: Arc<parking_lot::Mutex<Usage>>,
+}
+
+impl ZomeCallQuotas {
+    /// Constructor
+    pub fn new(config: ZomeCallQuotaConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            usage: Default::default(),
+        }
+    }
+
+    /// The quota which applies to calls counted against this key
+    pub fn quota(&self, key: &ZomeCallQuotaKey) -> &ZomeCallQuota {
+        match key {
+            ZomeCallQuotaKey::App(installed_app_id) => self.config.for_app(installed_app_id),
+            ZomeCallQuotaKey::RemoteAgent(_) => &self.config.remote_agent,
+        }
+    }
+
+    /// Admit a call counted against this key, if the rate and concurrency
+    /// limits allow it. The call counts as running until the returned
+    /// permit is dropped.
+    pub fn admit(&self, key: ZomeCallQuotaKey) -> Result<ZomeCallQuotaPermit, ZomeCallQuotaError> {
+        let quota = self.quota(&key).clone();
+        if quota.is_unlimited() {
+            return Ok(ZomeCallQuotaPermit {
+                quotas: self.clone(),
+                key,
+                quota,
+                counted: false,
+            });
+        }
+
+        let now = Instant::now();
+        {
+            let mut guard = self.usage.lock();
+            let all_usage = &mut *guard;
+            all_usage.prune(now);
+            let usage = all_usage.by_key.entry(key.clone()).or_default();
+            if let Some(limit) = quota.max_concurrent_calls {
+                if usage.concurrent_calls >= limit {
+                    if usage.is_idle(now) {
+                        // Only possible with a limit of zero
+                        all_usage.by_key.remove(&key);
+                    }
+                    return Err(ZomeCallQuotaError::ConcurrentCalls(key, limit));
+                }
+            }
+            if let Some(limit) = quota.calls_per_second {
+                match usage.window_start {
+                    Some(start) if now.duration_since(start) < RATE_WINDOW => {
+                        if usage.calls_in_window >= limit {
+                            return Err(ZomeCallQuotaError::CallsPerSecond(key, limit));
+                        }
+                    }
+                    _ => {
+                        usage.window_start = Some(now);
+                        usage.calls_in_window = 0;
+                        all_usage
+                            .window_ends
+                            .push_back((now + RATE_WINDOW, key.clone()));
+                    }
+                }
+                usage.calls_in_window += 1;
+            }
+            usage.concurrent_calls += 1;
+        }
+        Ok(ZomeCallQuotaPermit {
+            quotas: self.clone(),
+            key,
+            quota,
+            counted: true,
+        })
+    }
+}
+
+/// A zome call admitted under a quota. The call counts as running until
+/// this is dropped.
+pub struct ZomeCallQuotaPermit {
+    quotas: ZomeCallQuotas,
+    key: ZomeCallQuotaKey,
+    quota: ZomeCallQuota,
+    counted: bool,
+}
+
+impl ZomeCallQuotaPermit {
+    /// The quota the call was admitted under
+    pub fn quota(&self) -> &ZomeCallQuota {
+        &self.quota
+    }
+
+    /// Run the call, abandoning it if it exceeds the duration limit.
+    ///
+    /// Abandoning a call stops waiting for it, but cannot interrupt wasm
+    /// which is already executing, so the call runs in its own task which
+    /// holds this permit. An abandoned call keeps counting against the
+    /// concurrency limit until it has actually finished.
+    pub async fn run<F>(self, call: F) -> Result<F::Output, ZomeCallQuotaError>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let key = self.key.clone();
+        let max_call_duration_ms = self.quota.max_call_duration_ms;
+        let task = tokio::spawn(async move {
+            let _permit = self;
+            call.await
+        });
+        let result = match max_call_duration_ms {
+            Some(ms) => {
+                let limit = Duration::from_millis(ms);
+                tokio::time::timeout(limit, task)
+                    .await
+                    .map_err(|_| ZomeCallQuotaError::CallDuration(key, limit))?
+            }
+            None => task.await,
+        };
+        match result {
+            Ok(output) => Ok(output),
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+}
+
+impl Drop for ZomeCallQuotaPermit {
+    fn drop(&mut self) {
+        if self.counted {
+            let mut usage = self.quotas.usage.lock();
+            if let Some(entry) = usage.by_key.get_mut(&self.key) {
+                entry.concurrent_calls = entry.concurrent_calls.saturating_sub(1);
+                if entry.is_idle(Instant::now()) {
+                    usage.by_key.remove(&self.key);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use matches::assert_matches;
+
+    fn quotas(quota: ZomeCallQuota) -> (ZomeCallQuotas, ZomeCallQuotaKey) {
+        let config = ZomeCallQuotaConfig {
+            default_app: quota,
+            ..Default::default()
+        };
+        (
+            ZomeCallQuotas::new(config),
+            ZomeCallQuotaKey::App("app".into()),
+        )
+    }
+
+    #[test]
+    fn concurrent_calls_are_limited() {
+        let (quotas, key) = quotas(ZomeCallQuota {
+            max_concurrent_calls: Some(2),
+            ..Default::default()
+        });
+        let a = quotas.admit(key.clone()).unwrap();
+        let _b = quotas.admit(key.clone()).unwrap();
+        assert_matches!(
+            quotas.admit(key.clone()),
+            Err(ZomeCallQuotaError::ConcurrentCalls(_, 2))
+        );
+        drop(a);
+        quotas.admit(key).unwrap();
+    }
+
+    #[test]
+    fn calls_per_second_are_limited() {
+        let (quotas, key) = quotas(ZomeCallQuota {
+            calls_per_second: Some(3),
+            ..Default::default()
+        });
+        for _ in 0..3 {
+            quotas.admit(key.clone()).unwrap();
+        }
+        assert_matches!(
+            quotas.admit(key.clone()),
+            Err(ZomeCallQuotaError::CallsPerSecond(_, 3))
+        );
+
+        // Other keys have their own counters
+        quotas
+            .admit(ZomeCallQuotaKey::App("other app".into()))
+            .unwrap();
+        // Remote agents are unlimited by default
+        quotas
+            .admit(ZomeCallQuotaKey::RemoteAgent(fixt!(AgentPubKey)))
+            .unwrap();
+    }
+
+    #[test]
+    fn idle_usage_is_pruned() {
+        let (quotas, key) = quotas(ZomeCallQuota {
+            calls_per_second: Some(10),
+            max_concurrent_calls: Some(10),
+            ..Default::default()
+        });
+        let permit = quotas.admit(key.clone()).unwrap();
+        drop(permit);
+        // - The call is still counted in the current window
+        assert!(quotas.usage.lock().by_key.contains_key(&key));
+
+        std::thread::sleep(RATE_WINDOW);
+        let other = ZomeCallQuotaKey::App("other app".into());
+        let permit = quotas.admit(other.clone()).unwrap();
+        assert!(!quotas.usage.lock().by_key.contains_key(&key));
+
+        // - A running call is never pruned
+        std::thread::sleep(RATE_WINDOW);
+        quotas.admit(key.clone()).unwrap();
+        assert!(quotas.usage.lock().by_key.contains_key(&other));
+        drop(permit);
+        assert!(!quotas.usage.lock().by_key.contains_key(&other));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn long_calls_are_abandoned() {
+        let (quotas, key) = quotas(ZomeCallQuota {
+            max_call_duration_ms: Some(10),
+            ..Default::default()
+        });
+        let permit = quotas.admit(key.clone()).unwrap();
+        assert_matches!(permit.run(async { 1 }).await, Ok(1));
+        let permit = quotas.admit(key).unwrap();
+        assert_matches!(
+            permit
+                .run(tokio::time::sleep(Duration::from_millis(500)))
+                .await,
+            Err(ZomeCallQuotaError::CallDuration(_, _))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn abandoned_calls_count_until_they_finish() {
+        let (quotas, key) = quotas(ZomeCallQuota {
+            max_concurrent_calls: Some(1),
+            max_call_duration_ms: Some(10),
+            ..Default::default()
+        });
+        let (finish, finished) = tokio::sync::oneshot::channel::<()>();
+        let permit = quotas.admit(key.clone()).unwrap();
+        assert_matches!(
+            permit
+                .run(async move {
+                    let _ = finished.await;
+                })
+                .await,
+            Err(ZomeCallQuotaError::CallDuration(_, _))
+        );
+        // - The abandoned call is still running
+        assert_matches!(
+            quotas.admit(key.clone()),
+            Err(ZomeCallQuotaError::ConcurrentCalls(_, 1))
+        );
+
+        finish.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        quotas.admit(key).unwrap();
+    }
+}
@@ -11,7 +11,10 @@
 pub mod error;
 pub mod guest_callback;
 pub mod host_fn;
+pub mod memory_limit;
+pub mod module_cache;
 pub mod real_ribosome;
+pub mod wasm_metrics;
 
 use crate::conductor::api::CellConductorHandle;
 use crate::conductor::api::CellConductorReadHandle;
@@ -26,7 +29,6 @@ use crate::core::ribosome::guest_callback::post_commit::PostCommitInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
 use crate::core::ribosome::guest_callback::CallIterator;
-use derive_more::Constructor;
 use error::RibosomeResult;
 use guest_callback::entry_defs::EntryDefsHostAccess;
 use guest_callback::init::InitHostAccess;
@@ -43,6 +45,7 @@ use holochain_state::nonce::*;
 use holochain_types::prelude::*;
 use holochain_types::zome_types::GlobalZomeTypes;
 use mockall::automock;
+use std::collections::BTreeMap;
 use std::iter::Iterator;
 use std::sync::Arc;
 
@@ -60,6 +63,8 @@ pub struct CallContext {
     pub(crate) function_name: FunctionName,
     pub(crate) auth: InvocationAuth,
     pub(crate) host_context: HostContext,
+    pub(crate) deadline: Option<Timestamp>,
+    pub(crate) max_sleep: std::time::Duration,
 }
 
 impl CallContext {
@@ -74,6 +79,8 @@ impl CallContext {
             function_name,
             host_context,
             auth,
+            deadline: None,
+            max_sleep: host_fn::sleep::DEFAULT_MAX_SLEEP,
         }
     }
 
@@ -92,6 +99,17 @@ impl CallContext {
     pub fn auth(&self) -> InvocationAuth {
         self.auth.clone()
     }
+
+    /// The time after which the result of this call will no longer be respected,
+    /// if there is one.
+    pub fn deadline(&self) -> Option<Timestamp> {
+        self.deadline
+    }
+
+    /// The longest a single call to the `sleep` host function may sleep for.
+    pub fn max_sleep(&self) -> std::time::Duration {
+        self.max_sleep
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -237,6 +255,38 @@ impl FnComponents {
     }
 }
 
+/// The externs the host calls as callbacks rather than as zome functions.
+pub const CALLBACKS: [&str; 8] = [
+    "entry_defs",
+    "genesis_self_check",
+    "init",
+    "migrate_agent",
+    "migrate_agent_open",
+    "migrate_agent_close",
+    "post_commit",
+    "validate",
+];
+
+/// Is an extern with this name called by the host as a callback?
+pub fn is_callback(fn_name: &FunctionName) -> bool {
+    CALLBACKS.contains(&fn_name.as_ref())
+}
+
+fn filter_externs(
+    externs: &BTreeMap<ZomeName, Vec<ZomeFnInfo>>,
+    f: impl Fn(&ZomeFnInfo) -> bool,
+) -> BTreeMap<ZomeName, Vec<ZomeFnInfo>> {
+    externs
+        .iter()
+        .map(|(zome_name, fns)| {
+            (
+                zome_name.clone(),
+                fns.iter().filter(|info| f(info)).cloned().collect(),
+            )
+        })
+        .collect()
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(test, derive(arbitrary::Arbitrary))]
 pub enum ZomesToInvoke {
@@ -304,6 +354,11 @@ pub trait Invocation: Clone {
     /// careful about cloning invocations
     fn host_input(self) -> Result<ExternIO, SerializedBytesError>;
     fn auth(&self) -> InvocationAuth;
+    /// The time after which the caller will no longer respect the result
+    /// of this invocation. Callbacks run by the conductor have no deadline.
+    fn deadline(&self) -> Option<Timestamp> {
+        None
+    }
 }
 
 impl ZomeCallInvocation {
@@ -447,6 +502,9 @@ impl Invocation for ZomeCallInvocation {
     fn auth(&self) -> InvocationAuth {
         InvocationAuth::Cap(self.provenance.clone(), self.cap_secret)
     }
+    fn deadline(&self) -> Option<Timestamp> {
+        Some(self.expires_at)
+    }
 }
 
 impl ZomeCallInvocation {
@@ -509,13 +567,41 @@ impl From<ZomeCallInvocation> for ZomeCall {
     }
 }
 
-#[derive(Clone, Constructor)]
+#[derive(Clone)]
 pub struct ZomeCallHostAccess {
     pub workspace: HostFnWorkspace,
     pub keystore: MetaLairClient,
     pub network: HolochainP2pDna,
     pub signal_tx: SignalBroadcaster,
     pub call_zome_handle: CellConductorReadHandle,
+    /// The most fuel this call may use, if it is lower than the fuel limit
+    /// configured for zome calls.
+    pub fuel_limit: Option<u64>,
+}
+
+impl ZomeCallHostAccess {
+    pub fn new(
+        workspace: HostFnWorkspace,
+        keystore: MetaLairClient,
+        network: HolochainP2pDna,
+        signal_tx: SignalBroadcaster,
+        call_zome_handle: CellConductorReadHandle,
+    ) -> Self {
+        Self {
+            workspace,
+            keystore,
+            network,
+            signal_tx,
+            call_zome_handle,
+            fuel_limit: None,
+        }
+    }
+
+    /// Limit the fuel this call may use
+    pub fn with_fuel_limit(mut self, fuel_limit: Option<u64>) -> Self {
+        self.fuel_limit = fuel_limit;
+        self
+    }
 }
 
 impl std::fmt::Debug for ZomeCallHostAccess {
@@ -604,18 +690,18 @@ pub trait RibosomeT: Sized + std::fmt::Debug + Send + Sync {
     /// values without needing to make holochain a dependency.
     fn get_const_fn(&self, zome: &Zome, name: &str) -> Result<Option<i32>, RibosomeError>;
 
-    /// @todo list out all the available callbacks and maybe cache them somewhere
-    fn list_callbacks(&self) {
-        unimplemented!()
-        // pseudocode
-        // self.instance().exports().filter(|e| e.is_callback())
+    /// List every extern exported by each zome of this DNA,
+    /// both the zome functions and the callbacks.
+    fn list_externs(&self) -> RibosomeResult<Arc<BTreeMap<ZomeName, Vec<ZomeFnInfo>>>>;
+
+    /// List the callbacks implemented by each zome of this DNA.
+    fn list_callbacks(&self) -> RibosomeResult<BTreeMap<ZomeName, Vec<ZomeFnInfo>>> {
+        Ok(filter_externs(&self.list_externs()?, |f| f.is_callback))
     }
 
-    /// @todo list out all the available zome functions and maybe cache them somewhere
-    fn list_zome_fns(&self) {
-        unimplemented!()
-        // pseudocode
-        // self.instance().exports().filter(|e| !e.is_callback())
+    /// List the zome functions exposed by each zome of this DNA.
+    fn list_zome_fns(&self) -> RibosomeResult<BTreeMap<ZomeName, Vec<ZomeFnInfo>>> {
+        Ok(filter_externs(&self.list_externs()?, |f| !f.is_callback))
     }
 
     fn run_genesis_self_check(
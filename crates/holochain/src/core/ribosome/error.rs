@@ -94,6 +94,25 @@ pub enum RibosomeError {
 
     #[error(transparent)]
     ZomeTypesError(#[from] holochain_types::zome_types::ZomeTypesError),
+
+    /// A wasm function used up all of the fuel it was allowed.
+    #[error("Zome {0} function {1} ran out of fuel after {2} operations")]
+    WasmFuelExhausted(ZomeName, FunctionName, u64),
+
+    /// A wasm function tried to grow the guest memory past its limit.
+    #[error("Zome {0} function {1} ran out of memory at {2} bytes, its limit is {3} bytes")]
+    WasmMemoryLimitExceeded(ZomeName, FunctionName, u64, u64),
+}
+
+impl RibosomeError {
+    /// Whether this error means a wasm function exceeded its fuel or memory
+    /// limit. These errors are deterministic for a given input and limits.
+    pub fn is_wasm_limit_exceeded(&self) -> bool {
+        matches!(
+            self,
+            Self::WasmFuelExhausted(..) | Self::WasmMemoryLimitExceeded(..)
+        )
+    }
 }
 
 /// Type alias
@@ -26,6 +26,16 @@ impl PostCommitInvocation {
     pub fn new(zome: CoordinatorZome, actions: Vec<SignedActionHashed>) -> Self {
         Self { zome, actions }
     }
+
+    /// The zome whose `post_commit` callback is invoked
+    pub fn zome(&self) -> &CoordinatorZome {
+        &self.zome
+    }
+
+    /// The committed actions passed to `post_commit`
+    pub fn actions(&self) -> &[SignedActionHashed] {
+        &self.actions
+    }
 }
 
 #[derive(Clone, Constructor)]
@@ -90,31 +100,57 @@ pub async fn send_post_commit(
     actions: Vec<SignedActionHashed>,
     zomes: Vec<CoordinatorZome>,
 ) -> Result<(), tokio::sync::mpsc::error::SendError<()>> {
-    let cell_id = workspace.source_chain().cell_id();
-
     for zome in zomes {
-        conductor_handle
-            .post_commit_permit()
-            .await?
-            .send(PostCommitArgs {
-                host_access: PostCommitHostAccess {
-                    workspace: workspace.clone().into(),
-                    keystore: keystore.clone(),
-                    network: network.clone(),
-                    signal_tx: conductor_handle.signal_broadcaster(),
-                },
-                invocation: PostCommitInvocation::new(zome, actions.clone()),
-                cell_id: cell_id.clone(),
-            });
+        send_post_commit_attempt(
+            &conductor_handle,
+            &workspace,
+            &network,
+            &keystore,
+            actions.clone(),
+            zome,
+            1,
+        )
+        .await?;
     }
     Ok(())
 }
 
+/// Send a single `post_commit` invocation. `attempt` counts from 1 and is
+/// greater than 1 when a failed invocation is being retried.
+pub async fn send_post_commit_attempt(
+    conductor_handle: &ConductorHandle,
+    workspace: &SourceChainWorkspace,
+    network: &HolochainP2pDna,
+    keystore: &MetaLairClient,
+    actions: Vec<SignedActionHashed>,
+    zome: CoordinatorZome,
+    attempt: u32,
+) -> Result<(), tokio::sync::mpsc::error::SendError<()>> {
+    let cell_id = workspace.source_chain().cell_id();
+    conductor_handle
+        .post_commit_permit()
+        .await?
+        .send(PostCommitArgs {
+            host_access: PostCommitHostAccess {
+                workspace: workspace.clone().into(),
+                keystore: keystore.clone(),
+                network: network.clone(),
+                signal_tx: conductor_handle.signal_broadcaster(),
+            },
+            invocation: PostCommitInvocation::new(zome, actions),
+            cell_id,
+            attempt,
+        });
+    Ok(())
+}
+
 #[derive(Clone)]
 pub struct PostCommitArgs {
     pub host_access: PostCommitHostAccess,
     pub invocation: PostCommitInvocation,
     pub cell_id: CellId,
+    /// Which attempt at running this invocation this is, starting at 1
+    pub attempt: u32,
 }
 
 #[cfg(test)]
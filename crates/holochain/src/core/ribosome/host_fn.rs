@@ -80,14 +80,14 @@ host_fn_api_impls! {
 
     fn call (Vec<zt::call::Call>) -> Vec<zt::ZomeCallResponse>;
 
-    // @todo List all the local capability claims.
-    fn capability_claims (()) -> ();
+    // List the live capability claims of the local source chain.
+    fn capability_claims (zt::capability::CapClaimQuery) -> Vec<zt::capability::CapClaim>;
 
-    // @todo List all the local capability grants.
-    fn capability_grants (()) -> ();
+    // List the live capability grants of the local source chain.
+    fn capability_grants (zt::capability::CapGrantQuery) -> Vec<zt::capability::CapGrantInfo>;
 
-    // @todo Get the capability for the current zome call.
-    fn capability_info (()) -> ();
+    // Get the capability for the current zome call.
+    fn capability_info (()) -> zt::capability::CapabilityInfo;
 
     // The EntryDefId determines how a create is handled on the host side.
     // CapGrant and CapClaim are handled natively.
@@ -147,6 +147,8 @@ host_fn_api_impls! {
 
     fn get_details (Vec<zt::entry::GetInput>) -> Vec<Option<zt::metadata::Details>>;
 
+    fn get_meta (Vec<zt::entry::GetMetaInput>) -> Vec<Option<zt::metadata::MetadataSet>>;
+
     // Get links by entry hash from the cascade.
     fn get_links (Vec<zt::link::GetLinksInput>) -> Vec<Vec<zt::link::Link>>;
 
@@ -103,7 +103,7 @@ pub fn call(
                                 }
                             }
                             CallTarget::ConductorCell(target_cell) => {
-                                let cell_id_result: Result<CellId, RuntimeError> = match target_cell
+                                let cell_id_result: Result<(CellId, Option<CapSecret>), RuntimeError> = match target_cell
                                 {
                                     CallTargetCell::OtherRole(role_name) => {
                                         let this_cell_id = call_context
@@ -123,7 +123,7 @@ pub fn call(
                                                 wasm_error!(e).into()
                                             })
                                             .and_then(|c| {
-                                                c.ok_or_else(|| {
+                                                c.map(|cell_id| (cell_id, cap_secret)).ok_or_else(|| {
                                                     RuntimeError::from(wasm_error!(
                                                         WasmErrorInner::Host(
                                                             "Role not found.".to_string()
@@ -132,15 +132,47 @@ pub fn call(
                                                 })
                                             })
                                     }
-                                    CallTargetCell::OtherCell(cell_id) => Ok(cell_id),
-                                    CallTargetCell::Local => Ok(call_context
+                                    CallTargetCell::Dependency(dependency_name) => {
+                                        let this_cell_id = call_context
+                                            .host_context()
+                                            .call_zome_handle()
+                                            .cell_id()
+                                            .clone();
+                                        call_context
+                                            .host_context()
+                                            .call_zome_handle()
+                                            .find_cell_with_app_dependency(
+                                                &this_cell_id,
+                                                &dependency_name,
+                                            )
+                                            .await
+                                            .map_err(|e| -> RuntimeError {
+                                                wasm_error!(e).into()
+                                            })
+                                            .and_then(|c| {
+                                                // An explicitly given secret takes precedence
+                                                // over the one granted at install time.
+                                                c.map(|(cell_id, granted_secret)| {
+                                                    (cell_id, cap_secret.or(granted_secret))
+                                                })
+                                                .ok_or_else(|| {
+                                                    RuntimeError::from(wasm_error!(
+                                                        WasmErrorInner::Host(
+                                                            "App dependency not found.".to_string()
+                                                        )
+                                                    ))
+                                                })
+                                            })
+                                    }
+                                    CallTargetCell::OtherCell(cell_id) => Ok((cell_id, cap_secret)),
+                                    CallTargetCell::Local => Ok((call_context
                                         .host_context()
                                         .call_zome_handle()
                                         .cell_id()
-                                        .clone()),
+                                        .clone(), cap_secret)),
                                 };
                                 match cell_id_result {
-                                    Ok(cell_id) => {
+                                    Ok((cell_id, cap_secret)) => {
                                         let zome_call_unsigned = ZomeCallUnsigned {
                                             cell_id,
                                             zome_name,
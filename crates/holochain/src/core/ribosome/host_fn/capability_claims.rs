@@ -1,13 +1,41 @@
+use super::capability_grants::live_records;
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
-use std::sync::Arc;
+use holochain_types::prelude::*;
 use holochain_wasmer_host::prelude::*;
+use std::sync::Arc;
 
 /// lists all the local claims filtered by tag
 pub fn capability_claims(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: (),
-) -> Result<(), RuntimeError> {
-    unimplemented!();
+    call_context: Arc<CallContext>,
+    input: CapClaimQuery,
+) -> Result<Vec<CapClaim>, RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            read_workspace: Permission::Allow,
+            ..
+        } => Ok(live_records(&call_context, EntryType::CapClaim)?
+            .iter()
+            .filter_map(|record| record.entry().to_claim_option())
+            .filter(|claim| input.tag.as_ref().map_or(true, |tag| claim.tag() == tag))
+            .filter(|claim| {
+                input
+                    .grantor
+                    .as_ref()
+                    .map_or(true, |grantor| claim.grantor() == grantor)
+            })
+            .collect()),
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "capability_claims".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
 }
@@ -1,16 +1,94 @@
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
+use holochain_types::prelude::*;
 use holochain_wasmer_host::prelude::*;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 /// list all the grants stored locally in the chain filtered by tag
 /// this is only the current grants as per local CRUD
 pub fn capability_grants(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: (),
-) -> Result<(), RuntimeError> {
-    unimplemented!();
+    call_context: Arc<CallContext>,
+    input: CapGrantQuery,
+) -> Result<Vec<CapGrantInfo>, RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            read_workspace: Permission::Allow,
+            ..
+        } => Ok(live_records(&call_context, EntryType::CapGrant)?
+            .into_iter()
+            .filter_map(|record| {
+                let cap_grant = record.entry().to_grant_option()?;
+                Some(CapGrantInfo {
+                    cap_grant,
+                    action_hash: record.action_address().clone(),
+                    created_at: record.action().timestamp(),
+                })
+            })
+            .filter(|info| {
+                input
+                    .tag
+                    .as_ref()
+                    .map_or(true, |tag| &info.cap_grant.tag == tag)
+            })
+            .collect()),
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "capability_grants".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
+}
+
+/// The records of an entry type on the source chain, including those written
+/// in the current call, which have been neither deleted nor updated.
+pub(crate) fn live_records(
+    call_context: &CallContext,
+    entry_type: EntryType,
+) -> Result<Vec<Record>, RuntimeError> {
+    let workspace = call_context.host_context.workspace();
+    tokio_helper::block_forever_on(async move {
+        let source_chain = workspace
+            .source_chain()
+            .as_ref()
+            .expect("Must have source chain to query the source chain");
+        let records = source_chain
+            .query(
+                ChainQueryFilter::new()
+                    .entry_type(entry_type)
+                    .include_entries(true),
+            )
+            .await
+            .map_err(|e| -> RuntimeError {
+                wasm_error!(WasmErrorInner::Host(e.to_string())).into()
+            })?;
+        let deletes = source_chain
+            .query(ChainQueryFilter::new().action_type(ActionType::Delete))
+            .await
+            .map_err(|e| -> RuntimeError {
+                wasm_error!(WasmErrorInner::Host(e.to_string())).into()
+            })?;
+        let replaced: HashSet<ActionHash> = records
+            .iter()
+            .chain(deletes.iter())
+            .filter_map(|record| match record.action() {
+                Action::Update(update) => Some(update.original_action_address.clone()),
+                Action::Delete(delete) => Some(delete.deletes_address.clone()),
+                _ => None,
+            })
+            .collect();
+        Ok(records
+            .into_iter()
+            .filter(|record| !replaced.contains(record.action_address()))
+            .collect())
+    })
 }
 
 #[cfg(test)]
@@ -58,6 +136,110 @@ pub mod wasm_test {
         assert_eq!(entry_secret, secret);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_capability_grants_exclude_replaced_grants() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::Capability).await;
+
+        // - The unrestricted grant from init
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "list_cap_grants", CapGrantQuery::default())
+            .await;
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].cap_grant.access, CapAccess::Unrestricted);
+
+        let secret: CapSecret = conductor.call(&alice, "cap_secret", ()).await;
+        let action_hash: ActionHash = conductor
+            .call(&alice, "transferable_cap_grant", secret)
+            .await;
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "list_cap_grants", CapGrantQuery::default())
+            .await;
+        assert_eq!(grants.len(), 2);
+
+        // - An updated grant is only listed in its new version
+        let new_action_hash: ActionHash =
+            conductor.call(&alice, "roll_cap_grant", action_hash).await;
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "list_cap_grants", CapGrantQuery::default())
+            .await;
+        assert_eq!(grants.len(), 2);
+        assert!(grants
+            .iter()
+            .any(|info| info.action_hash == new_action_hash));
+        assert!(!grants
+            .iter()
+            .any(|info| info.cap_grant.access.secret() == Some(&secret)));
+
+        // - A grant revoked by its secret is no longer listed
+        let new_secret = grants
+            .iter()
+            .find_map(|info| info.cap_grant.access.secret().cloned())
+            .unwrap();
+        let revoked: Option<ActionHash> = conductor.call(&alice, "revoke_grant", new_secret).await;
+        assert!(revoked.is_some());
+        let revoked: Option<ActionHash> = conductor.call(&alice, "revoke_grant", new_secret).await;
+        assert!(revoked.is_none());
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(
+                &alice,
+                "list_cap_grants",
+                CapGrantQuery {
+                    tag: Some("".into()),
+                },
+            )
+            .await;
+        assert_eq!(grants.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_capability_claims_and_info() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor,
+            alice,
+            alice_pubkey,
+            bob,
+            bob_pubkey,
+            ..
+        } = RibosomeTestFixture::new(TestWasm::Capability).await;
+
+        let _: () = conductor
+            .call(&bob, "send_assigned_cap_claim", alice_pubkey.clone())
+            .await;
+
+        let claims: Vec<CapClaim> = conductor
+            .call(
+                &alice,
+                "list_cap_claims",
+                CapClaimQuery {
+                    tag: None,
+                    grantor: Some(bob_pubkey.clone()),
+                },
+            )
+            .await;
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].tag(), "has_cap_claim");
+        let claims: Vec<CapClaim> = conductor
+            .call(
+                &alice,
+                "list_cap_claims",
+                CapClaimQuery {
+                    tag: Some("other".into()),
+                    grantor: None,
+                },
+            )
+            .await;
+        assert!(claims.is_empty());
+
+        // - A call by the author is authorized by the author grant
+        let info: CapabilityInfo = conductor.call(&alice, "cap_info", ()).await;
+        assert_eq!(info.provenance, alice_pubkey);
+        assert_eq!(info.cap_grant, CapGrant::ChainAuthor(alice_pubkey));
+    }
+
     // MAYBE: [ B-03669 ] can move this to an integration test (may need to switch to using a RibosomeStore)
     #[tokio::test(flavor = "multi_thread")]
     async fn ribosome_authorized_call() -> anyhow::Result<()> {
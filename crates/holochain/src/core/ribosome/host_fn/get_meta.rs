@@ -0,0 +1,102 @@
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
+use crate::core::ribosome::RibosomeT;
+use futures::future::join_all;
+use holochain_cascade::Cascade;
+use holochain_p2p::actor::GetMetaOptions;
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use std::sync::Arc;
+
+#[allow(clippy::extra_unused_lifetimes)]
+pub fn get_meta<'a>(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    inputs: Vec<GetMetaInput>,
+) -> Result<Vec<Option<MetadataSet>>, RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            read_workspace: Permission::Allow,
+            ..
+        } => {
+            let results: Vec<Result<Option<MetadataSet>, _>> =
+                tokio_helper::block_forever_on(async move {
+                    join_all(inputs.into_iter().map(|input| async {
+                        let GetMetaInput {
+                            any_dht_hash,
+                            request,
+                        } = input;
+                        let options = GetMetaOptions {
+                            metadata_request: request,
+                            ..Default::default()
+                        };
+                        Cascade::from_workspace_and_network(
+                            &call_context.host_context.workspace(),
+                            call_context.host_context.network().to_owned(),
+                        )
+                        .get_meta(any_dht_hash, options)
+                        .await
+                    }))
+                    .await
+                });
+            let results: Result<Vec<_>, _> = results
+                .into_iter()
+                .map(|result| {
+                    result.map_err(|cascade_error| {
+                        wasm_error!(WasmErrorInner::Host(cascade_error.to_string()))
+                    })
+                })
+                .collect();
+            Ok(results?)
+        }
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "get_meta".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "slow_tests")]
+pub mod wasm_test {
+    use crate::core::ribosome::wasm_test::RibosomeTestFixture;
+    use hdk::prelude::*;
+    use holochain_wasm_test_utils::TestWasm;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_get_meta_test() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::Create).await;
+
+        let action_hash: ActionHash = conductor.call(&alice, "create_entry", ()).await;
+
+        let meta: Option<MetadataSet> = conductor.call(&alice, "get_entry_meta", ()).await;
+        let meta = meta.unwrap();
+        assert_eq!(meta.entry_dht_status, Some(EntryDhtStatus::Live));
+        assert!(meta
+            .actions
+            .iter()
+            .any(|timed| timed.action_hash == action_hash));
+        assert!(meta.deletes.is_empty());
+
+        let _: ActionHash = conductor
+            .call(&alice, "delete_post", action_hash.clone())
+            .await;
+
+        let meta: Option<MetadataSet> = conductor.call(&alice, "get_entry_meta", ()).await;
+        let meta = meta.unwrap();
+        assert_eq!(meta.entry_dht_status, Some(EntryDhtStatus::Dead));
+        assert_eq!(meta.deletes.len(), 1);
+
+        let meta: Option<MetadataSet> = conductor.call(&alice, "get_post_meta", action_hash).await;
+        assert_eq!(meta.unwrap().deletes.len(), 1);
+    }
+}
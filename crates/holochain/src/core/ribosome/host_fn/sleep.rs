@@ -1,12 +1,166 @@
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
-use std::sync::Arc;
+use holochain_types::access::Permission;
 use holochain_wasmer_host::prelude::*;
+use holochain_zome_types::Timestamp;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The longest a single call to `sleep` may sleep for, unless the
+/// conductor's wasm limits set `max_sleep_ms`.
+pub const DEFAULT_MAX_SLEEP: Duration = Duration::from_secs(10);
 
 pub fn sleep(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: core::time::Duration,
+    call_context: Arc<CallContext>,
+    input: core::time::Duration,
 ) -> Result<(), RuntimeError> {
-    unimplemented!()
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            non_determinism: Permission::Allow,
+            ..
+        } => {
+            // The thread and the wasm instance are held for the whole sleep.
+            let duration = input.min(call_context.max_sleep());
+            // There is no point sleeping past the time the caller will still
+            // respect the result of the call, so fail early instead.
+            if let Some(deadline) = call_context.deadline() {
+                if (Timestamp::now() + duration).unwrap_or(Timestamp::MAX) > deadline {
+                    return Err(wasm_error!(WasmErrorInner::Host(format!(
+                        "Cannot sleep for {:?} as the zome call expires at {}",
+                        duration, deadline
+                    )))
+                    .into());
+                }
+            }
+            // The wasm is suspended on this thread, but blocking in place lets
+            // the runtime move its other tasks elsewhere while we wait.
+            tokio_helper::block_forever_on(tokio::time::sleep(duration));
+            Ok(())
+        }
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "sleep".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "slow_tests")]
+pub mod wasm_test {
+    use super::sleep;
+    use crate::core::ribosome::wasm_test::RibosomeTestFixture;
+    use crate::core::ribosome::HostContext;
+    use crate::fixt::CallContextFixturator;
+    use crate::fixt::EntryDefsHostAccessFixturator;
+    use crate::fixt::MigrateAgentHostAccessFixturator;
+    use crate::fixt::RealRibosomeFixturator;
+    use crate::fixt::ValidateHostAccessFixturator;
+    use crate::sweettest::SweetConductor;
+    use crate::sweettest::SweetDnaFile;
+    use crate::sweettest::SweetZome;
+    use ::fixt::prelude::*;
+    use holochain_conductor_api::conductor::WasmCallLimits;
+    use holochain_conductor_api::conductor::WasmLimits;
+    use holochain_conductor_api::conductor::WasmLimitsConfig;
+    use holochain_wasm_test_utils::TestWasm;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    /// A conductor running the `SysTime` test wasm with this sleep limit
+    /// for zome calls.
+    async fn conductor_with_max_sleep(max_sleep: Duration) -> (SweetConductor, SweetZome) {
+        let mut config = crate::sweettest::standard_config();
+        config.wasm_limits = Some(WasmLimitsConfig {
+            default: WasmLimits {
+                zome_call: WasmCallLimits {
+                    max_sleep_ms: Some(max_sleep.as_millis() as u64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut conductor = SweetConductor::from_config(config).await;
+        let (dna_file, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::SysTime]).await;
+        let app = conductor.setup_app("app", [&dna_file]).await.unwrap();
+        let alice = app.cells()[0].zome(TestWasm::SysTime);
+        (conductor, alice)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sleep_is_denied_in_deterministic_contexts() {
+        let ribosome = Arc::new(
+            RealRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+                .next()
+                .unwrap(),
+        );
+        for host_context in [
+            HostContext::Validate(fixt!(ValidateHostAccess)),
+            HostContext::EntryDefs(fixt!(EntryDefsHostAccess)),
+            HostContext::MigrateAgent(fixt!(MigrateAgentHostAccess)),
+        ] {
+            let mut call_context = CallContextFixturator::new(::fixt::Unpredictable)
+                .next()
+                .unwrap();
+            call_context.host_context = host_context;
+
+            let started = Instant::now();
+            let result = sleep(
+                ribosome.clone(),
+                Arc::new(call_context),
+                Duration::from_secs(1),
+            );
+            assert!(result.is_err());
+            assert!(started.elapsed() < Duration::from_secs(1));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sleep_is_cut_short_to_the_configured_maximum() {
+        observability::test_run().ok();
+        let (conductor, alice) = conductor_with_max_sleep(Duration::from_millis(100)).await;
+
+        let started = Instant::now();
+        let _: () = conductor
+            .call(&alice, "sleep", Duration::from_secs(30))
+            .await;
+        assert!(started.elapsed() < Duration::from_secs(30));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn invoke_import_sleep_test() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::SysTime).await;
+
+        let started = Instant::now();
+        let _: () = conductor
+            .call(&alice, "sleep", Duration::from_millis(500))
+            .await;
+        assert!(started.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sleep_past_deadline_fails() {
+        observability::test_run().ok();
+        // Allow sleeping for longer than the zome call is valid for.
+        let (conductor, alice) = conductor_with_max_sleep(Duration::from_secs(60 * 60 * 24)).await;
+
+        let started = Instant::now();
+        let result: Result<(), _> = conductor
+            .call_fallible(&alice, "sleep", Duration::from_secs(60 * 60 * 24))
+            .await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
 }
@@ -0,0 +1,204 @@
+//! Caps the linear memory of wasm instances when the memory is created, so
+//! a guest can't grow its memory past the configured limit.
+//!
+//! Memories are created by the tunables of the store a module belongs to,
+//! so a module is re-created in a store with [`LimitingTunables`] for each
+//! limit it is instantiated with. The most recently used of these modules
+//! are kept for reuse.
+//!
+//! A capped memory records when it refuses to grow, so a failed call can be
+//! told apart from one which ran out of memory, see [`track_limit`].
+
+use super::RibosomeResult;
+use holochain_wasmer_host::prelude::*;
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::cell::Cell;
+use std::num::NonZeroUsize;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+/// How many memory capped modules are kept for reuse.
+const LIMITED_MODULES_CAPACITY: usize = 128;
+
+/// Modules whose memories are capped, keyed by wasm cache key and limit.
+static LIMITED_MODULES: Lazy<Mutex<LruCache<([u8; 32], Pages), Arc<Module>>>> = Lazy::new(|| {
+    Mutex::new(LruCache::new(
+        NonZeroUsize::new(LIMITED_MODULES_CAPACITY).expect("capacity is not zero"),
+    ))
+});
+
+thread_local! {
+    /// Set when a capped memory refuses to grow on this thread. Wasm runs
+    /// on the thread which calls it, so this is set by the call in progress.
+    static LIMIT_HIT: Cell<bool> = Cell::new(false);
+}
+
+/// Run a wasm call, also returning whether a capped memory refused to grow
+/// during the call.
+pub fn track_limit<R>(call: impl FnOnce() -> R) -> (R, bool) {
+    LIMIT_HIT.with(|hit| hit.set(false));
+    let result = call();
+    (result, LIMIT_HIT.with(|hit| hit.replace(false)))
+}
+
+/// The whole number of wasm pages which fit in this many bytes.
+pub fn pages_for_bytes(max_memory_bytes: u64) -> Pages {
+    Pages((max_memory_bytes / WASM_PAGE_SIZE as u64).min(u32::MAX as u64) as u32)
+}
+
+/// The module compiled for this key, in a store which caps the maximum size
+/// of its memory at `limit`.
+pub fn limited_module(key: [u8; 32], module: &Module, limit: Pages) -> RibosomeResult<Arc<Module>> {
+    let mut modules = LIMITED_MODULES.lock();
+    if let Some(module) = modules.get(&(key, limit)) {
+        return Ok(module.clone());
+    }
+    let engine = module.store().engine();
+    let store = Store::new_with_tunables(
+        engine.as_ref(),
+        LimitingTunables {
+            limit,
+            base: BaseTunables::for_target(engine.target()),
+        },
+    );
+    let compile_error =
+        |e: String| -> RuntimeError { wasm_error!(WasmErrorInner::Compile(e)).into() };
+    let bytes = module
+        .serialize()
+        .map_err(|e| compile_error(e.to_string()))?;
+    // Safety: the artifact was just serialized by the same engine.
+    let limited = Arc::new(
+        unsafe { Module::deserialize(&store, bytes.as_slice()) }
+            .map_err(|e| compile_error(e.to_string()))?,
+    );
+    modules.put((key, limit), limited.clone());
+    Ok(limited)
+}
+
+/// Tunables which lower the maximum of every memory to a limit.
+struct LimitingTunables {
+    limit: Pages,
+    base: BaseTunables,
+}
+
+impl LimitingTunables {
+    fn adjust_memory(&self, requested: &MemoryType) -> MemoryType {
+        let mut adjusted = *requested;
+        adjusted.maximum = Some(
+            requested
+                .maximum
+                .map_or(self.limit, |maximum| maximum.min(self.limit)),
+        );
+        adjusted
+    }
+
+    fn validate_memory(&self, ty: &MemoryType) -> Result<(), MemoryError> {
+        if ty.minimum > self.limit {
+            return Err(MemoryError::Generic(format!(
+                "The initial memory of {} pages is over the limit of {} pages",
+                ty.minimum.0, self.limit.0
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl loupe::MemoryUsage for LimitingTunables {
+    fn size_of_val(&self, _: &mut dyn loupe::MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+impl Tunables for LimitingTunables {
+    fn memory_style(&self, memory: &MemoryType) -> vm::MemoryStyle {
+        self.base.memory_style(&self.adjust_memory(memory))
+    }
+
+    fn table_style(&self, table: &TableType) -> vm::TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &vm::MemoryStyle,
+    ) -> Result<Arc<dyn vm::Memory>, MemoryError> {
+        let adjusted = self.adjust_memory(ty);
+        self.validate_memory(&adjusted)?;
+        Ok(Arc::new(LimitedMemory(
+            self.base.create_host_memory(&adjusted, style)?,
+        )))
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &vm::MemoryStyle,
+        vm_definition_location: NonNull<vm::VMMemoryDefinition>,
+    ) -> Result<Arc<dyn vm::Memory>, MemoryError> {
+        let adjusted = self.adjust_memory(ty);
+        self.validate_memory(&adjusted)?;
+        Ok(Arc::new(LimitedMemory(self.base.create_vm_memory(
+            &adjusted,
+            style,
+            vm_definition_location,
+        )?)))
+    }
+
+    fn create_host_table(
+        &self,
+        ty: &TableType,
+        style: &vm::TableStyle,
+    ) -> Result<Arc<dyn vm::Table>, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &vm::TableStyle,
+        vm_definition_location: NonNull<vm::VMTableDefinition>,
+    ) -> Result<Arc<dyn vm::Table>, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
+
+/// A memory created by [`LimitingTunables`], which notes when it can't grow.
+#[derive(Debug)]
+struct LimitedMemory(Arc<dyn vm::Memory>);
+
+impl loupe::MemoryUsage for LimitedMemory {
+    fn size_of_val(&self, tracker: &mut dyn loupe::MemoryUsageTracker) -> usize {
+        std::mem::size_of_val(self) + self.0.size_of_val(tracker)
+    }
+}
+
+impl vm::Memory for LimitedMemory {
+    fn ty(&self) -> MemoryType {
+        self.0.ty()
+    }
+
+    fn style(&self) -> &vm::MemoryStyle {
+        self.0.style()
+    }
+
+    fn size(&self) -> Pages {
+        self.0.size()
+    }
+
+    fn grow(&self, delta: Pages) -> Result<Pages, MemoryError> {
+        let result = self.0.grow(delta);
+        // This is the error for growing past the maximum, which the tunables
+        // have lowered to the limit.
+        if let Err(MemoryError::CouldNotGrow { .. }) = &result {
+            LIMIT_HIT.with(|hit| hit.set(true));
+        }
+        result
+    }
+
+    fn vmmemory(&self) -> NonNull<vm::VMMemoryDefinition> {
+        self.0.vmmemory()
+    }
+}
@@ -0,0 +1,186 @@
+//! An on-disk cache of compiled wasm modules, so that zomes don't need to be
+//! recompiled every time the conductor starts.
+//!
+//! Compiled artifacts are only valid for the engine which produced them, so
+//! they are stored in a directory named after the wasmer version, the CPU
+//! features of the host and the compiler and middleware configuration.
+//! Directories for any other engine are removed when the cache is opened.
+
+use crate::core::ribosome::real_ribosome::RealRibosome;
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::*;
+
+/// The name of the cache directory within the conductor's database directory.
+pub const MODULE_CACHE_DIRECTORY: &str = "wasm-cache";
+
+/// An on-disk cache of compiled wasm modules, keyed by wasm hash.
+#[derive(Debug)]
+pub struct ModuleDiskCache {
+    root: PathBuf,
+}
+
+impl ModuleDiskCache {
+    /// Open the cache in this directory, removing any artifacts compiled
+    /// by a different engine.
+    pub fn open(root: PathBuf) -> Self {
+        let cache = Self { root };
+        if let Err(e) = cache.remove_stale() {
+            warn!(path = ?cache.root, "Failed to remove stale compiled wasm: {}", e);
+        }
+        cache
+    }
+
+    /// The directory holding artifacts for the current engine.
+    fn engine_dir(&self) -> PathBuf {
+        self.root.join(engine_key())
+    }
+
+    fn path(&self, wasm_hash: &WasmHash) -> PathBuf {
+        self.engine_dir()
+            .join(WasmHashB64::from(wasm_hash.clone()).to_string())
+    }
+
+    /// Whether the compiled module for this wasm is in the cache.
+    pub fn contains(&self, wasm_hash: &WasmHash) -> bool {
+        self.path(wasm_hash).exists()
+    }
+
+    /// Load the compiled module for this wasm, if it is in the cache.
+    pub fn get(&self, wasm_hash: &WasmHash) -> Option<Module> {
+        let path = self.path(wasm_hash);
+        let bytes = std::fs::read(&path).ok()?;
+        let store = Store::new(&Universal::headless().engine());
+        // Safety: the artifact was serialized by this conductor with the same
+        // wasmer version and host, and is only read from our own cache directory.
+        match unsafe { Module::deserialize(&store, bytes.as_slice()) } {
+            Ok(module) => Some(module),
+            Err(e) => {
+                warn!(?path, "Discarding unreadable compiled wasm: {}", e);
+                std::fs::remove_file(&path).ok();
+                None
+            }
+        }
+    }
+
+    /// Store the compiled module for this wasm. Failures are logged, as the
+    /// cache is only an optimization.
+    pub fn put(&self, wasm_hash: &WasmHash, module: &Module) {
+        if let Err(e) = self.try_put(wasm_hash, module) {
+            warn!(?wasm_hash, "Failed to cache compiled wasm: {}", e);
+        }
+    }
+
+    fn try_put(&self, wasm_hash: &WasmHash, module: &Module) -> std::io::Result<()> {
+        let bytes = module
+            .serialize()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        let dir = self.engine_dir();
+        std::fs::create_dir_all(&dir)?;
+        // Write to a temporary file first so a crash can't leave a
+        // truncated artifact behind.
+        let mut file = tempfile::NamedTempFile::new_in(&dir)?;
+        file.write_all(&bytes)?;
+        file.persist(self.path(wasm_hash)).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Remove all compiled artifacts.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.root) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove artifacts compiled by any engine other than the current one.
+    fn remove_stale(&self) -> std::io::Result<()> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let current = engine_key();
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_name() != current.as_str() {
+                std::fs::remove_dir_all(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Identifies the engine which compiled an artifact.
+fn engine_key() -> String {
+    format!(
+        "wasmer-{}-{:x}-{}",
+        VERSION,
+        CpuFeature::for_host().as_u64(),
+        RealRibosome::compiler_key()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+
+    fn compile(wat: &str) -> Module {
+        let store = Store::new(&Universal::new(RealRibosome::cranelift()).engine());
+        Module::new(&store, wat).unwrap()
+    }
+
+    #[test]
+    fn modules_round_trip_and_stale_engines_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let stale = dir.path().join("wasmer-0.0.0-0");
+        std::fs::create_dir_all(&stale).unwrap();
+
+        let cache = ModuleDiskCache::open(dir.path().to_path_buf());
+        assert!(!stale.exists());
+
+        let wasm_hash = fixt!(WasmHash);
+        assert!(!cache.contains(&wasm_hash));
+        assert!(cache.get(&wasm_hash).is_none());
+
+        let module = compile(r#"(module (func (export "f") (result i32) i32.const 1))"#);
+        cache.put(&wasm_hash, &module);
+        let cached = cache.get(&wasm_hash).unwrap();
+        assert!(cached.exports().functions().any(|f| f.name() == "f"));
+
+        cache.clear().unwrap();
+        assert!(cache.get(&wasm_hash).is_none());
+    }
+
+    #[test]
+    fn cached_modules_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm_hash = fixt!(WasmHash);
+        let module = compile(r#"(module (func (export "f") (result i32) i32.const 1))"#);
+        ModuleDiskCache::open(dir.path().to_path_buf()).put(&wasm_hash, &module);
+
+        // Opening the cache again, as a restarted conductor does, keeps
+        // artifacts compiled with the current configuration.
+        let cache = ModuleDiskCache::open(dir.path().to_path_buf());
+        assert!(cache.contains(&wasm_hash));
+        let cached = cache.get(&wasm_hash).unwrap();
+        assert!(cached.exports().functions().any(|f| f.name() == "f"));
+    }
+
+    #[test]
+    fn artifacts_of_another_compiler_configuration_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join(format!(
+            "wasmer-{}-{:x}-cranelift-other-config",
+            VERSION,
+            CpuFeature::for_host().as_u64()
+        ));
+        std::fs::create_dir_all(&other).unwrap();
+
+        ModuleDiskCache::open(dir.path().to_path_buf());
+        assert!(!other.exists());
+    }
+}
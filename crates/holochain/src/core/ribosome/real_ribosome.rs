@@ -5,6 +5,9 @@ use super::guest_callback::post_commit::PostCommitHostAccess;
 use super::guest_callback::validate::ValidateHostAccess;
 use super::host_fn::get_agent_activity::get_agent_activity;
 use super::host_fn::HostFnApi;
+use super::memory_limit;
+use super::wasm_metrics;
+use super::wasm_metrics::WasmCallOutcome;
 use super::HostContext;
 use super::ZomeCallHostAccess;
 use crate::core::ribosome::error::RibosomeError;
@@ -41,6 +44,7 @@ use crate::core::ribosome::host_fn::get::get;
 use crate::core::ribosome::host_fn::get_details::get_details;
 use crate::core::ribosome::host_fn::get_link_details::get_link_details;
 use crate::core::ribosome::host_fn::get_links::get_links;
+use crate::core::ribosome::host_fn::get_meta::get_meta;
 use crate::core::ribosome::host_fn::hash::hash;
 use crate::core::ribosome::host_fn::must_get_action::must_get_action;
 use crate::core::ribosome::host_fn::must_get_agent_activity::must_get_agent_activity;
@@ -53,6 +57,7 @@ use crate::core::ribosome::host_fn::schedule::schedule;
 use crate::core::ribosome::host_fn::sign::sign;
 use crate::core::ribosome::host_fn::sign_ephemeral::sign_ephemeral;
 use crate::core::ribosome::host_fn::sleep::sleep;
+use crate::core::ribosome::host_fn::sleep::DEFAULT_MAX_SLEEP;
 use crate::core::ribosome::host_fn::sys_time::sys_time;
 use crate::core::ribosome::host_fn::trace::trace;
 use crate::core::ribosome::host_fn::unblock_agent::unblock_agent;
@@ -67,6 +72,8 @@ use crate::core::ribosome::host_fn::x_salsa20_poly1305_shared_secret_create_rand
 use crate::core::ribosome::host_fn::x_salsa20_poly1305_shared_secret_export::x_salsa20_poly1305_shared_secret_export;
 use crate::core::ribosome::host_fn::x_salsa20_poly1305_shared_secret_ingest::x_salsa20_poly1305_shared_secret_ingest;
 use crate::core::ribosome::host_fn::zome_info::zome_info;
+use crate::core::ribosome::is_callback;
+use crate::core::ribosome::module_cache::ModuleDiskCache;
 use crate::core::ribosome::real_ribosome::wasmparser::Operator as WasmOperator;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::Invocation;
@@ -80,13 +87,18 @@ use wasmer_middlewares::Metering;
 // without it.
 use kitsune_p2p_types::dependencies::lair_keystore_api::dependencies::parking_lot::lock_api::RwLock;
 
+use holochain_conductor_api::conductor::WasmCallLimits;
+use holochain_conductor_api::conductor::WasmLimits;
 use holochain_types::zome_types::GlobalZomeTypes;
 use holochain_types::zome_types::ZomeTypesError;
 use holochain_wasmer_host::prelude::*;
 use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
+use wasmer_middlewares::metering::MeteringPoints;
 
 #[cfg(not(test))]
 /// one hundred giga ops
@@ -114,6 +126,15 @@ pub struct RealRibosome {
 
     /// Dependencies for every zome.
     pub zome_dependencies: Arc<HashMap<ZomeName, Vec<ZomeIndex>>>,
+
+    /// Fuel and memory limits for calls into the wasm of this DNA.
+    pub wasm_limits: Arc<WasmLimits>,
+
+    /// Where compiled wasm is persisted between conductor restarts, if anywhere.
+    pub module_disk_cache: Option<Arc<ModuleDiskCache>>,
+
+    /// The externs exported by every zome, listed on first request.
+    pub externs: Arc<OnceCell<Arc<BTreeMap<ZomeName, Vec<ZomeFnInfo>>>>>,
 }
 
 struct HostFnBuilder {
@@ -212,8 +233,14 @@ static CONTEXT_MAP: ContextMap = Lazy::new(Default::default);
 static CONTEXT_KEY: AtomicU64 = AtomicU64::new(0);
 
 /// Create a key for the instance cache.
-/// It will be [WasmHash..DnaHash..context_key] all as bytes.
-fn instance_cache_key(wasm_hash: &WasmHash, dna_hash: &DnaHash, context_key: u64) -> [u8; 32] {
+/// It will be [WasmHash..DnaHash..memory_limit..context_key] all as bytes,
+/// so instances are only reused for calls with the same memory limit.
+fn instance_cache_key(
+    wasm_hash: &WasmHash,
+    dna_hash: &DnaHash,
+    memory_limit: Option<Pages>,
+    context_key: u64,
+) -> [u8; 32] {
     let mut bits = [0u8; 32];
     for (i, byte) in wasm_hash
         .get_raw_32()
@@ -225,6 +252,10 @@ fn instance_cache_key(wasm_hash: &WasmHash, dna_hash: &DnaHash, context_key: u64
     {
         bits[i] = byte;
     }
+    let limit = memory_limit.map_or(0, |limit| limit.0 as u64 + 1);
+    for (i, byte) in (16..24).zip(&limit.to_le_bytes()) {
+        bits[i] ^= *byte;
+    }
     for (i, byte) in (24..32).zip(&context_key.to_le_bytes()) {
         bits[i] = *byte;
     }
@@ -244,11 +275,23 @@ fn context_key_from_key(key: &[u8; 32]) -> u64 {
 impl RealRibosome {
     /// Create a new instance
     pub fn new(dna_file: DnaFile) -> RibosomeResult<Self> {
+        Self::new_with_module_cache(dna_file, None)
+    }
+
+    /// Create a new instance which loads compiled wasm from, and saves it to,
+    /// the given disk cache.
+    pub fn new_with_module_cache(
+        dna_file: DnaFile,
+        module_disk_cache: Option<Arc<ModuleDiskCache>>,
+    ) -> RibosomeResult<Self> {
         // Create an empty ribosome.
         let ribosome = Self {
             dna_file,
             zome_types: Default::default(),
             zome_dependencies: Default::default(),
+            wasm_limits: Default::default(),
+            module_disk_cache,
+            externs: Default::default(),
         };
 
         // Collect the number of entry and link types
@@ -334,15 +377,62 @@ impl RealRibosome {
             dna_file: ribosome.dna_file,
             zome_types,
             zome_dependencies: Arc::new(zome_dependencies),
+            wasm_limits: ribosome.wasm_limits,
+            module_disk_cache: ribosome.module_disk_cache,
+            externs: ribosome.externs,
         })
     }
 
+    /// Apply fuel and memory limits to calls into the wasm of this DNA.
+    pub fn with_wasm_limits(mut self, wasm_limits: WasmLimits) -> Self {
+        self.wasm_limits = Arc::new(wasm_limits);
+        self
+    }
+
+    /// The cap on the memory of instances used for calls made with this
+    /// host context.
+    fn memory_limit(&self, host_context: &HostContext) -> Option<Pages> {
+        self.wasm_call_limits(host_context)
+            .max_memory_bytes
+            .map(memory_limit::pages_for_bytes)
+    }
+
+    /// The fuel a call made with this host context may use. A zome call
+    /// admitted under a quota may be limited to less than the configured
+    /// limit, but never to more.
+    fn fuel_limit(limits: &WasmCallLimits, host_context: &HostContext) -> u64 {
+        let fuel = limits.fuel.unwrap_or(WASM_METERING_LIMIT);
+        match host_context {
+            HostContext::ZomeCall(ZomeCallHostAccess {
+                fuel_limit: Some(fuel_limit),
+                ..
+            }) => fuel.min(*fuel_limit),
+            _ => fuel,
+        }
+    }
+
+    /// The limits which apply to a call made with this host context.
+    fn wasm_call_limits(&self, host_context: &HostContext) -> &WasmCallLimits {
+        match host_context {
+            HostContext::Validate(_) => &self.wasm_limits.validate,
+            HostContext::Init(_) => &self.wasm_limits.init,
+            HostContext::PostCommit(_) => &self.wasm_limits.post_commit,
+            HostContext::ZomeCall(_) => &self.wasm_limits.zome_call,
+            HostContext::EntryDefs(_)
+            | HostContext::GenesisSelfCheck(_)
+            | HostContext::MigrateAgent(_) => &self.wasm_limits.other,
+        }
+    }
+
     #[cfg(any(test, feature = "test_utils"))]
     pub fn empty(dna_file: DnaFile) -> Self {
         Self {
             dna_file,
             zome_types: Default::default(),
             zome_dependencies: Default::default(),
+            wasm_limits: Default::default(),
+            module_disk_cache: None,
+            externs: Default::default(),
         }
     }
 
@@ -363,10 +453,75 @@ impl RealRibosome {
                 .ok();
         }
 
-        Ok(holochain_wasmer_host::module::MODULE_CACHE.write().get(
-            self.wasm_cache_key(zome_name)?,
-            &self.dna_file.get_wasm_for_zome(zome_name)?.code(),
-        )?)
+        let key = self.wasm_cache_key(zome_name)?;
+        if let Some(module_disk_cache) = &self.module_disk_cache {
+            use holochain_wasmer_host::module::PlruCache;
+            let in_memory = holochain_wasmer_host::module::MODULE_CACHE
+                .read()
+                .cache()
+                .contains_key(&key);
+            if !in_memory {
+                let wasm_hash = &self.dna_file.dna().get_wasm_zome(zome_name)?.wasm_hash;
+                if let Some(module) = module_disk_cache.get(wasm_hash) {
+                    let module = Arc::new(module);
+                    holochain_wasmer_host::module::MODULE_CACHE
+                        .write()
+                        .put_item(key, module.clone());
+                    return Ok(module);
+                }
+                let module = holochain_wasmer_host::module::MODULE_CACHE
+                    .write()
+                    .get(key, &self.dna_file.get_wasm_for_zome(zome_name)?.code())?;
+                module_disk_cache.put(wasm_hash, &module);
+                return Ok(module);
+            }
+        }
+
+        Ok(holochain_wasmer_host::module::MODULE_CACHE
+            .write()
+            .get(key, &self.dna_file.get_wasm_for_zome(zome_name)?.code())?)
+    }
+
+    /// Compile the wasm of every zome ahead of its first call, persisting
+    /// it to the disk cache if there is one.
+    pub fn precompile(&self) -> RibosomeResult<()> {
+        for (zome_name, zome_def) in self.dna_def().all_zomes() {
+            if let ZomeDef::Wasm(wasm_zome) = zome_def {
+                let module = self.module(zome_name)?;
+                // The module may have been compiled before the disk cache
+                // was in use, in which case it still needs to be persisted.
+                if let Some(module_disk_cache) = &self.module_disk_cache {
+                    if !module_disk_cache.contains(&wasm_zome.wasm_hash) {
+                        module_disk_cache.put(&wasm_zome.wasm_hash, &module);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The externs exported by the wasm of a zome, with the signatures
+    /// `hdk_extern` wrote into the wasm where it did.
+    fn wasm_externs(&self, zome_name: &ZomeName) -> RibosomeResult<Vec<ZomeFnInfo>> {
+        let module = self.module(zome_name)?;
+        let signatures: HashMap<FunctionName, ZomeFnSignature> = module
+            .custom_sections(EXTERN_SIGNATURES_SECTION)
+            .flat_map(|section| ZomeFnSignature::parse_section(&section))
+            .collect();
+        let mut externs: Vec<ZomeFnInfo> = module
+            .exports()
+            .functions()
+            .map(|f| FunctionName::new(f.name()))
+            // Allocation and const fns are exported for the host, not for calling.
+            .filter(|name| !name.as_ref().starts_with("__"))
+            .map(|name| ZomeFnInfo {
+                is_callback: is_callback(&name),
+                signature: signatures.get(&name).cloned(),
+                name,
+            })
+            .collect();
+        externs.sort();
+        Ok(externs)
     }
 
     pub fn wasm_cache_key(&self, zome_name: &ZomeName) -> Result<[u8; 32], DnaError> {
@@ -389,6 +544,7 @@ impl RealRibosome {
         context_key: u64,
         instance: Arc<Mutex<Instance>>,
         zome_name: &ZomeName,
+        memory_limit: Option<Pages>,
     ) -> RibosomeResult<()> {
         use holochain_wasmer_host::module::PlruCache;
         {
@@ -408,6 +564,7 @@ impl RealRibosome {
                 .map_err(DnaError::from)?
                 .wasm_hash,
             self.dna_file.dna_hash(),
+            memory_limit,
             context_key,
         );
         holochain_wasmer_host::module::INSTANCE_CACHE
@@ -417,12 +574,18 @@ impl RealRibosome {
         Ok(())
     }
 
+    /// Instantiate a zome's wasm. If there is a memory limit the instance's
+    /// memory can't grow past it.
     pub fn build_instance(
         &self,
         zome_name: &ZomeName,
         context_key: u64,
+        memory_limit: Option<Pages>,
     ) -> RibosomeResult<Arc<Mutex<Instance>>> {
-        let module = self.module(zome_name)?;
+        let mut module = self.module(zome_name)?;
+        if let Some(limit) = memory_limit {
+            module = memory_limit::limited_module(self.wasm_cache_key(zome_name)?, &module, limit)?;
+        }
         let imports: ImportObject = Self::imports(self, context_key, module.store());
         let instance = Arc::new(Mutex::new(Instance::new(&module, &imports).map_err(
             |e| -> RuntimeError { wasm_error!(WasmErrorInner::Compile(e.to_string())).into() },
@@ -436,6 +599,7 @@ impl RealRibosome {
     ) -> RibosomeResult<(Arc<Mutex<Instance>>, u64)> {
         use holochain_wasmer_host::module::PlruCache;
 
+        let memory_limit = self.memory_limit(&call_context.host_context);
         // Get the start of the possible keys.
         let key_start = instance_cache_key(
             &self
@@ -445,6 +609,7 @@ impl RealRibosome {
                 .map_err(DnaError::from)?
                 .wasm_hash,
             self.dna_file.dna_hash(),
+            memory_limit,
             0,
         );
         // Get the end of the possible keys.
@@ -456,6 +621,7 @@ impl RealRibosome {
                 .map_err(DnaError::from)?
                 .wasm_hash,
             self.dna_file.dna_hash(),
+            memory_limit,
             CONTEXT_KEY.load(std::sync::atomic::Ordering::Relaxed),
         );
         let mut lock = holochain_wasmer_host::module::INSTANCE_CACHE.write();
@@ -484,7 +650,8 @@ impl RealRibosome {
         }
         // We didn't get an instance hit so create a new key.
         let context_key = CONTEXT_KEY.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        let instance = self.build_instance(call_context.zome.zome_name(), context_key)?;
+        let instance =
+            self.build_instance(call_context.zome.zome_name(), context_key, memory_limit)?;
 
         // Update the context.
         {
@@ -496,10 +663,22 @@ impl RealRibosome {
         Ok((instance, context_key))
     }
 
+    /// Identifies the compiler and middleware configuration of
+    /// [`Self::cranelift`]. Modules compiled with a different configuration
+    /// can't be used in place of each other, so this must change whenever
+    /// that configuration does.
+    pub fn compiler_key() -> String {
+        format!(
+            "cranelift-canonical-nans-metering-{}-unit-cost",
+            WASM_METERING_LIMIT
+        )
+    }
+
     pub fn cranelift() -> Cranelift {
         let cost_function = |_operator: &WasmOperator| -> u64 { 1 };
-        // @todo 100 giga-ops is totally arbitrary cutoff so we probably
-        // want to make the limit configurable somehow.
+        // This is only the initial limit of a new instance, the limit
+        // for each call is set from the ribosome's `WasmLimits` before
+        // the call is made.
         let metering = Arc::new(Metering::new(WASM_METERING_LIMIT, cost_function));
         let mut cranelift = Cranelift::default();
         cranelift.canonicalize_nans(true).push_middleware(metering);
@@ -586,6 +765,7 @@ impl RealRibosome {
             .with_host_function(&mut ns, "__hc__capability_info_1", capability_info)
             .with_host_function(&mut ns, "__hc__get_1", get)
             .with_host_function(&mut ns, "__hc__get_details_1", get_details)
+            .with_host_function(&mut ns, "__hc__get_meta_1", get_meta)
             .with_host_function(&mut ns, "__hc__get_links_1", get_links)
             .with_host_function(&mut ns, "__hc__get_link_details_1", get_link_details)
             .with_host_function(&mut ns, "__hc__get_agent_activity_1", get_agent_activity)
@@ -662,6 +842,16 @@ macro_rules! do_callback {
     }};
 }
 
+impl RealRibosome {
+    fn do_validate(
+        &self,
+        host_access: ValidateHostAccess,
+        invocation: ValidateInvocation,
+    ) -> RibosomeResult<ValidateResult> {
+        do_callback!(self, host_access, invocation, ValidateCallbackResult)
+    }
+}
+
 impl RibosomeT for RealRibosome {
     fn dna_def(&self) -> &DnaDefHashed {
         self.dna_file.dna()
@@ -742,11 +932,19 @@ impl RibosomeT for RealRibosome {
         zome: &Zome,
         to_call: &FunctionName,
     ) -> Result<Option<ExternIO>, RibosomeError> {
+        let limits = self.wasm_call_limits(&host_context).clone();
+        let fuel = Self::fuel_limit(&limits, &host_context);
+        let memory_limit = self.memory_limit(&host_context);
         let call_context = CallContext {
             zome: zome.clone(),
             function_name: to_call.clone(),
             host_context,
             auth: invocation.auth(),
+            deadline: invocation.deadline(),
+            max_sleep: limits
+                .max_sleep_ms
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(DEFAULT_MAX_SLEEP),
         };
 
         match zome.zome_def() {
@@ -759,15 +957,78 @@ impl RibosomeT for RealRibosome {
                     // because it builds guards against memory leaks and handles imports correctly
                     let (instance, context_key) = self.instance(call_context)?;
 
-                    let result: Result<ExternIO, RuntimeError> = holochain_wasmer_host::guest::call(
-                        instance.clone(),
-                        to_call.as_ref(),
-                        // be aware of this clone!
-                        // the whole invocation is cloned!
-                        // @todo - is this a problem for large payloads like entries?
-                        invocation.to_owned().host_input()?,
+                    wasmer_middlewares::metering::set_remaining_points(&instance.lock(), fuel);
+
+                    let input = invocation.to_owned().host_input()?;
+                    let (result, memory_limit_hit): (Result<ExternIO, RuntimeError>, bool) =
+                        memory_limit::track_limit(|| {
+                            holochain_wasmer_host::guest::call(
+                                instance.clone(),
+                                to_call.as_ref(),
+                                // be aware of this clone!
+                                // the whole invocation is cloned!
+                                // @todo - is this a problem for large payloads like entries?
+                                input,
+                            )
+                        });
+
+                    let (remaining_fuel, memory_bytes) = {
+                        let instance = instance.lock();
+                        let remaining_fuel =
+                            match wasmer_middlewares::metering::get_remaining_points(&instance) {
+                                MeteringPoints::Remaining(remaining) => Some(remaining),
+                                MeteringPoints::Exhausted => None,
+                            };
+                        let memory_bytes = instance
+                            .exports
+                            .get_memory("memory")
+                            .map(|memory| memory.data_size())
+                            .unwrap_or_default();
+                        (remaining_fuel, memory_bytes)
+                    };
+                    let fuel_consumed = fuel - remaining_fuel.unwrap_or_default();
+                    // The memory can't grow past the limit, so a failed call
+                    // which was refused memory ran out of it.
+                    let memory_limit_exceeded = result.is_err() && memory_limit_hit;
+                    wasm_metrics::record(
+                        self.dna_hash(),
+                        zome.zome_name(),
+                        to_call,
+                        fuel_consumed,
+                        memory_bytes,
+                        if remaining_fuel.is_none() {
+                            WasmCallOutcome::FuelExhausted
+                        } else if memory_limit_exceeded {
+                            WasmCallOutcome::MemoryLimitExceeded
+                        } else {
+                            WasmCallOutcome::Finished
+                        },
                     );
 
+                    if remaining_fuel.is_none() {
+                        // The trap has left the instance in an unknown state
+                        // so it is not cached.
+                        CONTEXT_MAP.lock().remove(&context_key);
+                        return Err(RibosomeError::WasmFuelExhausted(
+                            zome.zome_name().clone(),
+                            to_call.clone(),
+                            fuel,
+                        ));
+                    }
+                    if let Some(max_memory_bytes) = limits.max_memory_bytes {
+                        if memory_limit_exceeded {
+                            // Linear memory never shrinks so this instance
+                            // must not be reused.
+                            CONTEXT_MAP.lock().remove(&context_key);
+                            return Err(RibosomeError::WasmMemoryLimitExceeded(
+                                zome.zome_name().clone(),
+                                to_call.clone(),
+                                memory_bytes,
+                                max_memory_bytes,
+                            ));
+                        }
+                    }
+
                     // a bit of typefu to avoid cloning the result.
                     let (can_cache, result) = match result {
                         Err(runtime_error) => match runtime_error.downcast::<WasmError>() {
@@ -781,7 +1042,7 @@ impl RibosomeT for RealRibosome {
 
                     // Cache this instance.
                     if can_cache {
-                        self.cache_instance(context_key, instance, zome.zome_name())?;
+                        self.cache_instance(context_key, instance, zome.zome_name(), memory_limit)?;
                     }
 
                     Ok(Some(result?))
@@ -809,6 +1070,8 @@ impl RibosomeT for RealRibosome {
             function_name: name.into(),
             host_context: HostContext::EntryDefs(EntryDefsHostAccess {}),
             auth: super::InvocationAuth::LocalCallback,
+            deadline: None,
+            max_sleep: DEFAULT_MAX_SLEEP,
         };
 
         match zome.zome_def() {
@@ -908,7 +1171,13 @@ impl RibosomeT for RealRibosome {
         host_access: ValidateHostAccess,
         invocation: ValidateInvocation,
     ) -> RibosomeResult<ValidateResult> {
-        do_callback!(self, host_access, invocation, ValidateCallbackResult)
+        match self.do_validate(host_access, invocation) {
+            // Running out of fuel or memory is as deterministic as the
+            // validation logic itself, so it is an invalid outcome rather
+            // than an error which would be retried.
+            Err(e) if e.is_wasm_limit_exceeded() => Ok(ValidateResult::Invalid(e.to_string())),
+            result => result,
+        }
     }
 
     fn run_init(
@@ -955,21 +1224,54 @@ impl RibosomeT for RealRibosome {
             .cloned()
             .map(|(name, def)| IntegrityZome::new(name, def))
     }
+
+    fn list_externs(&self) -> RibosomeResult<Arc<BTreeMap<ZomeName, Vec<ZomeFnInfo>>>> {
+        self.externs
+            .get_or_try_init(|| {
+                self.dna_def()
+                    .all_zomes()
+                    .map(|(zome_name, zome_def)| {
+                        let externs = match zome_def {
+                            ZomeDef::Wasm(_) => self.wasm_externs(zome_name)?,
+                            ZomeDef::Inline { inline_zome, .. } => inline_zome
+                                .0
+                                .functions()
+                                .into_iter()
+                                .map(|name| ZomeFnInfo {
+                                    is_callback: is_callback(&name),
+                                    signature: None,
+                                    name,
+                                })
+                                .collect(),
+                        };
+                        Ok((zome_name.clone(), externs))
+                    })
+                    .collect::<RibosomeResult<BTreeMap<_, _>>>()
+                    .map(Arc::new)
+            })
+            .cloned()
+    }
 }
 
 #[cfg(test)]
 #[cfg(feature = "slow_tests")]
 pub mod wasm_test {
+    use crate::core::ribosome::wasm_metrics;
     use crate::core::ribosome::wasm_test::RibosomeTestFixture;
+    use crate::core::ribosome::RibosomeT;
     use crate::core::ribosome::ZomeCall;
     use crate::sweettest::SweetConductor;
     use crate::sweettest::SweetDnaFile;
     use ::fixt::prelude::*;
     use hdk::prelude::*;
+    use holochain_conductor_api::conductor::WasmCallLimits;
+    use holochain_conductor_api::conductor::WasmLimits;
+    use holochain_conductor_api::conductor::WasmLimitsConfig;
     use holochain_state::nonce::fresh_nonce;
     use holochain_types::prelude::AgentPubKeyFixturator;
     use holochain_wasm_test_utils::TestWasm;
     use holochain_zome_types::zome_io::ZomeCallUnsigned;
+    use std::sync::Arc;
 
     #[tokio::test(flavor = "multi_thread")]
     /// Basic checks that we can call externs internally and externally the way we want using the
@@ -1095,6 +1397,55 @@ pub mod wasm_test {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_externs_test() {
+        observability::test_run().ok();
+
+        let (dna_file, _, _) =
+            SweetDnaFile::unique_from_test_wasms(vec![TestWasm::HdkExtern]).await;
+        let ribosome = super::RealRibosome::new(dna_file).unwrap();
+        let zome_name = TestWasm::HdkExtern.coordinator_zome_name();
+
+        let signature = |input: &str, output: &str| {
+            Some(ZomeFnSignature {
+                input: input.into(),
+                output: output.into(),
+            })
+        };
+        assert_eq!(
+            ribosome.list_zome_fns().unwrap().get(&zome_name).unwrap(),
+            &vec![
+                ZomeFnInfo {
+                    name: "bar".into(),
+                    is_callback: false,
+                    signature: signature("()", "ExternResult<String>"),
+                },
+                ZomeFnInfo {
+                    name: "foo".into(),
+                    is_callback: false,
+                    signature: signature("()", "ExternResult<String>"),
+                },
+                ZomeFnInfo {
+                    name: "infallible".into(),
+                    is_callback: false,
+                    signature: signature("()", "String"),
+                },
+            ]
+        );
+        assert!(ribosome
+            .list_callbacks()
+            .unwrap()
+            .get(&zome_name)
+            .unwrap()
+            .is_empty());
+
+        // The listing is cached and shared with clones of the ribosome.
+        assert!(Arc::ptr_eq(
+            &ribosome.list_externs().unwrap(),
+            &ribosome.clone().list_externs().unwrap()
+        ));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn the_incredible_halt_test() {
         observability::test_run().ok();
@@ -1120,4 +1471,95 @@ pub mod wasm_test {
         .await;
         assert!(create_result.unwrap().is_err());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wasm_fuel_limits_are_configurable() {
+        observability::test_run().ok();
+        let limits = WasmCallLimits {
+            fuel: Some(1_000_000),
+            max_memory_bytes: None,
+            max_sleep_ms: None,
+        };
+        let mut config = crate::sweettest::standard_config();
+        config.wasm_limits = Some(WasmLimitsConfig {
+            default: WasmLimits {
+                validate: limits.clone(),
+                zome_call: limits,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut conductor = SweetConductor::from_config(config).await;
+        let (dna_file, _, _) =
+            SweetDnaFile::unique_from_test_wasms(vec![TestWasm::TheIncredibleHalt]).await;
+        let app = conductor.setup_app("app", [&dna_file]).await.unwrap();
+        let alice = app.cells()[0].zome(TestWasm::TheIncredibleHalt);
+
+        // The infinite loop runs out of fuel long before the test times out.
+        let err = conductor
+            .call_fallible::<_, (), _>(&alice, "smash", ())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ran out of fuel"), "{}", err);
+        let metrics = wasm_metrics::get(
+            dna_file.dna_hash(),
+            &TestWasm::TheIncredibleHalt.into(),
+            &"smash".into(),
+        );
+        assert_eq!(metrics.calls, 1);
+        assert_eq!(metrics.fuel_exhausted, 1);
+        assert_eq!(metrics.fuel_consumed, 1_000_000);
+
+        // A validation callback running out of fuel makes the op invalid.
+        let err = conductor
+            .call_fallible::<_, ActionHash, _>(&alice, "create_a_thing", ())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ran out of fuel"), "{}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wasm_memory_can_not_grow_past_its_limit() {
+        observability::test_run().ok();
+        let max_memory_bytes = 16 * 1024 * 1024;
+        let mut config = crate::sweettest::standard_config();
+        config.wasm_limits = Some(WasmLimitsConfig {
+            default: WasmLimits {
+                zome_call: WasmCallLimits {
+                    fuel: None,
+                    max_memory_bytes: Some(max_memory_bytes),
+                    max_sleep_ms: None,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut conductor = SweetConductor::from_config(config).await;
+        let (dna_file, _, _) =
+            SweetDnaFile::unique_from_test_wasms(vec![TestWasm::RandomBytes]).await;
+        let app = conductor.setup_app("app", [&dna_file]).await.unwrap();
+        let alice = app.cells()[0].zome(TestWasm::RandomBytes);
+
+        let bytes: Bytes = conductor.call(&alice, "rand_random_bytes", 1024_u32).await;
+        assert_eq!(bytes.len(), 1024);
+
+        // The guest can't allocate more than its memory may grow to.
+        let err = conductor
+            .call_fallible::<_, Bytes, _>(&alice, "rand_random_bytes", 2 * max_memory_bytes as u32)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ran out of memory"), "{}", err);
+        let metrics = wasm_metrics::get(
+            dna_file.dna_hash(),
+            &TestWasm::RandomBytes.into(),
+            &"rand_random_bytes".into(),
+        );
+        assert_eq!(metrics.calls, 2);
+        assert_eq!(metrics.memory_limit_exceeded, 1);
+        assert!(metrics.max_memory_bytes <= max_memory_bytes);
+
+        // Calls within the limit still succeed on a fresh instance.
+        let bytes: Bytes = conductor.call(&alice, "rand_random_bytes", 1024_u32).await;
+        assert_eq!(bytes.len(), 1024);
+    }
 }
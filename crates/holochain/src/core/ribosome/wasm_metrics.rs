@@ -0,0 +1,98 @@
+//! Totals of the fuel and memory used by the wasm calls of each DNA.
+//!
+//! These are reported with the network metrics of the DNA's space by the
+//! `DumpNetworkMetrics` admin call.
+
+use holochain_p2p::DnaHashExt;
+use holochain_types::prelude::*;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+/// Usage totals for one zome function.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct WasmFnMetrics {
+    /// The number of calls made.
+    pub calls: u64,
+    /// The fuel consumed by all calls.
+    pub fuel_consumed: u64,
+    /// The largest the guest's memory has been at the end of a call.
+    pub max_memory_bytes: u64,
+    /// The number of calls which ran out of fuel.
+    pub fuel_exhausted: u64,
+    /// The number of calls which ran out of memory.
+    pub memory_limit_exceeded: u64,
+}
+
+/// How a metered wasm call ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmCallOutcome {
+    /// The call finished, successfully or with a guest error.
+    Finished,
+    /// The call ran out of fuel.
+    FuelExhausted,
+    /// The call ran out of memory.
+    MemoryLimitExceeded,
+}
+
+type ZomeMetrics = BTreeMap<String, BTreeMap<String, WasmFnMetrics>>;
+
+static WASM_METRICS: Lazy<Mutex<HashMap<DnaHash, ZomeMetrics>>> = Lazy::new(Default::default);
+
+/// Add a wasm call to the totals of its DNA.
+pub fn record(
+    dna_hash: &DnaHash,
+    zome_name: &ZomeName,
+    fn_name: &FunctionName,
+    fuel_consumed: u64,
+    memory_bytes: u64,
+    outcome: WasmCallOutcome,
+) {
+    let mut metrics = WASM_METRICS.lock();
+    let metrics = metrics
+        .entry(dna_hash.clone())
+        .or_default()
+        .entry(zome_name.to_string())
+        .or_default()
+        .entry(fn_name.to_string())
+        .or_default();
+    metrics.calls += 1;
+    metrics.fuel_consumed = metrics.fuel_consumed.saturating_add(fuel_consumed);
+    metrics.max_memory_bytes = metrics.max_memory_bytes.max(memory_bytes);
+    match outcome {
+        WasmCallOutcome::Finished => (),
+        WasmCallOutcome::FuelExhausted => metrics.fuel_exhausted += 1,
+        WasmCallOutcome::MemoryLimitExceeded => metrics.memory_limit_exceeded += 1,
+    }
+}
+
+/// The totals for a zome function of a DNA.
+pub fn get(dna_hash: &DnaHash, zome_name: &ZomeName, fn_name: &FunctionName) -> WasmFnMetrics {
+    WASM_METRICS
+        .lock()
+        .get(dna_hash)
+        .and_then(|zomes| zomes.get(&zome_name.to_string()))
+        .and_then(|fns| fns.get(&fn_name.to_string()))
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Add the totals of each DNA to the network metrics dump of its space, under
+/// a `wasm` key.
+pub fn add_to_network_dump(dump: &mut serde_json::Value) {
+    let metrics = WASM_METRICS.lock();
+    let by_space: HashMap<String, &ZomeMetrics> = metrics
+        .iter()
+        .map(|(dna_hash, zomes)| (dna_hash.to_kitsune().to_string(), zomes))
+        .collect();
+    for space in dump.as_array_mut().into_iter().flatten() {
+        let zomes = space
+            .get("space")
+            .and_then(|space| space.as_str())
+            .and_then(|space| by_space.get(space));
+        if let (Some(zomes), Some(space)) = (zomes, space.as_object_mut()) {
+            space.insert("wasm".into(), serde_json::json!(zomes));
+        }
+    }
+}
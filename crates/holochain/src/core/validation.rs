@@ -1,7 +1,11 @@
 //! Types needed for all validation
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
+use holo_hash::AgentPubKey;
 use holo_hash::DhtOpHash;
+use holochain_p2p::HolochainP2pDna;
+use holochain_p2p::HolochainP2pDnaT;
 use holochain_types::dht_op::DhtOp;
 
 use super::workflow::error::WorkflowResult;
@@ -28,6 +32,30 @@ macro_rules! from_sub_error {
     };
 }
 
+/// Record that an op by this author was found valid or invalid. An author
+/// with any invalid op is invalid.
+pub fn record_author_validity(
+    validity: &mut HashMap<AgentPubKey, bool>,
+    author: AgentPubKey,
+    valid: bool,
+) {
+    *validity.entry(author).or_insert(true) &= valid;
+}
+
+/// Report to the network whether the ops each author sent were valid,
+/// so that peers which send invalid data are scored lower. Failures are
+/// only logged, as scoring must never hold up validation.
+pub async fn report_author_validity(
+    network: &HolochainP2pDna,
+    validity: HashMap<AgentPubKey, bool>,
+) {
+    for (author, valid) in validity {
+        if let Err(err) = network.report_data_validity(author, valid).await {
+            tracing::warn!(?err, "failed to report data validity");
+        }
+    }
+}
+
 /// Type for deriving ordering of DhtOps
 /// Don't change the order of this enum unless
 /// you mean to change the order we process ops
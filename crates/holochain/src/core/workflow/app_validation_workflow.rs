@@ -13,6 +13,8 @@ use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomesToInvoke;
+use crate::core::validation::record_author_validity;
+use crate::core::validation::report_author_validity;
 use error::AppValidationResult;
 pub use error::*;
 use futures::stream::StreamExt;
@@ -30,6 +32,7 @@ use holochain_types::prelude::*;
 use holochain_zome_types::op::EntryCreationAction;
 use holochain_zome_types::op::Op;
 use rusqlite::Transaction;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use tracing::*;
 pub use types::Outcome;
@@ -105,6 +108,7 @@ async fn app_validation_workflow_inner(
                 let (op, op_hash) = so.into_inner();
                 let op_type = op.get_type();
                 let action = op.action();
+                let author = action.author().clone();
                 let dependency = get_dependency(op_type, &action);
                 let op_light = op.to_light();
 
@@ -125,7 +129,7 @@ async fn app_validation_workflow_inner(
                     }
                     Err(e) => Err(e),
                 };
-                (op_hash, dependency, op_light, r, activity)
+                (op_hash, author, dependency, op_light, r, activity)
             }
         }
     });
@@ -163,15 +167,16 @@ async fn app_validation_workflow_inner(
             "Committing {} ops",
             chunk.iter().map(|c| c.len()).sum::<usize>()
         );
-        let (t, a, r, activity) = workspace
+        let (t, a, r, activity, validity) = workspace
             .dht_db
             .async_commit(move |txn| {
                 let mut total = 0;
                 let mut awaiting = 0;
                 let mut rejected = 0;
                 let mut agent_activity = Vec::new();
+                let mut validity = HashMap::new();
                 for outcome in chunk.into_iter().flatten() {
-                    let (op_hash, dependency, op_light, outcome, activity) = outcome;
+                    let (op_hash, author, dependency, op_light, outcome, activity) = outcome;
                     // Get the outcome or return the error
                     let outcome = outcome.or_else(|outcome_or_err| outcome_or_err.try_into())?;
 
@@ -193,6 +198,7 @@ async fn app_validation_workflow_inner(
                     match outcome {
                         Outcome::Accepted => {
                             total += 1;
+                            record_author_validity(&mut validity, author, true);
                             if let Dependency::Null = dependency {
                                 put_integrated(txn, &op_hash, ValidationStatus::Valid)?;
                             } else {
@@ -206,6 +212,7 @@ async fn app_validation_workflow_inner(
                         }
                         Outcome::Rejected(_) => {
                             rejected += 1;
+                            record_author_validity(&mut validity, author, false);
                             tracing::warn!("Received invalid op! Warrants aren't implemented yet, so we can't do anything about this right now, but be warned that somebody on the network has maliciously hacked their node.\nOp: {:?}", op_light);
                             if let Dependency::Null = dependency {
                                 put_integrated(txn, &op_hash, ValidationStatus::Rejected)?;
@@ -215,9 +222,10 @@ async fn app_validation_workflow_inner(
                         }
                     }
                 }
-                WorkflowResult::Ok((total, awaiting, rejected, agent_activity))
+                WorkflowResult::Ok((total, awaiting, rejected, agent_activity, validity))
             })
             .await?;
+        report_author_validity(network, validity).await;
         // Once the database transaction is committed, add agent activity to the cache
         // that is ready for integration.
         for (author, seq, has_no_dependency) in activity {
@@ -37,6 +37,8 @@ pub struct CallZomeWorkflowArgs<RibosomeT> {
     pub conductor_handle: ConductorHandle,
     pub is_root_zome_call: bool,
     pub cell_id: CellId,
+    /// The most fuel the call may use, if it is limited by a quota
+    pub fuel_limit: Option<u64>,
 }
 
 #[instrument(skip(
@@ -143,6 +145,7 @@ where
         signal_tx,
         conductor_handle,
         cell_id,
+        fuel_limit,
         ..
     } = args;
 
@@ -156,7 +159,8 @@ where
         network.clone(),
         signal_tx,
         call_zome_handle,
-    );
+    )
+    .with_fuel_limit(fuel_limit);
     let (ribosome, result) =
         call_zome_function_authorized(ribosome, host_access, invocation).await?;
     tracing::trace!("After zome call");
@@ -24,6 +24,7 @@ use holochain_types::prelude::*;
 use holochain_zome_types::Entry;
 use holochain_zome_types::ValidationStatus;
 use rusqlite::Transaction;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::Arc;
 use tracing::*;
@@ -92,6 +93,7 @@ async fn sys_validation_workflow_inner(
     // Process each op
     let iter = sorted_ops.into_iter().map({
         let space = space.clone();
+        let network = network.clone();
         move |so| {
             // Create an incoming ops sender for any dependencies we find
             // that we are meant to be holding but aren't.
@@ -105,6 +107,7 @@ async fn sys_validation_workflow_inner(
                 let (op, op_hash) = so.into_inner();
                 let op_type = op.get_type();
                 let action = op.action();
+                let author = action.author().clone();
 
                 let dependency = get_dependency(op_type, &action);
 
@@ -116,7 +119,7 @@ async fn sys_validation_workflow_inner(
                     Some(incoming_dht_ops_sender),
                 )
                 .await;
-                r.map(|o| (op_hash, o, dependency))
+                r.map(|o| (op_hash, author, o, dependency))
             }
         }
     });
@@ -153,15 +156,18 @@ async fn sys_validation_workflow_inner(
     while let Some(chunk) = iter.next().await {
         let num_ops: usize = chunk.iter().map(|c| c.len()).sum();
         tracing::debug!("Committing {} ops", num_ops);
-        let (t, a, m, r) = space
+        let (t, a, m, r, validity) = space
             .dht_db
             .async_commit(move |txn| {
                 let mut total = 0;
                 let mut awaiting = 0;
                 let mut missing = 0;
                 let mut rejected = 0;
+                // Only rejections are reported here. Ops which pass are
+                // reported once they have been app validated.
+                let mut validity = HashMap::new();
                 for outcome in chunk.into_iter().flatten() {
-                    let (op_hash, outcome, dependency) = outcome?;
+                    let (op_hash, author, outcome, dependency) = outcome?;
                     match outcome {
                         Outcome::Accepted => {
                             total += 1;
@@ -192,6 +198,7 @@ async fn sys_validation_workflow_inner(
                         }
                         Outcome::Rejected => {
                             rejected += 1;
+                            record_author_validity(&mut validity, author, false);
                             if let Dependency::Null = dependency {
                                 put_integrated(txn, &op_hash, ValidationStatus::Rejected)?;
                             } else {
@@ -200,9 +207,10 @@ async fn sys_validation_workflow_inner(
                         }
                     }
                 }
-                WorkflowResult::Ok((total, awaiting, missing, rejected))
+                WorkflowResult::Ok((total, awaiting, missing, rejected, validity))
             })
             .await?;
+        report_author_validity(&network, validity).await;
 
         total += t;
         if let (Some(start), Some(round_time)) = (start, &mut round_time) {
@@ -38,6 +38,36 @@ impl SweetConductorConfig {
         })
     }
 
+    /// Use this transport instead of QUIC
+    pub fn with_transport(mut self, transport: kitsune_p2p::TransportConfig) -> Self {
+        if let Some(c) = self.network.as_mut() {
+            c.transport_pool = vec![transport];
+        }
+        self
+    }
+
+    /// Discover peers over mdns on the loopback interface instead of
+    /// with a bootstrap service. Only conductors using the same
+    /// multicast address will discover each other.
+    pub fn with_loopback_mdns(mut self, multicast_addr: std::net::SocketAddrV4) -> Self {
+        if let Some(c) = self.network.as_mut() {
+            c.network_type = kitsune_p2p::NetworkType::QuicMdns;
+            c.bootstrap_service = None;
+            c.transport_pool = vec![kitsune_p2p::TransportConfig::Quic {
+                bind_to: Some(url2::url2!("kitsune-quic://127.0.0.1:0")),
+                override_host: None,
+                override_port: None,
+            }];
+            *c = c.clone().tune(|mut tp| {
+                tp.mdns_multicast_addr = multicast_addr.to_string();
+                tp.mdns_interface = "127.0.0.1".to_string();
+                tp.mdns_query_interval_ms = 500;
+                tp
+            });
+        }
+        self
+    }
+
     /// Completely disable networking
     pub fn no_networking(mut self) -> Self {
         if let Some(c) = self.network.as_mut() {
@@ -101,6 +101,20 @@ impl SweetConductorHandle {
         self.0.signal_broadcaster().subscribe_merged()
     }
 
+    /// Get a stream of the outcomes of `post_commit` callbacks which failed,
+    /// or succeeded after being retried, since the time of this function call.
+    pub async fn post_commit_outcomes(
+        &self,
+    ) -> impl tokio_stream::Stream<Item = PostCommitOutcome> {
+        use tokio_stream::StreamExt;
+        self.signal_stream()
+            .await
+            .filter_map(|signal| match signal {
+                Signal::System(SystemSignal::PostCommit(outcome)) => Some(outcome),
+                _ => None,
+            })
+    }
+
     /// Intentionally private clone function, only to be used internally
     pub(super) fn clone_privately(&self) -> Self {
         Self(self.0.clone())
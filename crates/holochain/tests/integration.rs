@@ -3,6 +3,7 @@ mod authored_test;
 mod dht_arc;
 mod inline_zome_spec;
 mod integrity_zome;
+mod mdns;
 mod multi_conductor;
 mod network_tests;
 mod new_lair;
@@ -11,4 +12,6 @@ mod sharded_gossip;
 mod speed_tests;
 mod test_cli;
 mod test_utils;
+#[cfg(feature = "tx5_tests")]
+mod tx5;
 mod websocket;
@@ -246,6 +246,7 @@ async fn test_update_admin_interface() {
             name: TestCoordinatorWasm::CoordinatorZomeUpdate.into(),
             hash: None,
             location: ZomeLocation::Bundled(TestCoordinatorWasm::CoordinatorZomeUpdate.into()),
+            sha256: None,
             dependencies: Some(vec![ZomeDependency {
                 name: TestIntegrityWasm::IntegrityZome.into(),
             }]),
@@ -0,0 +1,64 @@
+//! Conductors which only find each other over mdns, on loopback.
+
+use hdk::prelude::*;
+use holochain::sweettest::*;
+use holochain::test_utils::consistency_10s;
+use holochain::test_utils::inline_zomes::simple_create_read_zome;
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+/// A multicast group and port that only this test process uses.
+fn multicast_addr(port_offset: u16) -> SocketAddrV4 {
+    SocketAddrV4::new(
+        Ipv4Addr::new(239, 255, 42, 98),
+        40000 + (std::process::id() % 10000) as u16 + port_offset,
+    )
+}
+
+/// Without any bootstrap service or peer exchange, Bob learns of Alice
+/// over mdns and can get her op.
+#[tokio::test(flavor = "multi_thread")]
+async fn mdns_discovery_on_loopback() {
+    let _g = observability::test_run().ok();
+    let config = SweetConductorConfig::standard().with_loopback_mdns(multicast_addr(0));
+    let mut conductors = SweetConductorBatch::from_config(2, config).await;
+
+    let (dna_file, _, _) =
+        SweetDnaFile::unique_from_inline_zomes(("simple", simple_create_read_zome())).await;
+    let apps = conductors.setup_app("app", &[dna_file]).await.unwrap();
+    let ((alice,), (bobbo,)) = apps.into_tuples();
+
+    let hash: ActionHash = conductors[0]
+        .call(&alice.zome("simple"), "create", ())
+        .await;
+    consistency_10s([&alice, &bobbo]).await;
+
+    let record: Option<Record> = conductors[1]
+        .call(&bobbo.zome("simple"), "read", hash)
+        .await;
+    let record = record.expect("Record was None: bobbo couldn't `get` it");
+    assert_eq!(record.action().author(), alice.agent_pubkey());
+}
+
+/// Conductors in several spaces discover each other in all of them.
+#[tokio::test(flavor = "multi_thread")]
+async fn mdns_discovery_multiple_spaces() {
+    let _g = observability::test_run().ok();
+    let config = SweetConductorConfig::standard().with_loopback_mdns(multicast_addr(1));
+    let mut conductors = SweetConductorBatch::from_config(2, config).await;
+
+    let (dna_a, _, _) =
+        SweetDnaFile::unique_from_inline_zomes(("simple", simple_create_read_zome())).await;
+    let (dna_b, _, _) =
+        SweetDnaFile::unique_from_inline_zomes(("simple", simple_create_read_zome())).await;
+    let apps = conductors.setup_app("app", &[dna_a, dna_b]).await.unwrap();
+    let ((alice_a, alice_b), (bobbo_a, bobbo_b)) = apps.into_tuples();
+
+    let _: ActionHash = conductors[0]
+        .call(&alice_a.zome("simple"), "create", ())
+        .await;
+    let _: ActionHash = conductors[1]
+        .call(&bobbo_b.zome("simple"), "create", ())
+        .await;
+    consistency_10s([&alice_a, &bobbo_a]).await;
+    consistency_10s([&alice_b, &bobbo_b]).await;
+}
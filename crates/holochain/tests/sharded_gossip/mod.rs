@@ -587,6 +587,7 @@ async fn mock_network_sharded_gossip() {
                                                 ShardedGossipWire::accept(
                                                     vec![interval.into()],
                                                     vec![agent_info],
+                                                    None,
                                                 ),
                                             ),
                                         };
@@ -1121,6 +1122,7 @@ async fn mock_network_sharding() {
                                                 ShardedGossipWire::accept(
                                                     vec![interval.into()],
                                                     vec![agent_info],
+                                                    None,
                                                 ),
                                             ),
                                         };
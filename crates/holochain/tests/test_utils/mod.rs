@@ -249,6 +249,7 @@ pub async fn register_and_install_dna_named(
         name: role_name,
         dna: AppRoleDnaManifest {
             location: Some(DnaLocation::Bundled(dna_path.clone())),
+            sha256: None,
             modifiers: mods,
             version: Some(version),
             clone_limit: 0,
@@ -346,6 +347,12 @@ pub fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
         keystore: KeystoreConfig::DangerTestKeystore,
         db_sync_strategy: DbSyncStrategy::default(),
         chc_namespace: None,
+        zome_call_quotas: None,
+        wasm_limits: None,
+        traffic_weights: None,
+        post_commit_retry: None,
+        trusted_publishers: None,
+        bundle_fetch: None,
     }
 }
 
@@ -0,0 +1,103 @@
+//! Conductors networked over tx5 WebRTC, using a signal server on loopback.
+
+use hdk::prelude::*;
+use holochain::sweettest::*;
+use holochain::test_utils::consistency_10s;
+use holochain::test_utils::inline_zomes::simple_create_read_zome;
+use kitsune_p2p::test_util::LocalSignalServer;
+
+async fn webrtc_conductors(
+    signal: &LocalSignalServer,
+    num: usize,
+    config: SweetConductorConfig,
+) -> SweetConductorBatch {
+    SweetConductorBatch::from_config(num, config.with_transport(signal.transport_config())).await
+}
+
+/// Alice's op reaches Bob by publish.
+#[tokio::test(flavor = "multi_thread")]
+async fn webrtc_publish() {
+    let _g = observability::test_run().ok();
+    let signal = LocalSignalServer::spawn().await.unwrap();
+    let mut conductors = webrtc_conductors(&signal, 2, SweetConductorConfig::standard()).await;
+
+    let (dna_file, _, _) =
+        SweetDnaFile::unique_from_inline_zomes(("simple", simple_create_read_zome())).await;
+    let apps = conductors.setup_app("app", &[dna_file]).await.unwrap();
+    conductors.exchange_peer_info().await;
+    let ((alice,), (bobbo,)) = apps.into_tuples();
+
+    let hash: ActionHash = conductors[0]
+        .call(&alice.zome("simple"), "create", ())
+        .await;
+    consistency_10s([&alice, &bobbo]).await;
+
+    let record: Option<Record> = conductors[1]
+        .call(&bobbo.zome("simple"), "read", hash)
+        .await;
+    let record = record.expect("Record was None: bobbo couldn't `get` it");
+    assert_eq!(record.action().author(), alice.agent_pubkey());
+}
+
+/// With publish disabled, ops reach every conductor by gossip,
+/// and the gossip rounds show up in the network metrics.
+#[tokio::test(flavor = "multi_thread")]
+async fn webrtc_gossip() {
+    let _g = observability::test_run().ok();
+    let signal = LocalSignalServer::spawn().await.unwrap();
+    let mut conductors = webrtc_conductors(
+        &signal,
+        3,
+        SweetConductorConfig::standard().historical_only(),
+    )
+    .await;
+
+    let (dna_file, _, _) =
+        SweetDnaFile::unique_from_inline_zomes(("simple", simple_create_read_zome())).await;
+    let apps = conductors.setup_app("app", &[dna_file]).await.unwrap();
+    conductors.exchange_peer_info().await;
+    let ((alice,), (bobbo,), (carol,)) = apps.into_tuples();
+
+    let _: ActionHash = conductors[0]
+        .call(&alice.zome("simple"), "create", ())
+        .await;
+    consistency_10s([&alice, &bobbo, &carol]).await;
+
+    let metrics = conductors[1].dump_network_metrics(None).await.unwrap();
+    let metrics: serde_json::Value = serde_json::from_str(&metrics).unwrap();
+    let rounds = metrics[0]["metrics"]["rounds"]
+        .as_object()
+        .expect("Missing gossip round history");
+    assert!(!rounds.is_empty());
+}
+
+/// A conductor whose first signal server is down
+/// joins the network through its fallback.
+#[tokio::test(flavor = "multi_thread")]
+async fn webrtc_signal_fallback() {
+    let _g = observability::test_run().ok();
+    let signal = LocalSignalServer::spawn().await.unwrap();
+
+    let fallback = kitsune_p2p::TransportConfig::WebRTC {
+        // Nothing is listening on port 1.
+        signal_url: "ws://127.0.0.1:1".to_string(),
+        fallback_signal_urls: vec![signal.url().to_string()],
+        ice_servers: vec![],
+    };
+    let mut conductors = SweetConductorBatch::from_configs(vec![
+        SweetConductorConfig::standard().with_transport(signal.transport_config()),
+        SweetConductorConfig::standard().with_transport(fallback),
+    ])
+    .await;
+
+    let (dna_file, _, _) =
+        SweetDnaFile::unique_from_inline_zomes(("simple", simple_create_read_zome())).await;
+    let apps = conductors.setup_app("app", &[dna_file]).await.unwrap();
+    conductors.exchange_peer_info().await;
+    let ((alice,), (bobbo,)) = apps.into_tuples();
+
+    let _: ActionHash = conductors[0]
+        .call(&alice.zome("simple"), "create", ())
+        .await;
+    consistency_10s([&alice, &bobbo]).await;
+}
@@ -4,6 +4,7 @@ use self::get_agent_activity_query::hashes::GetAgentActivityQuery;
 use self::get_agent_activity_query::must_get_agent_activity::must_get_agent_activity;
 use self::get_entry_ops_query::GetEntryOpsQuery;
 use self::get_links_ops_query::GetLinksOpsQuery;
+use self::get_meta_query::GetMetaQuery;
 use self::{
     get_agent_activity_query::deterministic::DeterministicGetAgentActivityQuery,
     get_record_query::GetRecordOpsQuery,
@@ -24,6 +25,7 @@ mod test;
 pub(crate) mod get_agent_activity_query;
 pub(crate) mod get_entry_ops_query;
 pub(crate) mod get_links_ops_query;
+pub(crate) mod get_meta_query;
 pub(crate) mod get_record_query;
 
 /// Handler for get_entry query to an Entry authority
@@ -107,3 +109,17 @@ pub async fn handle_get_links(
         .await?;
     Ok(results)
 }
+
+/// Handler for get_meta query to a Record/Entry authority
+#[instrument(skip(env))]
+pub async fn handle_get_meta(
+    env: DbRead<DbKindDht>,
+    hash: AnyDhtHash,
+    options: holochain_p2p::event::GetMetaOptions,
+) -> CascadeResult<MetadataSet> {
+    let query = GetMetaQuery::new(hash, options.metadata_request);
+    let results = env
+        .async_reader(move |txn| query.run(Txn::from(&txn)))
+        .await?;
+    Ok(results)
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use holo_hash::ActionHash;
+use holo_hash::AnyDhtHash;
+use holochain_sqlite::rusqlite::named_params;
+use holochain_sqlite::rusqlite::Row;
+use holochain_state::query::prelude::*;
+use holochain_state::query::StateQueryError;
+use holochain_types::dht_op::DhtOpType;
+use holochain_types::prelude::EntryDhtStatus;
+use holochain_types::prelude::MetadataSet;
+use holochain_types::prelude::TimedActionHash;
+use holochain_zome_types::Action;
+use holochain_zome_types::HasValidationStatus;
+use holochain_zome_types::Judged;
+use holochain_zome_types::MetadataRequest;
+use holochain_zome_types::SignedAction;
+use holochain_zome_types::ValidationStatus;
+
+#[derive(Debug, Clone)]
+pub struct GetMetaQuery {
+    hash: AnyDhtHash,
+    request: MetadataRequest,
+}
+
+impl GetMetaQuery {
+    pub fn new(hash: AnyDhtHash, request: MetadataRequest) -> Self {
+        Self { hash, request }
+    }
+}
+
+pub struct Item {
+    op_type: DhtOpType,
+    action: Action,
+    timed_hash: TimedActionHash,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    actions: BTreeSet<TimedActionHash>,
+    invalid_actions: BTreeSet<TimedActionHash>,
+    rejected: bool,
+    deletes: BTreeSet<TimedActionHash>,
+    deleted: HashSet<ActionHash>,
+    updates: BTreeSet<TimedActionHash>,
+    links: BTreeSet<TimedActionHash>,
+    removed_links: HashSet<ActionHash>,
+}
+
+impl Query for GetMetaQuery {
+    type Item = Judged<Item>;
+    type State = State;
+    type Output = MetadataSet;
+
+    fn query(&self) -> String {
+        "
+        SELECT Action.hash AS action_hash, Action.blob AS action_blob,
+        DhtOp.type AS dht_type, DhtOp.validation_status AS status
+        FROM DhtOp
+        JOIN Action On DhtOp.action_hash = Action.hash
+        WHERE DhtOp.type IN (
+            :store_entry, :store_record, :delete_entry, :delete_record,
+            :update_content, :update_record, :add_link, :remove_link
+        )
+        AND
+        DhtOp.basis_hash = :hash
+        AND
+        DhtOp.when_integrated IS NOT NULL
+        "
+        .into()
+    }
+
+    fn params(&self) -> Vec<Params> {
+        let params = named_params! {
+            ":store_entry": DhtOpType::StoreEntry,
+            ":store_record": DhtOpType::StoreRecord,
+            ":delete_entry": DhtOpType::RegisterDeletedEntryAction,
+            ":delete_record": DhtOpType::RegisterDeletedBy,
+            ":update_content": DhtOpType::RegisterUpdatedContent,
+            ":update_record": DhtOpType::RegisterUpdatedRecord,
+            ":add_link": DhtOpType::RegisterAddLink,
+            ":remove_link": DhtOpType::RegisterRemoveLink,
+            ":hash": self.hash,
+        };
+        params.to_vec()
+    }
+
+    fn as_map(&self) -> Arc<dyn Fn(&Row) -> StateQueryResult<Self::Item>> {
+        let f = |row: &Row| {
+            let SignedAction(action, _) =
+                from_blob::<SignedAction>(row.get(row.as_ref().column_index("action_blob")?)?)?;
+            let action_hash = row.get(row.as_ref().column_index("action_hash")?)?;
+            let op_type = row.get(row.as_ref().column_index("dht_type")?)?;
+            let validation_status = row.get(row.as_ref().column_index("status")?)?;
+            let timed_hash = TimedActionHash {
+                timestamp: action.timestamp(),
+                action_hash,
+            };
+            Ok(Judged::raw(
+                Item {
+                    op_type,
+                    action,
+                    timed_hash,
+                },
+                validation_status,
+            ))
+        };
+        Arc::new(f)
+    }
+
+    fn init_fold(&self) -> StateQueryResult<Self::State> {
+        Ok(Default::default())
+    }
+
+    fn fold(&self, mut state: Self::State, dht_op: Self::Item) -> StateQueryResult<Self::State> {
+        let status = dht_op.validation_status();
+        let Item {
+            op_type,
+            action,
+            timed_hash,
+        } = dht_op.data;
+        let valid = status == Some(ValidationStatus::Valid);
+        match op_type {
+            DhtOpType::StoreEntry | DhtOpType::StoreRecord => {
+                if valid {
+                    state.actions.insert(timed_hash);
+                } else {
+                    state.rejected |= status == Some(ValidationStatus::Rejected);
+                    state.invalid_actions.insert(timed_hash);
+                }
+            }
+            DhtOpType::RegisterDeletedEntryAction | DhtOpType::RegisterDeletedBy => {
+                if let (true, Action::Delete(delete)) = (valid, &action) {
+                    state.deleted.insert(delete.deletes_address.clone());
+                    state.deletes.insert(timed_hash);
+                }
+            }
+            DhtOpType::RegisterUpdatedContent | DhtOpType::RegisterUpdatedRecord => {
+                if valid {
+                    state.updates.insert(timed_hash);
+                }
+            }
+            DhtOpType::RegisterAddLink => {
+                if valid {
+                    state.links.insert(timed_hash);
+                }
+            }
+            DhtOpType::RegisterRemoveLink => {
+                if let (true, Action::DeleteLink(delete_link)) = (valid, &action) {
+                    state
+                        .removed_links
+                        .insert(delete_link.link_add_address.clone());
+                }
+            }
+            _ => return Err(StateQueryError::UnexpectedOp(op_type)),
+        }
+        Ok(state)
+    }
+
+    fn render<S>(&self, state: Self::State, _stores: S) -> StateQueryResult<Self::Output>
+    where
+        S: Store,
+    {
+        let State {
+            actions,
+            invalid_actions,
+            rejected,
+            deletes,
+            deleted,
+            updates,
+            links,
+            removed_links,
+        } = state;
+        let request = &self.request;
+
+        let entry_dht_status = if actions
+            .iter()
+            .any(|action| !deleted.contains(&action.action_hash))
+        {
+            Some(EntryDhtStatus::Live)
+        } else if !actions.is_empty() {
+            Some(EntryDhtStatus::Dead)
+        } else if rejected {
+            Some(EntryDhtStatus::Rejected)
+        } else if !invalid_actions.is_empty() {
+            Some(EntryDhtStatus::Abandoned)
+        } else {
+            None
+        };
+
+        Ok(MetadataSet {
+            actions: if request.all_valid_actions {
+                actions
+            } else {
+                Default::default()
+            },
+            invalid_actions: if request.all_invalid_actions {
+                invalid_actions
+            } else {
+                Default::default()
+            },
+            deletes: if request.all_deletes {
+                deletes
+            } else {
+                Default::default()
+            },
+            updates: if request.all_updates {
+                updates
+            } else {
+                Default::default()
+            },
+            links: if request.all_links {
+                links
+                    .into_iter()
+                    .filter(|link| !removed_links.contains(&link.action_hash))
+                    .collect()
+            } else {
+                Default::default()
+            },
+            entry_dht_status: entry_dht_status.filter(|_| request.entry_dht_status),
+        })
+    }
+}
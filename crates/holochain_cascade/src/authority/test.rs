@@ -189,6 +189,65 @@ async fn get_links() {
     assert_eq!(result, expected);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn get_meta() {
+    observability::test_run().ok();
+    let db = test_dht_db();
+
+    let td = EntryTestData::create();
+    let options = holochain_p2p::event::GetMetaOptions {
+        metadata_request: MetadataRequest {
+            entry_dht_status: true,
+            ..Default::default()
+        },
+    };
+    let timed = |action: Action, action_hash: &ActionHash| TimedActionHash {
+        timestamp: action.timestamp(),
+        action_hash: action_hash.clone(),
+    };
+    let create = timed(td.store_entry_op.as_content().action(), &td.create_hash);
+    let update = timed(td.update_content_op.as_content().action(), &td.update_hash);
+    let delete = timed(
+        td.delete_entry_action_op.as_content().action(),
+        &td.delete_hash,
+    );
+    let link = timed(
+        td.create_link_action.action().clone(),
+        td.create_link_action.as_hash(),
+    );
+
+    fill_db(&db.to_db(), td.store_entry_op.clone());
+    fill_db(&db.to_db(), td.update_content_op.clone());
+    fill_db(&db.to_db(), td.create_link_op.clone());
+
+    let result = handle_get_meta(db.to_db().into(), td.hash.clone().into(), options.clone())
+        .await
+        .unwrap();
+    let expected = MetadataSet {
+        actions: [create.clone()].into(),
+        updates: [update.clone()].into(),
+        links: [link].into(),
+        entry_dht_status: Some(EntryDhtStatus::Live),
+        ..Default::default()
+    };
+    assert_eq!(result, expected);
+
+    fill_db(&db.to_db(), td.delete_entry_action_op.clone());
+    fill_db(&db.to_db(), td.delete_link_op.clone());
+
+    let result = handle_get_meta(db.to_db().into(), td.hash.clone().into(), options.clone())
+        .await
+        .unwrap();
+    let expected = MetadataSet {
+        actions: [create].into(),
+        updates: [update].into(),
+        deletes: [delete].into(),
+        entry_dht_status: Some(EntryDhtStatus::Dead),
+        ..Default::default()
+    };
+    assert_eq!(result, expected);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn get_agent_activity() {
     observability::test_run().ok();
@@ -33,6 +33,7 @@ use holo_hash::AnyDhtHash;
 use holo_hash::EntryHash;
 use holochain_p2p::actor::GetActivityOptions;
 use holochain_p2p::actor::GetLinksOptions;
+use holochain_p2p::actor::GetMetaOptions;
 use holochain_p2p::actor::GetOptions as NetworkGetOptions;
 use holochain_p2p::HolochainP2pDna;
 use holochain_p2p::HolochainP2pDnaT;
@@ -387,6 +388,16 @@ where
         Ok(())
     }
 
+    #[instrument(skip(self, options))]
+    async fn fetch_meta(
+        &mut self,
+        hash: AnyDhtHash,
+        options: GetMetaOptions,
+    ) -> CascadeResult<Vec<MetadataSet>> {
+        let network = some_or_return!(self.network.as_mut(), Vec::with_capacity(0));
+        Ok(network.get_meta(hash, options).await?)
+    }
+
     #[instrument(skip(self, options))]
     async fn fetch_agent_activity(
         &mut self,
@@ -868,6 +879,28 @@ where
         Ok(results)
     }
 
+    #[instrument(skip(self, options))]
+    /// Get the metadata held by the authorities for a hash.
+    /// Metadata is not cached, so unless we are an authority for
+    /// the hash the responses from the network are merged.
+    pub async fn get_meta(
+        &mut self,
+        hash: AnyDhtHash,
+        options: GetMetaOptions,
+    ) -> CascadeResult<Option<MetadataSet>> {
+        let authority = self.am_i_an_authority(hash.clone().into()).await?;
+        match (authority, self.dht.clone()) {
+            (true, Some(vault)) => Ok(Some(
+                authority::handle_get_meta(vault, hash, (&options).into()).await?,
+            )),
+            _ => Ok(self
+                .fetch_meta(hash, options)
+                .await?
+                .into_iter()
+                .reduce(MetadataSet::merge)),
+        }
+    }
+
     /// Request a hash bounded chain query.
     pub async fn must_get_agent_activity(
         &mut self,
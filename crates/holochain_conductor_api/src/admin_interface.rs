@@ -2,8 +2,9 @@ use holo_hash::*;
 use holochain_types::prelude::*;
 use holochain_zome_types::cell::CellId;
 use kitsune_p2p::agent_store::AgentInfoSigned;
+use std::collections::BTreeMap;
 
-use crate::{AppInfo, FullStateDump};
+use crate::{AppInfo, FullStateDump, ProvisionedCell};
 
 /// Represents the available conductor functions to call over an admin interface.
 ///
@@ -43,6 +44,17 @@ pub enum AdminRequest {
     /// [`AdminResponse::DnaDefinitionReturned`]
     GetDnaDefinition(Box<DnaHash>),
 
+    /// List the zome functions and callbacks exported by every zome of a DNA,
+    /// along with their signatures where the zome declares them.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::ZomeFunctionsListed`]
+    ListZomeFunctions {
+        /// The hash of the DNA to list the zome functions of
+        dna_hash: DnaHash,
+    },
+
     /// Update coordinator zomes for an already installed DNA.
     ///
     /// Replaces any installed coordinator zomes with the same zome name.
@@ -214,6 +226,11 @@ pub enum AdminRequest {
 
     /// Dump the network metrics tracked by kitsune.
     ///
+    /// This includes the recent gossip rounds with each remote node,
+    /// which can help diagnose nodes that are not converging,
+    /// and the ops still waiting to be fetched with their sources
+    /// and how many times each source has been tried.
+    ///
     /// # Returns
     ///
     /// [`AdminResponse::NetworkMetricsDumped`]
@@ -320,6 +337,28 @@ pub enum AdminRequest {
     ///
     /// [`AdminResponse::CloneCellDeleted`]
     DeleteCloneCell(Box<DeleteCloneCellPayload>),
+
+    /// Provision the cell of a role whose provisioning was deferred at install.
+    ///
+    /// Same as [`AppRequest::ProvisionDeferredCell`](crate::AppRequest::ProvisionDeferredCell),
+    /// for use by tools which manage apps on a user's behalf.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::DeferredCellProvisioned`]
+    ProvisionDeferredCell(Box<ProvisionDeferredCellPayload>),
+
+    /// Remove all compiled wasm from the conductor's on-disk cache.
+    ///
+    /// Zomes are compiled again, and cached again, the next time they are
+    /// loaded. This is only needed to reclaim disk space or to recover from
+    /// a corrupted cache, as artifacts from other wasmer versions are
+    /// already removed on startup.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::WasmCacheCleared`]
+    ClearWasmCache,
 }
 
 /// Represents the possible responses to an [`AdminRequest`]
@@ -344,6 +383,11 @@ pub enum AdminResponse {
     /// The successful response to an [`AdminRequest::GetDnaDefinition`]
     DnaDefinitionReturned(DnaDef),
 
+    /// The successful response to an [`AdminRequest::ListZomeFunctions`].
+    ///
+    /// Contains the externs of each zome of the DNA, keyed by zome name.
+    ZomeFunctionsListed(BTreeMap<ZomeName, Vec<ZomeFnInfo>>),
+
     /// The successful response to an [`AdminRequest::UpdateCoordinators`]
     CoordinatorsUpdated,
 
@@ -449,6 +493,14 @@ pub enum AdminResponse {
 
     /// The successful response to an [`AdminRequest::DeleteCloneCell`].
     CloneCellDeleted,
+
+    /// The successful response to an [`AdminRequest::ProvisionDeferredCell`].
+    ///
+    /// The role's cell has been created and is returned.
+    DeferredCellProvisioned(ProvisionedCell),
+
+    /// The successful response to an [`AdminRequest::ClearWasmCache`].
+    WasmCacheCleared,
 }
 
 /// Error type that goes over the websocket wire.
@@ -474,6 +526,9 @@ pub enum ExternalApiWireError {
     ZomeCallUnauthorized(String),
     /// A countersigning session has failed.
     CountersigningSessionError(String),
+    /// The zome call was rejected or abandoned because it exceeded a quota
+    /// set in the conductor config.
+    ZomeCallQuotaExceeded(String),
 }
 
 impl ExternalApiWireError {
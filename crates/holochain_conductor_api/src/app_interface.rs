@@ -3,7 +3,8 @@ use holo_hash::AgentPubKey;
 use holochain_keystore::LairResult;
 use holochain_keystore::MetaLairClient;
 use holochain_types::prelude::*;
-use kitsune_p2p::dependencies::kitsune_p2p_fetch::FetchPoolInfo;
+use kitsune_p2p::dependencies::kitsune_p2p_fetch::{FetchPoolInfo, FetchPoolItemInfo};
+use kitsune_p2p::metrics::PeerScore;
 use std::collections::HashMap;
 
 /// Represents the available conductor functions to call over an app interface
@@ -68,6 +69,16 @@ pub enum AppRequest {
     /// [`AppResponse::CloneCellEnabled`]
     EnableCloneCell(Box<EnableCloneCellPayload>),
 
+    /// Provision the cell of a role whose provisioning was deferred at install.
+    ///
+    /// A membrane proof and a network seed may be given now, in which case the
+    /// network seed gives the cell a different DNA hash than the role's DNA.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::DeferredCellProvisioned`]
+    ProvisionDeferredCell(Box<ProvisionDeferredCellPayload>),
+
     /// Info about networking processes
     NetworkInfo(Box<NetworkInfoRequestPayload>),
 }
@@ -110,6 +121,11 @@ pub enum AppResponse {
     /// is returned.
     CloneCellEnabled(ClonedCell),
 
+    /// The successful response to an [`AppRequest::ProvisionDeferredCell`].
+    ///
+    /// The role's cell has been created and is returned.
+    DeferredCellProvisioned(ProvisionedCell),
+
     /// NetworkInfo is returned
     NetworkInfo(Vec<NetworkInfo>),
 }
@@ -211,7 +227,6 @@ pub enum CellInfo {
     Cloned(ClonedCell),
 
     /// Potential cells with deferred installation as defined in the bundle.
-    /// Not yet implemented.
     Stem(StemCell),
 }
 
@@ -244,7 +259,6 @@ impl CellInfo {
 }
 
 /// Cell whose instantiation has been deferred.
-/// Not yet implemented.
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct StemCell {
     /// The hash of the DNA that this cell would be instantiated from
@@ -325,11 +339,19 @@ impl AppInfo {
                 } else {
                     tracing::error!("no DNA definition found for cell id {}", provisioned_cell);
                 }
-            } else {
-                // no provisioned cell, thus there must be a deferred cell
-                // this is not implemented as of now
-                unimplemented!()
-            };
+            } else if role_assignment.is_deferred() {
+                let cell_id = role_assignment.cell_id();
+                if let Some(dna_def) = dna_definitions.get(cell_id) {
+                    cell_info_for_role.push(CellInfo::Stem(StemCell {
+                        original_dna_hash: dna_def.hash.to_owned(),
+                        dna_modifiers: dna_def.modifiers.to_owned(),
+                        name: None,
+                    }));
+                } else {
+                    tracing::error!("no DNA definition found for cell id {}", cell_id);
+                }
+            }
+            // otherwise the role is disabled and only has clone cells
 
             // push enabled clone cells to the vector of cell infos
             if let Some(clone_cells) = app.clone_cells_for_role_name(role_name) {
@@ -410,9 +432,27 @@ impl From<AppInfoStatus> for AppStatus {
     }
 }
 
+/// The most pending fetches listed in [`NetworkInfo::pending_fetches`].
+pub const MAX_PENDING_FETCHES: usize = 100;
+
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
 pub struct NetworkInfo {
     pub fetch_pool_info: FetchPoolInfo,
+    /// The first [`MAX_PENDING_FETCHES`] items waiting to be fetched, with
+    /// their sources and how many times each source has been asked.
+    /// `fetch_pool_info` counts all of them.
+    #[serde(default)]
+    pub pending_fetches: Vec<FetchPoolItemInfo>,
+    /// The scores of the remote agents we have interacted with.
+    pub peer_scores: Vec<AgentPeerScore>,
+}
+
+/// The score of a remote agent, which is used to choose which agents
+/// to gossip with and request data from.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AgentPeerScore {
+    pub agent: AgentPubKey,
+    pub score: PeerScore,
 }
 
 #[test]
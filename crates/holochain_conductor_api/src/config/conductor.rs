@@ -12,6 +12,10 @@ mod dpki_config;
 mod error;
 mod keystore_config;
 pub mod paths;
+mod post_commit_retry_config;
+mod traffic_weights_config;
+mod wasm_limits_config;
+mod zome_call_quota_config;
 //mod logger_config;
 //mod signal_config;
 pub use paths::DatabaseRootPath;
@@ -21,6 +25,10 @@ pub use dpki_config::DpkiConfig;
 //pub use logger_config::LoggerConfig;
 pub use error::*;
 pub use keystore_config::KeystoreConfig;
+pub use post_commit_retry_config::*;
+pub use traffic_weights_config::*;
+pub use wasm_limits_config::*;
+pub use zome_call_quota_config::*;
 //pub use signal_config::SignalConfig;
 use std::path::Path;
 
@@ -62,6 +70,40 @@ pub struct ConductorConfig {
     /// [sqlite documentation]: https://www.sqlite.org/pragma.html#pragma_synchronous
     #[serde(default)]
     pub db_sync_strategy: DbSyncStrategy,
+
+    /// Optional limits on zome calls made through app interfaces
+    /// and by remote agents. No limits are enforced if omitted.
+    #[serde(default)]
+    pub zome_call_quotas: Option<ZomeCallQuotaConfig>,
+
+    /// Optional limits on the fuel and memory used by wasm callbacks.
+    /// Built-in defaults are used if omitted.
+    #[serde(default)]
+    pub wasm_limits: Option<WasmLimitsConfig>,
+
+    /// Optional weights of the outbound traffic classes in the network
+    /// spaces of particular DNAs. The tuning params are used if omitted.
+    #[serde(default)]
+    pub traffic_weights: Option<TrafficWeightsConfig>,
+
+    /// Optional retrying of `post_commit` callbacks which return an error.
+    /// Failed callbacks are not retried if omitted.
+    #[serde(default)]
+    pub post_commit_retry: Option<PostCommitRetryConfig>,
+
+    /// Optional list of publishers whose app bundles may be installed.
+    /// If set, bundles which are unsigned or signed by any other publisher
+    /// are rejected at install. If omitted, any bundle may be installed,
+    /// though a bundle whose signature is invalid is always rejected.
+    #[serde(default)]
+    pub trusted_publishers: Option<Vec<mr_bundle::PublisherKey>>,
+
+    /// Optional config for fetching the resources of app bundles which are
+    /// located by URL, such as where to cache them and whether to work offline.
+    /// If omitted, this is read from the `HC_BUNDLE_CACHE_DIR` and
+    /// `HC_BUNDLE_OFFLINE` environment variables.
+    #[serde(default)]
+    pub bundle_fetch: Option<mr_bundle::FetchConfig>,
     //
     //
     // Which signals to emit
@@ -134,6 +176,12 @@ pub mod tests {
                 admin_interfaces: None,
                 db_sync_strategy: DbSyncStrategy::default(),
                 chc_namespace: None,
+                zome_call_quotas: None,
+                wasm_limits: None,
+                traffic_weights: None,
+                post_commit_retry: None,
+                trusted_publishers: None,
+                bundle_fetch: None,
             }
         );
     }
@@ -183,6 +231,10 @@ pub mod tests {
       network_type: quic_bootstrap
 
     db_sync_strategy: Fast
+
+    bundle_fetch:
+      cache_dir: /path/to/bundle_cache
+      offline: true
     "#;
         let result: ConductorConfigResult<ConductorConfig> = config_from_yaml(yaml);
         use holochain_p2p::kitsune_p2p::*;
@@ -225,6 +277,15 @@ pub mod tests {
                 network: Some(network_config),
                 db_sync_strategy: DbSyncStrategy::Fast,
                 chc_namespace: None,
+                zome_call_quotas: None,
+                wasm_limits: None,
+                traffic_weights: None,
+                post_commit_retry: None,
+                trusted_publishers: None,
+                bundle_fetch: Some(mr_bundle::FetchConfig {
+                    cache_dir: Some(PathBuf::from("/path/to/bundle_cache")),
+                    offline: true,
+                }),
             }
         );
     }
@@ -252,6 +313,12 @@ pub mod tests {
                 admin_interfaces: None,
                 db_sync_strategy: DbSyncStrategy::Fast,
                 chc_namespace: None,
+                zome_call_quotas: None,
+                wasm_limits: None,
+                traffic_weights: None,
+                post_commit_retry: None,
+                trusted_publishers: None,
+                bundle_fetch: None,
             }
         );
     }
@@ -0,0 +1,101 @@
+//! Retrying of `post_commit` callbacks which fail.
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+/// How failed `post_commit` callbacks are retried.
+///
+/// Failed invocations are stored in the conductor database, so pending
+/// retries survive a restart. The delay before each retry doubles, starting
+/// from `initial_backoff_ms` and capped at `max_backoff_ms`.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PostCommitRetryConfig {
+    /// The maximum number of times `post_commit` is run for the same
+    /// actions, including the first attempt.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// The delay before the first retry, in milliseconds.
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+
+    /// The longest delay between two retries, in milliseconds.
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60_000
+}
+
+impl Default for PostCommitRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+impl PostCommitRetryConfig {
+    /// The delay before retrying an invocation which has failed `attempt`
+    /// times, or `None` if it should not be retried again.
+    pub fn backoff(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let factor = 1u64
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(self.max_backoff_ms);
+        Some(Duration::from_millis(delay))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_commit_retry_config_yaml() {
+        let yaml = r#"---
+    max_attempts: 4
+    initial_backoff_ms: 100
+    "#;
+        let config: PostCommitRetryConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config,
+            PostCommitRetryConfig {
+                max_attempts: 4,
+                initial_backoff_ms: 100,
+                max_backoff_ms: 60_000,
+            }
+        );
+        assert_eq!(config.backoff(1), Some(Duration::from_millis(100)));
+        assert_eq!(config.backoff(2), Some(Duration::from_millis(200)));
+        assert_eq!(config.backoff(3), Some(Duration::from_millis(400)));
+        assert_eq!(config.backoff(4), None);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let config = PostCommitRetryConfig {
+            max_attempts: 100,
+            ..Default::default()
+        };
+        assert_eq!(config.backoff(70), Some(Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,59 @@
+//! Weights of the outbound traffic classes in the network space of each DNA.
+
+use holo_hash::DnaHash;
+use holo_hash::DnaHashB64;
+use holochain_p2p::kitsune_p2p::traffic::TrafficWeights;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Traffic class weights for the network spaces of particular DNAs.
+/// Spaces of other DNAs use the weights from the network tuning params.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct TrafficWeightsConfig {
+    /// Per-DNA weights, overriding the tuning params.
+    #[serde(default)]
+    pub dnas: HashMap<DnaHashB64, TrafficWeights>,
+}
+
+impl TrafficWeightsConfig {
+    /// The weights which apply to the given DNA
+    pub fn for_dna(&self, dna_hash: &DnaHash) -> TrafficWeights {
+        self.dnas
+            .get(&DnaHashB64::from(dna_hash.clone()))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traffic_weights_config_yaml() {
+        let dna_hash = DnaHash::from_raw_36(vec![0xdb; 36]);
+        let yaml = format!(
+            r#"---
+    dnas:
+      {}:
+        interactive: 16
+        historic_gossip: 4
+    "#,
+            DnaHashB64::from(dna_hash.clone())
+        );
+        let config: TrafficWeightsConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            config.for_dna(&dna_hash),
+            TrafficWeights {
+                interactive: Some(16),
+                historic_gossip: Some(4),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            config.for_dna(&DnaHash::from_raw_36(vec![0xdc; 36])),
+            TrafficWeights::default()
+        );
+    }
+}
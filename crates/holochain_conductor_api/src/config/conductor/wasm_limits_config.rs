@@ -0,0 +1,111 @@
+//! Limits on the CPU ("fuel") and memory which wasm callbacks may use.
+
+use holo_hash::DnaHash;
+use holo_hash::DnaHashB64;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Wasm fuel and memory limits for every DNA run by the conductor.
+///
+/// Note that validation treats a callback exceeding its limits as an invalid
+/// op. Every conductor on a network should use the same `validate` limits for
+/// a DNA, or they may disagree about the validity of ops.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct WasmLimitsConfig {
+    /// The limits applied to every DNA which has no entry in `dnas`.
+    #[serde(default)]
+    pub default: WasmLimits,
+
+    /// Per-DNA limits, overriding `default`.
+    #[serde(default)]
+    pub dnas: HashMap<DnaHashB64, WasmLimits>,
+}
+
+impl WasmLimitsConfig {
+    /// The limits which apply to the given DNA
+    pub fn for_dna(&self, dna_hash: &DnaHash) -> &WasmLimits {
+        self.dnas
+            .get(&DnaHashB64::from(dna_hash.clone()))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// The limits for each kind of wasm callback of a DNA.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct WasmLimits {
+    /// Limits for the `validate` callback.
+    #[serde(default)]
+    pub validate: WasmCallLimits,
+
+    /// Limits for the `init` callback.
+    #[serde(default)]
+    pub init: WasmCallLimits,
+
+    /// Limits for the `post_commit` callback.
+    #[serde(default)]
+    pub post_commit: WasmCallLimits,
+
+    /// Limits for extern functions called as zome calls.
+    #[serde(default)]
+    pub zome_call: WasmCallLimits,
+
+    /// Limits for all other callbacks, such as `entry_defs` and
+    /// `genesis_self_check`.
+    #[serde(default)]
+    pub other: WasmCallLimits,
+}
+
+/// Limits on a single call into wasm. Unset limits fall back to the
+/// conductor's built-in defaults.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct WasmCallLimits {
+    /// The maximum number of wasm operations the call may execute.
+    pub fuel: Option<u64>,
+
+    /// The maximum size, in bytes, the guest's linear memory may grow to,
+    /// rounded down to whole 64KiB wasm pages.
+    pub max_memory_bytes: Option<u64>,
+
+    /// The longest, in milliseconds, a single call to the `sleep` host
+    /// function may sleep for. Longer sleeps are cut short to this.
+    #[serde(default)]
+    pub max_sleep_ms: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_limits_config_yaml() {
+        let dna_hash = DnaHash::from_raw_36(vec![0xdb; 36]);
+        let yaml = format!(
+            r#"---
+    default:
+      validate:
+        fuel: 1000000
+        max_memory_bytes: 16777216
+    dnas:
+      {}:
+        zome_call:
+          fuel: 5000
+    "#,
+            DnaHashB64::from(dna_hash.clone())
+        );
+        let config: WasmLimitsConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            config
+                .for_dna(&DnaHash::from_raw_36(vec![0xdc; 36]))
+                .validate,
+            WasmCallLimits {
+                fuel: Some(1_000_000),
+                max_memory_bytes: Some(16 * 1024 * 1024),
+                max_sleep_ms: None,
+            }
+        );
+        let limits = config.for_dna(&dna_hash);
+        assert_eq!(limits.zome_call.fuel, Some(5000));
+        assert_eq!(limits.validate, WasmCallLimits::default());
+    }
+}
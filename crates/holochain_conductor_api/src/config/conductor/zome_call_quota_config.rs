@@ -0,0 +1,104 @@
+//! Limits on how many zome calls the conductor accepts, and how expensive
+//! each one may be, for a single installed app or remote agent.
+
+use holochain_types::prelude::InstalledAppId;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Zome call quotas for the app interfaces and for incoming `call_remote`s.
+///
+/// Calls from app interfaces are counted against the app which the called
+/// cell belongs to. A cell shared by several apps belongs to the app which
+/// provisioned it. Remote calls are counted against the calling agent.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct ZomeCallQuotaConfig {
+    /// The quota applied to every installed app which has no entry in `apps`.
+    #[serde(default)]
+    pub default_app: ZomeCallQuota,
+
+    /// Per-app quotas, overriding `default_app`.
+    #[serde(default)]
+    pub apps: HashMap<InstalledAppId, ZomeCallQuota>,
+
+    /// The quota applied to each remote agent making zome calls into this
+    /// conductor via `call_remote`.
+    #[serde(default)]
+    pub remote_agent: ZomeCallQuota,
+}
+
+impl ZomeCallQuotaConfig {
+    /// The quota which applies to the given app
+    pub fn for_app(&self, installed_app_id: &InstalledAppId) -> &ZomeCallQuota {
+        self.apps.get(installed_app_id).unwrap_or(&self.default_app)
+    }
+}
+
+/// A set of limits on zome calls. Any limit left unset is not enforced.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq, Eq)]
+pub struct ZomeCallQuota {
+    /// The maximum number of calls accepted in any one second.
+    pub calls_per_second: Option<u32>,
+
+    /// The maximum number of calls which may be running at the same time.
+    pub max_concurrent_calls: Option<u32>,
+
+    /// The maximum wall-clock time a single call may take, in milliseconds.
+    /// Calls running longer are abandoned and reported as over quota.
+    pub max_call_duration_ms: Option<u64>,
+
+    /// The maximum wasm fuel a single call may use. This can only lower the
+    /// fuel limit set for zome calls in the conductor's wasm limits.
+    pub max_fuel_per_call: Option<u64>,
+
+    /// The maximum number of network requests (gets, remote calls and
+    /// signals) a single call may make.
+    pub max_network_requests_per_call: Option<u32>,
+}
+
+impl ZomeCallQuota {
+    /// Whether this quota enforces any limit at all
+    pub fn is_unlimited(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zome_call_quota_config_yaml() {
+        let yaml = r#"---
+    default_app:
+      calls_per_second: 100
+      max_concurrent_calls: 10
+    apps:
+      chatty-app:
+        calls_per_second: 1000
+    remote_agent:
+      calls_per_second: 5
+      max_call_duration_ms: 2000
+      max_fuel_per_call: 1000000
+      max_network_requests_per_call: 20
+    "#;
+        let config: ZomeCallQuotaConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.default_app.calls_per_second, Some(100));
+        assert_eq!(
+            config
+                .for_app(&"other-app".to_string())
+                .max_concurrent_calls,
+            Some(10)
+        );
+        assert_eq!(
+            config.for_app(&"chatty-app".to_string()),
+            &ZomeCallQuota {
+                calls_per_second: Some(1000),
+                ..Default::default()
+            }
+        );
+        assert_eq!(config.remote_agent.max_network_requests_per_call, Some(20));
+        assert_eq!(config.remote_agent.max_fuel_per_call, Some(1_000_000));
+        assert!(ZomeCallQuota::default().is_unlimited());
+    }
+}
@@ -141,6 +141,16 @@ pub enum CapAccess {
     },
 }
 
+impl CapAccess {
+    /// The secret required by this access, if any.
+    pub fn secret(&self) -> Option<&CapSecret> {
+        match self {
+            CapAccess::Unrestricted => None,
+            CapAccess::Transferable { secret } | CapAccess::Assigned { secret, .. } => Some(secret),
+        }
+    }
+}
+
 /// Implements ().into() shorthand for CapAccess::Unrestricted
 impl From<()> for CapAccess {
     fn from(_: ()) -> Self {
@@ -226,6 +226,17 @@ impl RecordEntry {
             _ => None,
         }
     }
+
+    /// Provides CapClaimEntry if it exists
+    ///
+    /// same as as_option but handles cap claims
+    /// anything other tha RecordEntry::Present for a Entry::CapClaim returns None
+    pub fn to_claim_option(&self) -> Option<crate::entry::CapClaimEntry> {
+        match self.as_option() {
+            Some(Entry::CapClaim(cap_claim_entry)) => Some(cap_claim_entry.to_owned()),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(feature = "test_utils")]
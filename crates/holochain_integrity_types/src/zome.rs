@@ -94,3 +94,9 @@ impl AsRef<str> for FunctionName {
         self.0.as_ref()
     }
 }
+
+/// The custom wasm section `hdk_extern` writes the signature of every extern
+/// into, so the host can describe the functions of a zome without calling it.
+///
+/// Each extern adds one `name\tinput\toutput\n` line to the section.
+pub const EXTERN_SIGNATURES_SECTION: &str = "hc_extern_signatures";
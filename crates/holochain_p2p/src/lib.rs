@@ -153,6 +153,13 @@ pub trait HolochainP2pDnaT {
     /// New data has been integrated and is ready for gossiping.
     async fn new_integrated_data(&self) -> actor::HolochainP2pResult<()>;
 
+    /// Report whether data from an agent turned out to be valid.
+    async fn report_data_validity(
+        &self,
+        agent: AgentPubKey,
+        valid: bool,
+    ) -> actor::HolochainP2pResult<()>;
+
     /// Access to the specified CHC
     fn chc(&self) -> Option<ChcImpl>;
 }
@@ -164,11 +171,48 @@ pub struct HolochainP2pDna {
     sender: ghost_actor::GhostSender<actor::HolochainP2p>,
     dna_hash: Arc<DnaHash>,
     chc: Option<ChcImpl>,
+    request_budget: Option<Arc<RequestBudget>>,
 }
 
 /// A CHC implementation
 pub type ChcImpl = Arc<dyn Send + Sync + ChainHeadCoordinator<Item = SignedActionHashed>>;
 
+/// A limit on the number of network requests made through a [`HolochainP2pDna`]
+/// and all of its clones.
+struct RequestBudget {
+    max_requests: u32,
+    spent: std::sync::atomic::AtomicU32,
+}
+
+impl HolochainP2pDna {
+    /// Limit the number of requests (calls, signals and gets) which can be
+    /// made through this sender and its clones to `max_requests`.
+    /// Any further request fails with [`HolochainP2pError::RequestBudgetExceeded`].
+    pub fn with_request_budget(mut self, max_requests: u32) -> Self {
+        self.request_budget = Some(Arc::new(RequestBudget {
+            max_requests,
+            spent: std::sync::atomic::AtomicU32::new(0),
+        }));
+        self
+    }
+
+    fn spend_request_budget(&self) -> actor::HolochainP2pResult<()> {
+        match &self.request_budget {
+            Some(budget)
+                if budget
+                    .spent
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                    >= budget.max_requests =>
+            {
+                Err(HolochainP2pError::RequestBudgetExceeded(
+                    budget.max_requests,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl HolochainP2pDnaT for HolochainP2pDna {
     /// owned getter
@@ -205,6 +249,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         nonce: Nonce256Bits,
         expires_at: Timestamp,
     ) -> actor::HolochainP2pResult<SerializedBytes> {
+        self.spend_request_budget()?;
         self.sender
             .call_remote(
                 (*self.dna_hash).clone(),
@@ -236,6 +281,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         nonce: Nonce256Bits,
         expires_at: Timestamp,
     ) -> actor::HolochainP2pResult<()> {
+        self.spend_request_budget()?;
         self.sender
             .remote_signal(
                 (*self.dna_hash).clone(),
@@ -294,6 +340,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         dht_hash: holo_hash::AnyDhtHash,
         options: actor::GetOptions,
     ) -> actor::HolochainP2pResult<Vec<WireOps>> {
+        self.spend_request_budget()?;
         self.sender
             .get((*self.dna_hash).clone(), dht_hash, options)
             .instrument(tracing::debug_span!("HolochainP2p::get"))
@@ -306,6 +353,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         dht_hash: holo_hash::AnyDhtHash,
         options: actor::GetMetaOptions,
     ) -> actor::HolochainP2pResult<Vec<MetadataSet>> {
+        self.spend_request_budget()?;
         self.sender
             .get_meta((*self.dna_hash).clone(), dht_hash, options)
             .await
@@ -317,6 +365,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         link_key: WireLinkKey,
         options: actor::GetLinksOptions,
     ) -> actor::HolochainP2pResult<Vec<WireLinkOps>> {
+        self.spend_request_budget()?;
         self.sender
             .get_links((*self.dna_hash).clone(), link_key, options)
             .await
@@ -329,6 +378,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         query: ChainQueryFilter,
         options: actor::GetActivityOptions,
     ) -> actor::HolochainP2pResult<Vec<AgentActivityResponse<ActionHash>>> {
+        self.spend_request_budget()?;
         self.sender
             .get_agent_activity((*self.dna_hash).clone(), agent, query, options)
             .await
@@ -339,6 +389,7 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         author: AgentPubKey,
         filter: holochain_zome_types::chain::ChainFilter,
     ) -> actor::HolochainP2pResult<Vec<MustGetAgentActivityResponse>> {
+        self.spend_request_budget()?;
         self.sender
             .must_get_agent_activity((*self.dna_hash).clone(), author, filter)
             .await
@@ -381,6 +432,16 @@ impl HolochainP2pDnaT for HolochainP2pDna {
             .await
     }
 
+    async fn report_data_validity(
+        &self,
+        agent: AgentPubKey,
+        valid: bool,
+    ) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .report_data_validity((*self.dna_hash).clone(), agent, valid)
+            .await
+    }
+
     fn chc(&self) -> Option<ChcImpl> {
         self.chc.clone()
     }
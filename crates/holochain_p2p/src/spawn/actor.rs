@@ -940,6 +940,53 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
 
 impl ghost_actor::GhostHandler<HolochainP2p> for HolochainP2pActor {}
 
+/// Decode the responses to an rpc_multi request, keeping those which decode.
+/// Agents which sent responses that can't be decoded are reported to kitsune
+/// as having sent invalid data, in the background so scoring never delays
+/// the response. Whether the data itself is valid is reported once it has
+/// been validated. Fails only if no response could be decoded.
+fn decode_rpc_multi_responses<T>(
+    kitsune_p2p: &ghost_actor::GhostSender<kitsune_p2p::actor::KitsuneP2p>,
+    space: Arc<kitsune_p2p::KitsuneSpace>,
+    responses: Vec<kitsune_p2p::actor::RpcMultiResponse>,
+) -> HolochainP2pResult<Vec<T>>
+where
+    T: TryFrom<SerializedBytes, Error = SerializedBytesError>,
+{
+    let mut out = Vec::new();
+    let mut invalid = Vec::new();
+    let mut first_err = None;
+    for kitsune_p2p::actor::RpcMultiResponse { agent, response } in responses {
+        match T::try_from(SerializedBytes::from(UnsafeBytes::from(response))) {
+            Ok(decoded) => out.push(decoded),
+            Err(err) => {
+                tracing::debug!(?err, "discarding undecodable rpc_multi response");
+                invalid.push(agent);
+                first_err.get_or_insert(HolochainP2pError::from(err));
+            }
+        }
+    }
+
+    if !invalid.is_empty() {
+        let kitsune_p2p = kitsune_p2p.clone();
+        tokio::task::spawn(async move {
+            for agent in invalid {
+                if let Err(err) = kitsune_p2p
+                    .report_data_validity(space.clone(), agent, false)
+                    .await
+                {
+                    tracing::warn!(?err, "failed to report data validity");
+                }
+            }
+        });
+    }
+
+    match first_err {
+        Some(err) if out.is_empty() => Err(err),
+        _ => Ok(out),
+    }
+}
+
 impl HolochainP2pHandler for HolochainP2pActor {
     #[tracing::instrument(skip(self), level = "trace")]
     fn handle_join(
@@ -1167,19 +1214,14 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let kitsune_p2p = self.kitsune_p2p.clone();
         let tuning_params = self.tuning_params.clone();
         Ok(async move {
-            let input = kitsune_p2p::actor::RpcMulti::new(&tuning_params, space, basis, payload);
+            let input =
+                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space.clone(), basis, payload);
             let result = kitsune_p2p
                 .rpc_multi(input)
                 .instrument(tracing::debug_span!("rpc_multi"))
                 .await?;
 
-            let mut out = Vec::new();
-            for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
-            }
-
-            Ok(out)
+            decode_rpc_multi_responses(&kitsune_p2p, space, result)
         }
         .boxed()
         .into())
@@ -1201,16 +1243,11 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let kitsune_p2p = self.kitsune_p2p.clone();
         let tuning_params = self.tuning_params.clone();
         Ok(async move {
-            let input = kitsune_p2p::actor::RpcMulti::new(&tuning_params, space, basis, payload);
+            let input =
+                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space.clone(), basis, payload);
             let result = kitsune_p2p.rpc_multi(input).await?;
 
-            let mut out = Vec::new();
-            for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
-            }
-
-            Ok(out)
+            decode_rpc_multi_responses(&kitsune_p2p, space, result)
         }
         .boxed()
         .into())
@@ -1233,20 +1270,14 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let tuning_params = self.tuning_params.clone();
         Ok(async move {
             let mut input =
-                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space, basis, payload);
+                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space.clone(), basis, payload);
             // NOTE - We're just targeting a single remote node for now
             //        without doing any pagination / etc...
             //        Setting up RpcMulti to act like RpcSingle
             input.max_remote_agent_count = 1;
             let result = kitsune_p2p.rpc_multi(input).await?;
 
-            let mut out = Vec::new();
-            for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
-            }
-
-            Ok(out)
+            decode_rpc_multi_responses(&kitsune_p2p, space, result)
         }
         .boxed()
         .into())
@@ -1274,20 +1305,14 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let tuning_params = self.tuning_params.clone();
         Ok(async move {
             let mut input =
-                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space, basis, payload);
+                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space.clone(), basis, payload);
             // TODO - We're just targeting a single remote node for now
             //        without doing any pagination / etc...
             //        Setting up RpcMulti to act like RpcSingle
             input.max_remote_agent_count = 1;
             let result = kitsune_p2p.rpc_multi(input).await?;
 
-            let mut out = Vec::new();
-            for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
-            }
-
-            Ok(out)
+            decode_rpc_multi_responses(&kitsune_p2p, space, result)
         }
         .boxed()
         .into())
@@ -1312,20 +1337,14 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let tuning_params = self.tuning_params.clone();
         Ok(async move {
             let mut input =
-                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space, basis, payload);
+                kitsune_p2p::actor::RpcMulti::new(&tuning_params, space.clone(), basis, payload);
             // TODO - We're just targeting a single remote node for now
             //        without doing any pagination / etc...
             //        Setting up RpcMulti to act like RpcSingle
             input.max_remote_agent_count = 1;
             let result = kitsune_p2p.rpc_multi(input).await?;
 
-            let mut out = Vec::new();
-            for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
-            }
-
-            Ok(out)
+            decode_rpc_multi_responses(&kitsune_p2p, space, result)
         }
         .boxed()
         .into())
@@ -1381,6 +1400,26 @@ impl HolochainP2pHandler for HolochainP2pActor {
         )
     }
 
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_report_data_validity(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        valid: bool,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            Ok(kitsune_p2p
+                .report_data_validity(space, agent, valid)
+                .await?)
+        }
+        .boxed()
+        .into())
+    }
+
     #[tracing::instrument(skip(self), level = "trace")]
     fn handle_countersigning_session_negotiation(
         &mut self,
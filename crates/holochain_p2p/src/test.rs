@@ -151,6 +151,15 @@ impl HolochainP2pHandler for StubNetwork {
     ) -> HolochainP2pHandlerResult<bool> {
         Err("stub".into())
     }
+
+    fn handle_report_data_validity(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        valid: bool,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
     fn handle_countersigning_session_negotiation(
         &mut self,
         dna_hash: DnaHash,
@@ -30,6 +30,10 @@ pub enum HolochainP2pError {
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
 
+    /// The network request budget given to this sender has been used up
+    #[error("The limit of {0} network requests has been reached")]
+    RequestBudgetExceeded(u32),
+
     /// Chain Head Coordination error
     #[error(transparent)]
     ChcError(#[from] holochain_types::chc::ChcError),
@@ -346,6 +346,10 @@ ghost_actor::ghost_chan! {
         /// Check if any local agent in this space is an authority for a hash.
         fn authority_for_hash(dna_hash: DnaHash, basis: OpBasis) -> bool;
 
+        /// Report whether data from an agent turned out to be valid,
+        /// so that kitsune can prefer agents which send valid data.
+        fn report_data_validity(dna_hash: DnaHash, agent: AgentPubKey, valid: bool) -> ();
+
         /// Messages between agents negotiation a countersigning session.
         fn countersigning_session_negotiation(
             dna_hash: DnaHash,
@@ -383,6 +387,7 @@ impl HolochainP2pRefToDna for HolochainP2pRef {
             sender: self,
             dna_hash: Arc::new(dna_hash),
             chc,
+            request_budget: None,
         }
     }
 
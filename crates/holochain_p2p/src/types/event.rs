@@ -49,12 +49,18 @@ impl Default for GetRequest {
 }
 
 /// GetMeta options help control how the get is processed at various levels.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct GetMetaOptions {}
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct GetMetaOptions {
+    /// Which metadata to return.
+    #[serde(default)]
+    pub metadata_request: MetadataRequest,
+}
 
 impl From<&actor::GetMetaOptions> for GetMetaOptions {
-    fn from(_a: &actor::GetMetaOptions) -> Self {
-        Self {}
+    fn from(a: &actor::GetMetaOptions) -> Self {
+        Self {
+            metadata_request: a.metadata_request.clone(),
+        }
     }
 }
 
@@ -1,10 +1,13 @@
 use crate::prelude::{DatabaseError, DatabaseResult};
 use crate::sql::*;
 use holochain_zome_types::prelude::*;
-use kitsune_p2p::event::MetricRecord;
+use kitsune_p2p::dependencies::kitsune_p2p_fetch::FetchPoolItemInfo;
+use kitsune_p2p::event::{MetricRecord, MetricRecordKind};
+use kitsune_p2p::KitsuneAgent;
 use rusqlite::*;
 use std::{
     num::TryFromIntError,
+    sync::Arc,
     time::{Duration, SystemTime},
 };
 
@@ -33,11 +36,20 @@ pub fn time_from_micros(micros: i64) -> DatabaseResult<SystemTime> {
 pub trait AsP2pMetricStoreConExt {
     fn p2p_log_metrics(&mut self, metrics: Vec<MetricRecord>) -> DatabaseResult<()>;
     fn p2p_prune_metrics(&mut self) -> DatabaseResult<()>;
+    fn p2p_latest_metrics(&mut self) -> DatabaseResult<Vec<MetricRecord>>;
+    fn p2p_replace_fetch_pool(&mut self, items: Vec<FetchPoolItemInfo>) -> DatabaseResult<()>;
+    fn p2p_fetch_pool(&mut self) -> DatabaseResult<Vec<FetchPoolItemInfo>>;
 }
 
 pub trait AsP2pMetricStoreTxExt {
     fn p2p_log_metrics(&self, metrics: Vec<MetricRecord>) -> DatabaseResult<()>;
     fn p2p_prune_metrics(&self) -> DatabaseResult<()>;
+    /// The most recent unexpired record of each kind for each agent.
+    fn p2p_latest_metrics(&self) -> DatabaseResult<Vec<MetricRecord>>;
+    /// Replace the saved fetch pool items with these.
+    fn p2p_replace_fetch_pool(&self, items: Vec<FetchPoolItemInfo>) -> DatabaseResult<()>;
+    /// The saved fetch pool items, in the order they were saved.
+    fn p2p_fetch_pool(&self) -> DatabaseResult<Vec<FetchPoolItemInfo>>;
 }
 
 impl AsP2pMetricStoreConExt for crate::db::PConnGuard {
@@ -50,6 +62,21 @@ impl AsP2pMetricStoreConExt for crate::db::PConnGuard {
         use crate::db::WriteManager;
         self.with_commit_sync(move |writer| writer.p2p_prune_metrics())
     }
+
+    fn p2p_latest_metrics(&mut self) -> DatabaseResult<Vec<MetricRecord>> {
+        use crate::db::ReadManager;
+        self.with_reader(move |reader| reader.p2p_latest_metrics())
+    }
+
+    fn p2p_replace_fetch_pool(&mut self, items: Vec<FetchPoolItemInfo>) -> DatabaseResult<()> {
+        use crate::db::WriteManager;
+        self.with_commit_sync(move |writer| writer.p2p_replace_fetch_pool(items))
+    }
+
+    fn p2p_fetch_pool(&mut self) -> DatabaseResult<Vec<FetchPoolItemInfo>> {
+        use crate::db::ReadManager;
+        self.with_reader(move |reader| reader.p2p_fetch_pool())
+    }
 }
 
 impl AsP2pMetricStoreTxExt for Transaction<'_> {
@@ -84,4 +111,58 @@ impl AsP2pMetricStoreTxExt for Transaction<'_> {
         )?;
         Ok(())
     }
+
+    fn p2p_latest_metrics(&self) -> DatabaseResult<Vec<MetricRecord>> {
+        let now_micros = Timestamp::now().as_micros();
+        let mut stmt = self.prepare(sql_p2p_metrics::LATEST)?;
+        let records = stmt
+            .query_map(
+                named_params! {
+                    ":now_micros": now_micros,
+                },
+                |row| {
+                    let kind: String = row.get(0)?;
+                    let agent: Option<Vec<u8>> = row.get(1)?;
+                    let recorded_at: i64 = row.get(2)?;
+                    let expires_at: i64 = row.get(3)?;
+                    let data: Option<String> = row.get(4)?;
+                    Ok(MetricRecord {
+                        kind: MetricRecordKind::from_db(&kind),
+                        agent: agent.map(|a| Arc::new(KitsuneAgent(a))),
+                        recorded_at_utc: Timestamp::from_micros(recorded_at),
+                        expires_at_utc: Timestamp::from_micros(expires_at),
+                        data: data
+                            .and_then(|d| serde_json::from_str(&d).ok())
+                            .unwrap_or_default(),
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    fn p2p_replace_fetch_pool(&self, items: Vec<FetchPoolItemInfo>) -> DatabaseResult<()> {
+        self.execute(sql_p2p_metrics::FETCH_POOL_DELETE, [])?;
+        let mut stmt = self.prepare(sql_p2p_metrics::FETCH_POOL_INSERT)?;
+        for item in items {
+            let item = serde_json::to_string(&item).map_err(|e| DatabaseError::Other(e.into()))?;
+            stmt.execute(named_params! {
+                ":item": &item,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn p2p_fetch_pool(&self) -> DatabaseResult<Vec<FetchPoolItemInfo>> {
+        let mut stmt = self.prepare(sql_p2p_metrics::FETCH_POOL_SELECT)?;
+        let items = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            // Skip anything which no longer decodes rather than losing
+            // the whole pool.
+            .filter_map(|item| serde_json::from_str(&item).ok())
+            .collect();
+        Ok(items)
+    }
 }
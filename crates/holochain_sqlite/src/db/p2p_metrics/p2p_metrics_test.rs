@@ -66,3 +66,97 @@ async fn test_p2p_metric_store_sanity() {
     // clean up temp dir
     tmp_dir.close().unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_p2p_latest_metrics() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("p2p_latest_metrics")
+        .tempdir()
+        .unwrap();
+
+    let space = rand_space();
+    let agent = rand_agent();
+
+    let db = DbWrite::test(tmp_dir.path(), DbKindP2pMetrics(space.clone())).unwrap();
+
+    let permit = db.conn_permit().await;
+    let mut con = db.with_permit(permit).unwrap();
+
+    let record = |kind, data| MetricRecord {
+        kind,
+        agent: Some(agent.clone()),
+        recorded_at_utc: Timestamp::MIN,
+        expires_at_utc: Timestamp::MAX,
+        data: serde_json::json!(data),
+    };
+
+    con.p2p_log_metrics(vec![
+        record(MetricRecordKind::ReachabilityQuotient, 10.0),
+        record(MetricRecordKind::ReachabilityQuotient, 90.0),
+        record(MetricRecordKind::TimeoutRate, 5.0),
+        // Records without an agent are not returned
+        MetricRecord {
+            kind: MetricRecordKind::AggExtrapCov,
+            agent: None,
+            recorded_at_utc: Timestamp::MIN,
+            expires_at_utc: Timestamp::MAX,
+            data: serde_json::json!(42.42),
+        },
+    ])
+    .unwrap();
+
+    let mut latest = con.p2p_latest_metrics().unwrap();
+    latest.sort_by_key(|r| r.kind.to_db());
+    assert_eq!(latest.len(), 2);
+    assert!(matches!(
+        latest[0].kind,
+        MetricRecordKind::ReachabilityQuotient
+    ));
+    assert_eq!(latest[0].agent, Some(agent.clone()));
+    assert_eq!(latest[0].data, serde_json::json!(90.0));
+    assert!(matches!(latest[1].kind, MetricRecordKind::TimeoutRate));
+
+    // clean up temp dir
+    tmp_dir.close().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_p2p_fetch_pool_round_trip() {
+    use kitsune_p2p::dependencies::kitsune_p2p_fetch::*;
+    use kitsune_p2p::KitsuneOpHash;
+
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("p2p_fetch_pool_round_trip")
+        .tempdir()
+        .unwrap();
+
+    let space = rand_space();
+
+    let db = DbWrite::test(tmp_dir.path(), DbKindP2pMetrics(space.clone())).unwrap();
+
+    let permit = db.conn_permit().await;
+    let mut con = db.with_permit(permit).unwrap();
+
+    let item = |n: u8| FetchPoolItemInfo {
+        key: FetchKey::Op(Arc::new(KitsuneOpHash(vec![n; 36]))),
+        space: space.clone(),
+        size: Some(RoughInt::from(100 * n as usize)),
+        context: Some(FetchContext(n as u32)),
+        sources: vec![FetchSourceInfo {
+            source: FetchSource::Agent(rand_agent()),
+            attempts: n as u32,
+            last_request_ms_ago: Some(n as u64 * 1000),
+        }],
+    };
+
+    con.p2p_replace_fetch_pool(vec![item(1), item(2)]).unwrap();
+    assert_eq!(con.p2p_fetch_pool().unwrap().len(), 2);
+
+    // Saving again replaces what was there.
+    let items = vec![item(4), item(3)];
+    con.p2p_replace_fetch_pool(items.clone()).unwrap();
+    assert_eq!(con.p2p_fetch_pool().unwrap(), items);
+
+    // clean up temp dir
+    tmp_dir.close().unwrap();
+}
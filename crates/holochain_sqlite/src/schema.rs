@@ -35,6 +35,10 @@ pub static SCHEMA_CONDUCTOR: Lazy<Schema> = Lazy::new(|| Schema {
             forward: include_str!("sql/conductor/schema/1.sql").into(),
             _schema: "".into(),
         },
+        M {
+            forward: include_str!("sql/conductor/schema/2.sql").into(),
+            _schema: "".into(),
+        },
     ],
 });
 
@@ -47,7 +51,13 @@ pub static SCHEMA_P2P_STATE: Lazy<Schema> = Lazy::new(|| Schema {
 });
 
 pub static SCHEMA_P2P_METRICS: Lazy<Schema> = Lazy::new(|| Schema {
-    migrations: vec![M::initial(include_str!("sql/p2p_metrics/schema/0.sql"))],
+    migrations: vec![
+        M::initial(include_str!("sql/p2p_metrics/schema/0.sql")),
+        M {
+            forward: include_str!("sql/p2p_metrics/schema/1-up.sql").into(),
+            _schema: include_str!("sql/p2p_metrics/schema/1.sql").into(),
+        },
+    ],
 });
 
 pub struct Schema {
@@ -78,6 +78,13 @@ pub(crate) mod sql_p2p_agent_store {
 pub(crate) mod sql_p2p_metrics {
     pub(crate) const INSERT: &str = include_str!("sql/p2p_metrics/insert.sql");
     pub(crate) const PRUNE: &str = include_str!("sql/p2p_metrics/prune.sql");
+    pub(crate) const LATEST: &str = include_str!("sql/p2p_metrics/latest.sql");
+    pub(crate) const FETCH_POOL_INSERT: &str =
+        include_str!("sql/p2p_metrics/fetch_pool_insert.sql");
+    pub(crate) const FETCH_POOL_DELETE: &str =
+        include_str!("sql/p2p_metrics/fetch_pool_delete.sql");
+    pub(crate) const FETCH_POOL_SELECT: &str =
+        include_str!("sql/p2p_metrics/fetch_pool_select.sql");
 }
 
 pub(crate) mod sql_wasm {}
@@ -0,0 +1,13 @@
+-- no-sql-format --
+
+CREATE TABLE IF NOT EXISTS PostCommitRetry (
+    id                  INTEGER     PRIMARY KEY,
+    cell_id             BLOB        NOT NULL,
+    zome_name           TEXT        NOT NULL,
+    actions             BLOB        NOT NULL,
+    -- The number of times post_commit has already been run for these actions
+    attempt             INTEGER     NOT NULL,
+    -- literal integer from Timestamp in rust
+    next_attempt_us     INTEGER     NOT NULL
+);
+CREATE INDEX IF NOT EXISTS post_commit_retry_next_attempt_idx ON PostCommitRetry(next_attempt_us);
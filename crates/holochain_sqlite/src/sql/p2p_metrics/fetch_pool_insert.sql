@@ -0,0 +1,4 @@
+INSERT INTO
+  p2p_fetch_pool (item)
+VALUES
+  (:item);
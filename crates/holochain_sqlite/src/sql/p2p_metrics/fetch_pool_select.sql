@@ -0,0 +1,6 @@
+SELECT
+  item
+FROM
+  p2p_fetch_pool
+ORDER BY
+  rowid;
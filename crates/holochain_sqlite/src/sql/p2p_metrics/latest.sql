@@ -0,0 +1,21 @@
+SELECT
+  kind,
+  agent,
+  recorded_at_utc_micros,
+  expires_at_utc_micros,
+  data
+FROM
+  p2p_metrics
+WHERE
+  rowid IN (
+    SELECT
+      MAX(rowid)
+    FROM
+      p2p_metrics
+    WHERE
+      agent IS NOT NULL
+      AND expires_at_utc_micros > :now_micros
+    GROUP BY
+      kind,
+      agent
+  );
@@ -0,0 +1,9 @@
+-- no-sql-format --
+
+CREATE TABLE IF NOT EXISTS p2p_fetch_pool (
+    -- the position of this item in the fetch pool
+    rowid                  INTEGER PRIMARY KEY UNIQUE NOT NULL,
+
+    -- json encoded pending fetch pool item
+    item                   TEXT NOT NULL
+);
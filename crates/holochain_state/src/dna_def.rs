@@ -59,3 +59,13 @@ pub fn contains(txn: &Transaction<'_>, hash: &DnaHash) -> StateQueryResult<bool>
 pub fn put(txn: &mut Transaction, dna_def: DnaDef) -> StateMutationResult<()> {
     mutations::insert_dna_def(txn, &DnaDefHashed::from_content_sync(dna_def))
 }
+
+pub fn delete(txn: &mut Transaction, hash: &DnaHash) -> StateMutationResult<()> {
+    txn.execute(
+        "DELETE FROM DnaDef WHERE hash = :hash",
+        named_params! {
+            ":hash": hash
+        },
+    )?;
+    Ok(())
+}
@@ -37,6 +37,7 @@ pub mod host_fn_workspace;
 pub mod integrate;
 pub mod mutations;
 pub mod nonce;
+pub mod post_commit_retry;
 #[allow(missing_docs)]
 pub mod prelude;
 pub mod query;
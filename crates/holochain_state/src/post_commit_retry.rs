@@ -0,0 +1,235 @@
+//! Failed `post_commit` invocations which are waiting to be run again.
+//!
+//! These are kept in the conductor database so that retries survive a
+//! conductor restart.
+
+use crate::prelude::StateQueryResult;
+use crate::query::from_blob;
+use crate::query::to_blob;
+use holochain_sqlite::prelude::DbWrite;
+use holochain_sqlite::rusqlite::named_params;
+use holochain_types::prelude::*;
+use std::collections::HashSet;
+
+/// A `post_commit` invocation which failed and is due to be retried.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingPostCommit {
+    /// The cell whose chain the actions were committed to.
+    pub cell_id: CellId,
+    /// The zome whose `post_commit` callback failed.
+    pub zome_name: ZomeName,
+    /// The committed actions, as passed to `post_commit`.
+    pub actions: Vec<SignedActionHashed>,
+    /// How many times `post_commit` has already been run for these actions.
+    pub attempt: u32,
+    /// When the invocation should next be run.
+    pub next_attempt: Timestamp,
+}
+
+/// Store a failed invocation to be retried at `next_attempt`.
+pub async fn schedule_post_commit_retry(
+    db: &DbWrite<DbKindConductor>,
+    pending: PendingPostCommit,
+) -> StateQueryResult<()> {
+    db.async_commit(move |txn| {
+        txn.execute(
+            "
+            INSERT INTO PostCommitRetry
+            (cell_id, zome_name, actions, attempt, next_attempt_us)
+            VALUES
+            (:cell_id, :zome_name, :actions, :attempt, :next_attempt_us)
+            ",
+            named_params! {
+                ":cell_id": to_blob(&pending.cell_id)?,
+                ":zome_name": pending.zome_name.to_string(),
+                ":actions": to_blob(&pending.actions)?,
+                ":attempt": pending.attempt,
+                ":next_attempt_us": pending.next_attempt,
+            },
+        )?;
+        StateQueryResult::Ok(())
+    })
+    .await
+}
+
+/// Remove and return the invocations for the given cells which are due to
+/// be retried at `now`. Invocations for other cells, such as cells which
+/// are not running yet, are kept until they can be dispatched.
+pub async fn take_due_post_commit_retries(
+    db: &DbWrite<DbKindConductor>,
+    now: Timestamp,
+    cell_ids: HashSet<CellId>,
+) -> StateQueryResult<Vec<PendingPostCommit>> {
+    db.async_commit(move |txn| {
+        let rows = {
+            let mut stmt = txn.prepare(
+                "
+                SELECT id, cell_id, zome_name, actions, attempt, next_attempt_us
+                FROM PostCommitRetry
+                WHERE next_attempt_us <= :now
+                ORDER BY next_attempt_us ASC
+                ",
+            )?;
+            let rows = stmt.query_map(named_params! { ":now": now }, |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Vec<u8>>(3)?,
+                    row.get::<_, u32>(4)?,
+                    row.get::<_, Timestamp>(5)?,
+                ))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        let mut due = Vec::new();
+        for (id, cell_id, zome_name, actions, attempt, next_attempt) in rows {
+            let cell_id: CellId = from_blob(cell_id)?;
+            if !cell_ids.contains(&cell_id) {
+                continue;
+            }
+            txn.execute(
+                "DELETE FROM PostCommitRetry WHERE id = :id",
+                named_params! { ":id": id },
+            )?;
+            due.push(PendingPostCommit {
+                cell_id,
+                zome_name: zome_name.into(),
+                actions: from_blob(actions)?,
+                attempt,
+                next_attempt,
+            });
+        }
+        StateQueryResult::Ok(due)
+    })
+    .await
+}
+
+/// Remove the invocations waiting to be retried for these cells, whether
+/// due or not, such as when the cells are uninstalled.
+pub async fn delete_post_commit_retries(
+    db: &DbWrite<DbKindConductor>,
+    cell_ids: HashSet<CellId>,
+) -> StateQueryResult<()> {
+    db.async_commit(move |txn| {
+        for cell_id in cell_ids {
+            txn.execute(
+                "DELETE FROM PostCommitRetry WHERE cell_id = :cell_id",
+                named_params! { ":cell_id": to_blob(&cell_id)? },
+            )?;
+        }
+        StateQueryResult::Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_conductor_db;
+    use ::fixt::prelude::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn due_retries_are_taken_once() {
+        let db = test_conductor_db();
+        let now = Timestamp::now();
+        let cell_id = fixt!(CellId);
+        let cell_ids: HashSet<_> = [cell_id.clone()].into();
+        let pending = |attempt, next_attempt| PendingPostCommit {
+            cell_id: cell_id.clone(),
+            zome_name: "zome".into(),
+            actions: vec![],
+            attempt,
+            next_attempt,
+        };
+        let due = pending(1, now);
+        let later = pending(2, (now + std::time::Duration::from_secs(60)).unwrap());
+        schedule_post_commit_retry(&db.to_db(), due.clone())
+            .await
+            .unwrap();
+        schedule_post_commit_retry(&db.to_db(), later.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            take_due_post_commit_retries(&db.to_db(), now, cell_ids.clone())
+                .await
+                .unwrap(),
+            vec![due]
+        );
+        assert!(
+            take_due_post_commit_retries(&db.to_db(), now, cell_ids.clone())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            take_due_post_commit_retries(&db.to_db(), later.next_attempt, cell_ids)
+                .await
+                .unwrap(),
+            vec![later]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retries_are_kept_until_their_cell_runs() {
+        let db = test_conductor_db();
+        let now = Timestamp::now();
+        let pending = PendingPostCommit {
+            cell_id: fixt!(CellId),
+            zome_name: "zome".into(),
+            actions: vec![],
+            attempt: 1,
+            next_attempt: now,
+        };
+        schedule_post_commit_retry(&db.to_db(), pending.clone())
+            .await
+            .unwrap();
+
+        assert!(
+            take_due_post_commit_retries(&db.to_db(), now, [fixt!(CellId)].into())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            take_due_post_commit_retries(&db.to_db(), now, [pending.cell_id.clone()].into())
+                .await
+                .unwrap(),
+            vec![pending]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retries_of_deleted_cells_are_removed() {
+        let db = test_conductor_db();
+        let now = Timestamp::now();
+        let pending = |cell_id| PendingPostCommit {
+            cell_id,
+            zome_name: "zome".into(),
+            actions: vec![],
+            attempt: 1,
+            next_attempt: (now + std::time::Duration::from_secs(60)).unwrap(),
+        };
+        let deleted = pending(fixt!(CellId));
+        let kept = pending(fixt!(CellId));
+        schedule_post_commit_retry(&db.to_db(), deleted.clone())
+            .await
+            .unwrap();
+        schedule_post_commit_retry(&db.to_db(), kept.clone())
+            .await
+            .unwrap();
+
+        delete_post_commit_retries(&db.to_db(), [deleted.cell_id.clone()].into())
+            .await
+            .unwrap();
+
+        let cell_ids = [deleted.cell_id.clone(), kept.cell_id.clone()].into();
+        assert_eq!(
+            take_due_post_commit_retries(&db.to_db(), kept.next_attempt, cell_ids)
+                .await
+                .unwrap(),
+            vec![kept]
+        );
+    }
+}
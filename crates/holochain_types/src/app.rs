@@ -97,6 +97,19 @@ pub struct CreateCloneCellPayload {
     pub name: Option<String>,
 }
 
+/// The arguments to provision the cell of a role which was deferred at install.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ProvisionDeferredCellPayload {
+    /// The app id that the deferred role belongs to
+    pub app_id: InstalledAppId,
+    /// The name of the deferred role
+    pub role_name: RoleName,
+    /// Optionally set a proof of membership for the cell
+    pub membrane_proof: Option<MembraneProof>,
+    /// Optionally override the network seed of the role's DNA
+    pub network_seed: Option<NetworkSeed>,
+}
+
 /// Ways of specifying a clone cell.
 #[derive(Clone, Debug, Display, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
@@ -154,18 +167,38 @@ pub enum AppBundleSource {
     Bundle(AppBundle),
     /// A local file path
     Path(PathBuf),
-    // /// A URL
-    // Url(String),
+    /// A URL, optionally pinned to the SHA-256 hash of the bundle file.
+    /// `file://` URLs are read from the local filesystem.
+    Url {
+        /// Where to fetch the bundle from
+        url: String,
+        /// The expected hash of the bundle file
+        sha256: Option<mr_bundle::ResourceHash>,
+    },
 }
 
 impl AppBundleSource {
     /// Get the bundle from the source. Consumes the source.
-    pub async fn resolve(self) -> Result<AppBundle, AppBundleError> {
-        Ok(match self {
+    ///
+    /// The fetch config is used both to fetch a bundle located by URL, and by
+    /// the bundle to fetch any of its own resources located by URL.
+    pub async fn resolve(
+        self,
+        fetch_config: &mr_bundle::FetchConfig,
+    ) -> Result<AppBundle, AppBundleError> {
+        let bundle = match self {
             Self::Bundle(bundle) => bundle,
             Self::Path(path) => AppBundle::decode(&ffs::read(&path).await?)?,
-            // Self::Url(url) => todo!("reqwest::get"),
-        })
+            Self::Url { url, sha256 } => {
+                let bytes =
+                    mr_bundle::fetch::resolve_url(&url, sha256.as_ref(), fetch_config).await?;
+                AppBundle::decode(bytes.inner())?
+            }
+        };
+        Ok(bundle
+            .into_inner()
+            .with_fetch_config(fetch_config.clone())
+            .into())
     }
 }
 
@@ -404,6 +437,10 @@ pub struct InstalledAppCommon {
     agent_key: AgentPubKey,
     /// Assignments of DNA roles to cells and their clones, as specified in the AppManifest
     role_assignments: HashMap<RoleName, AppRoleAssignment>,
+    /// Roles of other installed apps which this app may call, keyed by the
+    /// dependency name declared in the AppManifest
+    #[serde(default)]
+    dependencies: HashMap<String, AppDependency>,
 }
 
 impl InstalledAppCommon {
@@ -425,9 +462,19 @@ impl InstalledAppCommon {
             installed_app_id: installed_app_id.to_string(),
             agent_key,
             role_assignments,
+            dependencies: HashMap::new(),
         })
     }
 
+    /// Builder: set the resolved dependencies on other apps
+    pub fn with_dependencies<I: IntoIterator<Item = (String, AppDependency)>>(
+        mut self,
+        dependencies: I,
+    ) -> Self {
+        self.dependencies = dependencies.into_iter().collect();
+        self
+    }
+
     /// Accessor
     pub fn id(&self) -> &InstalledAppId {
         &self.installed_app_id
@@ -501,6 +548,16 @@ impl InstalledAppCommon {
             .chain(self.disabled_clone_cell_ids())
     }
 
+    /// Whether this app provisioned the cell or one of its clones, rather
+    /// than sharing a cell provisioned by another app
+    pub fn owns_cell(&self, cell_id: &CellId) -> bool {
+        self.role_assignments.values().any(|role| {
+            (role.provisioned_cell() == Some(cell_id) && !role.is_shared)
+                || role.clones.values().any(|c| c == cell_id)
+                || role.disabled_clones.values().any(|c| c == cell_id)
+        })
+    }
+
     /// Iterator of all running cells, both provisioned and cloned.
     /// Provisioned cells will always be running if the app is running,
     /// but some cloned cells may be disabled and will not be returned.
@@ -537,6 +594,93 @@ impl InstalledAppCommon {
         &self.role_assignments
     }
 
+    /// Accessor for a particular dependency on another app
+    pub fn dependency(&self, name: &str) -> AppResult<&AppDependency> {
+        self.dependencies
+            .get(name)
+            .ok_or_else(|| AppError::DependencyMissing(name.to_string()))
+    }
+
+    /// Accessor
+    pub fn dependencies(&self) -> &HashMap<String, AppDependency> {
+        &self.dependencies
+    }
+
+    /// Claim a deferred role for the given cell, before the cell is created.
+    /// The cell's DNA may differ from the one recorded at install, if a network
+    /// seed was supplied when provisioning. A claimed role is no longer deferred,
+    /// so it can't be claimed twice. The claim is kept in the role until it is
+    /// provisioned or released, so that it can be released if the conductor
+    /// stops while the cell is being created.
+    pub fn claim_deferred_role(
+        &mut self,
+        role_name: &RoleName,
+        cell_id: CellId,
+        registered_dna: bool,
+    ) -> AppResult<()> {
+        let app_role_assignment = self.role_mut(role_name)?;
+        if !app_role_assignment.is_deferred {
+            return Err(AppError::RoleNotDeferred(role_name.clone()));
+        }
+        if cell_id.agent_pubkey() != app_role_assignment.agent_key() {
+            return Err(AppError::RoleAgentMismatch(
+                role_name.clone(),
+                cell_id.agent_pubkey().clone(),
+            ));
+        }
+        let original_cell_id = std::mem::replace(&mut app_role_assignment.base_cell_id, cell_id);
+        app_role_assignment.is_deferred = false;
+        app_role_assignment.claim = Some(DeferredRoleClaim {
+            original_cell_id,
+            registered_dna,
+        });
+        Ok(())
+    }
+
+    /// Return a claimed role to deferred, if its cell could not be created.
+    /// Returns the claim and the cell which was claimed.
+    pub fn release_deferred_role(
+        &mut self,
+        role_name: &RoleName,
+    ) -> AppResult<(DeferredRoleClaim, CellId)> {
+        let app_role_assignment = self.role_mut(role_name)?;
+        let claim = app_role_assignment
+            .claim
+            .take()
+            .ok_or_else(|| AppError::RoleNotClaimed(role_name.clone()))?;
+        let claimed_cell_id = std::mem::replace(
+            &mut app_role_assignment.base_cell_id,
+            claim.original_cell_id.clone(),
+        );
+        app_role_assignment.is_deferred = true;
+        Ok((claim, claimed_cell_id))
+    }
+
+    /// Release every claim on the roles of this app,
+    /// returning the claims and the cells which were claimed.
+    pub fn release_all_deferred_roles(&mut self) -> Vec<(DeferredRoleClaim, CellId)> {
+        let claimed: Vec<RoleName> = self
+            .role_assignments
+            .iter()
+            .filter(|(_, role)| role.claim.is_some())
+            .map(|(role_name, _)| role_name.clone())
+            .collect();
+        claimed
+            .iter()
+            .filter_map(|role_name| self.release_deferred_role(role_name).ok())
+            .collect()
+    }
+
+    /// Mark a claimed role as provisioned, once its cell has been created.
+    pub fn provision_claimed_role(&mut self, role_name: &RoleName) -> AppResult<()> {
+        let app_role_assignment = self.role_mut(role_name)?;
+        if app_role_assignment.claim.take().is_none() {
+            return Err(AppError::RoleNotClaimed(role_name.clone()));
+        }
+        app_role_assignment.is_provisioned = true;
+        Ok(())
+    }
+
     /// Add a clone cell.
     pub fn add_clone(&mut self, role_name: &RoleName, cell_id: &CellId) -> AppResult<CloneId> {
         let app_role_assignment = self.role_mut(role_name)?;
@@ -730,6 +874,9 @@ impl InstalledAppCommon {
                 let role = AppRoleAssignment {
                     base_cell_id: cell_id,
                     is_provisioned: true,
+                    is_deferred: false,
+                    is_shared: false,
+                    claim: None,
                     clones: HashMap::new(),
                     clone_limit: 256,
                     next_clone_index: 0,
@@ -742,6 +889,7 @@ impl InstalledAppCommon {
             installed_app_id,
             agent_key: _agent_key,
             role_assignments: roles,
+            dependencies: HashMap::new(),
         })
     }
 }
@@ -958,6 +1106,17 @@ pub struct AppRoleAssignment {
     /// If false, then `base_cell_id` is just recording what that cell will be
     /// called in the future.
     is_provisioned: bool,
+    /// Records whether provisioning of the base cell was deferred at install.
+    /// A deferred role is not provisioned until explicitly requested.
+    #[serde(default)]
+    is_deferred: bool,
+    /// Records whether the base cell was provisioned by another app, which
+    /// this app shares it with.
+    #[serde(default)]
+    is_shared: bool,
+    /// The claim on a deferred role whose cell is being created.
+    #[serde(default)]
+    claim: Option<DeferredRoleClaim>,
     /// The number of allowed clone cells.
     clone_limit: u32,
     /// The index of the next clone cell to be created.
@@ -977,6 +1136,9 @@ impl AppRoleAssignment {
         Self {
             base_cell_id,
             is_provisioned,
+            is_deferred: false,
+            is_shared: false,
+            claim: None,
             clone_limit,
             clones: HashMap::new(),
             next_clone_index: 0,
@@ -984,6 +1146,22 @@ impl AppRoleAssignment {
         }
     }
 
+    /// Constructor for a role whose base cell will be provisioned later.
+    pub fn new_deferred(base_cell_id: CellId, clone_limit: u32) -> Self {
+        Self {
+            is_deferred: true,
+            ..Self::new(base_cell_id, false, clone_limit)
+        }
+    }
+
+    /// Constructor for a role whose base cell was provisioned by another app.
+    pub fn new_shared(base_cell_id: CellId, clone_limit: u32) -> Self {
+        Self {
+            is_shared: true,
+            ..Self::new(base_cell_id, true, clone_limit)
+        }
+    }
+
     /// Accessor
     pub fn cell_id(&self) -> &CellId {
         &self.base_cell_id
@@ -1008,6 +1186,16 @@ impl AppRoleAssignment {
         }
     }
 
+    /// Accessor
+    pub fn is_deferred(&self) -> bool {
+        self.is_deferred
+    }
+
+    /// Accessor
+    pub fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
     /// Accessor
     pub fn clone_ids(&self) -> impl Iterator<Item = &CloneId> {
         self.clones.keys()
@@ -1033,8 +1221,33 @@ impl AppRoleAssignment {
     }
 }
 
+/// A claim on a deferred role, held while its cell is created.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DeferredRoleClaim {
+    /// The cell recorded for the role before it was claimed.
+    pub original_cell_id: CellId,
+    /// Whether the DNA of the claimed cell was registered for this claim,
+    /// so should be removed if the claim is released.
+    pub registered_dna: bool,
+}
+
+/// A role of another installed app which an app is allowed to call,
+/// resolved at install time from an [`AppDependencyManifest`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AppDependency {
+    /// The app which provides the role
+    pub installed_app_id: InstalledAppId,
+    /// The role of that app whose cell will be called
+    pub role_name: RoleName,
+    /// The secret of the capability which the conductor granted on the
+    /// dependency's cell at install time. `None` if the dependency's cell
+    /// belongs to the same agent, in which case no grant is needed.
+    pub cap_secret: Option<CapSecret>,
+}
+
 #[cfg(test)]
 mod tests {
+    use super::error::AppError;
     use super::{AppRoleAssignment, RunningApp};
     use crate::prelude::*;
     use ::fixt::prelude::*;
@@ -1127,4 +1340,83 @@ mod tests {
         // Assert the deleted cell cannot be enabled
         assert!(app.enable_clone_cell(&clone_id_0).is_err());
     }
+
+    #[test]
+    fn deferred_provisioning() {
+        let agent = fixt!(AgentPubKey);
+        let base_cell_id = CellId::new(fixt!(DnaHash), agent.clone());
+        let role_name: RoleName = "role_name".into();
+        let mut app = InstalledAppCommon::new(
+            "app",
+            agent.clone(),
+            vec![
+                (
+                    role_name.clone(),
+                    AppRoleAssignment::new_deferred(base_cell_id.clone(), 0),
+                ),
+                (
+                    "other".into(),
+                    AppRoleAssignment::new(fixt!(CellId), true, 0),
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(app.provisioned_cells().count(), 1);
+
+        // Only the app's agent can provision the role.
+        matches::assert_matches!(
+            app.claim_deferred_role(&role_name, fixt!(CellId), false),
+            Err(AppError::RoleAgentMismatch(_, _))
+        );
+
+        // The provisioned cell may have a different DNA than the one recorded at install.
+        let cell_id = CellId::new(fixt!(DnaHash), agent);
+        app.claim_deferred_role(&role_name, cell_id.clone(), true)
+            .unwrap();
+
+        // A claimed role can't be claimed again, and isn't provisioned yet.
+        matches::assert_matches!(
+            app.claim_deferred_role(&role_name, cell_id.clone(), true),
+            Err(AppError::RoleNotDeferred(_))
+        );
+        assert_eq!(app.role(&role_name).unwrap().provisioned_cell(), None);
+
+        // Releasing a claim makes the role deferred again.
+        let (claim, claimed_cell_id) = app.release_deferred_role(&role_name).unwrap();
+        assert_eq!(
+            claim,
+            DeferredRoleClaim {
+                original_cell_id: base_cell_id.clone(),
+                registered_dna: true,
+            }
+        );
+        assert_eq!(claimed_cell_id, cell_id);
+        let role = app.role(&role_name).unwrap();
+        assert!(role.is_deferred());
+        assert_eq!(role.cell_id(), &base_cell_id);
+
+        // Claims interrupted by a restart are all released.
+        app.claim_deferred_role(&role_name, cell_id.clone(), false)
+            .unwrap();
+        assert_eq!(app.release_all_deferred_roles().len(), 1);
+        assert!(app.release_all_deferred_roles().is_empty());
+        assert!(app.role(&role_name).unwrap().is_deferred());
+
+        app.claim_deferred_role(&role_name, cell_id.clone(), false)
+            .unwrap();
+        app.provision_claimed_role(&role_name).unwrap();
+        let role = app.role(&role_name).unwrap();
+        assert!(!role.is_deferred());
+        assert_eq!(role.provisioned_cell(), Some(&cell_id));
+
+        // A role can only be provisioned once.
+        matches::assert_matches!(
+            app.claim_deferred_role(&role_name, cell_id, false),
+            Err(AppError::RoleNotDeferred(_))
+        );
+        matches::assert_matches!(
+            app.provision_claimed_role(&role_name),
+            Err(AppError::RoleNotClaimed(_))
+        );
+    }
 }
@@ -2,7 +2,10 @@ use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use self::error::AppBundleResult;
 
-use super::{dna_gamut::DnaGamut, AppManifest, AppManifestValidated};
+use super::{
+    dna_gamut::{CellResolution, DnaGamut, DnaResolution},
+    AppManifest, AppManifestValidated,
+};
 use crate::prelude::*;
 
 #[allow(missing_docs)]
@@ -45,19 +48,39 @@ impl AppBundle {
         self.0
     }
 
+    /// Locations outside the bundle which are not pinned by a hash, both in
+    /// the app manifest and in the manifests of the DNA bundles it refers to.
+    /// DNAs which are themselves unpinned are reported without being looked
+    /// into, since nothing they contain is vouched for.
+    pub async fn unpinned_locations_nested(&self) -> AppBundleResult<Vec<mr_bundle::Location>> {
+        let mut unpinned = self.unpinned_locations();
+        for location in self.manifest().locations() {
+            if unpinned.contains(&location) {
+                continue;
+            }
+            let bytes = self.resolve(&location).await?;
+            let dna_bundle: DnaBundle = mr_bundle::Bundle::decode(&bytes)?.into();
+            unpinned.extend(dna_bundle.unpinned_locations());
+        }
+        Ok(unpinned)
+    }
+
     /// Given a DnaGamut, decide which of the available DNAs or Cells should be
     /// used for each cell in this app.
     pub async fn resolve_cells(
         self,
         agent: AgentPubKey,
-        _gamut: DnaGamut,
+        gamut: DnaGamut,
         membrane_proofs: HashMap<RoleName, MembraneProof>,
     ) -> AppBundleResult<AppRoleResolution> {
         let AppManifestValidated { name: _, roles } = self.manifest().clone().validate()?;
         let bundle = Arc::new(self);
+        let gamut = &gamut;
+        let agent = &agent;
         let tasks = roles.into_iter().map(|(role_name, role)| async {
             let bundle = bundle.clone();
-            Ok((role_name, bundle.resolve_cell(role).await?))
+            let op = bundle.resolve_cell(&role_name, role, gamut, agent).await?;
+            Ok((role_name, op))
         });
         let resolution = futures::future::join_all(tasks)
             .await
@@ -65,52 +88,54 @@ impl AppBundle {
             .collect::<AppBundleResult<Vec<_>>>()?
             .into_iter()
             .fold(
-                Ok(AppRoleResolution::new(agent.clone())),
-                |acc: AppBundleResult<AppRoleResolution>, (role_name, op)| {
-                    if let Ok(mut resolution) = acc {
-                        match op {
-                            CellProvisioningOp::Create(dna, clone_limit) => {
-                                let agent = resolution.agent.clone();
-                                let dna_hash = dna.dna_hash().clone();
-                                let cell_id = CellId::new(dna_hash, agent);
-                                let role = AppRoleAssignment::new(cell_id, true, clone_limit);
-                                // TODO: could sequentialize this to remove the clone
-                                let proof = membrane_proofs.get(&role_name).cloned();
-                                resolution.dnas_to_register.push((dna, proof));
-                                resolution.role_assignments.push((role_name, role));
-                            }
-                            CellProvisioningOp::Existing(cell_id, clone_limit) => {
-                                let role = AppRoleAssignment::new(cell_id, true, clone_limit);
-                                resolution.role_assignments.push((role_name, role));
-                            }
-                            CellProvisioningOp::Noop(cell_id, clone_limit) => {
-                                resolution.role_assignments.push((
-                                    role_name,
-                                    AppRoleAssignment::new(cell_id, false, clone_limit),
-                                ));
-                            }
-                            other => {
-                                tracing::error!(
-                                    "Encountered unexpected CellProvisioningOp: {:?}",
-                                    other
-                                );
-                                unimplemented!()
+                AppRoleResolution::new(agent.clone()),
+                |mut resolution, (role_name, op)| {
+                    match op {
+                        CellProvisioningOp::Create(dna, clone_limit) => {
+                            let agent = resolution.agent.clone();
+                            let dna_hash = dna.dna_hash().clone();
+                            let cell_id = CellId::new(dna_hash, agent);
+                            let role = AppRoleAssignment::new(cell_id, true, clone_limit);
+                            // TODO: could sequentialize this to remove the clone
+                            let proof = membrane_proofs.get(&role_name).cloned();
+                            resolution.dnas_to_register.push((dna, proof));
+                            resolution.role_assignments.push((role_name, role));
+                        }
+                        CellProvisioningOp::Existing(cell_id, clone_limit) => {
+                            let role = AppRoleAssignment::new_shared(cell_id, clone_limit);
+                            resolution.role_assignments.push((role_name, role));
+                        }
+                        CellProvisioningOp::Deferred(dna, clone_limit) => {
+                            let cell_id = CellId::new(dna.dna_hash().clone(), agent.clone());
+                            resolution.dnas_to_register_without_cells.push(dna);
+                            resolution.role_assignments.push((
+                                role_name,
+                                AppRoleAssignment::new_deferred(cell_id, clone_limit),
+                            ));
+                        }
+                        CellProvisioningOp::Noop(dna, cell_id, clone_limit) => {
+                            if let Some(dna) = dna {
+                                resolution.dnas_to_register_without_cells.push(dna);
                             }
+                            resolution.role_assignments.push((
+                                role_name,
+                                AppRoleAssignment::new(cell_id, false, clone_limit),
+                            ));
                         }
-                        Ok(resolution)
-                    } else {
-                        acc
                     }
+                    resolution
                 },
-            )?;
+            );
 
-        // let resolution = cells.into_iter();
         Ok(resolution)
     }
 
     async fn resolve_cell(
         &self,
+        role_name: &RoleName,
         role: AppRoleManifestValidated,
+        gamut: &DnaGamut,
+        agent: &AgentPubKey,
     ) -> AppBundleResult<CellProvisioningOp> {
         Ok(match role {
             AppRoleManifestValidated::Create {
@@ -118,83 +143,113 @@ impl AppBundle {
                 version,
                 clone_limit,
                 modifiers,
-                deferred: _,
+                deferred,
             } => {
-                self.resolve_cell_create(&location, version.as_ref(), clone_limit, modifiers)
-                    .await?
+                let dna = self
+                    .resolve_dna(role_name, &location, version.as_ref(), modifiers)
+                    .await?;
+                CellProvisioningOp::create(dna, clone_limit, deferred)
             }
 
-            AppRoleManifestValidated::CreateClone { .. } => {
-                unimplemented!("`create_clone` provisioning strategy is currently unimplemented")
+            AppRoleManifestValidated::CreateClone {
+                location,
+                version,
+                clone_limit,
+                mut modifiers,
+                deferred,
+            } => {
+                // A fresh network seed puts this cell on its own network,
+                // apart from any other cell of the same DNA.
+                modifiers.network_seed = Some(nanoid::nanoid!());
+                let dna = self
+                    .resolve_dna(role_name, &location, version.as_ref(), modifiers)
+                    .await?;
+                CellProvisioningOp::create(dna, clone_limit, deferred)
             }
+            // An existing cell has nothing left to provision, so `deferred`
+            // makes no difference here.
             AppRoleManifestValidated::UseExisting {
                 version,
                 clone_limit,
                 deferred: _,
-            } => self.resolve_cell_existing(&version, clone_limit),
+            } => match gamut.resolve_cell(version, agent) {
+                CellResolution::Match(cell_id, _) => {
+                    CellProvisioningOp::Existing(cell_id, clone_limit)
+                }
+                CellResolution::NoMatch | CellResolution::Conflict => {
+                    return Err(AppBundleError::NoExistingCell(role_name.clone()));
+                }
+            },
             AppRoleManifestValidated::CreateIfNotExists {
                 location,
                 version,
                 clone_limit,
                 modifiers,
-                deferred: _,
-            } => match self.resolve_cell_existing(&version, clone_limit) {
-                op @ CellProvisioningOp::Existing(_, _) => op,
-                CellProvisioningOp::NoMatch => {
-                    self.resolve_cell_create(&location, Some(&version), clone_limit, modifiers)
-                        .await?
+                deferred,
+            } => match gamut.resolve_cell(version.clone(), agent) {
+                CellResolution::Match(cell_id, _) => {
+                    CellProvisioningOp::Existing(cell_id, clone_limit)
                 }
-                CellProvisioningOp::Conflict(_) => {
-                    unimplemented!("conflicts are not handled, or even possible yet")
+                CellResolution::NoMatch => {
+                    let dna = self
+                        .resolve_dna(role_name, &location, Some(&version), modifiers)
+                        .await?;
+                    CellProvisioningOp::create(dna, clone_limit, deferred)
                 }
-                CellProvisioningOp::Create(_, _) => {
-                    unreachable!("resolve_cell_existing will never return a Create op")
-                }
-                CellProvisioningOp::Noop(_, _) => {
-                    unreachable!("resolve_cell_existing will never return a Noop")
+                CellResolution::Conflict => {
+                    return Err(AppBundleError::CellResolutionFailure(role_name.clone()));
                 }
             },
             AppRoleManifestValidated::Disabled {
-                version: _,
-                clone_limit: _,
+                location,
+                version,
+                clone_limit,
+                modifiers,
             } => {
-                unimplemented!("`disabled` provisioning strategy is currently unimplemented")
-                // CellProvisioningOp::Noop(clone_limit)
+                // No cell is created, but clones need the DNA to be registered,
+                // either from the bundle or already on the conductor.
+                let (dna, dna_hash) = match location {
+                    Some(location) => {
+                        let dna = self
+                            .resolve_dna(role_name, &location, Some(&version), modifiers)
+                            .await?;
+                        let dna_hash = dna.dna_hash().clone();
+                        (Some(dna), dna_hash)
+                    }
+                    None => match gamut.resolve_dna(version) {
+                        DnaResolution::Match(dna_hash, _) => (None, dna_hash.into()),
+                        DnaResolution::NoMatch | DnaResolution::Conflict => {
+                            return Err(AppBundleError::NoRegisteredDna(role_name.clone()));
+                        }
+                    },
+                };
+                CellProvisioningOp::Noop(dna, CellId::new(dna_hash, agent.clone()), clone_limit)
             }
         })
     }
 
-    async fn resolve_cell_create(
+    async fn resolve_dna(
         &self,
+        role_name: &RoleName,
         location: &mr_bundle::Location,
         version: Option<&DnaVersionSpec>,
-        clone_limit: u32,
         modifiers: DnaModifiersOpt,
-    ) -> AppBundleResult<CellProvisioningOp> {
+    ) -> AppBundleResult<DnaFile> {
         let bytes = self.resolve(location).await?;
-        let dna_bundle: DnaBundle = mr_bundle::Bundle::decode(&bytes)?.into();
+        let mut dna_bundle = mr_bundle::Bundle::decode(&bytes)?;
+        // Zomes of the DNA are fetched the same way as the app's resources
+        if let Some(fetch_config) = self.fetch_config() {
+            dna_bundle = dna_bundle.with_fetch_config(fetch_config.clone());
+        }
+        let dna_bundle: DnaBundle = dna_bundle.into();
         let (dna_file, original_dna_hash) = dna_bundle.into_dna_file(modifiers).await?;
         if let Some(spec) = version {
             if !spec.matches(original_dna_hash) {
-                return Ok(CellProvisioningOp::NoMatch);
+                return Err(AppBundleError::DnaVersionMismatch(role_name.clone()));
             }
         }
-        Ok(CellProvisioningOp::Create(dna_file, clone_limit))
+        Ok(dna_file)
     }
-
-    fn resolve_cell_existing(
-        &self,
-        _version: &DnaVersionSpec,
-        _clone_limit: u32,
-    ) -> CellProvisioningOp {
-        unimplemented!("Reusing existing cells is not yet implemented")
-    }
-}
-
-/// This function is called in places where it will be necessary to rework that
-/// area after use_existing has been implemented
-pub fn we_must_remember_to_rework_cell_panic_handling_after_implementing_use_existing_cell_resolution(
-) {
 }
 
 /// The answer to the question:
@@ -206,6 +261,8 @@ pub fn we_must_remember_to_rework_cell_panic_handling_after_implementing_use_exi
 pub struct AppRoleResolution {
     pub agent: AgentPubKey,
     pub dnas_to_register: Vec<(DnaFile, Option<MembraneProof>)>,
+    /// DNAs of disabled and deferred roles, whose cells are not created at install.
+    pub dnas_to_register_without_cells: Vec<DnaFile>,
     pub role_assignments: Vec<(RoleName, AppRoleAssignment)>,
 }
 
@@ -215,6 +272,7 @@ impl AppRoleResolution {
         Self {
             agent,
             dnas_to_register: Default::default(),
+            dnas_to_register_without_cells: Default::default(),
             role_assignments: Default::default(),
         }
     }
@@ -243,14 +301,19 @@ pub enum CellProvisioningOp {
     /// Use an existing Cell
     Existing(CellId, u32),
     /// No provisioning needed, but there might be a clone_limit, and so we need
-    /// to know which DNA and Agent to use for making clones
-    Noop(CellId, u32),
-    /// Couldn't find a DNA that matches the version spec; can't provision (should this be an Err?)
-    NoMatch,
-    /// Ambiguous result, needs manual resolution; can't provision (should this be an Err?)
-    Conflict(CellProvisioningConflict),
+    /// to know which DNA and Agent to use for making clones.
+    /// The DNA is given if it still needs to be registered.
+    Noop(Option<DnaFile>, CellId, u32),
+    /// Register the DNA, but leave the Cell to be provisioned on request
+    Deferred(DnaFile, u32),
 }
 
-/// Uninhabitable placeholder
-#[derive(Debug)]
-pub enum CellProvisioningConflict {}
+impl CellProvisioningOp {
+    fn create(dna: DnaFile, clone_limit: u32, deferred: bool) -> Self {
+        if deferred {
+            Self::Deferred(dna, clone_limit)
+        } else {
+            Self::Create(dna, clone_limit)
+        }
+    }
+}
@@ -9,6 +9,15 @@ pub enum AppBundleError {
     #[error("Could not resolve the app role '{0}'")]
     CellResolutionFailure(RoleName),
 
+    #[error("The app role '{0}' uses an existing cell, but no installed cell of this agent matches its DNA version spec")]
+    NoExistingCell(RoleName),
+
+    #[error("The app role '{0}' is disabled and has no DNA location, but no registered DNA matches its version spec")]
+    NoRegisteredDna(RoleName),
+
+    #[error("The DNA of the app role '{0}' does not match its version spec")]
+    DnaVersionMismatch(RoleName),
+
     #[error(transparent)]
     AppManifestError(#[from] AppManifestError),
 
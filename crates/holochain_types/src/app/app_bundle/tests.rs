@@ -70,7 +70,238 @@ async fn provisioning_1_create() {
     let expected = AppRoleResolution {
         agent,
         dnas_to_register: vec![(dna, None)],
+        dnas_to_register_without_cells: vec![],
         role_assignments: vec![("name".into(), role)],
     };
     assert_eq!(resolution, expected);
 }
+
+/// Change the provisioning strategy of the fixture's role,
+/// optionally dropping the location of its DNA
+fn with_provisioning(
+    bundle: AppBundle,
+    provisioning: CellProvisioning,
+    keep_location: bool,
+) -> AppBundle {
+    let AppManifest::V1(mut manifest) = bundle.manifest().clone();
+    manifest.roles[0].provisioning = Some(provisioning);
+    if !keep_location {
+        manifest.roles[0].dna.location = None;
+    }
+    bundle
+        .into_inner()
+        .update_manifest(manifest.into())
+        .unwrap()
+        .into()
+}
+
+/// Test that an existing cell of the same agent is reused,
+/// and that installation fails if there is none
+#[tokio::test]
+async fn provisioning_use_existing() {
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna) = app_bundle_fixture(DnaModifiersOpt::none()).await;
+    let bundle = with_provisioning(
+        bundle,
+        CellProvisioning::UseExisting { deferred: false },
+        false,
+    );
+    let cell_id = CellId::new(dna.dna_hash().clone(), agent.clone());
+
+    let resolution = AppBundle::decode(&bundle.encode().unwrap())
+        .unwrap()
+        .resolve_cells(
+            agent.clone(),
+            DnaGamut::new([cell_id.clone()]),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    assert!(resolution.dnas_to_register.is_empty());
+    assert!(resolution.cells_to_create().is_empty());
+    assert_eq!(
+        resolution.role_assignments,
+        vec![("name".into(), AppRoleAssignment::new(cell_id, true, 50))]
+    );
+
+    // A cell of another agent can't be used.
+    let other_cell_id = CellId::new(dna.dna_hash().clone(), fixt!(AgentPubKey));
+    let err = bundle
+        .resolve_cells(agent, DnaGamut::new([other_cell_id]), Default::default())
+        .await
+        .unwrap_err();
+    matches::assert_matches!(err, AppBundleError::NoExistingCell(role) if role == "name");
+}
+
+/// Test that each clone-provisioned cell gets its own network
+#[tokio::test]
+async fn provisioning_create_clone() {
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna) = app_bundle_fixture(DnaModifiersOpt::none()).await;
+    let bundle = with_provisioning(
+        bundle,
+        CellProvisioning::CreateClone { deferred: false },
+        true,
+    );
+    let bytes = bundle.encode().unwrap();
+
+    let mut dna_hashes = vec![];
+    for _ in 0..2 {
+        let resolution = AppBundle::decode(&bytes)
+            .unwrap()
+            .resolve_cells(agent.clone(), DnaGamut::placeholder(), Default::default())
+            .await
+            .unwrap();
+        assert_eq!(resolution.cells_to_create().len(), 1);
+        dna_hashes.push(resolution.dnas_to_register[0].0.dna_hash().clone());
+    }
+    assert_ne!(dna_hashes[0], dna_hashes[1]);
+    assert!(!dna_hashes.contains(dna.dna_hash()));
+}
+
+/// Test that a disabled role creates no cell, but has a DNA for its clones
+#[tokio::test]
+async fn provisioning_disabled() {
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna) = app_bundle_fixture(DnaModifiersOpt::none()).await;
+    let cell_id = CellId::new(dna.dna_hash().clone(), agent.clone());
+    let expected_role = AppRoleAssignment::new(cell_id, false, 50);
+
+    // The DNA is taken from the bundle.
+    let bytes = bundle.encode().unwrap();
+    let resolution = with_provisioning(
+        AppBundle::decode(&bytes).unwrap(),
+        CellProvisioning::Disabled,
+        true,
+    )
+    .resolve_cells(agent.clone(), DnaGamut::placeholder(), Default::default())
+    .await
+    .unwrap();
+    assert!(resolution.cells_to_create().is_empty());
+    assert_eq!(resolution.dnas_to_register_without_cells, vec![dna.clone()]);
+    assert_eq!(
+        resolution.role_assignments,
+        vec![("name".into(), expected_role.clone())]
+    );
+
+    // Without a location, the DNA must already be registered.
+    let bundle = with_provisioning(
+        AppBundle::decode(&bytes).unwrap(),
+        CellProvisioning::Disabled,
+        false,
+    );
+    let bytes = bundle.encode().unwrap();
+    let err = bundle
+        .resolve_cells(agent.clone(), DnaGamut::placeholder(), Default::default())
+        .await
+        .unwrap_err();
+    matches::assert_matches!(err, AppBundleError::NoRegisteredDna(role) if role == "name");
+
+    let resolution = AppBundle::decode(&bytes)
+        .unwrap()
+        .resolve_cells(
+            agent,
+            DnaGamut::placeholder().with_dnas([dna.dna_hash().clone()]),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    assert!(resolution.dnas_to_register_without_cells.is_empty());
+    assert_eq!(
+        resolution.role_assignments,
+        vec![("name".into(), expected_role)]
+    );
+}
+
+/// Test that a deferred role registers its DNA but creates no cell
+#[tokio::test]
+async fn provisioning_deferred() {
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna) = app_bundle_fixture(DnaModifiersOpt::none()).await;
+    let bundle = with_provisioning(bundle, CellProvisioning::Create { deferred: true }, true);
+    let cell_id = CellId::new(dna.dna_hash().clone(), agent.clone());
+
+    let resolution = bundle
+        .resolve_cells(agent, DnaGamut::placeholder(), Default::default())
+        .await
+        .unwrap();
+    assert!(resolution.cells_to_create().is_empty());
+    assert_eq!(resolution.dnas_to_register_without_cells, vec![dna]);
+
+    let (role_name, role) = &resolution.role_assignments[0];
+    assert_eq!(role_name, "name");
+    assert!(role.is_deferred());
+    assert_eq!(role.provisioned_cell(), None);
+    assert_eq!(role.cell_id(), &cell_id);
+}
+
+/// Test that zomes of bundled DNAs are fetched with the app bundle's config
+#[tokio::test]
+async fn nested_dna_uses_fetch_config() {
+    let dna_manifest = DnaManifestCurrent {
+        name: "nested".into(),
+        integrity: IntegrityManifest {
+            network_seed: None,
+            properties: None,
+            origin_time: Timestamp::HOLOCHAIN_EPOCH.into(),
+            zomes: vec![ZomeManifest {
+                name: "zome".into(),
+                hash: None,
+                location: mr_bundle::Location::Url("https://example.com/zome.wasm".into()),
+                sha256: None,
+                dependencies: Default::default(),
+            }],
+        },
+        coordinator: CoordinatorManifest { zomes: vec![] },
+    };
+    let dna_bundle: DnaBundle =
+        mr_bundle::Bundle::new_unchecked(dna_manifest.try_into().unwrap(), vec![])
+            .unwrap()
+            .into();
+    let manifest = AppManifestCurrentBuilder::default()
+        .name("app".into())
+        .description(None)
+        .roles(vec![AppRoleManifest {
+            name: "nested".into(),
+            dna: AppRoleDnaManifest {
+                location: Some(mr_bundle::Location::Bundled("nested.dna".into())),
+                sha256: None,
+                modifiers: DnaModifiersOpt::none(),
+                version: None,
+                clone_limit: 0,
+            },
+            provisioning: Some(CellProvisioning::Create { deferred: false }),
+        }])
+        .build()
+        .unwrap();
+    let bundle = AppBundle::new(
+        manifest.into(),
+        vec![(PathBuf::from("nested.dna"), dna_bundle)],
+        PathBuf::from("."),
+    )
+    .await
+    .unwrap();
+    let bundle: AppBundle = bundle
+        .into_inner()
+        .with_fetch_config(mr_bundle::FetchConfig {
+            cache_dir: None,
+            offline: true,
+        })
+        .into();
+
+    // Offline, the unpinned zome can't be fetched
+    matches::assert_matches!(
+        bundle
+            .resolve_cells(
+                fixt!(AgentPubKey),
+                DnaGamut::placeholder(),
+                Default::default()
+            )
+            .await,
+        Err(AppBundleError::DnaError(DnaError::MrBundleError(
+            mr_bundle::error::MrBundleError::BundleError(
+                mr_bundle::error::BundleError::UnpinnedOffline(_)
+            )
+        )))
+    );
+}
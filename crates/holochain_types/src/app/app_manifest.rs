@@ -38,6 +38,16 @@ impl Manifest for AppManifest {
         }
     }
 
+    fn resource_hash(&self, location: &Location) -> Option<mr_bundle::ResourceHash> {
+        match self {
+            AppManifest::V1(m) => m
+                .roles
+                .iter()
+                .find(|role| role.dna.location.as_ref() == Some(location))
+                .and_then(|role| role.dna.sha256.clone()),
+        }
+    }
+
     fn path() -> PathBuf {
         "happ.yaml".into()
     }
@@ -76,6 +86,13 @@ impl AppManifest {
             Self::V1(manifest) => manifest.roles.clone(),
         }
     }
+
+    /// Returns the list of dependencies on other apps that this manifest declares
+    pub fn app_dependencies(&self) -> Vec<AppDependencyManifest> {
+        match self {
+            Self::V1(manifest) => manifest.dependencies.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
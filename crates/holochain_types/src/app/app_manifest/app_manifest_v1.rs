@@ -9,8 +9,8 @@ use super::{
 };
 use crate::prelude::{RoleName, YamlProperties};
 use holo_hash::{DnaHash, DnaHashB64};
-use holochain_zome_types::{DnaModifiersOpt, NetworkSeed};
-use std::collections::HashMap;
+use holochain_zome_types::{DnaModifiersOpt, GrantedFunction, NetworkSeed};
+use std::collections::{HashMap, HashSet};
 
 /// Version 1 of the App manifest schema
 #[derive(
@@ -27,6 +27,35 @@ pub struct AppManifestV1 {
 
     /// The roles that need to be filled (by DNAs) for this app.
     pub roles: Vec<AppRoleManifest>,
+
+    /// Roles of other installed apps which the zomes of this app may call.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(default)]
+    pub dependencies: Vec<AppDependencyManifest>,
+}
+
+/// A named dependency on a role of another app installed on the same conductor.
+///
+/// Zomes in the dependent app address the dependency by its name, via
+/// `CallTargetCell::Dependency`. When the app is installed, the conductor
+/// grants the dependent app's agent access to the listed functions of the
+/// depended-upon cell, so no manual capability grant is needed.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AppDependencyManifest {
+    /// The name by which this dependency is referred to in zome calls
+    pub name: String,
+
+    /// The InstalledAppId of the app which provides the role
+    pub installed_app_id: String,
+
+    /// The role of that app whose cell will be called
+    pub role_name: RoleName,
+
+    /// The zome functions which this app needs to call. Access is only
+    /// granted to these functions.
+    pub functions: Vec<GrantedFunction>,
 }
 
 /// Description of an app "role" defined by this app.
@@ -74,6 +103,11 @@ pub struct AppRoleDnaManifest {
     #[serde(flatten)]
     pub location: Option<mr_bundle::Location>,
 
+    /// The SHA-256 hash of the DNA bundle file, as a hex string.
+    /// A DNA located by URL is checked against this hash before it is used,
+    /// and can then be cached for reuse without the network.
+    pub sha256: Option<mr_bundle::ResourceHash>,
+
     /// Optional default modifier values. May be overridden during installation.
     #[serde(default)]
     pub modifiers: DnaModifiersOpt<YamlProperties>,
@@ -97,6 +131,7 @@ impl AppRoleDnaManifest {
             location: Some(mr_bundle::Location::Bundled(
                 "./path/to/my/dnabundle.dna".into(),
             )),
+            sha256: None,
             modifiers: DnaModifiersOpt::none(),
             version: None,
             clone_limit: 0,
@@ -166,7 +201,8 @@ pub enum CellProvisioning {
     /// Always create a new Cell when installing this App
     Create { deferred: bool },
     /// Always create a new Cell when installing the App,
-    /// and use a unique network seed to ensure a distinct DHT network
+    /// and use a unique network seed to ensure a distinct DHT network.
+    /// Any network seed given in the modifiers is replaced.
     CreateClone { deferred: bool },
     /// Require that a Cell is already installed which matches the DNA version
     /// spec, and which has an Agent that's associated with this App's agent
@@ -176,6 +212,8 @@ pub enum CellProvisioning {
     CreateIfNotExists { deferred: bool },
     /// Disallow provisioning altogether. In this case, we expect
     /// `clone_limit > 0`: otherwise, no Cells will ever be created.
+    /// If the DNA has a location, it is registered for making clones,
+    /// otherwise a DNA matching the version spec must already be registered.
     Disabled,
 }
 
@@ -207,7 +245,16 @@ impl AppManifestV1 {
             name,
             roles,
             description: _,
+            dependencies,
         } = self;
+        let mut dependency_names = HashSet::new();
+        for dependency in dependencies.iter() {
+            if !dependency_names.insert(&dependency.name) {
+                return Err(AppManifestError::DuplicateDependencyName(
+                    dependency.name.clone(),
+                ));
+            }
+        }
         let roles = roles
             .into_iter()
             .map(
@@ -218,6 +265,7 @@ impl AppManifestV1 {
                  }| {
                     let AppRoleDnaManifest {
                         location,
+                        sha256: _,
                         version,
                         clone_limit,
                         modifiers,
@@ -261,6 +309,8 @@ impl AppManifestV1 {
                         CellProvisioning::Disabled => AppRoleManifestValidated::Disabled {
                             clone_limit,
                             version: Self::require(version, "roles.dna.version")?,
+                            location,
+                            modifiers,
                         },
                     };
                     AppManifestResult::Ok((name, validated))
@@ -319,6 +369,7 @@ pub mod tests {
             name: "name".into(),
             dna: AppRoleDnaManifest {
                 location,
+                sha256: None,
                 modifiers,
                 version: Some(version),
                 clone_limit: 50,
@@ -329,6 +380,7 @@ pub mod tests {
             name: "Test app".to_string(),
             description: Some("Serialization roundtrip test".to_string()),
             roles,
+            dependencies: vec![],
         };
         (manifest, hashes)
     }
@@ -391,6 +443,44 @@ roles:
         assert_eq!(actual.get(fields[3]), expected.get(fields[3]));
     }
 
+    #[test]
+    fn manifest_v1_dependencies() {
+        let yaml = r#"---
+manifest_version: "1"
+name: "Dependent app"
+description: ~
+roles: []
+dependencies:
+  - name: profiles
+    installed_app_id: profiles-app
+    role_name: profiles
+    functions:
+      - [profiles, get_my_profile]
+"#;
+        let manifest: AppManifest = serde_yaml::from_str(yaml).unwrap();
+        let AppManifest::V1(manifest) = manifest;
+        assert_eq!(
+            manifest.dependencies,
+            vec![AppDependencyManifest {
+                name: "profiles".into(),
+                installed_app_id: "profiles-app".into(),
+                role_name: "profiles".into(),
+                functions: vec![("profiles".into(), "get_my_profile".into())],
+            }]
+        );
+        manifest.clone().validate().unwrap();
+
+        // Dependency names must be unique
+        let mut duplicated = manifest.clone();
+        duplicated
+            .dependencies
+            .push(manifest.dependencies[0].clone());
+        matches::assert_matches!(
+            duplicated.validate(),
+            Err(AppManifestError::DuplicateDependencyName(name)) if name == "profiles"
+        );
+    }
+
     #[tokio::test]
     async fn manifest_v1_set_network_seed() {
         let mut u = arbitrary::Unstructured::new(&[0]);
@@ -83,8 +83,12 @@ pub enum AppRoleManifestValidated {
     },
     /// Disallow provisioning altogether. In this case, we expect
     /// `clone_limit > 0`: otherwise, no cells will ever be created.
+    /// Clones are made from the DNA at `location` if given, or else from
+    /// a DNA already registered on the conductor.
     Disabled {
         version: DnaVersionSpec,
         clone_limit: u32,
+        location: Option<DnaLocation>,
+        modifiers: DnaModifiersOpt,
     },
 }
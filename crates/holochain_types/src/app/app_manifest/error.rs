@@ -12,6 +12,9 @@ pub enum AppManifestError {
     #[error("Invalid manifest for app role '{0}': Using strategy 'disabled' with clone_limit == 0 is pointless")]
     InvalidStrategyDisabled(RoleName),
 
+    #[error("Invalid manifest: the dependency name '{0}' is used more than once")]
+    DuplicateDependencyName(String),
+
     #[error(transparent)]
     SerializationError(#[from] SerializedBytesError),
 }
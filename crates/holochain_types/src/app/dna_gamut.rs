@@ -46,6 +46,14 @@ impl DnaGamut {
         Self(map)
     }
 
+    /// Add DNAs which are registered, whether or not any Cell uses them.
+    pub fn with_dnas<I: IntoIterator<Item = DnaHash>>(mut self, dnas: I) -> Self {
+        for dna in dnas {
+            self.0.entry(dna).or_default();
+        }
+        self
+    }
+
     #[allow(missing_docs)]
     pub fn placeholder() -> Self {
         Self::new(std::iter::empty())
@@ -62,18 +70,17 @@ impl DnaGamut {
     }
 
     /// Given a version spec, return the best-matching CellId
-    // TODO: use DPKI to filter Cells which belong to Agents that are not
-    //       associated with the provided agent
-    pub fn resolve_cell(&self, spec: DnaVersionSpec, _agent: &AgentPubKey) -> CellResolution {
+    // TODO: use DPKI to also match Cells which belong to Agents that are
+    //       associated with the provided agent. Until then only the agent's
+    //       own Cells can be used.
+    pub fn resolve_cell(&self, spec: DnaVersionSpec, agent: &AgentPubKey) -> CellResolution {
         for hash in spec.dna_hashes() {
-            if let Some(agent) = self
+            let has_cell = self
                 .0
                 .get(hash.as_ref())
-                // TODO: this is where an agent check could go, but for now we
-                //       just return the first one available
-                .map(|agents| agents.iter().next())
-                .unwrap_or(None)
-            {
+                .map(|agents| agents.contains(agent))
+                .unwrap_or(false);
+            if has_cell {
                 return CellResolution::Match(
                     CellId::new(hash.clone().into(), agent.clone()),
                     DnaVersion,
@@ -20,6 +20,18 @@ pub enum AppError {
     #[error("Tried to access missing role name: '{0}'")]
     RoleNameMissing(RoleName),
 
+    #[error("Role '{0}' is not awaiting deferred provisioning")]
+    RoleNotDeferred(RoleName),
+
+    #[error("Role '{0}' has not been claimed for deferred provisioning")]
+    RoleNotClaimed(RoleName),
+
+    #[error("Role '{0}' can't be provisioned for agent {1}, which is not the agent of its app")]
+    RoleAgentMismatch(RoleName, AgentPubKey),
+
+    #[error("Tried to access missing app dependency: '{0}'")]
+    DependencyMissing(String),
+
     #[error("Tried to install app '{0}' which contains duplicate role names. The following role names have duplicates: {1:?}")]
     DuplicateRoleNames(InstalledAppId, Vec<RoleName>),
 }
@@ -16,6 +16,7 @@ use holochain_sqlite::rusqlite::types::FromSql;
 use holochain_sqlite::rusqlite::ToSql;
 use holochain_zome_types::action;
 use holochain_zome_types::prelude::*;
+use kitsune_p2p_dht::hash::RegionHash;
 use kitsune_p2p_dht::region::RegionData;
 use kitsune_p2p_dht::Loc;
 use serde::Deserialize;
@@ -122,12 +123,52 @@ impl kitsune_p2p_dht::prelude::OpRegion for DhtOp {
         self.timestamp()
     }
 
+    /// The hash, size and count of this op, measured the same way as the
+    /// `FETCH_OP_REGION` query measures ops stored in the database:
+    /// the size is that of the signed action, plus that of the entry only
+    /// for ops which carry the entry itself.
     fn region_data(&self) -> RegionData {
-        unimplemented!()
+        #[derive(Serialize, Debug)]
+        struct SignedActionRef<'a>(&'a Action, &'a Signature);
+
+        let hash = DhtOpHash::with_data_sync(self);
+        let action = self.action();
+        let action_size =
+            holochain_serialized_bytes::encode(&SignedActionRef(&action, self.signature()))
+                .map(|b| b.len())
+                .unwrap_or_default();
+        let entry_size = match self {
+            DhtOp::StoreRecord(_, _, _) | DhtOp::StoreEntry(_, _, _) => self
+                .entry()
+                .and_then(|e| holochain_serialized_bytes::encode(e).ok())
+                .map(|b| b.len())
+                .unwrap_or_default(),
+            _ => 0,
+        };
+        RegionData {
+            hash: RegionHash::from_vec(hash.get_raw_39().to_vec())
+                .expect("DhtOpHash is always 39 bytes"),
+            size: (action_size + entry_size).min(u32::MAX as usize) as u32,
+            count: 1,
+        }
     }
 
-    fn bound(_timestamp: Timestamp, _loc: kitsune_p2p_dht::Loc) -> Self {
-        unimplemented!()
+    /// An agent activity op for an author whose location is `loc`.
+    ///
+    /// Note that DhtOps are ordered by op type and timestamp rather than by
+    /// location, so a range between two bounds must still be filtered by
+    /// location.
+    fn bound(timestamp: Timestamp, loc: kitsune_p2p_dht::Loc) -> Self {
+        let mut author = vec![0; 32];
+        author.extend_from_slice(&loc.as_u32().to_le_bytes());
+        DhtOp::RegisterAgentActivity(
+            Signature([0; 64]),
+            Action::Dna(action::Dna {
+                author: AgentPubKey::from_raw_36(author),
+                timestamp,
+                hash: DnaHash::from_raw_36(vec![0; 36]),
+            }),
+        )
     }
 }
 
@@ -462,3 +462,33 @@ fn test_all_ops_basis() {
         check_all_ops(record);
     }
 }
+
+#[test]
+fn op_region_bound_has_timestamp_and_loc() {
+    use kitsune_p2p_dht::prelude::OpRegion;
+    for loc in [0u32, 1, 0x1234_5678, u32::MAX] {
+        let timestamp = Timestamp::now();
+        let op = <DhtOp as OpRegion>::bound(timestamp, loc.into());
+        assert_eq!(OpRegion::loc(&op), loc.into());
+        assert_eq!(OpRegion::timestamp(&op), timestamp);
+    }
+}
+
+#[test]
+fn op_region_data_counts_entry_only_for_ops_containing_it() {
+    use kitsune_p2p_dht::prelude::OpRegion;
+    for record in all_records() {
+        for op in produce_ops_from_record(&record).unwrap() {
+            let data = op.region_data();
+            assert_eq!(data.count, 1);
+            let action_only = DhtOp::RegisterAgentActivity(op.signature().clone(), op.action());
+            let action_size = action_only.region_data().size;
+            match (op.get_type(), op.entry()) {
+                (DhtOpType::StoreRecord | DhtOpType::StoreEntry, Some(_)) => {
+                    assert!(data.size > action_size)
+                }
+                _ => assert_eq!(data.size, action_size),
+            }
+        }
+    }
+}
@@ -169,6 +169,7 @@ impl DnaBundle {
                         name,
                         hash: Some(hash),
                         location: Location::Bundled(PathBuf::from(filename)),
+                        sha256: None,
                         dependencies: Some(dependencies),
                     }
                 })
@@ -192,6 +193,7 @@ impl DnaBundle {
                         name,
                         hash: Some(hash),
                         location: Location::Bundled(PathBuf::from(filename)),
+                        sha256: None,
                         dependencies: Some(dependencies),
                     }
                 })
@@ -273,6 +275,7 @@ mod tests {
                         name: "zome1".into(),
                         hash: None,
                         location: mr_bundle::Location::Bundled(path1.clone()),
+                        sha256: None,
                         dependencies: Default::default(),
                     },
                     ZomeManifest {
@@ -280,6 +283,7 @@ mod tests {
                         // Intentional wrong hash
                         hash: Some(hash1.clone().into()),
                         location: mr_bundle::Location::Bundled(path2.clone()),
+                        sha256: None,
                         dependencies: Default::default(),
                     },
                 ],
@@ -343,4 +347,94 @@ mod tests {
             SerializedBytes::try_from(properties).unwrap()
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn dna_bundle_with_url_zome_is_verified() {
+        let dir = tempfile::tempdir().unwrap();
+        let wasm = vec![1, 2, 3];
+        let path = dir.path().join("zome1.wasm");
+        std::fs::write(&path, &wasm).unwrap();
+        let url = format!("file://{}", path.display());
+        let mut manifest = DnaManifestCurrent {
+            name: "name".into(),
+            integrity: IntegrityManifest {
+                network_seed: None,
+                properties: None,
+                origin_time: Timestamp::HOLOCHAIN_EPOCH.into(),
+                zomes: vec![ZomeManifest {
+                    name: "zome1".into(),
+                    hash: None,
+                    location: mr_bundle::Location::Url(url),
+                    // Intentional wrong hash
+                    sha256: Some(mr_bundle::ResourceHash::of(&[4, 5, 6])),
+                    dependencies: Default::default(),
+                }],
+            },
+            coordinator: CoordinatorManifest { zomes: vec![] },
+        };
+
+        // - Show that conversion fails due to hash mismatch
+        let bad_bundle: DnaBundle =
+            mr_bundle::Bundle::new_unchecked(manifest.clone().try_into().unwrap(), vec![])
+                .unwrap()
+                .into();
+        matches::assert_matches!(
+            bad_bundle.into_dna_file(DnaModifiersOpt::none()).await,
+            Err(DnaError::MrBundleError(
+                mr_bundle::error::MrBundleError::BundleError(
+                    mr_bundle::error::BundleError::ResourceHashMismatch { .. }
+                )
+            ))
+        );
+
+        // - Correct the hash and try again
+        manifest.integrity.zomes[0].sha256 = Some(mr_bundle::ResourceHash::of(&wasm));
+        let bundle: DnaBundle =
+            mr_bundle::Bundle::new_unchecked(manifest.try_into().unwrap(), vec![])
+                .unwrap()
+                .into();
+        let dna_file: DnaFile = bundle
+            .into_dna_file(DnaModifiersOpt::none())
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(dna_file.dna_def().integrity_zomes.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn dna_bundle_with_path_zome_is_verified() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zome1.wasm");
+        std::fs::write(&path, [1, 2, 3]).unwrap();
+        let manifest = DnaManifestCurrent {
+            name: "name".into(),
+            integrity: IntegrityManifest {
+                network_seed: None,
+                properties: None,
+                origin_time: Timestamp::HOLOCHAIN_EPOCH.into(),
+                zomes: vec![ZomeManifest {
+                    name: "zome1".into(),
+                    hash: None,
+                    location: mr_bundle::Location::Path(path),
+                    // Intentional wrong hash
+                    sha256: Some(mr_bundle::ResourceHash::of(&[4, 5, 6])),
+                    dependencies: Default::default(),
+                }],
+            },
+            coordinator: CoordinatorManifest { zomes: vec![] },
+        };
+
+        let bundle: DnaBundle =
+            mr_bundle::Bundle::new_unchecked(manifest.try_into().unwrap(), vec![])
+                .unwrap()
+                .into();
+        matches::assert_matches!(
+            bundle.into_dna_file(DnaModifiersOpt::none()).await,
+            Err(DnaError::MrBundleError(
+                mr_bundle::error::MrBundleError::BundleError(
+                    mr_bundle::error::BundleError::ResourceHashMismatch { .. }
+                )
+            ))
+        );
+    }
 }
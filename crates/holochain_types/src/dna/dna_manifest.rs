@@ -34,6 +34,15 @@ impl mr_bundle::Manifest for ValidatedDnaManifest {
         }
     }
 
+    fn resource_hash(&self, location: &mr_bundle::Location) -> Option<mr_bundle::ResourceHash> {
+        match &self.0 {
+            DnaManifest::V1(m) => m
+                .all_zomes()
+                .find(|zome| &zome.location == location)
+                .and_then(|zome| zome.sha256.clone()),
+        }
+    }
+
     fn path() -> PathBuf {
         "dna.yaml".into()
     }
@@ -56,6 +56,16 @@ use serde_with::serde_as;
 ///     - name: zome4
 ///       bundled: ../dna2/zomes/zome2.wasm
 /// ```
+///
+/// Zomes may also be fetched from a URL, in which case the SHA-256 hash of the
+/// wasm should be given so it can be verified and cached:
+///
+/// ```yaml
+///   zomes:
+///     - name: zome5
+///       url: https://example.com/zomes/zome5.wasm
+///       sha256: 8d1cdbb1a6a2ef0ca31c2c1b1e0dd8e3ae9bd45e5de7db4e9c49ba2c58f5a0b6
+/// ```
 
 #[serde_as]
 #[derive(
@@ -149,6 +159,11 @@ pub struct ZomeManifest {
     #[serde(flatten)]
     pub location: ZomeLocation,
 
+    /// The SHA-256 hash of the wasm file, as a hex string.
+    /// A zome located by URL is checked against this hash before it is used,
+    /// and can then be cached for reuse without the network.
+    pub sha256: Option<mr_bundle::ResourceHash>,
+
     /// The integrity zomes this zome depends on.
     /// The order of these must match the order the types
     /// are used in the zome.
@@ -36,6 +36,34 @@ pub enum SystemSignal {
     Test(String),
     /// A countersigning session has successfully completed.
     SuccessfulCountersigning(holo_hash::EntryHash),
+    /// A `post_commit` callback failed, or succeeded after having failed.
+    PostCommit(PostCommitOutcome),
+}
+
+/// The result of running a zome's `post_commit` callback.
+///
+/// Only failures, and successes of retried invocations, are signalled.
+#[derive(Clone, Debug, Serialize, Deserialize, SerializedBytes, PartialEq, Eq)]
+pub struct PostCommitOutcome {
+    /// The cell whose chain the actions were committed to
+    pub cell_id: CellId,
+    /// The zome whose `post_commit` callback was run
+    pub zome_name: ZomeName,
+    /// The committed actions which were passed to `post_commit`
+    pub action_hashes: Vec<holo_hash::ActionHash>,
+    /// Which attempt this was, starting at 1
+    pub attempt: u32,
+    /// The error returned by the callback, if it failed
+    pub error: Option<String>,
+    /// When the callback will next be retried, if it failed and will be
+    pub next_attempt: Option<Timestamp>,
+}
+
+impl PostCommitOutcome {
+    /// Whether the callback succeeded
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
 }
 
 /// Create a test signal
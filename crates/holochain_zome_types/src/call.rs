@@ -13,6 +13,9 @@ pub enum CallTargetCell {
     OtherCell(CellId),
     OtherRole(RoleName),
     Local,
+    /// A cell in another installed app, addressed by the name of a
+    /// dependency declared in the calling app's manifest.
+    Dependency(String),
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -27,10 +27,12 @@
 mod grant;
 pub use grant::*;
 
+use holo_hash::{ActionHash, AgentPubKey};
 pub use holochain_integrity_types::capability::*;
 use serde::{Deserialize, Serialize};
 
 use crate::CellId;
+use crate::Timestamp;
 
 /// Parameters for granting a zome call capability.
 #[derive(Debug, Deserialize, Serialize)]
@@ -41,3 +43,41 @@ pub struct GrantZomeCallCapabilityPayload {
     /// signing for as well as access level, secret and assignees.
     pub cap_grant: ZomeCallCapGrant,
 }
+
+/// Filter for listing the capability grants of the local source chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CapGrantQuery {
+    /// Only list grants with this tag.
+    pub tag: Option<String>,
+}
+
+/// A capability grant which is live on the local source chain,
+/// i.e. neither deleted nor replaced by an update.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CapGrantInfo {
+    /// The grant entry.
+    pub cap_grant: ZomeCallCapGrant,
+    /// The action which committed the grant. Deleting this action revokes the grant.
+    pub action_hash: ActionHash,
+    /// When the grant was committed.
+    pub created_at: Timestamp,
+}
+
+/// Filter for listing the capability claims of the local source chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CapClaimQuery {
+    /// Only list claims with this tag.
+    pub tag: Option<String>,
+    /// Only list claims on grants of this agent.
+    pub grantor: Option<AgentPubKey>,
+}
+
+/// The capability which authorized the current zome call.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CapabilityInfo {
+    /// The agent who made the call.
+    pub provenance: AgentPubKey,
+    /// The grant which allowed the call.
+    /// Calls made by the chain author are granted by [`CapGrant::ChainAuthor`].
+    pub cap_grant: CapGrant,
+}
@@ -171,6 +171,28 @@ impl GetInput {
     }
 }
 
+/// Zome input for get_meta calls.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct GetMetaInput {
+    /// Any DHT hash to get the metadata of.
+    pub any_dht_hash: holo_hash::AnyDhtHash,
+    /// The metadata to return.
+    pub request: crate::request::MetadataRequest,
+}
+
+impl GetMetaInput {
+    /// Constructor.
+    pub fn new(
+        any_dht_hash: holo_hash::AnyDhtHash,
+        request: crate::request::MetadataRequest,
+    ) -> Self {
+        Self {
+            any_dht_hash,
+            request,
+        }
+    }
+}
+
 /// Zome input type for all update operations.
 #[derive(PartialEq, Debug, Deserialize, Serialize, Clone)]
 pub struct UpdateInput {
@@ -2,8 +2,12 @@
 use crate::record::Record;
 use crate::record::SignedActionHashed;
 use crate::validate::ValidationStatus;
+use crate::ActionHashed;
 use crate::Entry;
+use crate::Timestamp;
+use holo_hash::ActionHash;
 use holochain_serialized_bytes::prelude::*;
+use std::collections::BTreeSet;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, SerializedBytes)]
 #[serde(tag = "type", content = "content")]
@@ -91,3 +95,72 @@ pub enum EntryDhtStatus {
     /// **not implemented** We have agreed to drop this [Entry] content from the system. Action can stay with no entry
     Purged,
 }
+
+/// Timestamp of when the action was created with the actions hash.
+#[derive(Debug, Hash, PartialOrd, Ord, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TimedActionHash {
+    /// Time when this action was created
+    pub timestamp: Timestamp,
+    /// Hash of the action
+    pub action_hash: ActionHash,
+}
+
+/// Metadata returned from a GetMeta request.
+/// The Ord derive on TimedActionHash means each set is ordered by time.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, SerializedBytes)]
+pub struct MetadataSet {
+    /// Actions that created or updated an entry.
+    /// These are the actions that show the entry exists.
+    pub actions: BTreeSet<TimedActionHash>,
+    /// Actions that created or updated an entry, but did not pass validation.
+    pub invalid_actions: BTreeSet<TimedActionHash>,
+    /// Deletes on an action
+    pub deletes: BTreeSet<TimedActionHash>,
+    /// Updates on an action or entry
+    pub updates: BTreeSet<TimedActionHash>,
+    /// Links based on the entry or action which have not been deleted
+    #[serde(default)]
+    pub links: BTreeSet<TimedActionHash>,
+    /// The status of an entry from an authority.
+    /// This is simply a faster way of determining if
+    /// there are any live actions on an entry.
+    pub entry_dht_status: Option<EntryDhtStatus>,
+}
+
+impl MetadataSet {
+    /// Combine the metadata returned by several authorities.
+    /// An entry is live if any of the authorities sees it as live.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.actions.extend(other.actions);
+        self.invalid_actions.extend(other.invalid_actions);
+        self.deletes.extend(other.deletes);
+        self.updates.extend(other.updates);
+        self.links.extend(other.links);
+        self.entry_dht_status = match (self.entry_dht_status, other.entry_dht_status) {
+            (Some(EntryDhtStatus::Live), _) | (_, Some(EntryDhtStatus::Live)) => {
+                Some(EntryDhtStatus::Live)
+            }
+            (status, other_status) => status.or(other_status),
+        };
+        self
+    }
+}
+
+impl From<ActionHashed> for TimedActionHash {
+    fn from(h: ActionHashed) -> Self {
+        let (action, hash) = h.into_inner();
+        TimedActionHash {
+            timestamp: action.timestamp(),
+            action_hash: hash,
+        }
+    }
+}
+
+impl From<ActionHash> for TimedActionHash {
+    fn from(h: ActionHash) -> Self {
+        TimedActionHash {
+            timestamp: Timestamp::now(),
+            action_hash: h,
+        }
+    }
+}
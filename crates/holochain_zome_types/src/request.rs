@@ -8,13 +8,15 @@ pub struct MetadataRequest {
     /// Get all the actions on an entry.
     /// Invalid request on an action.
     pub all_valid_actions: bool,
-    // TODO: Implement after validation
-    /// Placeholder
+    /// Get all the actions on an entry which did not pass validation.
     pub all_invalid_actions: bool,
     /// Get all the deletes on an action
     pub all_deletes: bool,
     /// Get all the updates on an entry or action
     pub all_updates: bool,
+    /// Get all the live links based on an entry or action
+    #[serde(default = "all_links_default")]
+    pub all_links: bool,
     /// Placeholder
     pub follow_redirects: bool,
     /// Request the status of an entry.
@@ -23,6 +25,12 @@ pub struct MetadataRequest {
     pub entry_dht_status: bool,
 }
 
+/// Requests which don't set `all_links` get the links, as with
+/// [`MetadataRequest::default`].
+fn all_links_default() -> bool {
+    true
+}
+
 impl Default for MetadataRequest {
     fn default() -> Self {
         Self {
@@ -30,6 +38,7 @@ impl Default for MetadataRequest {
             all_invalid_actions: false,
             all_deletes: true,
             all_updates: true,
+            all_links: true,
             follow_redirects: false,
             entry_dht_status: false,
         }
@@ -373,3 +373,77 @@ impl CoordinatorZomeDef {
         self.0
     }
 }
+
+/// An extern exported by the wasm of a zome.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ZomeFnInfo {
+    /// The name of the extern.
+    pub name: FunctionName,
+    /// Whether the host calls this extern as a callback, e.g. `init`,
+    /// rather than it being a zome function.
+    pub is_callback: bool,
+    /// The signature declared with `hdk_extern`, if the wasm includes it.
+    pub signature: Option<ZomeFnSignature>,
+}
+
+/// The input and output types of an extern as written in its declaration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ZomeFnSignature {
+    /// The type of the single input parameter.
+    pub input: String,
+    /// The return type.
+    pub output: String,
+}
+
+impl ZomeFnSignature {
+    /// Parse the signatures written into the
+    /// [`EXTERN_SIGNATURES_SECTION`] of a wasm.
+    /// Malformed lines are skipped.
+    pub fn parse_section(section: &[u8]) -> Vec<(FunctionName, Self)> {
+        String::from_utf8_lossy(section)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t');
+                let name = parts.next()?;
+                let input = parts.next()?;
+                let output = parts.next()?;
+                Some((
+                    FunctionName::new(name),
+                    Self {
+                        input: input.to_string(),
+                        output: output.to_string(),
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_extern_signatures() {
+        let section = b"foo\t()\tExternResult<String>\nbroken\nbar\tActionHash\tExternResult<()>\n";
+        assert_eq!(
+            ZomeFnSignature::parse_section(section),
+            vec![
+                (
+                    FunctionName::new("foo"),
+                    ZomeFnSignature {
+                        input: "()".into(),
+                        output: "ExternResult<String>".into(),
+                    }
+                ),
+                (
+                    FunctionName::new("bar"),
+                    ZomeFnSignature {
+                        input: "ActionHash".into(),
+                        output: "ExternResult<()>".into(),
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -32,9 +32,21 @@ fn bootstrap(bench: &mut Criterion) {
 
     let mut url = url2!("http://127.0.0.1:0");
     let (driver, addr, _shutdown) = runtime.block_on(async {
-        kitsune_p2p_bootstrap::run(([127, 0, 0, 1], 0), vec![])
-            .await
-            .unwrap()
+        // The agent infos below carry fake signatures
+        kitsune_p2p_bootstrap::run_with_store(
+            ([127, 0, 0, 1], 0),
+            vec![],
+            kitsune_p2p_bootstrap::PRUNE_EXPIRED_FREQ,
+            Arc::new(kitsune_p2p_bootstrap::MemoryStore::default()),
+            kitsune_p2p_bootstrap::SpaceQuota::default(),
+            kitsune_p2p_bootstrap::ProtectionConfig {
+                verify_signatures: false,
+                max_expiry_ms: u64::MAX,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
     });
     runtime.spawn(async move {
         driver.await;
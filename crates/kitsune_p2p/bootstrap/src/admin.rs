@@ -0,0 +1,126 @@
+use crate::protection::Ban;
+use crate::store::Store;
+
+use super::*;
+use warp::{http::StatusCode, reply::WithStatus, Filter};
+
+/// Admin ops to ban and unban spaces and agents, and list the bans.
+/// Requests must carry the configured admin token in `X-Admin-Token`.
+pub(crate) fn admin(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply + Sized, Error = warp::Rejection> + Clone {
+    let op = |op: &'static str| {
+        warp::post()
+            .and(warp::header::exact("X-Op", op))
+            .and(warp::header::optional::<String>("X-Admin-Token"))
+            .and(with_store(store.clone()))
+    };
+    let ban = op("ban")
+        .and(warp::body::content_length_limit(SIZE_LIMIT))
+        .and(warp::body::bytes())
+        .map(|token: Option<String>, store: Store, ban: Bytes| {
+            update_bans(token, &store, &ban, Store::ban).unwrap_or_else(|reply| reply)
+        });
+    let unban = op("unban")
+        .and(warp::body::content_length_limit(SIZE_LIMIT))
+        .and(warp::body::bytes())
+        .map(|token: Option<String>, store: Store, ban: Bytes| {
+            update_bans(token, &store, &ban, Store::unban).unwrap_or_else(|reply| reply)
+        });
+    let bans = op("bans").map(|token: Option<String>, store: Store| {
+        list_bans(token, &store).unwrap_or_else(|reply| reply)
+    });
+    ban.or(unban).unify().or(bans).unify()
+}
+
+fn update_bans(
+    token: Option<String>,
+    store: &Store,
+    ban: &Bytes,
+    update: fn(&Store, &Ban) -> StoreResult<()>,
+) -> Result<WithStatus<Vec<u8>>, WithStatus<Vec<u8>>> {
+    authorize(store, token.as_deref())?;
+    let ban: Ban =
+        rmp_decode(&mut AsRef::<[u8]>::as_ref(ban)).map_err(|_| status(StatusCode::BAD_REQUEST))?;
+    update(store, &ban).map_err(|_| status(StatusCode::INTERNAL_SERVER_ERROR))?;
+    Ok(status(StatusCode::OK))
+}
+
+fn list_bans(
+    token: Option<String>,
+    store: &Store,
+) -> Result<WithStatus<Vec<u8>>, WithStatus<Vec<u8>>> {
+    authorize(store, token.as_deref())?;
+    let bans = store
+        .bans()
+        .map_err(|_| status(StatusCode::INTERNAL_SERVER_ERROR))?;
+    let mut buf = Vec::new();
+    rmp_encode(&mut buf, bans).map_err(|_| status(StatusCode::INTERNAL_SERVER_ERROR))?;
+    Ok(warp::reply::with_status(buf, StatusCode::OK))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::protection::ProtectionConfig;
+    use crate::store::{MemoryStore, SpaceQuota};
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ban_requires_admin_token() {
+        let store = Store::with_backend(
+            Arc::new(MemoryStore::default()),
+            vec![],
+            SpaceQuota::default(),
+            ProtectionConfig {
+                admin_token: Some("secret".into()),
+                ..Default::default()
+            },
+        );
+        let filter = super::admin(store.clone());
+        let ban = Ban::Agent(Arc::new(fixt!(KitsuneAgent, Unpredictable)));
+        let mut buf = Vec::new();
+        rmp_encode(&mut buf, &ban).unwrap();
+
+        let request = |op: &str, token: &str| {
+            warp::test::request()
+                .method("POST")
+                .header("X-Op", op)
+                .header("X-Admin-Token", token)
+        };
+
+        let res = request("ban", "wrong")
+            .body(buf.clone())
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 403);
+        let res = warp::test::request()
+            .method("POST")
+            .header("X-Op", "ban")
+            .body(buf.clone())
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 401);
+        let res = request("bans", "wrong").reply(&filter).await;
+        assert_eq!(res.status(), 403);
+        assert!(store.bans().unwrap().is_empty());
+
+        let res = request("ban", "secret")
+            .body(buf.clone())
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 200);
+
+        let res = request("bans", "secret").reply(&filter).await;
+        assert_eq!(res.status(), 200);
+        let bans: Vec<Ban> = rmp_decode(&mut res.body().as_ref()).unwrap();
+        assert_eq!(bans, vec![ban]);
+
+        let res = request("unban", "secret").body(buf).reply(&filter).await;
+        assert_eq!(res.status(), 200);
+        assert!(store.bans().unwrap().is_empty());
+    }
+}
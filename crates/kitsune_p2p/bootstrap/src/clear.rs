@@ -1,20 +1,29 @@
 use crate::store::Store;
 
 use super::*;
-use warp::Filter;
+use warp::{http::StatusCode, Filter};
 
 pub(crate) fn clear(
     store: Store,
 ) -> impl Filter<Extract = impl warp::Reply + Sized, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::header::exact("X-Op", "clear"))
+        .and(warp::header::optional::<String>("X-Admin-Token"))
         .and(with_store(store))
         .and_then(clear_info)
 }
 
-async fn clear_info(store: Store) -> Result<impl warp::Reply, warp::Rejection> {
-    store.clear();
-    Ok(warp::reply())
+async fn clear_info(
+    token: Option<String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Err(reply) = authorize(&store, token.as_deref()) {
+        return Ok(reply);
+    }
+    if store.clear().is_err() {
+        return Ok(status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+    Ok(status(StatusCode::OK))
 }
 
 #[cfg(test)]
@@ -22,12 +31,21 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
+    use crate::{MemoryStore, ProtectionConfig, SpaceQuota};
     use fixt::prelude::*;
     use kitsune_p2p::{agent_store::AgentInfoSigned, fixt::*, KitsuneSpace};
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_clear() {
-        let store = Store::new(vec![]);
+        let store = Store::with_backend(
+            Arc::new(MemoryStore::default()),
+            vec![],
+            SpaceQuota::default(),
+            ProtectionConfig {
+                admin_token: Some("secret".into()),
+                ..Default::default()
+            },
+        );
 
         let filter = super::clear(store.clone());
         let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
@@ -44,13 +62,26 @@ mod tests {
             )
             .await
             .unwrap();
-            store.put(info);
+            store.put(info).unwrap();
+        }
+
+        for (token, code) in [(None, 401), (Some("wrong"), 403)] {
+            let mut req = warp::test::request()
+                .method("POST")
+                .header("Content-type", "application/octet")
+                .header("X-Op", "clear");
+            if let Some(token) = token {
+                req = req.header("X-Admin-Token", token);
+            }
+            assert_eq!(req.reply(&filter).await.status(), code);
+            assert_eq!(store.all().get(space.as_ref()).unwrap().len(), 20);
         }
 
         let res = warp::test::request()
             .method("POST")
             .header("Content-type", "application/octet")
             .header("X-Op", "clear")
+            .header("X-Admin-Token", "secret")
             .reply(&filter)
             .await;
         assert_eq!(res.status(), 200);
@@ -7,18 +7,24 @@ use std::sync::atomic::AtomicUsize;
 use kitsune_p2p_types::codec::rmp_decode;
 use kitsune_p2p_types::codec::rmp_encode;
 use store::Store;
-use warp::{hyper::body::Bytes, Filter};
+use warp::{http::StatusCode, hyper::body::Bytes, reply::WithStatus, Filter};
 
 static NOW: AtomicUsize = AtomicUsize::new(0);
-static RANDOM: AtomicUsize = AtomicUsize::new(0);
-static PUT: AtomicUsize = AtomicUsize::new(0);
 
+mod admin;
 mod clear;
+mod metrics;
 mod now;
+mod protection;
 mod proxy_list;
 mod put;
 mod random;
 mod store;
+#[cfg(test)]
+mod test_util;
+
+pub use protection::{Ban, ProtectionConfig};
+pub use store::{BootstrapStore, MemoryStore, SpaceQuota, SqliteStore, StoreResult};
 
 /// No reason to accept a peer data bigger then 1KB.
 // TODO: Maybe even that's too high?
@@ -45,7 +51,28 @@ pub async fn run_with_prune_freq(
     proxy_list: Vec<String>,
     prune_frequency: std::time::Duration,
 ) -> Result<(BootstrapDriver, SocketAddr, BootstrapShutdown), String> {
-    let store = Store::new(proxy_list);
+    run_with_store(
+        addr,
+        proxy_list,
+        prune_frequency,
+        std::sync::Arc::new(MemoryStore::default()),
+        SpaceQuota::default(),
+        ProtectionConfig::default(),
+    )
+    .await
+}
+
+/// Run a bootstrap server which keeps agent infos in the given store,
+/// limits each space to the given quota and protects puts as configured.
+pub async fn run_with_store(
+    addr: impl Into<SocketAddr> + 'static,
+    proxy_list: Vec<String>,
+    prune_frequency: std::time::Duration,
+    backend: std::sync::Arc<dyn BootstrapStore>,
+    quota: SpaceQuota,
+    protection: ProtectionConfig,
+) -> Result<(BootstrapDriver, SocketAddr, BootstrapShutdown), String> {
+    let store = Store::with_backend(backend, proxy_list, quota, protection);
 
     {
         let store = store.clone();
@@ -61,6 +88,8 @@ pub async fn run_with_prune_freq(
         .or(put::put(store.clone()))
         .or(random::random(store.clone()))
         .or(proxy_list::proxy_list(store.clone()))
+        .or(metrics::metrics(store.clone()))
+        .or(admin::admin(store.clone()))
         .or(clear::clear(store));
 
     let (s, r) = tokio::sync::oneshot::channel();
@@ -79,8 +108,98 @@ pub async fn run_with_prune_freq(
     }
 }
 
+/// An empty reply with the given status.
+fn status(status: StatusCode) -> WithStatus<Vec<u8>> {
+    warp::reply::with_status(Vec::new(), status)
+}
+
+/// Check that an admin request carries the admin token, replying with a 401
+/// if it has no token and a 403 if the token is wrong.
+fn authorize(store: &Store, token: Option<&str>) -> Result<(), WithStatus<Vec<u8>>> {
+    match token {
+        None => Err(status(StatusCode::UNAUTHORIZED)),
+        Some(token) if !store.protection().is_admin(Some(token)) => {
+            Err(status(StatusCode::FORBIDDEN))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
 fn with_store(
     store: Store,
 ) -> impl Filter<Extract = (Store,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || store.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+    use kitsune_p2p_types::agent_info::AgentInfoSigned;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_server_only_stores_signed_agent_infos() {
+        let (driver, addr, shutdown) = run_with_prune_freq(
+            ([127, 0, 0, 1], 0),
+            vec![],
+            std::time::Duration::from_millis(100),
+        )
+        .await
+        .unwrap();
+        tokio::spawn(driver);
+
+        let client = reqwest::Client::new();
+        let post = |op: &'static str, body: Vec<u8>| {
+            client
+                .post(format!("http://{}", addr))
+                .header("X-Op", op)
+                .body(body)
+                .send()
+        };
+
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+        let good = test_util::signed_info(space.clone(), now, now + 60_000).await;
+        let forged = AgentInfoSigned::sign(
+            space.clone(),
+            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+            u32::MAX / 4,
+            vec!["fake:".into()],
+            now,
+            now + 60_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+
+        for (info, status) in [(&good, 200), (&forged, 400)] {
+            let mut buf = Vec::new();
+            rmp_encode(&mut buf, info).unwrap();
+            assert_eq!(post("put", buf).await.unwrap().status(), status);
+        }
+
+        let mut buf = Vec::new();
+        rmp_encode(
+            &mut buf,
+            kitsune_p2p_types::bootstrap::RandomQuery {
+                space,
+                limit: kitsune_p2p_types::bootstrap::RandomLimit(10),
+            },
+        )
+        .unwrap();
+        let body = post("random", buf).await.unwrap().bytes().await.unwrap();
+        let random: Vec<Vec<u8>> = rmp_decode(&mut body.as_ref()).unwrap();
+        let random: Vec<AgentInfoSigned> = random
+            .into_iter()
+            .map(|bytes| rmp_decode(&mut bytes.as_slice()).unwrap())
+            .collect();
+        assert_eq!(random, vec![good]);
+
+        // Admin ops are refused when no admin token is configured
+        assert_ne!(post("bans", vec![]).await.unwrap().status(), 200);
+
+        shutdown();
+    }
+}
@@ -12,6 +12,43 @@ struct Args {
     /// multiple times
     #[clap(short, long, verbatim_doc_comment)]
     proxy: Vec<String>,
+
+    /// keep agent infos in a SQLite database
+    /// at this path instead of in memory, so
+    /// they survive a restart. several servers
+    /// on the same host may share the same
+    /// database
+    #[clap(long, verbatim_doc_comment)]
+    sqlite: Option<std::path::PathBuf>,
+
+    /// reject new agents in a space which
+    /// already has this many agents
+    #[clap(long, verbatim_doc_comment)]
+    max_agents_per_space: Option<usize>,
+
+    /// reject puts from an IP address which
+    /// has made this many puts in the last minute
+    #[clap(long, verbatim_doc_comment)]
+    puts_per_ip_per_minute: Option<u32>,
+
+    /// reject puts for a space which has
+    /// received this many puts in the last minute
+    #[clap(long, verbatim_doc_comment)]
+    puts_per_space_per_minute: Option<u32>,
+
+    /// the number of trusted reverse proxies
+    /// in front of the server. client IP
+    /// addresses are taken from the
+    /// X-Forwarded-For entry this many hops
+    /// from the right
+    #[clap(long, default_value = "0", verbatim_doc_comment)]
+    trusted_proxies: usize,
+
+    /// enable the ban, unban and bans admin
+    /// ops for requests carrying this token in
+    /// the X-Admin-Token header
+    #[clap(long, verbatim_doc_comment)]
+    admin_token: Option<String>,
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -27,7 +64,37 @@ async fn main() {
         .next()
         .unwrap();
 
-    match kitsune_p2p_bootstrap::run(addr, args.proxy).await {
+    let store: std::sync::Arc<dyn kitsune_p2p_bootstrap::BootstrapStore> = match args.sqlite {
+        Some(path) => match kitsune_p2p_bootstrap::SqliteStore::open(path) {
+            Ok(store) => std::sync::Arc::new(store),
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        },
+        None => std::sync::Arc::new(kitsune_p2p_bootstrap::MemoryStore::default()),
+    };
+    let quota = kitsune_p2p_bootstrap::SpaceQuota {
+        max_agents: args.max_agents_per_space,
+    };
+    let protection = kitsune_p2p_bootstrap::ProtectionConfig {
+        puts_per_ip_per_minute: args.puts_per_ip_per_minute,
+        puts_per_space_per_minute: args.puts_per_space_per_minute,
+        trusted_proxies: args.trusted_proxies,
+        admin_token: args.admin_token,
+        ..Default::default()
+    };
+
+    match kitsune_p2p_bootstrap::run_with_store(
+        addr,
+        args.proxy,
+        kitsune_p2p_bootstrap::PRUNE_EXPIRED_FREQ,
+        store,
+        quota,
+        protection,
+    )
+    .await
+    {
         Ok((driver, addr, _shutdown)) => {
             println!("http://{}", addr);
             driver.await;
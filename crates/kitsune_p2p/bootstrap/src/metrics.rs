@@ -0,0 +1,39 @@
+use crate::store::Store;
+
+use super::*;
+use warp::Filter;
+
+pub(crate) fn metrics(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply + Sized, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::header::exact("X-Op", "metrics"))
+        .and(with_store(store))
+        .and_then(get_metrics)
+}
+
+async fn get_metrics(store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&store.metrics().to_json()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_metrics() {
+        let store = Store::new(vec![]);
+        store.prune();
+
+        let filter = super::metrics(store);
+        let res = warp::test::request()
+            .method("POST")
+            .header("X-Op", "metrics")
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 200);
+        let metrics: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(metrics["prune"]["total"], 1);
+        assert_eq!(metrics["put"]["total"], 0);
+    }
+}
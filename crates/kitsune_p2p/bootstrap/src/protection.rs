@@ -0,0 +1,287 @@
+//! Checks which stop a single client from flooding the bootstrap server
+//! with agent infos.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use kitsune_p2p_types::{
+    agent_info::AgentInfoSigned,
+    bin_types::{KitsuneAgent, KitsuneSpace},
+    dependencies::lair_keystore_api::dependencies::sodoken,
+};
+use parking_lot::Mutex;
+use subtle::ConstantTimeEq;
+
+use crate::store::PutError;
+
+/// The window over which put rates are counted.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Limits applied to puts, to protect the server from abuse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtectionConfig {
+    /// Whether agent info signatures are verified before storing.
+    /// Only disable this for testing.
+    pub verify_signatures: bool,
+
+    /// How far in the future, in milliseconds, an agent info may have been
+    /// signed, to allow for clock skew between the agent and the server.
+    pub max_clock_skew_ms: u64,
+
+    /// The longest time, in milliseconds, an agent info may be valid for
+    /// after it was signed.
+    pub max_expiry_ms: u64,
+
+    /// The maximum number of puts accepted from a single IP address per minute.
+    pub puts_per_ip_per_minute: Option<u32>,
+
+    /// The maximum number of puts accepted for a single space per minute.
+    pub puts_per_space_per_minute: Option<u32>,
+
+    /// The number of trusted proxies in front of the server. When set, the
+    /// client's IP address is taken from the `X-Forwarded-For` entry added by
+    /// the outermost trusted proxy, which is this many entries from the right.
+    /// Entries further left are set by the client and are never trusted.
+    pub trusted_proxies: usize,
+
+    /// The token which admin requests must carry in the `X-Admin-Token`
+    /// header. Admin requests are refused if this is not set.
+    pub admin_token: Option<String>,
+}
+
+impl Default for ProtectionConfig {
+    fn default() -> Self {
+        Self {
+            verify_signatures: true,
+            max_clock_skew_ms: 1000 * 60 * 5,
+            max_expiry_ms: 1000 * 60 * 60,
+            puts_per_ip_per_minute: None,
+            puts_per_space_per_minute: None,
+            trusted_proxies: 0,
+            admin_token: None,
+        }
+    }
+}
+
+/// A space or agent whose agent infos are refused by the server.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Ban {
+    /// No agent infos are accepted for this space.
+    Space(Arc<KitsuneSpace>),
+    /// No agent infos are accepted for this agent, in any space.
+    Agent(Arc<KitsuneAgent>),
+}
+
+/// Counts events per key over fixed windows of [`RATE_WINDOW`].
+#[derive(Debug)]
+struct RateLimiter<K> {
+    limit: Option<u32>,
+    windows: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    fn new(limit: Option<u32>) -> Self {
+        Self {
+            limit,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count an event for this key, returning false if the limit is exceeded.
+    fn check(&self, key: K) -> bool {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let now = Instant::now();
+        let mut windows = self.windows.lock();
+        let (start, count) = windows.entry(key).or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    /// Forget windows which have ended.
+    fn prune(&self) {
+        let now = Instant::now();
+        self.windows
+            .lock()
+            .retain(|_, (start, _)| now.duration_since(*start) < RATE_WINDOW);
+    }
+}
+
+/// The runtime state of the [`ProtectionConfig`] checks.
+#[derive(Debug)]
+pub(crate) struct Protection {
+    config: ProtectionConfig,
+    per_ip: RateLimiter<IpAddr>,
+    per_space: RateLimiter<Arc<KitsuneSpace>>,
+}
+
+impl Protection {
+    pub fn new(config: ProtectionConfig) -> Self {
+        Self {
+            per_ip: RateLimiter::new(config.puts_per_ip_per_minute),
+            per_space: RateLimiter::new(config.puts_per_space_per_minute),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ProtectionConfig {
+        &self.config
+    }
+
+    /// Whether this request carries the admin token.
+    pub fn is_admin(&self, token: Option<&str>) -> bool {
+        match (&self.config.admin_token, token) {
+            (Some(expected), Some(token)) => expected.as_bytes().ct_eq(token.as_bytes()).into(),
+            _ => false,
+        }
+    }
+
+    /// Check the timestamps and signature of a put.
+    pub async fn check_valid(&self, info: &AgentInfoSigned, now_ms: u64) -> Result<(), PutError> {
+        if info.signed_at_ms > now_ms.saturating_add(self.config.max_clock_skew_ms)
+            || info.expires_at_ms <= now_ms
+            || info.expires_at_ms.saturating_sub(info.signed_at_ms) > self.config.max_expiry_ms
+        {
+            return Err(PutError::InvalidTime);
+        }
+        if self.config.verify_signatures && !verify_signature(info).await {
+            return Err(PutError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Count a put towards the rate limits. Only call this for puts which
+    /// are valid and not banned, so rejected puts don't use up the budget.
+    pub fn check_rate(&self, info: &AgentInfoSigned, ip: Option<IpAddr>) -> Result<(), PutError> {
+        if let Some(ip) = ip {
+            if !self.per_ip.check(ip) {
+                return Err(PutError::RateLimited);
+            }
+        }
+        if !self.per_space.check(info.space.clone()) {
+            return Err(PutError::RateLimited);
+        }
+        Ok(())
+    }
+
+    pub fn prune(&self) {
+        self.per_ip.prune();
+        self.per_space.prune();
+    }
+}
+
+/// Whether the agent info was signed by its agent.
+async fn verify_signature(info: &AgentInfoSigned) -> bool {
+    if info.agent.0.len() < 32 || info.signature.0.len() != 64 {
+        return false;
+    }
+    let mut pub_key = [0; 32];
+    pub_key.copy_from_slice(&info.agent.0[..32]);
+    let mut signature = [0; 64];
+    signature.copy_from_slice(&info.signature.0);
+    sodoken::sign::verify_detached(
+        sodoken::BufReadSized::new_no_lock(signature),
+        sodoken::BufRead::new_no_lock(&info.encoded_bytes[..]),
+        sodoken::BufReadSized::new_no_lock(pub_key),
+    )
+    .await
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    fn now_ms() -> u64 {
+        std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_signatures_and_times_are_checked() {
+        let protection = Protection::new(ProtectionConfig::default());
+        let space = Arc::new(fixt!(KitsuneSpace));
+        let now = now_ms();
+
+        let good = signed_info(space.clone(), now, now + 60_000).await;
+        protection.check_valid(&good, now).await.unwrap();
+
+        let forged = AgentInfoSigned::sign(
+            space.clone(),
+            good.agent.clone(),
+            u32::MAX / 4,
+            vec!["fake:".into()],
+            now,
+            now + 60_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            protection.check_valid(&forged, now).await,
+            Err(PutError::InvalidSignature)
+        ));
+
+        for (signed_at_ms, expires_at_ms) in [
+            // Expired
+            (now - 2000, now - 1000),
+            // Signed in the future
+            (now + 1000 * 60 * 10, now + 1000 * 60 * 20),
+            // Valid for too long
+            (now, now + 1000 * 60 * 60 * 24),
+        ] {
+            let info = signed_info(space.clone(), signed_at_ms, expires_at_ms).await;
+            assert!(matches!(
+                protection.check_valid(&info, now).await,
+                Err(PutError::InvalidTime)
+            ));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rate_limits() {
+        let protection = Protection::new(ProtectionConfig {
+            puts_per_ip_per_minute: Some(2),
+            puts_per_space_per_minute: Some(3),
+            ..Default::default()
+        });
+        let space = Arc::new(fixt!(KitsuneSpace));
+        let now = now_ms();
+        let info = signed_info(space.clone(), now, now + 60_000).await;
+        let ip_a: IpAddr = [10, 0, 0, 1].into();
+        let ip_b: IpAddr = [10, 0, 0, 2].into();
+
+        protection.check_rate(&info, Some(ip_a)).unwrap();
+        protection.check_rate(&info, Some(ip_a)).unwrap();
+        assert!(matches!(
+            protection.check_rate(&info, Some(ip_a)),
+            Err(PutError::RateLimited)
+        ));
+        // Another IP can still put until the space's limit is reached
+        protection.check_rate(&info, Some(ip_b)).unwrap();
+        assert!(matches!(
+            protection.check_rate(&info, Some(ip_b)),
+            Err(PutError::RateLimited)
+        ));
+
+        let ip_c: IpAddr = [10, 0, 0, 3].into();
+        let other = signed_info(Arc::new(fixt!(KitsuneSpace)), now, now + 60_000).await;
+        protection.check_rate(&other, Some(ip_c)).unwrap();
+    }
+}
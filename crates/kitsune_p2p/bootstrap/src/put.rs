@@ -1,8 +1,9 @@
-use crate::store::Store;
+use crate::store::{PutError, Store};
 
 use super::*;
 use kitsune_p2p_types::agent_info::AgentInfoSigned;
-use warp::Filter;
+use std::net::IpAddr;
+use warp::{http::StatusCode, reply::WithStatus, Filter};
 
 pub(crate) fn put(
     store: Store,
@@ -11,31 +12,64 @@ pub(crate) fn put(
         .and(warp::header::exact("X-Op", "put"))
         .and(warp::body::content_length_limit(SIZE_LIMIT))
         .and(warp::body::bytes())
+        .and(warp::addr::remote())
+        .and(warp::header::optional::<String>("X-Forwarded-For"))
         .and(with_store(store))
         .and_then(put_info)
 }
 
-async fn put_info(peer: Bytes, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
-    let peer: AgentInfoSigned =
-        rmp_decode(&mut AsRef::<[u8]>::as_ref(&peer)).map_err(|_| warp::reject())?;
-    // TODO: Return rejection if agent info was invalid?
-    if valid(&peer) {
-        store.put(peer);
-    }
-    PUT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-    let mut buf = Vec::with_capacity(1);
-    rmp_encode(&mut buf, ()).map_err(|_| warp::reject())?;
-    Ok(buf)
+async fn put_info(
+    peer: Bytes,
+    remote: Option<SocketAddr>,
+    forwarded_for: Option<String>,
+    store: Store,
+) -> Result<WithStatus<Vec<u8>>, warp::Rejection> {
+    let peer: AgentInfoSigned = match rmp_decode(&mut AsRef::<[u8]>::as_ref(&peer)) {
+        Ok(peer) => peer,
+        Err(_) => return Ok(status(StatusCode::BAD_REQUEST)),
+    };
+    let ip = client_ip(&store, remote, forwarded_for);
+    let (code, e) = match store.put_checked(peer, ip).await {
+        Ok(()) => {
+            let mut buf = Vec::with_capacity(1);
+            rmp_encode(&mut buf, ()).map_err(|_| warp::reject())?;
+            return Ok(warp::reply::with_status(buf, StatusCode::OK));
+        }
+        Err(PutError::Store(e)) => {
+            tracing::warn!(?e, "Failed to store agent info");
+            return Ok(status(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+        Err(e @ PutError::QuotaExceeded) | Err(e @ PutError::RateLimited) => {
+            (StatusCode::TOO_MANY_REQUESTS, e)
+        }
+        Err(e @ PutError::Banned) => (StatusCode::FORBIDDEN, e),
+        Err(e @ PutError::InvalidTime) | Err(e @ PutError::InvalidSignature) => {
+            (StatusCode::BAD_REQUEST, e)
+        }
+    };
+    Ok(warp::reply::with_status(e.to_string().into_bytes(), code))
 }
 
-fn valid(peer: &AgentInfoSigned) -> bool {
-    // TODO: verify signature
-    // Verify time
-    peer.expires_at_ms as u128
-        > std::time::UNIX_EPOCH
-            .elapsed()
-            .expect("Bootstrap system clock is set before the epoch")
-            .as_millis()
+/// The address of the client making a request. When the server is behind
+/// trusted proxies, this is the `X-Forwarded-For` entry added by the outermost
+/// of them. Entries to the left of it were sent by the client and may be
+/// spoofed, so they are ignored.
+fn client_ip(
+    store: &Store,
+    remote: Option<SocketAddr>,
+    forwarded_for: Option<String>,
+) -> Option<IpAddr> {
+    let trusted_proxies = store.protection().config().trusted_proxies;
+    if trusted_proxies > 0 {
+        if let Some(ip) = forwarded_for
+            .as_deref()
+            .and_then(|f| f.rsplit(',').nth(trusted_proxies - 1))
+            .and_then(|ip| ip.trim().parse().ok())
+        {
+            return Some(ip);
+        }
+    }
+    remote.map(|addr| addr.ip())
 }
 
 #[cfg(test)]
@@ -43,36 +77,46 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
+    use crate::protection::{Ban, ProtectionConfig};
+    use crate::store::{MemoryStore, SpaceQuota};
+    use crate::test_util::signed_info;
     use fixt::prelude::*;
     use kitsune_p2p::fixt::*;
 
+    fn now_ms() -> u64 {
+        std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64
+    }
+
+    async fn put_status<F>(filter: &F, info: &AgentInfoSigned) -> StatusCode
+    where
+        F: Filter + 'static,
+        F::Extract: warp::Reply + Send,
+    {
+        let mut buf = Vec::new();
+        rmp_encode(&mut buf, info).unwrap();
+        warp::test::request()
+            .method("POST")
+            .header("Content-type", "application/octet")
+            .header("X-Op", "put")
+            .body(buf)
+            .reply(filter)
+            .await
+            .status()
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_put() {
         let store = Store::new(vec![]);
         let filter = put(store.clone());
 
-        let info = AgentInfoSigned::sign(
+        let now = now_ms();
+        let info = signed_info(
             Arc::new(fixt!(KitsuneSpace, Unpredictable)),
-            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
-            u32::MAX / 4,
-            fixt!(UrlList, Empty),
-            0,
-            std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
-            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+            now,
+            now + 60_000,
         )
-        .await
-        .unwrap();
-        let mut buf = Vec::new();
-        rmp_encode(&mut buf, info.clone()).unwrap();
-
-        let res = warp::test::request()
-            .method("POST")
-            .header("Content-type", "application/octet")
-            .header("X-Op", "put")
-            .body(buf)
-            .reply(&filter)
-            .await;
-        assert_eq!(res.status(), 200);
+        .await;
+        assert_eq!(put_status(&filter, &info).await, 200);
         assert_eq!(
             *store
                 .all()
@@ -83,4 +127,99 @@ mod tests {
             info
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_put_rejects_invalid_and_banned() {
+        let store = Store::new(vec![]);
+        let filter = put(store.clone());
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let now = now_ms();
+
+        let forged = AgentInfoSigned::sign(
+            space.clone(),
+            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+            u32::MAX / 4,
+            fixt!(UrlList, Empty),
+            now,
+            now + 60_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(put_status(&filter, &forged).await, 400);
+
+        let expired = signed_info(space.clone(), now - 2000, now - 1000).await;
+        assert_eq!(put_status(&filter, &expired).await, 400);
+
+        store.ban(&Ban::Space(space.clone())).unwrap();
+        let banned = signed_info(space.clone(), now, now + 60_000).await;
+        assert_eq!(put_status(&filter, &banned).await, 403);
+
+        assert!(store.all().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rejected_puts_are_not_rate_counted() {
+        let store = Store::with_backend(
+            Arc::new(MemoryStore::default()),
+            vec![],
+            SpaceQuota::default(),
+            ProtectionConfig {
+                puts_per_ip_per_minute: Some(1),
+                puts_per_space_per_minute: Some(1),
+                ..Default::default()
+            },
+        );
+        let filter = put(store.clone());
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let now = now_ms();
+
+        let expired = signed_info(space.clone(), now - 2000, now - 1000).await;
+        let banned = signed_info(space.clone(), now, now + 60_000).await;
+        store.ban(&Ban::Agent(banned.agent.clone())).unwrap();
+        for _ in 0..3 {
+            assert_eq!(put_status(&filter, &expired).await, 400);
+            assert_eq!(put_status(&filter, &banned).await, 403);
+        }
+
+        let info = signed_info(space.clone(), now, now + 60_000).await;
+        assert_eq!(put_status(&filter, &info).await, 200);
+        let info = signed_info(space, now, now + 60_000).await;
+        assert_eq!(put_status(&filter, &info).await, 429);
+    }
+
+    #[test]
+    fn test_client_ip_ignores_spoofed_forwarded_for() {
+        let store = |trusted_proxies| {
+            Store::with_backend(
+                Arc::new(MemoryStore::default()),
+                vec![],
+                SpaceQuota::default(),
+                ProtectionConfig {
+                    trusted_proxies,
+                    ..Default::default()
+                },
+            )
+        };
+        let remote: SocketAddr = ([10, 0, 0, 1], 5000).into();
+        let forwarded = || Some("1.1.1.1, 2.2.2.2, 3.3.3.3".to_string());
+
+        assert_eq!(
+            client_ip(&store(0), Some(remote), forwarded()),
+            Some(remote.ip())
+        );
+        assert_eq!(
+            client_ip(&store(1), Some(remote), forwarded()),
+            Some([3, 3, 3, 3].into())
+        );
+        assert_eq!(
+            client_ip(&store(2), Some(remote), forwarded()),
+            Some([2, 2, 2, 2].into())
+        );
+        // Not enough hops, so the request didn't come through the proxies
+        assert_eq!(
+            client_ip(&store(4), Some(remote), forwarded()),
+            Some(remote.ip())
+        );
+    }
 }
@@ -18,10 +18,9 @@ pub(crate) fn random(
 async fn random_info(query: Bytes, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
     let query: RandomQuery =
         rmp_decode(&mut AsRef::<[u8]>::as_ref(&query)).map_err(|_| warp::reject())?;
-    let result = store.random(query);
+    let result = store.random(query).map_err(|_| warp::reject())?;
     let mut buf = Vec::with_capacity(result.len());
     rmp_encode(&mut buf, result).map_err(|_| warp::reject())?;
-    RANDOM.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     Ok(buf)
 }
 
@@ -58,18 +57,9 @@ mod tests {
         let filter = super::random(store.clone());
         let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
         let mut peers = Vec::new();
+        let now = std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
         for _ in 0..20 {
-            let info = AgentInfoSigned::sign(
-                space.clone(),
-                Arc::new(fixt!(KitsuneAgent, Unpredictable)),
-                u32::MAX / 4,
-                vec!["fake:".into()],
-                0,
-                std::time::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 + 60_000_000,
-                |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
-            )
-            .await
-            .unwrap();
+            let info = crate::test_util::signed_info(space.clone(), now, now + 60_000).await;
             peers.push(info);
         }
         put(store.clone(), peers.clone()).await;
@@ -1,94 +1,474 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
 use kitsune_p2p_types::{
     agent_info::AgentInfoSigned,
     bin_types::{KitsuneAgent, KitsuneSpace},
     bootstrap::RandomQuery,
-    codec::rmp_encode,
 };
-use parking_lot::RwLock;
-use rand::seq::IteratorRandom;
 
-type AgentMap = HashMap<Arc<KitsuneAgent>, AgentInfoSigned>;
-type SpaceMap = HashMap<Arc<KitsuneSpace>, AgentMap>;
+use crate::protection::{Ban, Protection, ProtectionConfig};
+
+mod memory;
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+/// Result type for [`BootstrapStore`] operations.
+pub type StoreResult<T> = Result<T, String>;
+
+/// Where the bootstrap server keeps agent infos.
+///
+/// Implementations must be safe to share between the server's request
+/// handlers. A store which is shared between several server instances
+/// lets those instances run behind a load balancer. [`SqliteStore`] can
+/// only be shared by instances on the same host, because SQLite's file
+/// locking is not reliable over network filesystems.
+pub trait BootstrapStore: std::fmt::Debug + Send + Sync + 'static {
+    /// Store an agent info, unless a newer one for the same agent in the
+    /// same space is already stored.
+    fn put(&self, info: AgentInfoSigned) -> StoreResult<()>;
+
+    /// Like [`BootstrapStore::put`], but store nothing and return false if
+    /// the agent is not yet stored and its space already holds `max_agents`.
+    /// The check and the put are atomic, so concurrent puts can't overfill
+    /// the space.
+    fn put_within_quota(&self, info: AgentInfoSigned, max_agents: usize) -> StoreResult<bool>;
+
+    /// Up to `query.limit` randomly chosen, encoded agent infos from the
+    /// queried space which have not expired at `now_ms` and have at least
+    /// one url.
+    fn random(&self, query: &RandomQuery, now_ms: u64) -> StoreResult<Vec<Vec<u8>>>;
+
+    /// Remove all agent infos which expired before `now_ms`, returning how
+    /// many were removed.
+    fn prune(&self, now_ms: u64) -> StoreResult<usize>;
+
+    /// Remove all agent infos.
+    fn clear(&self) -> StoreResult<()>;
+
+    /// All stored agent infos.
+    fn all(&self) -> StoreResult<Vec<AgentInfoSigned>>;
+
+    /// Refuse agent infos for a space or agent, removing any already stored.
+    fn ban(&self, ban: &Ban) -> StoreResult<()>;
+
+    /// Lift a ban.
+    fn unban(&self, ban: &Ban) -> StoreResult<()>;
+
+    /// Whether either this space or this agent is banned.
+    fn is_banned(&self, space: &KitsuneSpace, agent: &KitsuneAgent) -> StoreResult<bool>;
+
+    /// All current bans.
+    fn bans(&self) -> StoreResult<Vec<Ban>>;
+}
+
+/// Limits on what a single space may store in the bootstrap server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpaceQuota {
+    /// The maximum number of agents stored for any one space. Puts for new
+    /// agents in a full space are rejected, while agents already stored can
+    /// still update their info.
+    pub max_agents: Option<usize>,
+}
+
+/// Why a put was not stored.
+#[derive(Debug)]
+pub(crate) enum PutError {
+    /// The space has reached its [`SpaceQuota`].
+    QuotaExceeded,
+    /// Too many puts from this client or for this space.
+    RateLimited,
+    /// The space or agent is banned.
+    Banned,
+    /// The agent info has expired, or its times are out of bounds.
+    InvalidTime,
+    /// The agent info was not signed by its agent.
+    InvalidSignature,
+    /// The store failed.
+    Store(String),
+}
+
+impl std::fmt::Display for PutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PutError::QuotaExceeded => write!(f, "space is full"),
+            PutError::RateLimited => write!(f, "too many puts, try again later"),
+            PutError::Banned => write!(f, "space or agent is banned"),
+            PutError::InvalidTime => write!(f, "agent info is expired or its times are invalid"),
+            PutError::InvalidSignature => write!(f, "agent info signature is invalid"),
+            PutError::Store(e) => write!(f, "store error: {}", e),
+        }
+    }
+}
+
+/// Counters for the requests handled by a bootstrap server.
+#[derive(Debug)]
+pub(crate) struct Metrics {
+    start: Instant,
+    puts: AtomicU64,
+    puts_rejected: AtomicU64,
+    randoms: AtomicU64,
+    prunes: AtomicU64,
+    pruned_agents: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            puts: AtomicU64::new(0),
+            puts_rejected: AtomicU64::new(0),
+            randoms: AtomicU64::new(0),
+            prunes: AtomicU64::new(0),
+            pruned_agents: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    /// The totals and average per-second rates since the server started.
+    pub fn to_json(&self) -> serde_json::Value {
+        let uptime = self.start.elapsed().as_secs_f64().max(1.0);
+        let count = |counter: &AtomicU64| {
+            let total = counter.load(Ordering::Relaxed);
+            serde_json::json!({
+                "total": total,
+                "per_second": total as f64 / uptime,
+            })
+        };
+        serde_json::json!({
+            "uptime_secs": self.start.elapsed().as_secs(),
+            "put": count(&self.puts),
+            "put_rejected": count(&self.puts_rejected),
+            "random": count(&self.randoms),
+            "prune": count(&self.prunes),
+            "pruned_agents": count(&self.pruned_agents),
+        })
+    }
+}
 
 #[derive(Clone, Debug)]
-pub(crate) struct Store(Arc<RwLock<SpaceMap>>, Arc<Vec<String>>);
+pub(crate) struct Store {
+    backend: Arc<dyn BootstrapStore>,
+    proxy_list: Arc<Vec<String>>,
+    quota: Arc<SpaceQuota>,
+    protection: Arc<Protection>,
+    metrics: Arc<Metrics>,
+}
 
 impl Store {
+    #[cfg(test)]
     pub fn new(proxy_list: Vec<String>) -> Self {
-        Self(Arc::new(RwLock::new(HashMap::new())), Arc::new(proxy_list))
+        Self::with_backend(
+            Arc::new(MemoryStore::default()),
+            proxy_list,
+            SpaceQuota::default(),
+            ProtectionConfig::default(),
+        )
+    }
+
+    pub fn with_backend(
+        backend: Arc<dyn BootstrapStore>,
+        proxy_list: Vec<String>,
+        quota: SpaceQuota,
+        protection: ProtectionConfig,
+    ) -> Self {
+        Self {
+            backend,
+            proxy_list: Arc::new(proxy_list),
+            quota: Arc::new(quota),
+            protection: Arc::new(Protection::new(protection)),
+            metrics: Default::default(),
+        }
+    }
+
+    pub fn protection(&self) -> &Protection {
+        &self.protection
     }
 
     pub fn proxy_list(&self) -> Arc<Vec<String>> {
-        self.1.clone()
+        self.proxy_list.clone()
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
     }
 
     pub fn prune(&self) {
-        let now = std::time::UNIX_EPOCH
-            .elapsed()
-            .expect("Bootstrap server time set before epoch")
-            .as_millis() as u64;
-
-        self.0.write().retain(|_, map| {
-            map.retain(|_, info| info.expires_at_ms >= now);
-            !map.is_empty()
-        });
-    }
-
-    pub fn put(&self, info: AgentInfoSigned) {
-        let mut lock = self.0.write();
-        let space_map = lock.entry(info.space.clone()).or_insert_with(HashMap::new);
-        match space_map.entry(info.agent.clone()) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                if info.signed_at_ms > e.get().signed_at_ms {
-                    e.insert(info);
-                }
-            }
-            std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(info);
+        self.protection.prune();
+        let now = now_ms();
+        match self.backend.prune(now) {
+            Ok(pruned) => {
+                self.metrics.prunes.fetch_add(1, Ordering::Relaxed);
+                self.metrics
+                    .pruned_agents
+                    .fetch_add(pruned as u64, Ordering::Relaxed);
             }
+            Err(e) => tracing::warn!(?e, "Failed to prune expired agents"),
         }
     }
 
-    pub fn random(&self, query: RandomQuery) -> Vec<Vec<u8>> {
-        // TODO: Max this limit
-        let limit = query.limit.0 as usize;
-        let mut rng = rand::thread_rng();
-        let now = std::time::UNIX_EPOCH
-            .elapsed()
-            .expect("Bootstrap server time set before epoch")
-            .as_millis() as u64;
-        self.0
-            .read()
-            .get(query.space.as_ref())
-            .map(|space| {
-                space
-                    .values()
-                    .filter_map(|i| {
-                        if i.expires_at_ms <= now {
-                            return None;
-                        }
-                        if i.url_list.is_empty() {
-                            return None;
-                        }
-                        let mut buf = Vec::new();
-                        match rmp_encode(&mut buf, i) {
-                            Ok(_) => Some(buf),
-                            Err(_) => None,
-                        }
-                    })
-                    .choose_multiple(&mut rng, limit)
-            })
-            .unwrap_or_default()
+    /// Check a put from a client against the [`ProtectionConfig`] and the
+    /// bans before storing it.
+    pub async fn put_checked(
+        &self,
+        info: AgentInfoSigned,
+        ip: Option<IpAddr>,
+    ) -> Result<(), PutError> {
+        // Check the ban before the rate limits, so banned clients can't use
+        // up the budget of the IP or space they share with others.
+        let checked = match self.protection.check_valid(&info, now_ms()).await {
+            Ok(()) => match self.backend.is_banned(&info.space, &info.agent) {
+                Ok(false) => self.protection.check_rate(&info, ip),
+                Ok(true) => Err(PutError::Banned),
+                Err(e) => Err(PutError::Store(e)),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = checked {
+            self.metrics.puts_rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(e);
+        }
+        self.put(info)
+    }
+
+    pub fn put(&self, info: AgentInfoSigned) -> Result<(), PutError> {
+        let result = match self.quota.max_agents {
+            Some(max_agents) => match self.backend.put_within_quota(info, max_agents) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(PutError::QuotaExceeded),
+                Err(e) => Err(PutError::Store(e)),
+            },
+            None => self.backend.put(info).map_err(PutError::Store),
+        };
+        let counter = match result {
+            Ok(_) => &self.metrics.puts,
+            Err(_) => &self.metrics.puts_rejected,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
     }
 
-    pub fn clear(&self) {
-        self.0.write().clear()
+    pub fn random(&self, query: RandomQuery) -> StoreResult<Vec<Vec<u8>>> {
+        self.metrics.randoms.fetch_add(1, Ordering::Relaxed);
+        self.backend.random(&query, now_ms())
+    }
+
+    pub fn clear(&self) -> StoreResult<()> {
+        self.backend.clear()
+    }
+
+    pub fn ban(&self, ban: &Ban) -> StoreResult<()> {
+        self.backend.ban(ban)
+    }
+
+    pub fn unban(&self, ban: &Ban) -> StoreResult<()> {
+        self.backend.unban(ban)
+    }
+
+    pub fn bans(&self) -> StoreResult<Vec<Ban>> {
+        self.backend.bans()
     }
 
     #[cfg(test)]
-    pub fn all(&self) -> HashMap<Arc<KitsuneSpace>, HashMap<Arc<KitsuneAgent>, AgentInfoSigned>> {
-        self.0.read().clone()
+    pub fn all(
+        &self,
+    ) -> std::collections::HashMap<
+        Arc<KitsuneSpace>,
+        std::collections::HashMap<Arc<KitsuneAgent>, AgentInfoSigned>,
+    > {
+        use std::collections::HashMap;
+        let mut all: HashMap<_, HashMap<_, _>> = HashMap::new();
+        for info in self.backend.all().unwrap() {
+            all.entry(info.space.clone())
+                .or_default()
+                .insert(info.agent.clone(), info);
+        }
+        all
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::UNIX_EPOCH
+        .elapsed()
+        .expect("Bootstrap server time set before epoch")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+    use kitsune_p2p_types::bootstrap::RandomLimit;
+    use kitsune_p2p_types::codec::rmp_decode;
+
+    async fn info(
+        space: Arc<KitsuneSpace>,
+        signed_at_ms: u64,
+        expires_at_ms: u64,
+    ) -> AgentInfoSigned {
+        AgentInfoSigned::sign(
+            space,
+            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+            u32::MAX / 4,
+            vec!["fake:".into()],
+            signed_at_ms,
+            expires_at_ms,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Exercise the behaviour every backend must share.
+    async fn check_backend(store: impl BootstrapStore) {
+        let space = Arc::new(fixt!(KitsuneSpace));
+        let now = now_ms();
+        let live = info(space.clone(), now, now + 60_000).await;
+        let expired = info(space.clone(), now - 2000, now - 1000).await;
+        store.put(live.clone()).unwrap();
+        store.put(expired.clone()).unwrap();
+        assert_eq!(store.all().unwrap().len(), 2);
+
+        // Older infos don't replace newer ones
+        let older = AgentInfoSigned::sign(
+            space.clone(),
+            live.agent.clone(),
+            u32::MAX / 4,
+            vec![],
+            now - 1,
+            now + 60_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+        store.put(older).unwrap();
+
+        let query = RandomQuery {
+            space: space.clone(),
+            limit: RandomLimit(10),
+        };
+        let random: Vec<AgentInfoSigned> = store
+            .random(&query, now)
+            .unwrap()
+            .into_iter()
+            .map(|bytes| rmp_decode(&mut bytes.as_slice()).unwrap())
+            .collect();
+        assert_eq!(random, vec![live.clone()]);
+
+        assert_eq!(store.prune(now).unwrap(), 1);
+        assert_eq!(store.all().unwrap(), vec![live]);
+
+        store.clear().unwrap();
+        assert!(store.all().unwrap().is_empty());
+
+        // Bans remove stored agents and are reported for either key
+        let banned_agent = info(space.clone(), now, now + 60_000).await;
+        let other = info(space.clone(), now, now + 60_000).await;
+        store.put(banned_agent.clone()).unwrap();
+        store.put(other.clone()).unwrap();
+        let ban = Ban::Agent(banned_agent.agent.clone());
+        store.ban(&ban).unwrap();
+        assert!(store.is_banned(&space, &banned_agent.agent).unwrap());
+        assert!(!store.is_banned(&space, &other.agent).unwrap());
+        assert_eq!(store.all().unwrap(), vec![other.clone()]);
+
+        store.ban(&Ban::Space(space.clone())).unwrap();
+        assert!(store.is_banned(&space, &other.agent).unwrap());
+        assert!(store.all().unwrap().is_empty());
+        assert_eq!(store.bans().unwrap().len(), 2);
+
+        store.unban(&ban).unwrap();
+        store.unban(&Ban::Space(space.clone())).unwrap();
+        assert!(!store.is_banned(&space, &banned_agent.agent).unwrap());
+        assert!(store.bans().unwrap().is_empty());
+
+        // Only agents already stored may put into a full space
+        let first = info(space.clone(), now, now + 60_000).await;
+        assert!(store.put_within_quota(first.clone(), 1).unwrap());
+        let second = info(space.clone(), now, now + 60_000).await;
+        assert!(!store.put_within_quota(second, 1).unwrap());
+        let update = AgentInfoSigned::sign(
+            space.clone(),
+            first.agent.clone(),
+            u32::MAX / 4,
+            vec!["fake:".into()],
+            now + 1,
+            now + 60_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+        assert!(store.put_within_quota(update.clone(), 1).unwrap());
+        assert_eq!(store.all().unwrap(), vec![update]);
+
+        // An empty quota refuses agents in new spaces too
+        let new_space = info(Arc::new(fixt!(KitsuneSpace)), now, now + 60_000).await;
+        assert!(!store.put_within_quota(new_space, 0).unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_memory_store() {
+        check_backend(MemoryStore::default()).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sqlite_store() {
+        check_backend(SqliteStore::open_in_memory().unwrap()).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_space_quota() {
+        let store = Store::with_backend(
+            Arc::new(MemoryStore::default()),
+            vec![],
+            SpaceQuota {
+                max_agents: Some(2),
+            },
+            ProtectionConfig::default(),
+        );
+        let space = Arc::new(fixt!(KitsuneSpace));
+        let now = now_ms();
+        let first = info(space.clone(), now, now + 60_000).await;
+        store.put(first.clone()).unwrap();
+        store
+            .put(info(space.clone(), now, now + 60_000).await)
+            .unwrap();
+        assert!(matches!(
+            store.put(info(space.clone(), now, now + 60_000).await),
+            Err(PutError::QuotaExceeded)
+        ));
+
+        // Agents already in the space can still update their info
+        let update = AgentInfoSigned::sign(
+            space.clone(),
+            first.agent.clone(),
+            u32::MAX / 4,
+            vec!["fake:".into()],
+            now + 1,
+            now + 60_000,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap();
+        store.put(update).unwrap();
+
+        // Other spaces have their own quota
+        store
+            .put(info(Arc::new(fixt!(KitsuneSpace)), now, now + 60_000).await)
+            .unwrap();
+
+        let metrics = store.metrics().to_json();
+        assert_eq!(metrics["put"]["total"], 4);
+        assert_eq!(metrics["put_rejected"]["total"], 1);
     }
 }
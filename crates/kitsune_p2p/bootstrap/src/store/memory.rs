@@ -0,0 +1,150 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use kitsune_p2p_types::{
+    agent_info::AgentInfoSigned,
+    bin_types::{KitsuneAgent, KitsuneSpace},
+    bootstrap::RandomQuery,
+    codec::rmp_encode,
+};
+use parking_lot::RwLock;
+use rand::seq::IteratorRandom;
+
+use super::{BootstrapStore, StoreResult};
+use crate::protection::Ban;
+
+type AgentMap = HashMap<Arc<KitsuneAgent>, AgentInfoSigned>;
+type SpaceMap = HashMap<Arc<KitsuneSpace>, AgentMap>;
+
+/// Store an agent info, unless a newer one is already stored.
+fn put_into(spaces: &mut SpaceMap, info: AgentInfoSigned) {
+    let space_map = spaces
+        .entry(info.space.clone())
+        .or_insert_with(HashMap::new);
+    match space_map.entry(info.agent.clone()) {
+        std::collections::hash_map::Entry::Occupied(mut e) => {
+            if info.signed_at_ms > e.get().signed_at_ms {
+                e.insert(info);
+            }
+        }
+        std::collections::hash_map::Entry::Vacant(e) => {
+            e.insert(info);
+        }
+    }
+}
+
+/// Keeps agent infos in memory. Everything is lost when the server stops.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    spaces: RwLock<SpaceMap>,
+    bans: RwLock<HashSet<Ban>>,
+}
+
+impl BootstrapStore for MemoryStore {
+    fn put(&self, info: AgentInfoSigned) -> StoreResult<()> {
+        put_into(&mut self.spaces.write(), info);
+        Ok(())
+    }
+
+    fn put_within_quota(&self, info: AgentInfoSigned, max_agents: usize) -> StoreResult<bool> {
+        let mut lock = self.spaces.write();
+        let (stored, count) = lock
+            .get(info.space.as_ref())
+            .map_or((false, 0), |space_map| {
+                (space_map.contains_key(info.agent.as_ref()), space_map.len())
+            });
+        if !stored && count >= max_agents {
+            return Ok(false);
+        }
+        put_into(&mut lock, info);
+        Ok(true)
+    }
+
+    fn random(&self, query: &RandomQuery, now_ms: u64) -> StoreResult<Vec<Vec<u8>>> {
+        // TODO: Max this limit
+        let limit = query.limit.0 as usize;
+        let mut rng = rand::thread_rng();
+        Ok(self
+            .spaces
+            .read()
+            .get(query.space.as_ref())
+            .map(|space| {
+                space
+                    .values()
+                    .filter_map(|i| {
+                        if i.expires_at_ms <= now_ms {
+                            return None;
+                        }
+                        if i.url_list.is_empty() {
+                            return None;
+                        }
+                        let mut buf = Vec::new();
+                        match rmp_encode(&mut buf, i) {
+                            Ok(_) => Some(buf),
+                            Err(_) => None,
+                        }
+                    })
+                    .choose_multiple(&mut rng, limit)
+            })
+            .unwrap_or_default())
+    }
+
+    fn prune(&self, now_ms: u64) -> StoreResult<usize> {
+        let mut pruned = 0;
+        self.spaces.write().retain(|_, map| {
+            let before = map.len();
+            map.retain(|_, info| info.expires_at_ms >= now_ms);
+            pruned += before - map.len();
+            !map.is_empty()
+        });
+        Ok(pruned)
+    }
+
+    fn clear(&self) -> StoreResult<()> {
+        self.spaces.write().clear();
+        Ok(())
+    }
+
+    fn all(&self) -> StoreResult<Vec<AgentInfoSigned>> {
+        Ok(self
+            .spaces
+            .read()
+            .values()
+            .flat_map(|space| space.values().cloned())
+            .collect())
+    }
+
+    fn ban(&self, ban: &Ban) -> StoreResult<()> {
+        self.bans.write().insert(ban.clone());
+        let mut spaces = self.spaces.write();
+        match ban {
+            Ban::Space(space) => {
+                spaces.remove(space);
+            }
+            Ban::Agent(agent) => spaces.retain(|_, map| {
+                map.remove(agent);
+                !map.is_empty()
+            }),
+        }
+        Ok(())
+    }
+
+    fn unban(&self, ban: &Ban) -> StoreResult<()> {
+        self.bans.write().remove(ban);
+        Ok(())
+    }
+
+    fn is_banned(&self, space: &KitsuneSpace, agent: &KitsuneAgent) -> StoreResult<bool> {
+        let bans = self.bans.read();
+        Ok(bans.iter().any(|ban| match ban {
+            Ban::Space(s) => s.as_ref() == space,
+            Ban::Agent(a) => a.as_ref() == agent,
+        }))
+    }
+
+    fn bans(&self) -> StoreResult<Vec<Ban>> {
+        Ok(self.bans.read().iter().cloned().collect())
+    }
+}
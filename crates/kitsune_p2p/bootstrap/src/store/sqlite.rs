@@ -0,0 +1,260 @@
+use std::{path::Path, sync::Arc};
+
+use kitsune_p2p_types::{
+    agent_info::AgentInfoSigned,
+    bin_types::{KitsuneAgent, KitsuneSpace},
+    bootstrap::RandomQuery,
+    codec::{rmp_decode, rmp_encode},
+};
+use parking_lot::Mutex;
+use rusqlite::{named_params, Connection, TransactionBehavior};
+
+use super::{BootstrapStore, StoreResult};
+use crate::protection::Ban;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS agent_info (
+        space         BLOB    NOT NULL,
+        agent         BLOB    NOT NULL,
+        signed_at_ms  INTEGER NOT NULL,
+        expires_at_ms INTEGER NOT NULL,
+        has_urls      INTEGER NOT NULL,
+        encoded       BLOB    NOT NULL,
+        PRIMARY KEY (space, agent)
+    );
+    CREATE INDEX IF NOT EXISTS agent_info_expires_at_ms ON agent_info (expires_at_ms);
+    CREATE INDEX IF NOT EXISTS agent_info_agent ON agent_info (agent);
+
+    CREATE TABLE IF NOT EXISTS ban (
+        kind TEXT NOT NULL,
+        key  BLOB NOT NULL,
+        PRIMARY KEY (kind, key)
+    );
+";
+
+/// The `(kind, key)` columns of a ban.
+fn ban_keys(ban: &Ban) -> (&'static str, &[u8]) {
+    match ban {
+        Ban::Space(space) => ("space", &space.0[..]),
+        Ban::Agent(agent) => ("agent", &agent.0[..]),
+    }
+}
+
+/// Keeps agent infos in a SQLite database, so they survive a restart.
+///
+/// The database is opened in WAL mode, so several bootstrap servers on the
+/// same host can share one database file. Don't share the file between
+/// hosts over a network filesystem, where SQLite's locking is unreliable.
+#[derive(Debug)]
+pub struct SqliteStore(Mutex<Connection>);
+
+impl SqliteStore {
+    /// Open, or create, the database at this path.
+    pub fn open(path: impl AsRef<Path>) -> StoreResult<Self> {
+        Self::init(Connection::open(path).map_err(|e| e.to_string())?)
+    }
+
+    /// Open a database which only lives as long as this store.
+    pub fn open_in_memory() -> StoreResult<Self> {
+        Self::init(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn init(conn: Connection) -> StoreResult<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| e.to_string())?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))
+            .map_err(|e| e.to_string())?;
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        Ok(Self(Mutex::new(conn)))
+    }
+}
+
+/// Store an agent info, unless a newer one is already stored.
+fn insert(conn: &Connection, info: &AgentInfoSigned) -> StoreResult<()> {
+    let mut encoded = Vec::new();
+    rmp_encode(&mut encoded, info).map_err(|e| e.to_string())?;
+    conn.execute(
+        "
+        INSERT INTO agent_info
+        (space, agent, signed_at_ms, expires_at_ms, has_urls, encoded)
+        VALUES
+        (:space, :agent, :signed_at_ms, :expires_at_ms, :has_urls, :encoded)
+        ON CONFLICT (space, agent) DO UPDATE SET
+            signed_at_ms = excluded.signed_at_ms,
+            expires_at_ms = excluded.expires_at_ms,
+            has_urls = excluded.has_urls,
+            encoded = excluded.encoded
+        WHERE excluded.signed_at_ms > agent_info.signed_at_ms
+        ",
+        named_params! {
+            ":space": &info.space.0,
+            ":agent": &info.agent.0,
+            ":signed_at_ms": info.signed_at_ms,
+            ":expires_at_ms": info.expires_at_ms,
+            ":has_urls": !info.url_list.is_empty(),
+            ":encoded": encoded,
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn decode(encoded: Vec<u8>) -> StoreResult<AgentInfoSigned> {
+    rmp_decode(&mut encoded.as_slice()).map_err(|e| e.to_string())
+}
+
+impl BootstrapStore for SqliteStore {
+    fn put(&self, info: AgentInfoSigned) -> StoreResult<()> {
+        insert(&self.0.lock(), &info)
+    }
+
+    fn put_within_quota(&self, info: AgentInfoSigned, max_agents: usize) -> StoreResult<bool> {
+        let mut conn = self.0.lock();
+        // Take the write lock up front, so other servers sharing the
+        // database can't fill the space between the count and the insert.
+        let txn = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(|e| e.to_string())?;
+        let full: bool = txn
+            .query_row(
+                "
+                SELECT NOT EXISTS(
+                    SELECT 1 FROM agent_info WHERE space = :space AND agent = :agent
+                ) AND (SELECT COUNT(*) FROM agent_info WHERE space = :space) >= :max_agents
+                ",
+                named_params! {
+                    ":space": &info.space.0,
+                    ":agent": &info.agent.0,
+                    ":max_agents": max_agents as i64,
+                },
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if full {
+            return Ok(false);
+        }
+        insert(&txn, &info)?;
+        txn.commit().map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    fn random(&self, query: &RandomQuery, now_ms: u64) -> StoreResult<Vec<Vec<u8>>> {
+        let conn = self.0.lock();
+        let mut stmt = conn
+            .prepare_cached(
+                "
+                SELECT encoded FROM agent_info
+                WHERE space = :space AND expires_at_ms > :now AND has_urls
+                ORDER BY RANDOM()
+                LIMIT :limit
+                ",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(
+                named_params! {
+                    ":space": &query.space.0,
+                    ":now": now_ms,
+                    ":limit": query.limit.0,
+                },
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    fn prune(&self, now_ms: u64) -> StoreResult<usize> {
+        self.0
+            .lock()
+            .execute(
+                "DELETE FROM agent_info WHERE expires_at_ms < :now",
+                named_params! { ":now": now_ms },
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    fn clear(&self) -> StoreResult<()> {
+        self.0
+            .lock()
+            .execute("DELETE FROM agent_info", [])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn all(&self) -> StoreResult<Vec<AgentInfoSigned>> {
+        let conn = self.0.lock();
+        let mut stmt = conn
+            .prepare("SELECT encoded FROM agent_info")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.map(|encoded| decode(encoded.map_err(|e| e.to_string())?))
+            .collect()
+    }
+
+    fn ban(&self, ban: &Ban) -> StoreResult<()> {
+        let (kind, key) = ban_keys(ban);
+        let mut conn = self.0.lock();
+        let txn = conn.transaction().map_err(|e| e.to_string())?;
+        txn.execute(
+            "INSERT OR IGNORE INTO ban (kind, key) VALUES (:kind, :key)",
+            named_params! { ":kind": kind, ":key": key },
+        )
+        .map_err(|e| e.to_string())?;
+        let delete = match ban {
+            Ban::Space(_) => "DELETE FROM agent_info WHERE space = :key",
+            Ban::Agent(_) => "DELETE FROM agent_info WHERE agent = :key",
+        };
+        txn.execute(delete, named_params! { ":key": key })
+            .map_err(|e| e.to_string())?;
+        txn.commit().map_err(|e| e.to_string())
+    }
+
+    fn unban(&self, ban: &Ban) -> StoreResult<()> {
+        let (kind, key) = ban_keys(ban);
+        self.0
+            .lock()
+            .execute(
+                "DELETE FROM ban WHERE kind = :kind AND key = :key",
+                named_params! { ":kind": kind, ":key": key },
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn is_banned(&self, space: &KitsuneSpace, agent: &KitsuneAgent) -> StoreResult<bool> {
+        self.0
+            .lock()
+            .query_row(
+                "
+                SELECT EXISTS(
+                    SELECT 1 FROM ban
+                    WHERE (kind = 'space' AND key = :space)
+                    OR (kind = 'agent' AND key = :agent)
+                )
+                ",
+                named_params! { ":space": &space.0, ":agent": &agent.0 },
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    fn bans(&self) -> StoreResult<Vec<Ban>> {
+        let conn = self.0.lock();
+        let mut stmt = conn
+            .prepare("SELECT kind, key FROM ban")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let kind: String = row.get(0)?;
+                let key: Vec<u8> = row.get(1)?;
+                Ok(match kind.as_str() {
+                    "space" => Ban::Space(Arc::new(KitsuneSpace(key))),
+                    _ => Ban::Agent(Arc::new(KitsuneAgent(key))),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+}
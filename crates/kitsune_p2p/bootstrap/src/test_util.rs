@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use kitsune_p2p_types::{
+    agent_info::AgentInfoSigned,
+    bin_types::{KitsuneAgent, KitsuneBinType, KitsuneSignature, KitsuneSpace},
+    dependencies::lair_keystore_api::dependencies::sodoken,
+};
+
+/// An agent info for a new agent, signed with that agent's key.
+pub(crate) async fn signed_info(
+    space: Arc<KitsuneSpace>,
+    signed_at_ms: u64,
+    expires_at_ms: u64,
+) -> AgentInfoSigned {
+    let pub_key = sodoken::BufWriteSized::new_no_lock();
+    let sec_key = sodoken::BufWriteSized::new_no_lock();
+    sodoken::sign::keypair(pub_key.clone(), sec_key.clone())
+        .await
+        .unwrap();
+    let agent = KitsuneAgent::new(pub_key.read_lock().to_vec());
+    let sec_key = sec_key.to_read_sized();
+    AgentInfoSigned::sign(
+        space,
+        Arc::new(agent),
+        u32::MAX / 4,
+        vec!["fake:".into()],
+        signed_at_ms,
+        expires_at_ms,
+        |data| {
+            let data = data.to_vec();
+            async move {
+                let sig = sodoken::BufWriteSized::new_no_lock();
+                sodoken::sign::detached(sig.clone(), data, sec_key)
+                    .await
+                    .unwrap();
+                let sig = sig.read_lock().to_vec();
+                Ok(Arc::new(KitsuneSignature(sig)))
+            }
+        },
+    )
+    .await
+    .unwrap()
+}
@@ -6,7 +6,7 @@ use crate::{
     spacetime::{GossipParams, Topology},
 };
 use futures::future::FutureExt;
-use std::{collections::BTreeSet, ops::Bound, sync::Arc};
+use std::{collections::BTreeSet, sync::Arc};
 
 use super::op_data::OpData;
 
@@ -36,11 +36,14 @@ impl<D: RegionDataConstraints, O: OpRegion<D>> AccessOpStore<O, D> for OpStore<O
         let region = region.to_bounds(self.topo());
         let (x0, x1) = region.x;
         let (t0, t1) = region.t;
-        let op0 = O::bound(t0, x0);
-        let op1 = O::bound(t1, x0);
+        // Host ops are not necessarily ordered by (timestamp, loc),
+        // so the bounds are checked for every op rather than using a range.
         self.ops
-            .range((Bound::Included(op0), Bound::Included(op1)))
-            .filter(|o| x0 <= o.loc() && o.loc() <= x1)
+            .iter()
+            .filter(|o| {
+                let (t, x) = (o.timestamp(), o.loc());
+                t0 <= t && t <= t1 && x0 <= x && x <= x1
+            })
             .cloned()
             .collect()
     }
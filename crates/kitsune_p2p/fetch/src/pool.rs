@@ -10,6 +10,7 @@
 //! order of last_fetch time, but they are guaranteed to be at least as old as the specified
 //! interval.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::time::{Duration, Instant};
 
@@ -74,6 +75,17 @@ pub trait FetchPoolConfig: 'static + Send + Sync {
         std::time::Duration::from_secs(5 * 60)
     }
 
+    /// How many times may a particular source be asked for a particular item?
+    /// Once a source has used up its attempts, and has had its
+    /// `source_retry_delay` to respond to the last one, it is dropped from the item.
+    /// An item with no sources left is dropped from the pool.
+    /// `None` means no limit. Sources in different spaces may have
+    /// different limits.
+    fn source_retry_limit(&self, space: &KSpace, source: &FetchSource) -> Option<u32> {
+        let _ = (space, source);
+        None
+    }
+
     /// When a fetch key is added twice, this determines how the two different contexts
     /// get reconciled.
     fn merge_fetch_contexts(&self, a: u32, b: u32) -> u32;
@@ -125,10 +137,12 @@ pub struct FetchPoolItem {
 struct SourceRecord {
     source: FetchSource,
     last_request: Option<Instant>,
+    /// How many times this source has been asked for the item
+    attempts: u32,
 }
 
 /// A source to fetch from: either a node, or an agent on a node
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum FetchSource {
     /// An agent on a node
     Agent(KAgent),
@@ -202,6 +216,55 @@ impl FetchPool {
             out
         })
     }
+
+    /// Get the pending items for these spaces, in queue order.
+    pub fn items(&self, spaces: &HashSet<KSpace>) -> Vec<FetchPoolItemInfo> {
+        self.first_items(spaces, usize::MAX)
+    }
+
+    /// Get at most `limit` of the pending items for these spaces, in queue order.
+    pub fn first_items(&self, spaces: &HashSet<KSpace>, limit: usize) -> Vec<FetchPoolItemInfo> {
+        self.state.share_ref(|s| {
+            s.queue
+                .iter()
+                .filter(|(_, v)| spaces.contains(&v.space))
+                .take(limit)
+                .map(|(key, v)| v.info(key.clone()))
+                .collect()
+        })
+    }
+
+    /// Put back items previously obtained from [`FetchPool::items`],
+    /// e.g. after a restart. Items which are already in the pool are
+    /// left as they are, since they are more up to date.
+    pub fn restore(&self, items: Vec<FetchPoolItemInfo>) {
+        self.state.share_mut(|s| {
+            for info in items {
+                if let Entry::Vacant(e) = s.queue.entry(info.key) {
+                    e.insert(FetchPoolItem {
+                        sources: Sources(
+                            info.sources
+                                .into_iter()
+                                .map(|s| SourceRecord {
+                                    source: s.source,
+                                    last_request: s.last_request_ms_ago.map(|ms| {
+                                        let now = Instant::now();
+                                        now.checked_sub(Duration::from_millis(ms)).unwrap_or(now)
+                                    }),
+                                    attempts: s.attempts,
+                                })
+                                .collect(),
+                        ),
+                        space: info.space,
+                        size: info.size,
+                        context: info.context,
+                        last_fetch: None,
+                    });
+                }
+            }
+            tracing::debug!("FetchPool (size = {}) items restored", s.queue.len());
+        })
+    }
 }
 
 impl State {
@@ -276,6 +339,12 @@ impl<'a> Iterator for StateIter<'a> {
             .collect();
         for key in keys {
             let item = self.state.queue.get_refresh(&key)?;
+            item.sources.drop_exhausted(&item.space, self.config);
+            if item.sources.0.is_empty() {
+                tracing::debug!(?key, "FetchPool item dropped, no sources left");
+                self.state.queue.remove(&key);
+                continue;
+            }
             let item_not_recently_fetched = item
                 .last_fetch
                 .map(|t| t.elapsed() >= self.config.item_retry_delay())
@@ -292,11 +361,35 @@ impl<'a> Iterator for StateIter<'a> {
     }
 }
 
+impl FetchPoolItem {
+    fn info(&self, key: FetchKey) -> FetchPoolItemInfo {
+        FetchPoolItemInfo {
+            key,
+            space: self.space.clone(),
+            size: self.size,
+            context: self.context,
+            sources: self
+                .sources
+                .0
+                .iter()
+                .map(|s| FetchSourceInfo {
+                    source: s.source.clone(),
+                    attempts: s.attempts,
+                    last_request_ms_ago: s
+                        .last_request
+                        .map(|t| t.elapsed().as_millis().min(u64::MAX as u128) as u64),
+                })
+                .collect(),
+        }
+    }
+}
+
 impl SourceRecord {
     fn new(source: FetchSource) -> Self {
         Self {
             source,
             last_request: None,
+            attempts: 0,
         }
     }
 
@@ -304,6 +397,7 @@ impl SourceRecord {
         Self {
             source: FetchSource::Agent(agent),
             last_request: None,
+            attempts: 0,
         }
     }
 }
@@ -322,12 +416,27 @@ impl Sources {
             .map(|(i, s)| (i, s.source.clone()))
         {
             self.0[i].last_request = Some(Instant::now());
+            self.0[i].attempts += 1;
             self.0.rotate_left(i + 1);
             Some(agent)
         } else {
             None
         }
     }
+
+    /// Drop the sources which have used up their attempts and have had
+    /// long enough to respond to the last one.
+    fn drop_exhausted(&mut self, space: &KSpace, config: &dyn FetchPoolConfig) {
+        let interval = config.source_retry_delay();
+        self.0
+            .retain(|s| match config.source_retry_limit(space, &s.source) {
+                Some(limit) if s.attempts >= limit => s
+                    .last_request
+                    .map(|t| t.elapsed() < interval)
+                    .unwrap_or(false),
+                _ => true,
+            });
+    }
 }
 
 #[cfg(test)]
@@ -408,11 +517,13 @@ mod tests {
             SourceRecord {
                 source: source(1),
                 last_request: Some(Instant::now()),
+                attempts: 1,
             }
             .into(),
             SourceRecord {
                 source: source(2),
                 last_request: None,
+                attempts: 0,
             }
             .into(),
         ]);
@@ -440,6 +551,112 @@ mod tests {
         assert_eq!(ss.next(sec1), None);
     }
 
+    struct LimitConfig;
+
+    impl FetchPoolConfig for LimitConfig {
+        fn merge_fetch_contexts(&self, a: u32, b: u32) -> u32 {
+            a | b
+        }
+
+        fn item_retry_delay(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+
+        fn source_retry_delay(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+
+        fn source_retry_limit(&self, _: &KSpace, s: &FetchSource) -> Option<u32> {
+            if *s == source(1) {
+                Some(1)
+            } else {
+                Some(2)
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sources_are_dropped_after_retry_limit() {
+        let cfg = LimitConfig;
+        let mut q = State::default();
+        q.push(&cfg, req(1, None, source(2)));
+        q.push(&cfg, req(1, None, source(1)));
+
+        let fetched = |q: &mut State| {
+            q.iter_mut(&cfg)
+                .map(|(_, _, source, _)| source)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fetched(&mut q), vec![source(1)]);
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(fetched(&mut q), vec![source(2)]);
+
+        // Source 1 has used its only attempt, and had long enough to respond.
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(fetched(&mut q), vec![source(2)]);
+        assert_eq!(q.queue[&key_op(1)].sources.0.len(), 1);
+
+        // Source 2 has now used both of its attempts, so the item is dropped.
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(fetched(&mut q), vec![]);
+        assert!(q.queue.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn items_can_be_restored() {
+        let pool = FetchPool::new(Arc::new(Config(1, 10)));
+        pool.push(req(1, ctx(1), source(1)));
+        pool.push(req(2, None, source(2)));
+        pool.push(FetchPoolPush {
+            space: space(1),
+            ..req(3, None, source(3))
+        });
+        assert_eq!(pool.get_items_to_fetch().len(), 3);
+
+        let items = pool.items(&[space(0)].into_iter().collect());
+        assert_eq!(
+            items.iter().map(|i| i.key.clone()).collect::<Vec<_>>(),
+            vec![key_op(1), key_op(2)]
+        );
+        assert_eq!(
+            pool.first_items(&[space(0)].into_iter().collect(), 1),
+            items[..1]
+        );
+        assert_eq!(items[0].sources[0].attempts, 1);
+        assert_eq!(items[0].sources[0].last_request_ms_ago, Some(0));
+
+        let restored = FetchPool::new(Arc::new(Config(1, 10)));
+        restored.push(req(2, None, source(4)));
+        restored.restore(items.clone());
+
+        let restored_items = restored.items(&[space(0)].into_iter().collect());
+        // The item which was already pending is kept as it was.
+        assert_eq!(restored_items[0].key, key_op(2));
+        assert_eq!(restored_items[0].sources[0].source, source(4));
+        assert_eq!(restored_items[1], items[0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn restored_sources_keep_their_retry_delay() {
+        let pool = FetchPool::new(Arc::new(LimitConfig));
+        pool.push(req(1, None, source(1)));
+        assert_eq!(pool.get_items_to_fetch().len(), 1);
+        let items = pool.items(&[space(0)].into_iter().collect());
+
+        let restored = FetchPool::new(Arc::new(LimitConfig));
+        restored.restore(items);
+
+        // Source 1 has used its only attempt, but still has time to respond.
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(restored.get_items_to_fetch().is_empty());
+        assert_eq!(restored.items(&[space(0)].into_iter().collect()).len(), 1);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(restored.get_items_to_fetch().is_empty());
+        assert!(restored.items(&[space(0)].into_iter().collect()).is_empty());
+    }
+
     #[test]
     fn queue_push() {
         let mut q = State::default();
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use kitsune_p2p_types::KSpace;
 
-use crate::FetchPool;
+use crate::{FetchContext, FetchKey, FetchPool, FetchSource, RoughInt};
 
 /// Read-only access to the queue
 #[derive(Clone, derive_more::From)]
@@ -23,6 +23,11 @@ impl FetchPoolReader {
             num_ops_to_fetch: count,
         }
     }
+
+    /// Get at most `limit` of the pending items, filtered by space
+    pub fn items(&self, spaces: HashSet<KSpace>, limit: usize) -> Vec<FetchPoolItemInfo> {
+        self.0.first_items(&spaces, limit)
+    }
 }
 
 /// Info about the fetch queue
@@ -35,6 +40,41 @@ pub struct FetchPoolInfo {
     pub num_ops_to_fetch: usize,
 }
 
+/// A pending item in the fetch queue
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FetchPoolItemInfo {
+    /// What is being fetched
+    pub key: FetchKey,
+
+    /// The space the item belongs to
+    pub space: KSpace,
+
+    /// The approximate size of the item, if known
+    pub size: Option<RoughInt>,
+
+    /// Opaque user data specified by the host
+    pub context: Option<FetchContext>,
+
+    /// The sources the item can be fetched from, in the order they will be tried
+    pub sources: Vec<FetchSourceInfo>,
+}
+
+/// A source of a pending item in the fetch queue
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FetchSourceInfo {
+    /// The source
+    pub source: FetchSource,
+
+    /// How many times this source has been asked for the item
+    pub attempts: u32,
+
+    /// How long ago, in milliseconds, this source was last asked for the
+    /// item. A restored source keeps what was left of its retry delay,
+    /// counted from when it is restored.
+    #[serde(default)]
+    pub last_request_ms_ago: Option<u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
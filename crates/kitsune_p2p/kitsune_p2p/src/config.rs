@@ -24,7 +24,14 @@ pub(crate) enum KitsuneP2pTx2Backend {
 #[cfg(feature = "tx2")]
 pub(crate) enum KitsuneP2pTx2ProxyConfig {
     NoProxy,
-    Specific(TxUrl),
+    Specific {
+        proxy_url: TxUrl,
+        auth_token: Option<String>,
+    },
+    List {
+        proxy_urls: Vec<TxUrl>,
+        auth_token: Option<String>,
+    },
     Bootstrap {
         #[allow(dead_code)]
         bootstrap_url: TxUrl,
@@ -38,6 +45,13 @@ pub(crate) struct KitsuneP2pTx2Config {
     pub use_proxy: KitsuneP2pTx2ProxyConfig,
 }
 
+#[cfg(feature = "tx5")]
+pub(crate) struct KitsuneP2pTx5Config {
+    /// The signal servers to try, in order.
+    pub signal_urls: Vec<String>,
+    pub ice_servers: Vec<IceServer>,
+}
+
 /// Configure the kitsune actor.
 #[non_exhaustive]
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -129,9 +143,17 @@ impl KitsuneP2pConfig {
                     _ => return Err("kitsune tx2 backend must be mem or quic".into()),
                 };
                 let use_proxy = match proxy_config {
-                    ProxyConfig::RemoteProxyClient { proxy_url } => {
-                        Specific(proxy_url.clone().into())
-                    }
+                    ProxyConfig::RemoteProxyClient { proxy_url } => Specific {
+                        proxy_url: proxy_url.clone().into(),
+                        auth_token: None,
+                    },
+                    ProxyConfig::RemoteProxyClientList {
+                        proxy_urls,
+                        auth_token,
+                    } => List {
+                        proxy_urls: proxy_urls.iter().cloned().map(Into::into).collect(),
+                        auth_token: auth_token.clone(),
+                    },
                     ProxyConfig::RemoteProxyClientFromBootstrap {
                         bootstrap_url,
                         fallback_proxy_url,
@@ -167,6 +189,25 @@ impl KitsuneP2pConfig {
         }
     }
 
+    /// Get the signal servers and ICE servers from a WebRTC transport.
+    #[cfg(feature = "tx5")]
+    pub(crate) fn to_tx5(&self) -> KitsuneResult<KitsuneP2pTx5Config> {
+        match self.transport_pool.get(0) {
+            Some(TransportConfig::WebRTC {
+                signal_url,
+                fallback_signal_urls,
+                ice_servers,
+            }) => Ok(KitsuneP2pTx5Config {
+                signal_urls: std::iter::once(signal_url)
+                    .chain(fallback_signal_urls)
+                    .cloned()
+                    .collect(),
+                ice_servers: ice_servers.clone(),
+            }),
+            _ => Err("Cannot convert non-WebRTC config into tx5".into()),
+        }
+    }
+
     /// Return a copy with the tuning params altered
     pub fn tune(
         mut self,
@@ -181,6 +222,7 @@ impl KitsuneP2pConfig {
 }
 
 /// Configure the network bindings for underlying kitsune transports.
+/// Every space joined through a kitsune instance shares its transports.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransportConfig {
@@ -231,9 +273,37 @@ pub enum TransportConfig {
     WebRTC {
         /// The url of the signal server to connect to for addressability.
         signal_url: String,
+
+        /// Signal servers to try, in order, if `signal_url` can't be reached.
+        /// Default: empty = only use `signal_url`
+        #[serde(default)]
+        fallback_signal_urls: Vec<String>,
+
+        /// STUN and TURN servers used for NAT traversal. If a direct
+        /// connection can't be made, data is relayed through a TURN server.
+        /// Default: empty = use the servers advertised by the signal server
+        #[serde(default)]
+        ice_servers: Vec<IceServer>,
     },
 }
 
+/// A STUN or TURN server used to set up WebRTC connections.
+#[cfg(feature = "tx5")]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct IceServer {
+    /// The urls of the server, e.g. "stun:stun.example.com:3478"
+    /// or "turn:turn.example.com:3478?transport=udp".
+    pub urls: Vec<String>,
+
+    /// The username to authenticate with a TURN server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// The credential to authenticate with a TURN server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
 #[cfg(feature = "tx2")]
 #[derive(Clone)]
 /// A simple wrapper around the [`AdaptorFactory`](tx2::tx2_adapter::AdapterFactory)
@@ -272,6 +342,19 @@ pub enum ProxyConfig {
         proxy_url: Url2,
     },
 
+    /// We want to be hosted at one of a list of remote proxies.
+    /// The first reachable proxy is used, and if it goes away
+    /// we fail over to the next one.
+    /// A list of one proxy can be used to present a token to a single proxy.
+    RemoteProxyClientList {
+        /// The remote proxy urls to be hosted at, in order of preference
+        proxy_urls: Vec<Url2>,
+
+        /// The token to present to proxies which require one
+        #[serde(default)]
+        auth_token: Option<String>,
+    },
+
     /// We want to be hosted at a remote proxy location.
     /// We'd like to fetch a proxy list from a bootstrap server,
     /// with an optional fallback to a specific proxy.
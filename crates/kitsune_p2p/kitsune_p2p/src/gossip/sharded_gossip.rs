@@ -33,6 +33,7 @@ pub use self::bandwidth::BandwidthThrottle;
 use self::ops::OpsBatchQueue;
 use self::state_map::RoundStateMap;
 use crate::metrics::MetricsSync;
+use crate::metrics::RoundTraffic;
 
 use super::{HowToConnect, MetaOpKey};
 
@@ -54,6 +55,7 @@ pub mod test_utils;
 mod accept;
 mod agents;
 mod bloom;
+mod iblt;
 mod initiate;
 mod ops;
 mod state_map;
@@ -95,6 +97,26 @@ struct TimedBloomFilter {
     time: TimeWindow,
 }
 
+/// The ways two nodes can find which op hashes they are missing
+/// from each other during recent gossip.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpReconciliation {
+    /// Send bloom filters of the op hashes held.
+    /// These have a 1% false positive rate so some missing ops are
+    /// not found until a later round.
+    Bloom,
+    /// Send invertible bloom lookup tables of the op hashes held.
+    /// These find the exact difference, as long as it is small enough
+    /// for the table to decode.
+    Iblt,
+}
+
+impl Default for OpReconciliation {
+    fn default() -> Self {
+        Self::Bloom
+    }
+}
+
 /// Gossip has two distinct variants which share a lot of similarities but
 /// are fundamentally different and serve different purposes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -248,8 +270,15 @@ impl ShardedGossip {
             }
         };
 
-        let gossip = gossip.encode_vec().map_err(KitsuneError::other)?;
-        let bytes = gossip.len();
+        let encoded = gossip.encode_vec().map_err(KitsuneError::other)?;
+        let bytes = encoded.len();
+        // An initiate is sent before its round exists,
+        // so it would be counted against the previous round.
+        if !matches!(gossip, ShardedGossipWire::Initiate(_)) {
+            self.gossip
+                .record_round_traffic(&cert, true, &RoundTraffic::message(&gossip, bytes));
+        }
+        let gossip = encoded;
         let gossip = wire::Wire::gossip(
             self.gossip.space.clone(),
             gossip.into(),
@@ -308,6 +337,7 @@ impl ShardedGossip {
                 .to_string()
                 .replace("ShardedGossipWire::", "");
             let len = msg.encode_vec().expect("can't encode msg").len();
+            let traffic = RoundTraffic::message(&msg, len);
             let outgoing = match self.gossip.process_incoming(con.peer_id(), msg).await {
                 Ok(r) => {
                     tracing::debug!(
@@ -326,10 +356,16 @@ impl ShardedGossip {
                 }
                 Err(e) => {
                     tracing::error!("FAILED to process incoming gossip {:?}", e);
+                    self.gossip
+                        .record_round_error(&con.peer_id(), format!("Failed to process: {}", e));
                     self.gossip.remove_state(&con.peer_id(), true)?;
                     vec![ShardedGossipWire::error(e.to_string())]
                 }
             };
+            // Recorded after processing so an incoming initiate
+            // is counted against the round it starts.
+            self.gossip
+                .record_round_traffic(&con.peer_id(), false, &traffic);
             self.state.share_mut(|i, _| {
                 i.push_outgoing(outgoing.into_iter().map(|msg| {
                     (
@@ -344,6 +380,8 @@ impl ShardedGossip {
         if let Some(outgoing) = outgoing {
             let cert = outgoing.0.clone();
             if let Err(err) = self.process_outgoing(outgoing).await {
+                self.gossip
+                    .record_round_error(&cert, format!("Failed to send: {}", err));
                 self.gossip.remove_state(&cert, true)?;
                 tracing::error!(
                     "Gossip failed to send outgoing message because of: {:?}",
@@ -625,7 +663,7 @@ pub struct RoundState {
     /// The remote agents hosted by the remote node, used for metrics tracking
     pub(crate) remote_agent_list: Vec<AgentInfoSigned>,
     /// The common ground with our gossip partner for the purposes of this round
-    common_arc_set: Arc<DhtArcSet>,
+    pub(crate) common_arc_set: Arc<DhtArcSet>,
     /// We've received the last op bloom filter from our partner
     /// (the one with `finished` == true)
     received_all_incoming_op_blooms: bool,
@@ -649,11 +687,18 @@ pub struct RoundState {
     round_timeout: std::time::Duration,
     /// The RegionSet we will send to our gossip partner during Historical
     /// gossip (will be None for Recent).
-    region_set_sent: Option<Arc<RegionSetLtcs>>,
+    pub(crate) region_set_sent: Option<Arc<RegionSetLtcs>>,
     /// Region diffs, if doing Historical gossip
     pub(crate) region_diffs: RegionDiffs,
     /// Unique string ID for this round
     pub(crate) id: String,
+    /// Number of bloom filters we have asked our partner for in place of
+    /// IBLTs we couldn't decode, which we are still waiting for.
+    num_requested_fallback_blooms: u16,
+    /// How op hashes are reconciled this round.
+    pub(crate) reconciliation: OpReconciliation,
+    /// We initiated this round, rather than accepting it.
+    pub(crate) initiated: bool,
 }
 
 /// Our region diff and their region diff
@@ -675,12 +720,15 @@ impl RoundState {
             regions_are_queued: false,
             bloom_batch_cursor: None,
             num_expected_op_blooms: 0,
+            num_requested_fallback_blooms: 0,
             ops_batch_queue: OpsBatchQueue::new(),
             id: nanoid::nanoid!(),
             last_touch: Instant::now(),
             round_timeout,
             region_set_sent,
             region_diffs: Default::default(),
+            reconciliation: Default::default(),
+            initiated: false,
         }
     }
 }
@@ -705,6 +753,25 @@ impl ShardedGossipLocal {
     /// The number of bloom filters we want to send in a single gossip iteration.
     const UPPER_BLOOM_BOUND: usize = 10;
 
+    /// The ways this node can reconcile op hashes, in order of preference.
+    fn supported_reconciliation(&self) -> Vec<OpReconciliation> {
+        match self.gossip_type {
+            GossipType::Recent if self.tuning_params.gossip_iblt_reconciliation => {
+                vec![OpReconciliation::Iblt, OpReconciliation::Bloom]
+            }
+            _ => vec![OpReconciliation::Bloom],
+        }
+    }
+
+    /// Choose our most preferred way to reconcile op hashes
+    /// which the remote node also supports.
+    fn choose_reconciliation(&self, remote: &[OpReconciliation]) -> OpReconciliation {
+        self.supported_reconciliation()
+            .into_iter()
+            .find(|r| remote.contains(r))
+            .unwrap_or_default()
+    }
+
     /// Calculate the time range for a gossip round.
     fn calculate_time_range(&self) -> TimeWindow {
         const NOW: Duration = Duration::from_secs(0);
@@ -751,6 +818,26 @@ impl ShardedGossipLocal {
             .share_mut(|i, _| Ok(i.remove_state(id, self.gossip_type, error)))
     }
 
+    /// Record gossip sent to or received from a remote node in the round history.
+    fn record_round_traffic(&self, id: &StateKey, sent: bool, traffic: &RoundTraffic) {
+        self.inner
+            .share_ref(|i| {
+                i.metrics.write().record_round_traffic(id, sent, traffic);
+                Ok(())
+            })
+            .ok();
+    }
+
+    /// Record why the current round with a remote node failed.
+    fn record_round_error(&self, id: &StateKey, message: String) {
+        self.inner
+            .share_ref(|i| {
+                i.metrics.write().record_round_error(id, message);
+                Ok(())
+            })
+            .ok();
+    }
+
     fn remove_target(&self, id: &StateKey, error: bool) -> KitsuneResult<()> {
         self.inner.share_mut(|i, _| {
             if i.initiate_tgt
@@ -804,6 +891,36 @@ impl ShardedGossipLocal {
         })
     }
 
+    /// We asked for a bloom filter in place of an IBLT we couldn't decode,
+    /// so the round isn't finished until it arrives.
+    fn request_fallback_bloom(&self, state_id: &StateKey) -> KitsuneResult<()> {
+        self.inner.share_mut(|i, _| {
+            if let Some(state) = i.round_map.get_mut(state_id) {
+                state.num_requested_fallback_blooms += 1;
+            }
+            Ok(())
+        })
+    }
+
+    /// A bloom filter we asked for in place of an IBLT has been received.
+    fn received_fallback_bloom(&self, state_id: &StateKey) -> KitsuneResult<()> {
+        self.inner.share_mut(|i, _| {
+            let finished = i
+                .round_map
+                .get_mut(state_id)
+                .map(|state| {
+                    state.num_requested_fallback_blooms =
+                        state.num_requested_fallback_blooms.saturating_sub(1);
+                    state.is_finished()
+                })
+                .unwrap_or(false);
+            if finished {
+                i.remove_state(state_id, self.gossip_type, false);
+            }
+            Ok(())
+        })
+    }
+
     fn decrement_op_blooms(&self, state_id: &StateKey) -> KitsuneResult<Option<RoundState>> {
         self.inner.share_mut(|i, _| {
             let remove_state = |state: &mut RoundState| {
@@ -856,15 +973,17 @@ impl ShardedGossipLocal {
                 intervals,
                 id,
                 agent_list,
+                reconciliation,
             }) => {
-                self.incoming_initiate(peer_cert, intervals, id, agent_list)
+                self.incoming_initiate(peer_cert, intervals, id, agent_list, reconciliation)
                     .await?
             }
             ShardedGossipWire::Accept(Accept {
                 intervals,
                 agent_list,
+                reconciliation,
             }) => {
-                self.incoming_accept(peer_cert, intervals, agent_list)
+                self.incoming_accept(peer_cert, intervals, agent_list, reconciliation)
                     .await?
             }
             ShardedGossipWire::Agents(Agents { filter }) => {
@@ -885,12 +1004,16 @@ impl ShardedGossipLocal {
                 missing_hashes,
                 finished,
             }) => {
-                let state = if finished {
+                // A table is checked before the round can finish, because if it
+                // can't be decoded the round waits for a bloom filter instead.
+                let finish_after =
+                    finished && matches!(missing_hashes, EncodedTimedBloomFilter::HaveIblt { .. });
+                let state = if finished && !finish_after {
                     self.incoming_op_blooms_finished(&peer_cert)?
                 } else {
                     self.get_state(&peer_cert)?
                 };
-                match state {
+                let gossip = match state {
                     Some(state) => match missing_hashes {
                         EncodedTimedBloomFilter::NoOverlap => Vec::with_capacity(0),
                         EncodedTimedBloomFilter::MissingAllHashes { time_window } => {
@@ -904,15 +1027,34 @@ impl ShardedGossipLocal {
                             filter,
                             time_window,
                         } => {
+                            // Bloom filters are only sent in rounds reconciled
+                            // with IBLTs when we asked for one.
+                            let is_fallback = state.reconciliation == OpReconciliation::Iblt;
                             let filter = TimedBloomFilter {
                                 bloom: Some(decode_bloom_filter(&filter)),
                                 time: time_window,
                             };
-                            self.incoming_op_bloom(state, filter, None).await?
+                            let gossip = self.incoming_op_bloom(state, filter, None).await?;
+                            if is_fallback {
+                                self.received_fallback_bloom(&peer_cert)?;
+                            }
+                            gossip
+                        }
+                        EncodedTimedBloomFilter::HaveIblt { table, time_window } => {
+                            let table = iblt::Iblt::from_bytes(&table)?;
+                            self.incoming_op_iblt(&peer_cert, state, table, time_window)
+                                .await?
+                        }
+                        EncodedTimedBloomFilter::IbltUndecodable { time_window } => {
+                            self.incoming_iblt_undecodable(state, time_window).await?
                         }
                     },
                     None => Vec::with_capacity(0),
+                };
+                if finish_after {
+                    self.incoming_op_blooms_finished(&peer_cert)?;
                 }
+                gossip
             }
             ShardedGossipWire::OpRegions(OpRegions { region_set }) => {
                 if let Some(state) = self.incoming_op_blooms_finished(&peer_cert)? {
@@ -1000,6 +1142,7 @@ impl ShardedGossipLocal {
             },
             ShardedGossipWire::NoAgents(_) => {
                 tracing::warn!("No agents to gossip with on the node {:?}", peer_cert);
+                self.record_round_error(&peer_cert, "Remote node has no agents".to_string());
                 self.remove_state(&peer_cert, true)?;
                 Vec::with_capacity(0)
             }
@@ -1014,6 +1157,7 @@ impl ShardedGossipLocal {
             }
             ShardedGossipWire::Error(Error { message }) => {
                 tracing::warn!("gossiping with: {:?} and got error: {}", peer_cert, message);
+                self.record_round_error(&peer_cert, format!("Remote error: {}", message));
                 self.remove_state(&peer_cert, true)?;
                 Vec::with_capacity(0)
             }
@@ -1044,6 +1188,8 @@ impl ShardedGossipLocal {
                     tracing::warn!("The node {:?} has timed out their gossip round", cert);
                     let mut metrics = i.metrics.write();
                     metrics.record_error(&r.remote_agent_list, self.gossip_type.into());
+                    metrics.record_timeout_event(true, &r.remote_agent_list);
+                    metrics.record_round_error(&cert, "Round timed out".to_string());
                     metrics.complete_current_round(&cert, true);
                 }
                 Ok(())
@@ -1078,9 +1224,11 @@ impl RoundState {
     /// - This node has received all the ops blooms from the remote node.
     /// - This node has no saved ops bloom batch cursor.
     /// - This node has no queued missing ops to send to the remote node.
+    /// - This node isn't waiting for bloom filters in place of IBLTs it couldn't decode.
     /// - If running historical gossip, the number of ops sent/received matches expectations
     fn is_finished(&self) -> bool {
         self.num_expected_op_blooms == 0
+            && self.num_requested_fallback_blooms == 0
             && !self.has_pending_historical_op_data
             && self.received_all_incoming_op_blooms
             && self.regions_are_queued
@@ -1129,13 +1277,30 @@ pub enum EncodedTimedBloomFilter {
         /// The time window these hashes are for.
         time_window: TimeWindow,
     },
+    /// I have overlap and I have some hashes.
+    /// Please send any ops missing from this invertible bloom lookup table.
+    /// Only sent to nodes which have agreed to [`OpReconciliation::Iblt`].
+    HaveIblt {
+        /// The encoded table.
+        table: PoolBuf,
+        /// The time window these hashes are for.
+        time_window: TimeWindow,
+    },
+    /// Your table for this time window was too different to decode.
+    /// Please send a bloom filter of your hashes in it instead.
+    /// Only sent in reply to [`EncodedTimedBloomFilter::HaveIblt`].
+    IbltUndecodable {
+        /// The time window of the table.
+        time_window: TimeWindow,
+    },
 }
 
 impl EncodedTimedBloomFilter {
-    /// Get the size in bytes of the bloom filter, if one exists
+    /// Get the size in bytes of the bloom filter or table, if one exists
     pub fn size(&self) -> usize {
         match self {
             Self::HaveHashes { filter, .. } => filter.len(),
+            Self::HaveIblt { table, .. } => table.len(),
             _ => 0,
         }
     }
@@ -1169,6 +1334,10 @@ kitsune_p2p_types::write_codec_enum! {
             id.1: u32,
             /// List of active local agents represented by this node.
             agent_list.2: Vec<AgentInfoSigned>,
+        } ext {
+            /// The ways this node can reconcile op hashes, in order of preference.
+            /// Empty if this node only supports bloom filters.
+            reconciliation.0: Vec<OpReconciliation>,
         },
 
         /// Accept an incoming round of gossip from a remote node
@@ -1178,6 +1347,11 @@ kitsune_p2p_types::write_codec_enum! {
             intervals.0: Vec<DhtArcRange>,
             /// List of active local agents represented by this node.
             agent_list.1: Vec<AgentInfoSigned>,
+        } ext {
+            /// The way op hashes are reconciled this round, chosen from
+            /// those in the [`Initiate`]. None if this node only supports
+            /// bloom filters.
+            reconciliation.0: Option<OpReconciliation>,
         },
 
         /// Send Agent Info Bloom
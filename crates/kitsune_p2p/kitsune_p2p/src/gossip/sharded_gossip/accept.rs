@@ -4,11 +4,14 @@ impl ShardedGossipLocal {
     /// Incoming accept gossip round message.
     /// - Send back the agent bloom and ops bloom gossip messages.
     /// - Only send the agent bloom if this is a recent gossip type.
+    /// - Reconcile op hashes the way the remote chose, falling back to
+    ///   bloom filters if the remote didn't choose or chose a way we don't support.
     pub(super) async fn incoming_accept(
         &self,
         peer_cert: Arc<[u8; 32]>,
         remote_arc_set: Vec<DhtArcRange>,
         remote_agent_list: Vec<AgentInfoSigned>,
+        reconciliation: Option<OpReconciliation>,
     ) -> KitsuneResult<Vec<ShardedGossipWire>> {
         let (local_agents, when_initiated, accept_is_from_target) =
             self.inner.share_mut(|i, _| {
@@ -54,17 +57,23 @@ impl ShardedGossipLocal {
                 .map(|(_, a)| a.into())
                 .collect();
 
+        let reconciliation = reconciliation
+            .filter(|r| self.supported_reconciliation().contains(r))
+            .unwrap_or_default();
+
         let mut gossip = Vec::new();
 
         // Generate the bloom filters and new state.
-        let state = self
+        let mut state = self
             .generate_blooms_or_regions(
                 remote_agent_list.clone(),
                 local_agent_arcs,
                 remote_arc_set,
+                reconciliation,
                 &mut gossip,
             )
             .await?;
+        state.initiated = true;
 
         self.inner.share_mut(|inner, _| {
             // TODO: What happen if we are in the middle of a new outgoing and
@@ -51,6 +51,46 @@ impl ShardedGossipLocal {
         common_arc_set: &Arc<DhtArcSet>,
         search_time_window: TimeWindow,
     ) -> KitsuneResult<Batch<TimedBloomFilter>> {
+        let batch = self
+            .generate_op_filters_for_time_window(
+                common_arc_set,
+                search_time_window,
+                |hashes| async move {
+                    let mut bloom = bloomfilter::Bloom::new_for_fp_rate(hashes.len(), Self::TGT_FP);
+
+                    let mut iter = hashes.into_iter().peekable();
+
+                    while iter.peek().is_some() {
+                        for hash in iter.by_ref().take(100) {
+                            bloom.set(&MetaOpKey::Op(hash));
+                        }
+                        // Yield to the conductor every 100 hashes. Because tasks have
+                        // polling budgets this gives the runtime a chance to schedule other
+                        // tasks so they don't starve.
+                        tokio::task::yield_now().await;
+                    }
+                    bloom
+                },
+            )
+            .await?;
+        Ok(batch.map(|(time, bloom)| TimedBloomFilter { bloom, time }))
+    }
+
+    /// Generate a filter of all ops for each chunk of the search time window,
+    /// using `build` to create each filter from the chunk's hashes.
+    /// - The filter is `None` for chunks with no hashes.
+    /// - A batch has at most [`Self::UPPER_BLOOM_BOUND`] filters. If there are
+    ///   more chunks then a partial batch is returned with the cursor to continue from.
+    pub(super) async fn generate_op_filters_for_time_window<T, F, Fut>(
+        &self,
+        common_arc_set: &Arc<DhtArcSet>,
+        search_time_window: TimeWindow,
+        build: F,
+    ) -> KitsuneResult<Batch<(TimeWindow, Option<T>)>>
+    where
+        F: Fn(Vec<Arc<KitsuneOpHash>>) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
         use futures::TryStreamExt;
 
         // If the common arc set is empty there's no
@@ -61,6 +101,7 @@ impl ShardedGossipLocal {
 
         let mut total_blooms = 0;
         let search_end = search_time_window.end;
+        let build = &build;
 
         let stream = store::hash_chunks_query(
             self.evt_sender.clone(),
@@ -76,7 +117,7 @@ impl ShardedGossipLocal {
                 total_blooms += 1;
                 futures::future::ready(Ok(total_blooms <= Self::UPPER_BLOOM_BOUND))
             })
-            // Fold the chunks into a batch of [`TimedBloomFilter`].
+            // Fold the chunks into a batch of filters.
             .try_fold(
                 // Start with a partial batch where the cursor is
                 // set to the end of the time window.
@@ -97,39 +138,18 @@ impl ShardedGossipLocal {
                         let complete = search_end == window.end;
 
                         // If there were no hashes found then create an
-                        // empty bloom filter for this time window.
-
-                        let bloom = if hashes.is_empty() {
-                            TimedBloomFilter {
-                                bloom: None,
-                                time: window,
-                            }
+                        // empty filter for this time window,
+                        // otherwise create the filter from the hashes.
+                        let filter = if hashes.is_empty() {
+                            None
                         } else {
-                            // Otherwise create the bloom filter from the hashes.
-                            let mut bloom =
-                                bloomfilter::Bloom::new_for_fp_rate(hashes.len(), Self::TGT_FP);
-
-                            let mut iter = hashes.into_iter().peekable();
-
-                            while iter.peek().is_some() {
-                                for hash in iter.by_ref().take(100) {
-                                    bloom.set(&MetaOpKey::Op(hash));
-                                }
-                                // Yield to the conductor every 100 hashes. Because tasks have
-                                // polling budgets this gives the runtime a chance to schedule other
-                                // tasks so they don't starve.
-                                tokio::task::yield_now().await;
-                            }
-                            TimedBloomFilter {
-                                bloom: Some(bloom),
-                                time: window,
-                            }
+                            Some(build(hashes).await)
                         };
                         match batch {
                             Batch::Partial { mut data, .. } | Batch::Complete(mut data) => {
-                                // Add this bloom to the batch and set it to complete
-                                // if this is the final bloom.
-                                data.push(bloom);
+                                // Add this filter to the batch and set it to complete
+                                // if this is the final filter.
+                                data.push((window, filter));
                                 if complete {
                                     Ok(Batch::Complete(data))
                                 } else {
@@ -249,3 +269,16 @@ pub(super) enum Batch<T> {
     Complete(Vec<T>),
     Partial { cursor: Timestamp, data: Vec<T> },
 }
+
+impl<T> Batch<T> {
+    /// Map each item of this batch, keeping the cursor.
+    pub(super) fn map<U>(self, f: impl FnMut(T) -> U) -> Batch<U> {
+        match self {
+            Batch::Complete(data) => Batch::Complete(data.into_iter().map(f).collect()),
+            Batch::Partial { cursor, data } => Batch::Partial {
+                cursor,
+                data: data.into_iter().map(f).collect(),
+            },
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Invertible bloom lookup tables (IBLT) of op hashes.
+//!
+//! Unlike a bloom filter, one table can be subtracted from another and the
+//! result decoded to find exactly which hashes are only in one of the two sets,
+//! as long as that difference is small compared to the size of the table.
+
+use crate::gossip::sharded_gossip::store::TimeChunk;
+
+use super::*;
+
+/// The number of cells each key is added to.
+/// The cells are split into this many partitions and each key
+/// is added to one cell in each partition.
+const NUM_HASHES: usize = 3;
+
+/// The smallest table that will be created.
+const MIN_CELLS: usize = 30;
+
+/// The fraction of a set of hashes which is expected to be
+/// different from the remote node's set. Tables are sized to
+/// decode a difference of this size.
+const EXPECTED_DIFF_RATIO: f64 = 0.02;
+
+/// The number of cells needed per key in the difference
+/// for a table to reliably decode.
+const CELLS_PER_DIFF: f64 = 1.5;
+
+/// Encoded size of a table's seed.
+const SEED_BYTES: usize = 8;
+
+/// Encoded size of a cell: count, key sum and check sum.
+const CELL_BYTES: usize = 4 + 8 + 4;
+
+/// The ops in a time window, summarised as an IBLT.
+#[derive(Debug, Clone)]
+pub(super) struct TimedIblt {
+    /// The table for the time window.
+    /// If this is none then we have no hashes
+    /// for this time window.
+    pub(super) iblt: Option<Iblt>,
+    /// The time window for this table.
+    pub(super) time: TimeWindow,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Cell {
+    count: i32,
+    key_sum: u64,
+    check_sum: u32,
+}
+
+/// An invertible bloom lookup table of op hash keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Iblt {
+    seed: u64,
+    cells: Vec<Cell>,
+}
+
+/// The keys found by decoding the difference between two tables.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct IbltDiff {
+    /// Keys that are only in the local table.
+    pub(super) local: HashSet<u64>,
+    /// Keys that are only in the remote table.
+    pub(super) remote: HashSet<u64>,
+}
+
+impl ShardedGossipLocal {
+    /// Generate IBLTs of all ops.
+    /// - The time windows are chunked the same as
+    ///   [`generate_op_blooms_for_time_window`](Self::generate_op_blooms_for_time_window).
+    /// - Each table is sized to decode a difference of about
+    ///   [`EXPECTED_DIFF_RATIO`] of the hashes in its time window.
+    pub(super) async fn generate_op_iblts_for_time_window(
+        &self,
+        common_arc_set: &Arc<DhtArcSet>,
+        search_time_window: TimeWindow,
+    ) -> KitsuneResult<bloom::Batch<TimedIblt>> {
+        let batch = self
+            .generate_op_filters_for_time_window(
+                common_arc_set,
+                search_time_window,
+                |hashes| async move {
+                    let mut iblt = Iblt::for_set_size(hashes.len());
+
+                    let mut iter = hashes.into_iter().peekable();
+
+                    while iter.peek().is_some() {
+                        for hash in iter.by_ref().take(100) {
+                            iblt.insert(Iblt::key(&hash));
+                        }
+                        // Yield to avoid starving the runtime.
+                        tokio::task::yield_now().await;
+                    }
+                    iblt
+                },
+            )
+            .await?;
+        Ok(batch.map(|(time, iblt)| TimedIblt { iblt, time }))
+    }
+
+    /// Check an IBLT for missing ops.
+    /// - All the local hashes in the time window are put in a table
+    ///   matching the remote table and the remote table is subtracted.
+    /// - The decoded local keys are the hashes the remote node is missing.
+    /// - If the difference is too large to decode then none is returned,
+    ///   and the time window should be reconciled with a bloom filter instead.
+    pub(super) async fn check_op_iblt(
+        &self,
+        common_arc_set: DhtArcSet,
+        remote_iblt: &Iblt,
+        time_window: TimeWindow,
+    ) -> KitsuneResult<Option<Vec<Arc<KitsuneOpHash>>>> {
+        use futures::TryStreamExt;
+
+        let mut local_iblt = remote_iblt.empty_like();
+        let mut hashes = HashMap::new();
+        let mut stream = store::hash_chunks_query(
+            self.evt_sender.clone(),
+            self.space.clone(),
+            common_arc_set,
+            time_window,
+            false,
+        );
+        // The tables must cover exactly the same time window so
+        // take every chunk instead of batching.
+        while let Some(TimeChunk { hashes: chunk, .. }) = stream.try_next().await? {
+            for hash in chunk {
+                let key = Iblt::key(&hash);
+                local_iblt.insert(key);
+                hashes.insert(key, hash);
+            }
+            // Yield to avoid starving the runtime.
+            tokio::task::yield_now().await;
+        }
+
+        local_iblt.subtract(remote_iblt)?;
+        match local_iblt.decode() {
+            Some(diff) => Ok(Some(
+                diff.local
+                    .into_iter()
+                    .filter_map(|key| hashes.remove(&key))
+                    .collect(),
+            )),
+            None => {
+                self.inner.share_ref(|i| {
+                    i.metrics.write().record_iblt_decode_failure();
+                    Ok(())
+                })?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Generate a single bloom filter of all ops in exactly this time window,
+    /// to replace one of our tables which the remote node couldn't decode.
+    /// - The filter is empty if there are no ops, which asks for all the
+    ///   remote node's ops in the time window.
+    pub(super) async fn generate_fallback_op_bloom(
+        &self,
+        common_arc_set: DhtArcSet,
+        time_window: TimeWindow,
+    ) -> KitsuneResult<BloomFilter> {
+        use futures::TryStreamExt;
+
+        let mut hashes = Vec::new();
+        let mut stream = store::hash_chunks_query(
+            self.evt_sender.clone(),
+            self.space.clone(),
+            common_arc_set,
+            time_window,
+            false,
+        );
+        while let Some(TimeChunk { hashes: chunk, .. }) = stream.try_next().await? {
+            hashes.extend(chunk);
+            // Yield to avoid starving the runtime.
+            tokio::task::yield_now().await;
+        }
+
+        let mut bloom = bloomfilter::Bloom::new_for_fp_rate(hashes.len().max(1), Self::TGT_FP);
+        for hash in hashes {
+            bloom.set(&MetaOpKey::Op(hash));
+        }
+        Ok(bloom)
+    }
+}
+
+impl Iblt {
+    /// Create an empty table sized for a set of this many hashes.
+    pub(super) fn for_set_size(len: usize) -> Self {
+        let diff = len as f64 * EXPECTED_DIFF_RATIO * CELLS_PER_DIFF;
+        Self::new(rand::random(), (diff.ceil() as usize).max(MIN_CELLS))
+    }
+
+    fn new(seed: u64, num_cells: usize) -> Self {
+        // Round up so every partition is the same size.
+        let num_cells = (num_cells.max(1) + NUM_HASHES - 1) / NUM_HASHES * NUM_HASHES;
+        Self {
+            seed,
+            cells: vec![Cell::default(); num_cells],
+        }
+    }
+
+    /// Create an empty table which can be subtracted from this one.
+    pub(super) fn empty_like(&self) -> Self {
+        Self::new(self.seed, self.cells.len())
+    }
+
+    /// The key for an op hash.
+    pub(super) fn key(hash: &KitsuneOpHash) -> u64 {
+        // FNV-1a
+        let key = hash.0.iter().fold(0xcbf29ce484222325_u64, |key, byte| {
+            (key ^ *byte as u64).wrapping_mul(0x100000001b3)
+        });
+        mix(key)
+    }
+
+    /// Add a key to the table.
+    pub(super) fn insert(&mut self, key: u64) {
+        self.update(key, 1);
+    }
+
+    /// Subtract the other table from this table, leaving the keys
+    /// which are only in one of the two.
+    pub(super) fn subtract(&mut self, other: &Self) -> KitsuneResult<()> {
+        if self.seed != other.seed || self.cells.len() != other.cells.len() {
+            return Err(KitsuneError::other(
+                "Cannot subtract tables with different seeds or sizes",
+            ));
+        }
+        for (cell, other) in self.cells.iter_mut().zip(&other.cells) {
+            cell.count = cell.count.wrapping_sub(other.count);
+            cell.key_sum ^= other.key_sum;
+            cell.check_sum ^= other.check_sum;
+        }
+        Ok(())
+    }
+
+    /// Decode a table which has had another table subtracted from it.
+    /// Returns `None` if the difference was too large to decode.
+    pub(super) fn decode(mut self) -> Option<IbltDiff> {
+        let mut diff = IbltDiff::default();
+        let mut pure: Vec<usize> = (0..self.cells.len()).filter(|i| self.is_pure(*i)).collect();
+
+        while let Some(i) = pure.pop() {
+            // Removing other keys may have changed this cell.
+            if !self.is_pure(i) {
+                continue;
+            }
+            let Cell { count, key_sum, .. } = self.cells[i];
+            if count == 1 {
+                diff.local.insert(key_sum);
+            } else {
+                diff.remote.insert(key_sum);
+            }
+            self.update(key_sum, -count);
+            pure.extend(self.indices(key_sum).filter(|j| self.is_pure(*j)));
+        }
+
+        if self.cells.iter().all(|cell| *cell == Cell::default()) {
+            Some(diff)
+        } else {
+            None
+        }
+    }
+
+    /// Encode this table to send to a remote node.
+    pub(super) fn encode(&self) -> PoolBuf {
+        let mut buf = PoolBuf::new();
+        buf.reserve(SEED_BYTES + self.cells.len() * CELL_BYTES);
+        buf.extend_from_slice(&self.seed.to_le_bytes());
+        for cell in &self.cells {
+            buf.extend_from_slice(&cell.count.to_le_bytes());
+            buf.extend_from_slice(&cell.key_sum.to_le_bytes());
+            buf.extend_from_slice(&cell.check_sum.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Decode a table sent by a remote node.
+    pub(super) fn from_bytes(bytes: &[u8]) -> KitsuneResult<Self> {
+        let num_cells = bytes.len().saturating_sub(SEED_BYTES) / CELL_BYTES;
+        if bytes.len() != SEED_BYTES + num_cells * CELL_BYTES
+            || num_cells == 0
+            || num_cells % NUM_HASHES != 0
+        {
+            return Err(KitsuneError::other(format!(
+                "Invalid IBLT of {} bytes",
+                bytes.len()
+            )));
+        }
+        let seed = u64::from_le_bytes(*arrayref::array_ref![bytes, 0, 8]);
+        let cells = bytes[SEED_BYTES..]
+            .chunks_exact(CELL_BYTES)
+            .map(|cell| Cell {
+                count: i32::from_le_bytes(*arrayref::array_ref![cell, 0, 4]),
+                key_sum: u64::from_le_bytes(*arrayref::array_ref![cell, 4, 8]),
+                check_sum: u32::from_le_bytes(*arrayref::array_ref![cell, 12, 4]),
+            })
+            .collect();
+        Ok(Self { seed, cells })
+    }
+
+    /// The cell in each partition for this key.
+    fn indices(&self, key: u64) -> impl Iterator<Item = usize> {
+        let partition = self.cells.len() / NUM_HASHES;
+        let seed = self.seed;
+        (0..NUM_HASHES).map(move |i| {
+            let h = mix(key ^ mix(seed.wrapping_add(i as u64)));
+            i * partition + (h % partition as u64) as usize
+        })
+    }
+
+    fn check_sum(&self, key: u64) -> u32 {
+        (mix(key ^ !self.seed) >> 32) as u32
+    }
+
+    fn update(&mut self, key: u64, count: i32) {
+        let check_sum = self.check_sum(key);
+        for i in self.indices(key) {
+            let cell = &mut self.cells[i];
+            cell.count = cell.count.wrapping_add(count);
+            cell.key_sum ^= key;
+            cell.check_sum ^= check_sum;
+        }
+    }
+
+    /// A cell is pure if it holds exactly one key.
+    fn is_pure(&self, i: usize) -> bool {
+        let cell = &self.cells[i];
+        (cell.count == 1 || cell.count == -1) && cell.check_sum == self.check_sum(cell.key_sum)
+    }
+}
+
+/// The splitmix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
@@ -54,7 +54,12 @@ impl ShardedGossipLocal {
                 .await
                 .map_err(KitsuneError::other)?;
 
-            let gossip = ShardedGossipWire::initiate(intervals, id, agent_list);
+            let gossip = ShardedGossipWire::initiate(
+                intervals,
+                id,
+                agent_list,
+                self.supported_reconciliation(),
+            );
 
             let tgt = ShardedGossipTarget {
                 remote_agent_list: agent_info_list,
@@ -78,12 +83,14 @@ impl ShardedGossipLocal {
     /// Receiving an incoming initiate.
     /// - Send back the accept, agent bloom and ops bloom gossip messages.
     /// - Only send the agent bloom if this is a recent gossip type.
+    /// - Choose how op hashes are reconciled from the ways the remote supports.
     pub(super) async fn incoming_initiate(
         &self,
         peer_cert: Arc<[u8; 32]>,
         remote_arc_set: Vec<DhtArcRange>,
         remote_id: u32,
         remote_agent_list: Vec<AgentInfoSigned>,
+        remote_reconciliation: Vec<OpReconciliation>,
     ) -> KitsuneResult<Vec<ShardedGossipWire>> {
         let (local_agents, same_as_target, already_in_progress) =
             self.inner.share_mut(|i, _| {
@@ -143,8 +150,14 @@ impl ShardedGossipLocal {
             .await
             .map_err(KitsuneError::other)?;
 
+        let reconciliation = self.choose_reconciliation(&remote_reconciliation);
+
         // Send the intervals back as the accept message.
-        let mut gossip = vec![ShardedGossipWire::accept(local_arcs.clone(), agent_list)];
+        let mut gossip = vec![ShardedGossipWire::accept(
+            local_arcs.clone(),
+            agent_list,
+            Some(reconciliation),
+        )];
 
         // Generate the bloom filters and new state.
         let state = self
@@ -152,6 +165,7 @@ impl ShardedGossipLocal {
                 remote_agent_list.clone(),
                 local_arcs,
                 remote_arc_set,
+                reconciliation,
                 &mut gossip,
             )
             .await?;
@@ -189,12 +203,14 @@ impl ShardedGossipLocal {
     /// Generate the bloom filters and generate a new state.
     /// - Agent bloom is only generated if this is a `Recent` gossip type.
     /// - Empty blooms are not created.
+    /// - Op hashes are sent using the agreed reconciliation.
     /// - A new state is created for this round.
     pub(super) async fn generate_blooms_or_regions(
         &self,
         remote_agent_list: Vec<AgentInfoSigned>,
         local_arcs: Vec<DhtArcRange>,
         remote_arc_set: Vec<DhtArcRange>,
+        reconciliation: OpReconciliation,
         gossip: &mut Vec<ShardedGossipWire>,
     ) -> KitsuneResult<RoundState> {
         // Create the common arc set from the remote and local arcs.
@@ -217,6 +233,7 @@ impl ShardedGossipLocal {
 
         // Generate the new state.
         let mut state = self.new_state(remote_agent_list, common_arc_set, region_set)?;
+        state.reconciliation = reconciliation;
 
         // Generate the agent bloom.
         if let GossipType::Recent = self.gossip_type {
@@ -230,6 +247,13 @@ impl ShardedGossipLocal {
             // for purposes of determining the round is complete
             state.regions_are_queued = true;
 
+            self.inner.share_ref(|i| {
+                i.metrics
+                    .write()
+                    .record_reconciliation_round(reconciliation);
+                Ok(())
+            })?;
+
             self.next_bloom_batch(state, gossip).await
         } else {
             // Everything has already been taken care of for Historical
@@ -259,9 +283,19 @@ impl ShardedGossipLocal {
         if let Some(cursor) = state.bloom_batch_cursor.take() {
             window.start = cursor;
         }
-        let blooms = self
-            .generate_op_blooms_for_time_window(&state.common_arc_set, window)
-            .await?;
+        let blooms = match state.reconciliation {
+            OpReconciliation::Bloom => self
+                .generate_op_blooms_for_time_window(&state.common_arc_set, window)
+                .await?
+                .map(|bloom| {
+                    let filter = bloom.bloom.map(|bloom| encode_bloom_filter(&bloom));
+                    (bloom.time, filter)
+                }),
+            OpReconciliation::Iblt => self
+                .generate_op_iblts_for_time_window(&state.common_arc_set, window)
+                .await?
+                .map(|table| (table.time, table.iblt.map(|iblt| iblt.encode()))),
+        };
 
         let blooms = match blooms {
             bloom::Batch::Complete(blooms) => blooms,
@@ -282,23 +316,27 @@ impl ShardedGossipLocal {
         }
 
         let len = blooms.len();
+        let mut filter_bytes = 0;
 
         // Encode each bloom found for this time window.
-        for (i, bloom) in blooms.into_iter().enumerate() {
-            let time_window = bloom.time;
-            let bloom = match bloom.bloom {
+        for (i, (time_window, bytes)) in blooms.into_iter().enumerate() {
+            let bloom = match bytes {
                 // We have some hashes so request all missing from the bloom.
-                Some(bloom) => {
-                    let bytes = encode_bloom_filter(&bloom);
-                    EncodedTimedBloomFilter::HaveHashes {
+                Some(bytes) => match state.reconciliation {
+                    OpReconciliation::Bloom => EncodedTimedBloomFilter::HaveHashes {
                         filter: bytes,
                         time_window,
-                    }
-                }
+                    },
+                    OpReconciliation::Iblt => EncodedTimedBloomFilter::HaveIblt {
+                        table: bytes,
+                        time_window,
+                    },
+                },
                 // We have no hashes for this time window but we do have agents
                 // that hold the arc so request all the ops the remote holds.
                 None => EncodedTimedBloomFilter::MissingAllHashes { time_window },
             };
+            filter_bytes += bloom.size();
             state.increment_expected_op_blooms();
 
             // Check if this is the final time window and the final bloom for this window.
@@ -309,6 +347,13 @@ impl ShardedGossipLocal {
             }
         }
 
+        self.inner.share_ref(|i| {
+            i.metrics
+                .write()
+                .record_op_filter_bytes(state.reconciliation, filter_bytes);
+            Ok(())
+        })?;
+
         Ok(state)
     }
 }
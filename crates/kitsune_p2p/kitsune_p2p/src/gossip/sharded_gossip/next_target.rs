@@ -89,35 +89,50 @@ impl ShardedGossipLocal {
 
 /// Find the next remote node to sync with.
 fn next_remote_node(
-    mut remote_nodes: Vec<Node>,
+    remote_nodes: Vec<Node>,
     metrics: &MetricsSync,
     tuning_params: KitsuneP2pTuningParams,
 ) -> Option<Node> {
     use rand::prelude::*;
     let mut rng = thread_rng();
 
-    // Sort the nodes by longest time since we last successfully gossiped with them.
-    // Randomly break ties between nodes we haven't successfully gossiped with.
-    // Note the smaller an Instant the longer it is in the past.
-    remote_nodes.sort_unstable_by(|a, b| {
-        match (
-            metrics.read().last_success(&a.agent_info_list),
-            metrics.read().last_success(&b.agent_info_list),
-        ) {
-            // Choose the smallest (oldest) Instant.
-            (Some(a), Some(b)) => a.cmp(b),
+    let now = Instant::now();
+    let mut remote_nodes: Vec<_> = remote_nodes
+        .into_iter()
+        .map(|node| {
+            let metrics = metrics.read();
+            let score = metrics.peer_score(&node.agent_info_list);
+            // How long since we last successfully gossiped with the node,
+            // weighted by its score so better nodes are preferred.
+            let weighted_age = metrics
+                .last_success(&node.agent_info_list)
+                .map(|m| now.duration_since(m.instant).as_secs_f32() * score);
+            (weighted_age, score, node)
+        })
+        .collect();
+
+    // Sort the nodes by longest weighted time since we last successfully gossiped with them.
+    // Nodes we haven't successfully gossiped with go first, best score first,
+    // randomly breaking ties between equal scores.
+    remote_nodes.sort_unstable_by(|(a, a_score, _), (b, b_score, _)| {
+        match (a, b) {
+            // Choose the largest (oldest) weighted age.
+            (Some(a), Some(b)) => b.partial_cmp(a).unwrap_or(Ordering::Equal),
             // Put a behind b that hasn't been gossiped with.
             (Some(_), None) => Ordering::Greater,
             // Put b behind a that hasn't been gossiped with.
             (None, Some(_)) => Ordering::Less,
-            // Randomly break ties.
-            (None, None) => {
-                if rng.gen() {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
+            // Prefer the better score and randomly break ties.
+            (None, None) => match b_score.partial_cmp(a_score) {
+                Some(Ordering::Equal) | None => {
+                    if rng.gen() {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    }
                 }
-            }
+                Some(o) => o,
+            },
         }
     });
 
@@ -126,8 +141,8 @@ fn next_remote_node(
     remote_nodes
         .into_iter()
         // Don't initiate with nodes we are currently gossiping with.
-        .filter(|n| !metrics.read().is_current_round(&n.agent_info_list))
-        .find(|n| {
+        .filter(|(_, _, n)| !metrics.read().is_current_round(&n.agent_info_list))
+        .find(|(_, _, n)| {
             match metrics.read().last_outcome(&n.agent_info_list) {
                 Some(RoundOutcome::Success(when)) => {
                     // If we should force initiate then we don't need to wait for the delay.
@@ -136,12 +151,15 @@ fn next_remote_node(
                             >= tuning_params.gossip_peer_on_success_next_gossip_delay_ms
                 }
                 Some(RoundOutcome::Error(when)) => {
-                    when.elapsed().as_millis() as u32
-                        >= tuning_params.gossip_peer_on_error_next_gossip_delay_ms
+                    // Back off from nodes which time out or send invalid data.
+                    let backoff = metrics.read().error_backoff(&n.agent_info_list);
+                    when.elapsed().as_millis() as f32
+                        >= tuning_params.gossip_peer_on_error_next_gossip_delay_ms as f32 * backoff
                 }
                 _ => true,
             }
         })
+        .map(|(_, _, n)| n)
 }
 
 #[cfg(test)]
@@ -493,6 +511,64 @@ mod tests {
         assert!(r.is_some());
     }
 
+    #[test]
+    /// Test we prefer nodes with better scores when
+    /// we haven't gossiped with any of them.
+    fn prefer_better_scores() {
+        // - Create 10 remote nodes.
+        let remote_nodes = create_remote_nodes(10);
+
+        let metrics = MetricsSync::default();
+
+        // - Record invalid data from all but the last node.
+        for node in remote_nodes.iter().take(9) {
+            metrics
+                .write()
+                .record_data_validity_event(false, &node.agent_info_list);
+        }
+
+        // - Expect the last node to always be chosen.
+        for _ in 0..10 {
+            let r = next_remote_node(remote_nodes.clone(), &metrics, tuning_params_no_delay());
+            assert_eq!(r, remote_nodes.last().cloned());
+        }
+    }
+
+    #[test]
+    /// Test we wait longer after an error from nodes which time out.
+    fn back_off_from_timeouts() {
+        let remote_nodes = create_remote_nodes(1);
+
+        let metrics = MetricsSync::default();
+
+        // - Record a timed out round.
+        metrics.write().record_initiate(
+            &remote_nodes[0].agent_info_list,
+            GossipModuleType::ShardedRecent,
+        );
+        metrics.write().record_error(
+            &remote_nodes[0].agent_info_list,
+            GossipModuleType::ShardedRecent,
+        );
+        metrics
+            .write()
+            .record_timeout_event(true, &remote_nodes[0].agent_info_list);
+
+        // - Use up 50 ms.
+        std::thread::sleep(Duration::from_millis(50));
+
+        // - The error delay has passed but the backoff hasn't.
+        let r = next_remote_node(remote_nodes.clone(), &metrics, tuning_params_delay(0, 40));
+        assert!(r.is_none());
+
+        // - Use up another 150 ms.
+        std::thread::sleep(Duration::from_millis(150));
+
+        // - Now the backoff has passed.
+        let r = next_remote_node(remote_nodes.clone(), &metrics, tuning_params_delay(0, 40));
+        assert_eq!(r, remote_nodes.first().cloned());
+    }
+
     /// Test that given N remote nodes and a force initiate trigger
     /// we will choose the least recent node even if it's too recent.
     #[test_case(1)]
@@ -95,10 +95,89 @@ impl ShardedGossipLocal {
             }
         };
 
+        self.record_missing_hash_bytes(OpReconciliation::Bloom, &missing_hashes)?;
+
         self.batch_missing_ops_from_bloom(state, missing_hashes, queue_id)
             .await
     }
 
+    /// Incoming ops IBLT.
+    /// - Send back chunks of missing ops.
+    /// - Don't send a chunk larger then MAX_SEND_BUF_SIZE.
+    /// - If the table can't be decoded then ask for a bloom filter
+    ///   of the same time window instead.
+    pub(super) async fn incoming_op_iblt(
+        &self,
+        peer_cert: &StateKey,
+        state: RoundState,
+        remote_iblt: iblt::Iblt,
+        time_window: TimeWindow,
+    ) -> KitsuneResult<Vec<ShardedGossipWire>> {
+        // Check which ops are missing.
+        let missing_hashes = match self
+            .check_op_iblt(
+                (*state.common_arc_set).clone(),
+                &remote_iblt,
+                time_window.clone(),
+            )
+            .await?
+        {
+            Some(missing_hashes) => missing_hashes,
+            None => {
+                self.request_fallback_bloom(peer_cert)?;
+                return Ok(vec![ShardedGossipWire::op_bloom(
+                    EncodedTimedBloomFilter::IbltUndecodable { time_window },
+                    false,
+                )]);
+            }
+        };
+
+        self.record_missing_hash_bytes(OpReconciliation::Iblt, &missing_hashes)?;
+
+        self.batch_missing_ops_from_bloom(state, missing_hashes, None)
+            .await
+    }
+
+    /// The remote node couldn't decode one of our IBLTs.
+    /// - Send a bloom filter of the same time window instead.
+    /// - The reply to the bloom filter replaces the reply to the table,
+    ///   so the number of expected replies is unchanged.
+    pub(super) async fn incoming_iblt_undecodable(
+        &self,
+        state: RoundState,
+        time_window: TimeWindow,
+    ) -> KitsuneResult<Vec<ShardedGossipWire>> {
+        let bloom = self
+            .generate_fallback_op_bloom((*state.common_arc_set).clone(), time_window.clone())
+            .await?;
+        let bloom = EncodedTimedBloomFilter::HaveHashes {
+            filter: encode_bloom_filter(&bloom),
+            time_window,
+        };
+        self.inner.share_ref(|i| {
+            i.metrics
+                .write()
+                .record_op_filter_bytes(OpReconciliation::Bloom, bloom.size());
+            Ok(())
+        })?;
+        Ok(vec![ShardedGossipWire::op_bloom(bloom, false)])
+    }
+
+    /// Record the bytes of hashes found missing from a remote filter.
+    fn record_missing_hash_bytes(
+        &self,
+        reconciliation: OpReconciliation,
+        missing_hashes: &[Arc<KitsuneOpHash>],
+    ) -> KitsuneResult<()> {
+        let bytes = missing_hashes.iter().map(|hash| hash.0.len()).sum();
+        self.inner.share_ref(|i| {
+            i.metrics
+                .write()
+                .record_missing_hash_bytes(reconciliation, bytes);
+            Ok(())
+        })
+    }
+
     pub(super) async fn queue_incoming_regions(
         &self,
         peer_cert: &Arc<[u8; 32]>,
@@ -72,6 +72,27 @@ pub fn check_ops_bloom<'a>(
                 .filter(|op| !filter.check(&MetaOpKey::Op((**op).clone())))
                 .collect()
         }
+        EncodedTimedBloomFilter::HaveIblt { table, time_window } => {
+            let remote = iblt::Iblt::from_bytes(&table).expect("Invalid IBLT");
+            let ops: Vec<_> = ops
+                .filter(|(t, _)| time_window.contains(t))
+                .map(|(_, h)| h)
+                .collect();
+            let mut local = remote.empty_like();
+            for op in &ops {
+                local.insert(iblt::Iblt::key(op));
+            }
+            local.subtract(&remote).unwrap();
+            match local.decode() {
+                Some(diff) => ops
+                    .into_iter()
+                    .filter(|op| diff.local.contains(&iblt::Iblt::key(op)))
+                    .collect(),
+                // Too different to decode so a bloom filter is requested instead.
+                None => vec![],
+            }
+        }
+        EncodedTimedBloomFilter::IbltUndecodable { .. } => vec![],
     }
 }
 
@@ -9,6 +9,7 @@ use fixt::prelude::*;
 
 mod bloom;
 mod common;
+mod iblt;
 mod ops;
 mod test_two_nodes;
 
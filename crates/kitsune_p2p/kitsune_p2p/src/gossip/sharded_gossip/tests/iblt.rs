@@ -0,0 +1,91 @@
+use crate::gossip::sharded_gossip::iblt::Iblt;
+
+use super::*;
+
+fn hashes(range: std::ops::Range<u32>) -> Vec<KitsuneOpHash> {
+    range
+        .map(|i| {
+            let mut bytes = vec![0; 36];
+            bytes[..4].copy_from_slice(&i.to_le_bytes());
+            KitsuneOpHash(bytes)
+        })
+        .collect()
+}
+
+fn table(like: Option<&Iblt>, len: usize, hashes: &[KitsuneOpHash]) -> Iblt {
+    let mut table = match like {
+        Some(like) => like.empty_like(),
+        None => Iblt::for_set_size(len),
+    };
+    for hash in hashes {
+        table.insert(Iblt::key(hash));
+    }
+    table
+}
+
+#[test]
+fn iblt_finds_exact_difference() {
+    let ours = hashes(0..10_000);
+    let theirs = hashes(10..10_005);
+
+    let remote = table(None, theirs.len(), &theirs);
+    let mut local = table(Some(&remote), ours.len(), &ours);
+    local.subtract(&remote).unwrap();
+    let diff = local.decode().expect("Small difference should decode");
+
+    let expected_local: HashSet<_> = ours[..10].iter().map(Iblt::key).collect();
+    let expected_remote: HashSet<_> = theirs[9990..].iter().map(Iblt::key).collect();
+    assert_eq!(diff.local, expected_local);
+    assert_eq!(diff.remote, expected_remote);
+}
+
+#[test]
+fn iblt_equal_sets_have_no_difference() {
+    let ours = hashes(0..500);
+    let remote = table(None, ours.len(), &ours);
+    let mut local = table(Some(&remote), ours.len(), &ours);
+    local.subtract(&remote).unwrap();
+    let diff = local.decode().unwrap();
+    assert!(diff.local.is_empty());
+    assert!(diff.remote.is_empty());
+}
+
+#[test]
+fn iblt_large_difference_fails_to_decode() {
+    let ours = hashes(0..10_000);
+    let theirs = hashes(5000..15_000);
+
+    let remote = table(None, theirs.len(), &theirs);
+    let mut local = table(Some(&remote), ours.len(), &ours);
+    local.subtract(&remote).unwrap();
+    assert!(local.decode().is_none());
+}
+
+#[test]
+fn iblt_mismatched_tables_cannot_subtract() {
+    let mut a = Iblt::for_set_size(10);
+    let b = Iblt::for_set_size(10_000);
+    assert!(a.subtract(&b).is_err());
+}
+
+#[test]
+fn iblt_encoding_round_trips() {
+    let table = table(None, 100, &hashes(0..100));
+    let encoded = table.encode();
+    assert_eq!(Iblt::from_bytes(&encoded).unwrap(), table);
+    assert!(Iblt::from_bytes(&encoded[..encoded.len() - 1]).is_err());
+    assert!(Iblt::from_bytes(&[]).is_err());
+}
+
+#[test]
+fn iblt_is_smaller_than_bloom_for_large_sets() {
+    let ours: Vec<_> = hashes(0..10_000).into_iter().map(Arc::new).collect();
+    let iblt = table(None, ours.len(), &hashes(0..10_000)).encode();
+    let bloom = test_utils::create_op_bloom(ours);
+    assert!(
+        iblt.len() < bloom.len(),
+        "iblt {} bytes, bloom {} bytes",
+        iblt.len(),
+        bloom.len()
+    );
+}
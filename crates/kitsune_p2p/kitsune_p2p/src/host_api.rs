@@ -1,4 +1,4 @@
-use kitsune_p2p_fetch::OpHashSized;
+use kitsune_p2p_fetch::{FetchPoolItemInfo, OpHashSized};
 use must_future::MustBoxFuture;
 use std::sync::Arc;
 
@@ -15,6 +15,7 @@ use kitsune_p2p_types::{
 };
 
 use crate::event::{GetAgentInfoSignedEvt, MetricRecord};
+use crate::types::traffic::TrafficWeights;
 
 /// A boxed future result with dynamic error type
 pub type KitsuneHostResult<'a, T> =
@@ -70,6 +71,42 @@ pub trait KitsuneHost: 'static + Send + Sync {
         records: Vec<MetricRecord>,
     ) -> KitsuneHostResult<()>;
 
+    /// Get the most recent per agent metric records stored by
+    /// [`KitsuneHost::record_metrics`], to restore peer scores on startup.
+    fn query_metrics(&self, space: Arc<KitsuneSpace>) -> KitsuneHostResult<Vec<MetricRecord>> {
+        let _space = space;
+        futures::FutureExt::boxed(async move { Ok(vec![]) }).into()
+    }
+
+    /// Save the pending items of the fetch pool for this space,
+    /// replacing any saved before.
+    fn persist_fetch_pool(
+        &self,
+        space: Arc<KitsuneSpace>,
+        items: Vec<FetchPoolItemInfo>,
+    ) -> KitsuneHostResult<()> {
+        let _space = space;
+        let _items = items;
+        futures::FutureExt::boxed(async move { Ok(()) }).into()
+    }
+
+    /// Get the fetch pool items saved by [`KitsuneHost::persist_fetch_pool`],
+    /// to restore pending fetches on startup.
+    fn load_fetch_pool(
+        &self,
+        space: Arc<KitsuneSpace>,
+    ) -> KitsuneHostResult<Vec<FetchPoolItemInfo>> {
+        let _space = space;
+        futures::FutureExt::boxed(async move { Ok(vec![]) }).into()
+    }
+
+    /// Get the weights of the outbound traffic classes in this space,
+    /// overriding the tuning params. Called when the space is created.
+    fn traffic_weights(&self, space: Arc<KitsuneSpace>) -> TrafficWeights {
+        let _space = space;
+        TrafficWeights::default()
+    }
+
     /// Get the quantum Topology associated with this Space.
     fn get_topology(&self, space: Arc<KitsuneSpace>) -> KitsuneHostResult<Topology>;
 
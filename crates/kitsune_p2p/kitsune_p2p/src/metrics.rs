@@ -8,12 +8,15 @@ use std::time::Duration;
 use tokio::time::Instant;
 
 use crate::gossip::sharded_gossip::NodeId;
+use crate::gossip::sharded_gossip::OpReconciliation;
 use crate::gossip::sharded_gossip::RegionDiffs;
 use crate::gossip::sharded_gossip::RoundState;
+use crate::gossip::sharded_gossip::ShardedGossipWire;
 use crate::types::event::*;
 use crate::types::*;
 use kitsune_p2p_timestamp::Timestamp;
 use kitsune_p2p_types::agent_info::AgentInfoSigned;
+use kitsune_p2p_types::dht_arc::DhtArcRange;
 
 use num_traits::*;
 
@@ -46,6 +49,11 @@ impl RunAvg {
         self.1 = self.1.saturating_add(count);
         self.0 = (self.0 * (self.1 - count) as f32 + (v.as_() * count as f32)) / self.1 as f32;
     }
+
+    /// True if nothing has been pushed onto the average
+    pub fn is_empty(&self) -> bool {
+        self.1 == 0
+    }
 }
 
 macro_rules! mk_from {
@@ -105,6 +113,23 @@ const MAX_TRIGGERS: u8 = 2;
 /// per remote node.
 const MAX_HISTORY: usize = 10;
 
+/// Maximum number of completed rounds we will keep
+/// the details of per remote node.
+const MAX_COMPLETED_ROUNDS: usize = 20;
+
+/// The score of an agent we have no metrics for.
+pub(crate) const NEUTRAL_PEER_SCORE: f32 = 50.0;
+
+/// Latency at or below which an agent's score is not reduced (100ms).
+const GOOD_LATENCY_MICROS: f32 = 100_000.0;
+
+/// The most an agent's score can be reduced by high latency.
+const MIN_LATENCY_FACTOR: f32 = 0.25;
+
+/// The most the delay before gossiping again with an agent
+/// after an error can be increased by.
+const MAX_ERROR_BACKOFF: f32 = 4.0;
+
 #[derive(Debug, Clone, Default)]
 /// The history of gossip with an agent on a remote node.
 /// We record metrics per agent,
@@ -117,6 +142,12 @@ pub struct PeerAgentHistory {
     /// Running average for latency microseconds for any direct
     /// request/response calls to remote agent.
     pub latency_micros: RunAvg,
+    /// Percentage of requests and gossip rounds with the
+    /// remote agent which timed out.
+    pub timeout_rate: RunAvg,
+    /// Percentage of data from the remote agent which
+    /// the host reported as invalid.
+    pub invalid_data_rate: RunAvg,
     /// Times we recorded successful initiates to this node (they accepted).
     pub initiates: VecDeque<RoundMetric>,
     /// Times we recorded initates from this node (we accepted).
@@ -129,6 +160,54 @@ pub struct PeerAgentHistory {
     pub current_round: bool,
 }
 
+impl PeerAgentHistory {
+    /// Combine the metrics for this agent into a score from 0 (worst) to 100 (best).
+    /// Agents we have no metrics for get a neutral score of 50.
+    pub fn score(&self) -> f32 {
+        let mut score = if self.reachability_quotient.is_empty() {
+            NEUTRAL_PEER_SCORE
+        } else {
+            *self.reachability_quotient
+        };
+        if *self.latency_micros > GOOD_LATENCY_MICROS {
+            score *= (GOOD_LATENCY_MICROS / *self.latency_micros).max(MIN_LATENCY_FACTOR);
+        }
+        score *= 1.0 - *self.timeout_rate / 100.0;
+        // Sending invalid data is worse than being slow or unreachable.
+        let valid = 1.0 - *self.invalid_data_rate / 100.0;
+        score *= valid * valid;
+        score.clamp(0.0, 100.0)
+    }
+
+    /// The score of this agent along with the metrics it was made from.
+    pub fn peer_score(&self) -> PeerScore {
+        PeerScore {
+            score: self.score().round() as u8,
+            reachability_quotient: self.reachability_quotient.round() as u8,
+            latency_micros: *self.latency_micros as u64,
+            timeout_rate: self.timeout_rate.round() as u8,
+            invalid_data_rate: self.invalid_data_rate.round() as u8,
+        }
+    }
+}
+
+/// How well a remote agent has behaved.
+/// Agents with better scores are preferred as gossip partners
+/// and as authorities to request data from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PeerScore {
+    /// The combined score from 0 (worst) to 100 (best).
+    pub score: u8,
+    /// See [`PeerAgentHistory::reachability_quotient`].
+    pub reachability_quotient: u8,
+    /// See [`PeerAgentHistory::latency_micros`].
+    pub latency_micros: u64,
+    /// See [`PeerAgentHistory::timeout_rate`].
+    pub timeout_rate: u8,
+    /// See [`PeerAgentHistory::invalid_data_rate`].
+    pub invalid_data_rate: u8,
+}
+
 /// Detailed info about the history of gossip with this node
 #[derive(Debug, Clone, Default)]
 pub struct PeerNodeHistory {
@@ -151,6 +230,80 @@ pub struct RoundMetric {
     pub gossip_type: GossipModuleType,
 }
 
+/// Gossip messages sent to or received from the remote node during a round
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundTraffic {
+    /// Number of gossip messages
+    pub messages: u32,
+    /// Total encoded size of the gossip messages
+    pub bytes: u64,
+    /// Number of op hashes
+    pub op_hashes: u32,
+    /// Number of agent infos
+    pub agents: u32,
+    /// Number of regions in region sets
+    pub regions: u32,
+}
+
+impl RoundTraffic {
+    /// The traffic of a single gossip message
+    pub fn message(msg: &ShardedGossipWire, bytes: usize) -> Self {
+        let mut traffic = Self {
+            messages: 1,
+            bytes: bytes as u64,
+            ..Default::default()
+        };
+        match msg {
+            ShardedGossipWire::MissingOpHashes(m) => traffic.op_hashes = m.ops.len() as u32,
+            ShardedGossipWire::MissingAgents(m) => traffic.agents = m.agents.len() as u32,
+            ShardedGossipWire::OpRegions(m) => traffic.regions = m.region_set.count() as u32,
+            _ => (),
+        }
+        traffic
+    }
+
+    /// Add other traffic to this traffic
+    pub fn add(&mut self, other: &Self) {
+        self.messages += other.messages;
+        self.bytes += other.bytes;
+        self.op_hashes += other.op_hashes;
+        self.agents += other.agents;
+        self.regions += other.regions;
+    }
+}
+
+/// Details of a gossip round, used to diagnose nodes which don't converge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundDetails {
+    /// We initiated this round, rather than accepting it
+    pub initiated: bool,
+    /// The arcs of the remote agents
+    pub remote_arcs: Vec<DhtArcRange>,
+    /// The arcs common to both nodes, which were gossiped over
+    pub common_arcs: Vec<DhtArcRange>,
+    /// If historical, the number of regions we sent to compare
+    pub regions_compared: Option<usize>,
+    /// Gossip sent to the remote node
+    pub sent: RoundTraffic,
+    /// Gossip received from the remote node
+    pub received: RoundTraffic,
+    /// Why the round failed, if it did and the reason is known
+    pub error_message: Option<String>,
+}
+
+impl RoundDetails {
+    fn update(&mut self, round_state: &RoundState) {
+        self.initiated = round_state.initiated;
+        self.remote_arcs = round_state
+            .remote_agent_list
+            .iter()
+            .map(|info| info.storage_arc.inner())
+            .collect();
+        self.common_arcs = round_state.common_arc_set.intervals();
+        self.regions_compared = round_state.region_set_sent.as_ref().map(|r| r.count());
+    }
+}
+
 /// Metrics about a completed gossip round
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CompletedRound {
@@ -166,6 +319,8 @@ pub struct CompletedRound {
     pub error: bool,
     /// If historical, the region diffs
     pub region_diffs: RegionDiffs,
+    /// Details of what was gossiped
+    pub details: RoundDetails,
 }
 
 impl CompletedRound {
@@ -188,6 +343,8 @@ pub struct CurrentRound {
     pub start_time: Instant,
     /// If historical, the region diffs
     pub region_diffs: RegionDiffs,
+    /// Details of what has been gossiped so far
+    pub details: RoundDetails,
 }
 
 impl CurrentRound {
@@ -199,6 +356,7 @@ impl CurrentRound {
             start_time,
             last_touch: Instant::now(),
             region_diffs: Default::default(),
+            details: Default::default(),
         }
     }
 
@@ -206,6 +364,7 @@ impl CurrentRound {
     pub fn update(&mut self, round_state: &RoundState) {
         self.last_touch = Instant::now();
         self.region_diffs = round_state.region_diffs.clone();
+        self.details.update(round_state);
     }
 
     /// Convert to a CompletedRound
@@ -217,6 +376,7 @@ impl CurrentRound {
             end_time: Instant::now(),
             error,
             region_diffs: self.region_diffs,
+            details: self.details,
         }
     }
 }
@@ -268,6 +428,32 @@ impl Ord for CurrentRound {
     }
 }
 
+/// The bytes this node sent to reconcile op hashes one way,
+/// to compare the ways of reconciling against each other.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconciliationMetrics {
+    /// Recent gossip rounds which reconciled this way.
+    pub rounds: u64,
+    /// Bytes of filters sent to describe the op hashes held.
+    pub filter_bytes: u64,
+    /// Bytes of op hashes sent because they were missing
+    /// from a remote filter.
+    pub missing_hash_bytes: u64,
+    /// Tables which were too different to decode, so every
+    /// op hash in their time window was sent instead.
+    pub decode_failures: u64,
+}
+
+impl ReconciliationMetrics {
+    /// The average bytes sent per round.
+    pub fn bytes_per_round(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        (self.filter_bytes + self.missing_hash_bytes) as f64 / self.rounds as f64
+    }
+}
+
 #[derive(Debug, Default)]
 /// Metrics tracking for remote nodes to help
 /// choose which remote node to initiate the next round with.
@@ -281,6 +467,9 @@ pub struct Metrics {
     /// Aggregate Extrapolated Dht Coverage
     agg_extrap_cov: RunAvg,
 
+    /// Bytes sent to reconcile op hashes, for each way of reconciling.
+    reconciliation: HashMap<OpReconciliation, ReconciliationMetrics>,
+
     // Number of times we need to force initiate
     // the next round.
     pub(crate) force_initiates: u8,
@@ -351,6 +540,26 @@ impl Metrics {
                 expires_at_utc: expires_at,
                 data: serde_json::json!(*node.latency_micros),
             });
+
+            if !node.timeout_rate.is_empty() {
+                out.push(MetricRecord {
+                    kind: MetricRecordKind::TimeoutRate,
+                    agent: Some(agent.clone()),
+                    recorded_at_utc: now,
+                    expires_at_utc: expires_at,
+                    data: serde_json::json!(*node.timeout_rate),
+                });
+            }
+
+            if !node.invalid_data_rate.is_empty() {
+                out.push(MetricRecord {
+                    kind: MetricRecordKind::InvalidDataRate,
+                    agent: Some(agent.clone()),
+                    recorded_at_utc: now,
+                    expires_at_utc: expires_at,
+                    data: serde_json::json!(*node.invalid_data_rate),
+                });
+            }
         }
 
         out.push(MetricRecord {
@@ -364,6 +573,39 @@ impl Metrics {
         out
     }
 
+    /// Restore per agent metrics from records written by
+    /// [`Metrics::dump_historical`] in a previous run,
+    /// so remote agents keep their scores across restarts.
+    /// Metrics already recorded in this run are not replaced.
+    pub fn restore_historical(&mut self, records: Vec<MetricRecord>) {
+        for record in records {
+            let (agent, value) = match (record.agent, record.data.as_f64()) {
+                (Some(agent), Some(value)) => (agent, value),
+                _ => continue,
+            };
+            // Reachability and latency are recorded as zero when nothing
+            // had been pushed, so zero can't be told apart from no data.
+            let zero_is_data = match record.kind {
+                MetricRecordKind::ReachabilityQuotient | MetricRecordKind::LatencyMicros => false,
+                MetricRecordKind::TimeoutRate | MetricRecordKind::InvalidDataRate => true,
+                _ => continue,
+            };
+            if value <= 0.0 && !zero_is_data {
+                continue;
+            }
+            let history = self.agent_history.entry(agent).or_default();
+            let avg = match record.kind {
+                MetricRecordKind::ReachabilityQuotient => &mut history.reachability_quotient,
+                MetricRecordKind::LatencyMicros => &mut history.latency_micros,
+                MetricRecordKind::TimeoutRate => &mut history.timeout_rate,
+                _ => &mut history.invalid_data_rate,
+            };
+            if avg.is_empty() {
+                *avg = RunAvg::from(value);
+            }
+        }
+    }
+
     /// Dump json encoded metrics
     pub fn dump(&self) -> serde_json::Value {
         let agents: serde_json::Value = self
@@ -375,6 +617,27 @@ impl Metrics {
                     serde_json::json!({
                         "reachability_quotient": *i.reachability_quotient,
                         "latency_micros": *i.latency_micros,
+                        "timeout_rate": *i.timeout_rate,
+                        "invalid_data_rate": *i.invalid_data_rate,
+                        "score": i.score(),
+                    }),
+                )
+            })
+            .collect::<serde_json::map::Map<String, serde_json::Value>>()
+            .into();
+
+        let reconciliation: serde_json::Value = self
+            .reconciliation
+            .iter()
+            .map(|(r, m)| {
+                (
+                    format!("{:?}", r),
+                    serde_json::json!({
+                        "rounds": m.rounds,
+                        "filter_bytes": m.filter_bytes,
+                        "missing_hash_bytes": m.missing_hash_bytes,
+                        "decode_failures": m.decode_failures,
+                        "bytes_per_round": m.bytes_per_round(),
                     }),
                 )
             })
@@ -384,9 +647,44 @@ impl Metrics {
         serde_json::json!({
             "aggExtrapCov": *self.agg_extrap_cov,
             "agents": agents,
+            "reconciliation": reconciliation,
+            "rounds": self.dump_round_history(),
         })
     }
 
+    /// Record a recent gossip round which reconciles op hashes this way.
+    pub fn record_reconciliation_round(&mut self, reconciliation: OpReconciliation) {
+        self.reconciliation
+            .entry(reconciliation)
+            .or_default()
+            .rounds += 1;
+    }
+
+    /// Record the bytes of op filters sent.
+    pub fn record_op_filter_bytes(&mut self, reconciliation: OpReconciliation, bytes: usize) {
+        self.reconciliation
+            .entry(reconciliation)
+            .or_default()
+            .filter_bytes += bytes as u64;
+    }
+
+    /// Record the bytes of op hashes sent because they were
+    /// missing from a remote filter.
+    pub fn record_missing_hash_bytes(&mut self, reconciliation: OpReconciliation, bytes: usize) {
+        self.reconciliation
+            .entry(reconciliation)
+            .or_default()
+            .missing_hash_bytes += bytes as u64;
+    }
+
+    /// Record a remote IBLT which could not be decoded.
+    pub fn record_iblt_decode_failure(&mut self) {
+        self.reconciliation
+            .entry(OpReconciliation::Iblt)
+            .or_default()
+            .decode_failures += 1;
+    }
+
     /// Record an individual extrapolated coverage event
     /// (either from us or a remote)
     /// and add it to our running aggregate extrapolated coverage metric.
@@ -436,6 +734,38 @@ impl Metrics {
         }
     }
 
+    /// Record whether a request or gossip round with
+    /// the remote agents timed out.
+    pub fn record_timeout_event<'a, T, I>(&mut self, timed_out: bool, remote_agent_list: I)
+    where
+        T: Into<AgentLike<'a>>,
+        I: IntoIterator<Item = T>,
+    {
+        for agent_info in remote_agent_list {
+            let history = self
+                .agent_history
+                .entry(agent_info.into().agent().clone())
+                .or_default();
+            history.timeout_rate.push(if timed_out { 100 } else { 0 });
+        }
+    }
+
+    /// Record whether data from the remote agents
+    /// was found to be valid by the host.
+    pub fn record_data_validity_event<'a, T, I>(&mut self, valid: bool, remote_agent_list: I)
+    where
+        T: Into<AgentLike<'a>>,
+        I: IntoIterator<Item = T>,
+    {
+        for agent_info in remote_agent_list {
+            let history = self
+                .agent_history
+                .entry(agent_info.into().agent().clone())
+                .or_default();
+            history.invalid_data_rate.push(if valid { 0 } else { 100 });
+        }
+    }
+
     /// Record a gossip round has been initiated by us.
     pub fn record_initiate<'a, T, I>(&mut self, remote_agent_list: I, gossip_type: GossipModuleType)
     where
@@ -496,6 +826,7 @@ impl Metrics {
                 .entry(agent_info.into().agent().clone())
                 .or_default();
             history.reachability_quotient.push(100);
+            history.timeout_rate.push(0);
             let round = RoundMetric {
                 instant: Instant::now(),
                 gossip_type,
@@ -573,10 +904,125 @@ impl Metrics {
         let history = self.node_history.entry(node.clone()).or_default();
         let r = history.current_round.take();
         if let Some(r) = r {
+            if history.completed_rounds.len() >= MAX_COMPLETED_ROUNDS {
+                history.completed_rounds.pop_front();
+            }
             history.completed_rounds.push_back(r.completed(error))
         }
     }
 
+    /// Record gossip sent to or received from a remote node.
+    /// Traffic after a round has completed is added
+    /// to that completed round.
+    pub fn record_round_traffic(&mut self, node: &NodeId, sent: bool, traffic: &RoundTraffic) {
+        if let Some(history) = self.node_history.get_mut(node) {
+            let details = match (
+                &mut history.current_round,
+                history.completed_rounds.back_mut(),
+            ) {
+                (Some(current), _) => &mut current.details,
+                (None, Some(completed)) => &mut completed.details,
+                (None, None) => return,
+            };
+            if sent {
+                details.sent.add(traffic);
+            } else {
+                details.received.add(traffic);
+            }
+        }
+    }
+
+    /// Record why the current round with a remote node failed.
+    pub fn record_round_error(&mut self, node: &NodeId, message: String) {
+        if let Some(current) = self
+            .node_history
+            .get_mut(node)
+            .and_then(|h| h.current_round.as_mut())
+        {
+            current.details.error_message = Some(message);
+        }
+    }
+
+    /// Dump the current and recently completed rounds with each remote node
+    pub fn dump_round_history(&self) -> serde_json::Value {
+        fn traffic(t: &RoundTraffic) -> serde_json::Value {
+            serde_json::json!({
+                "messages": t.messages,
+                "bytes": t.bytes,
+                "op_hashes": t.op_hashes,
+                "agents": t.agents,
+                "regions": t.regions,
+            })
+        }
+        fn round(
+            id: &str,
+            gossip_type: &GossipModuleType,
+            start_time: Instant,
+            end_time: Option<Instant>,
+            error: bool,
+            details: &RoundDetails,
+        ) -> serde_json::Value {
+            serde_json::json!({
+                "id": id,
+                "gossip_type": format!("{:?}", gossip_type),
+                "initiated": details.initiated,
+                "started_ms_ago": start_time.elapsed().as_millis() as u64,
+                "duration_ms": end_time
+                    .map(|end| end.duration_since(start_time).as_millis() as u64),
+                "complete": end_time.is_some(),
+                "error": error,
+                "error_message": details.error_message,
+                "remote_arcs": details.remote_arcs,
+                "common_arcs": details.common_arcs,
+                "regions_compared": details.regions_compared,
+                "sent": traffic(&details.sent),
+                "received": traffic(&details.received),
+            })
+        }
+
+        self.node_history
+            .iter()
+            .map(|(node, history)| {
+                let mut rounds: Vec<_> = history
+                    .completed_rounds
+                    .iter()
+                    .map(|r| {
+                        round(
+                            &r.id,
+                            &r.gossip_type,
+                            r.start_time,
+                            Some(r.end_time),
+                            r.error,
+                            &r.details,
+                        )
+                    })
+                    .collect();
+                if let Some(r) = &history.current_round {
+                    rounds.push(round(
+                        &r.id,
+                        &r.gossip_type,
+                        r.start_time,
+                        None,
+                        false,
+                        &r.details,
+                    ));
+                }
+                (
+                    base64::encode_config(&node[..], base64::URL_SAFE_NO_PAD),
+                    serde_json::json!({
+                        "remote_agents": history
+                            .remote_agents
+                            .iter()
+                            .map(|a| a.to_string())
+                            .collect::<Vec<_>>(),
+                        "rounds": rounds,
+                    }),
+                )
+            })
+            .collect::<serde_json::map::Map<String, serde_json::Value>>()
+            .into()
+    }
+
     /// Record that we should force initiate the next few rounds.
     pub fn record_force_initiate(&mut self) {
         self.force_initiates = MAX_TRIGGERS;
@@ -673,6 +1119,56 @@ impl Metrics {
         }
     }
 
+    /// Return the average (mean) score of the supplied remote agents.
+    /// See [`PeerAgentHistory::score`].
+    pub fn peer_score<'a, T, I>(&self, remote_agent_list: I) -> f32
+    where
+        T: Into<AgentLike<'a>>,
+        I: IntoIterator<Item = T>,
+    {
+        let (sum, cnt) = remote_agent_list
+            .into_iter()
+            .map(|agent_info| {
+                self.agent_history
+                    .get(agent_info.into().agent())
+                    .map_or(NEUTRAL_PEER_SCORE, |info| info.score())
+            })
+            .fold((0.0, 0.0), |acc, x| (acc.0 + x, acc.1 + 1.0));
+        if cnt <= 0.0 {
+            NEUTRAL_PEER_SCORE
+        } else {
+            sum / cnt
+        }
+    }
+
+    /// How many times longer than usual to wait before gossiping
+    /// with the supplied remote agents again after an error.
+    /// Agents which time out or send invalid data are backed off from,
+    /// up to [`MAX_ERROR_BACKOFF`] times the usual delay.
+    pub fn error_backoff<'a, T, I>(&self, remote_agent_list: I) -> f32
+    where
+        T: Into<AgentLike<'a>>,
+        I: IntoIterator<Item = T>,
+    {
+        remote_agent_list
+            .into_iter()
+            .filter_map(|agent_info| self.agent_history.get(agent_info.into().agent()))
+            .map(|info| {
+                let valid = 1.0 - *info.invalid_data_rate / 100.0;
+                let reliability = (1.0 - *info.timeout_rate / 100.0) * valid * valid;
+                (1.0 / reliability).min(MAX_ERROR_BACKOFF)
+            })
+            .fold(1.0, f32::max)
+    }
+
+    /// The scores of all the remote agents we have metrics for.
+    pub fn peer_scores(&self) -> Vec<(Arc<KitsuneAgent>, PeerScore)> {
+        self.agent_history
+            .iter()
+            .map(|(agent, history)| (agent.clone(), history.peer_score()))
+            .collect()
+    }
+
     /// Getter
     pub fn peer_agent_histories(&self) -> &HashMap<Arc<KitsuneAgent>, PeerAgentHistory> {
         &self.agent_history
@@ -682,6 +1178,11 @@ impl Metrics {
     pub fn peer_node_histories(&self) -> &HashMap<NodeId, PeerNodeHistory> {
         &self.node_history
     }
+
+    /// Getter
+    pub fn reconciliation(&self) -> &HashMap<OpReconciliation, ReconciliationMetrics> {
+        &self.reconciliation
+    }
 }
 
 impl PeerAgentHistory {
@@ -877,4 +1378,92 @@ mod tests {
         a5.push_n(1, 255);
         assert_eq!(1.0, *a5);
     }
+
+    #[test]
+    fn test_peer_scores() {
+        let mut metrics = Metrics::default();
+        let good = Arc::new(KitsuneAgent(vec![1; 36]));
+        let slow = Arc::new(KitsuneAgent(vec![2; 36]));
+        let invalid = Arc::new(KitsuneAgent(vec![3; 36]));
+        let unknown = Arc::new(KitsuneAgent(vec![4; 36]));
+
+        for agent in [&good, &slow, &invalid] {
+            metrics.record_reachability_event(true, [agent]);
+            metrics.record_timeout_event(false, [agent]);
+        }
+        metrics.record_latency_micros(1_000, [&good]);
+        metrics.record_latency_micros(1_000_000, [&slow]);
+        metrics.record_data_validity_event(false, [&invalid]);
+
+        assert_eq!(metrics.peer_score([&good]), 100.0);
+        assert_eq!(metrics.peer_score([&slow]), 100.0 * MIN_LATENCY_FACTOR);
+        assert_eq!(metrics.peer_score([&invalid]), 0.0);
+        assert_eq!(metrics.peer_score([&unknown]), NEUTRAL_PEER_SCORE);
+        assert_eq!(metrics.error_backoff([&good]), 1.0);
+        assert_eq!(metrics.error_backoff([&invalid]), MAX_ERROR_BACKOFF);
+
+        // Scores survive being written to and restored from historical records.
+        let mut restored = Metrics::default();
+        restored.restore_historical(metrics.dump_historical());
+        for agent in [&good, &slow, &invalid] {
+            assert_eq!(
+                restored.agent_history[agent].peer_score(),
+                metrics.agent_history[agent].peer_score()
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_history() {
+        let mut metrics = Metrics::default();
+        let node: NodeId = Arc::new([1; 32]);
+        let mut state = RoundState::new(
+            vec![],
+            Arc::new(kitsune_p2p_types::dht_arc::DhtArcSet::Full),
+            None,
+            Duration::from_secs(60),
+        );
+        state.initiated = true;
+        let ops = ShardedGossipWire::missing_op_hashes(
+            vec![
+                kitsune_p2p_fetch::OpHashSized::new(Arc::new(KitsuneOpHash(vec![0; 36])), None);
+                3
+            ],
+            0,
+        );
+
+        for i in 0..MAX_COMPLETED_ROUNDS + 5 {
+            metrics.update_current_round(&node, GossipModuleType::ShardedRecent, &state);
+            metrics.record_round_traffic(&node, true, &RoundTraffic::message(&ops, 100));
+            if i % 2 == 0 {
+                metrics.record_round_error(&node, "Round timed out".to_string());
+            }
+            metrics.complete_current_round(&node, i % 2 == 0);
+        }
+        // Traffic after the round has completed counts against it.
+        metrics.record_round_traffic(&node, false, &RoundTraffic::message(&ops, 50));
+
+        let history = &metrics.node_history[&node];
+        assert_eq!(history.completed_rounds.len(), MAX_COMPLETED_ROUNDS);
+        let last = history.completed_rounds.back().unwrap();
+        assert!(last.details.initiated);
+        assert_eq!(last.details.common_arcs, vec![DhtArcRange::Full]);
+        assert_eq!(last.details.sent.messages, 1);
+        assert_eq!(last.details.sent.bytes, 100);
+        assert_eq!(last.details.sent.op_hashes, 3);
+        assert_eq!(last.details.received.bytes, 50);
+        assert_eq!(
+            last.details.error_message,
+            Some("Round timed out".to_string())
+        );
+
+        let dump = metrics.dump();
+        let rounds = dump["rounds"].as_object().unwrap();
+        assert_eq!(rounds.len(), 1);
+        let rounds = rounds.values().next().unwrap()["rounds"]
+            .as_array()
+            .unwrap();
+        assert_eq!(rounds.len(), MAX_COMPLETED_ROUNDS);
+        assert_eq!(rounds[0]["sent"]["op_hashes"], 3);
+    }
 }
@@ -5,8 +5,11 @@ use crate::actor::*;
 use crate::event::*;
 use crate::gossip::sharded_gossip::BandwidthThrottles;
 use crate::gossip::sharded_gossip::KitsuneDiagnostics;
+use crate::metrics::MetricsSync;
+use crate::metrics::NEUTRAL_PEER_SCORE;
 use crate::types::gossip::GossipModuleType;
-use crate::types::metrics::KitsuneMetrics;
+use crate::types::metrics::count_wire_write;
+use crate::types::traffic::TrafficShapers;
 use crate::wire::MetricExchangeMsg;
 use crate::*;
 use futures::future::FutureExt;
@@ -92,6 +95,43 @@ ghost_actor::ghost_chan! {
     }
 }
 
+/// The peer metrics of each space, shared with the fetch pool so that it can
+/// limit the retries of each source by its score.
+type SpaceMetrics = Arc<parking_lot::Mutex<HashMap<KSpace, MetricsSync>>>;
+
+/// The peer scores of each space, taken from [`SpaceMetrics`] once per
+/// fetch tick so that the fetch pool doesn't lock the metrics for every source.
+#[derive(Clone, Default)]
+struct PeerScoreSnapshot(Arc<parking_lot::RwLock<HashMap<KSpace, HashMap<KAgent, f32>>>>);
+
+impl PeerScoreSnapshot {
+    fn refresh(&self, space_metrics: &SpaceMetrics) {
+        let scores = space_metrics
+            .lock()
+            .iter()
+            .map(|(space, metrics)| {
+                let scores = metrics
+                    .read()
+                    .peer_agent_histories()
+                    .iter()
+                    .map(|(agent, history)| (agent.clone(), history.score()))
+                    .collect();
+                (space.clone(), scores)
+            })
+            .collect();
+        *self.0.write() = scores;
+    }
+
+    fn get(&self, space: &KSpace, agent: &KAgent) -> f32 {
+        self.0
+            .read()
+            .get(space)
+            .and_then(|scores| scores.get(agent))
+            .copied()
+            .unwrap_or(NEUTRAL_PEER_SCORE)
+    }
+}
+
 pub(crate) struct KitsuneP2pActor {
     channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<Self>,
     internal_sender: ghost_actor::GhostSender<Internal>,
@@ -108,8 +148,12 @@ pub(crate) struct KitsuneP2pActor {
     >,
     config: Arc<KitsuneP2pConfig>,
     bandwidth_throttles: BandwidthThrottles,
+    traffic_shapers: TrafficShapers,
     parallel_notify_permit: Arc<tokio::sync::Semaphore>,
     fetch_pool: FetchPool,
+    space_metrics: SpaceMetrics,
+    /// Shared by all spaces when the network type is `QuicMdns`.
+    mdns: Option<kitsune_p2p_mdns::MdnsService>,
 }
 
 impl KitsuneP2pActor {
@@ -123,22 +167,9 @@ impl KitsuneP2pActor {
     ) -> KitsuneP2pResult<Self> {
         crate::types::metrics::init();
 
-        let metrics = Tx2ApiMetrics::default().set_write_len(|d, l| {
-            let t = match d {
-                "Wire::Failure" => KitsuneMetrics::Failure,
-                "Wire::Call" => KitsuneMetrics::Call,
-                "Wire::CallResp" => KitsuneMetrics::CallResp,
-                "Wire::Notify" => KitsuneMetrics::Notify,
-                "Wire::NotifyResp" => KitsuneMetrics::NotifyResp,
-                "Wire::Gossip" => KitsuneMetrics::Gossip,
-                "Wire::PeerGet" => KitsuneMetrics::PeerGet,
-                "Wire::PeerGetResp" => KitsuneMetrics::PeerGetResp,
-                "Wire::PeerQuery" => KitsuneMetrics::PeerQuery,
-                "Wire::PeerQueryResp" => KitsuneMetrics::PeerQueryResp,
-                _ => return,
-            };
-            KitsuneMetrics::count(t, l);
-        });
+        let metrics = Tx2ApiMetrics::default().set_write_len(count_wire_write);
+
+        let traffic_shapers = TrafficShapers::new(config.tuning_params.clone());
 
         let mut ep_hnd = None;
         let mut ep_evt = None;
@@ -146,7 +177,9 @@ impl KitsuneP2pActor {
         #[cfg(feature = "tx2")]
         if ep_hnd.is_none() && config.is_tx2() {
             tracing::trace!("tx2");
-            let (h, e) = MetaNet::new_tx2(config.clone(), tls_config, metrics).await?;
+            let (h, e) =
+                MetaNet::new_tx2(config.clone(), tls_config, metrics, traffic_shapers.clone())
+                    .await?;
             ep_hnd = Some(h);
             ep_evt = Some(e);
         }
@@ -154,12 +187,14 @@ impl KitsuneP2pActor {
         #[cfg(feature = "tx5")]
         if ep_hnd.is_none() && config.is_tx5() {
             tracing::trace!("tx5");
-            let signal_url = match config.transport_pool.get(0).unwrap() {
-                TransportConfig::WebRTC { signal_url } => signal_url.clone(),
-                _ => unreachable!(),
-            };
-            let (h, e) =
-                MetaNet::new_tx5(config.tuning_params.clone(), host.clone(), signal_url).await?;
+            let tx5_config = config.to_tx5().map_err(KitsuneP2pError::other)?;
+            let (h, e) = MetaNet::new_tx5(
+                config.tuning_params.clone(),
+                host.clone(),
+                tx5_config,
+                traffic_shapers.clone(),
+            )
+            .await?;
             ep_hnd = Some(h);
             ep_evt = Some(e);
         }
@@ -169,6 +204,11 @@ impl KitsuneP2pActor {
             _ => return Err("tx2 or tx5 feature must be enabled".into()),
         };
 
+        let mdns = match config.network_type {
+            NetworkType::QuicMdns => Some(space::mdns::spawn_service(&config.tuning_params)?),
+            NetworkType::QuicBootstrap => None,
+        };
+
         struct FetchResponseConfig(kitsune_p2p_types::config::KitsuneP2pTuningParams);
 
         impl kitsune_p2p_fetch::FetchResponseConfig for FetchResponseConfig {
@@ -210,16 +250,43 @@ impl KitsuneP2pActor {
             config.tuning_params.clone(),
         ));
 
-        // TODO - use a real config
-        let fetch_pool = FetchPool::new_bitwise_or();
+        struct FetchPoolTuning {
+            tuning_params: kitsune_p2p_types::config::KitsuneP2pTuningParams,
+            peer_scores: PeerScoreSnapshot,
+        }
+
+        impl FetchPoolConfig for FetchPoolTuning {
+            fn source_retry_limit(&self, space: &KSpace, source: &FetchSource) -> Option<u32> {
+                let limit = match self.tuning_params.fetch_pool_source_retry_limit {
+                    0 => return None,
+                    limit => limit,
+                };
+                let FetchSource::Agent(agent) = source;
+                let score = self.peer_scores.get(space, agent);
+                Some(source_retry_limit_for_score(limit, score))
+            }
+
+            fn merge_fetch_contexts(&self, a: u32, b: u32) -> u32 {
+                a | b
+            }
+        }
+
+        let space_metrics = SpaceMetrics::default();
+        let peer_scores = PeerScoreSnapshot::default();
+        let fetch_pool = FetchPool::new(Arc::new(FetchPoolTuning {
+            tuning_params: config.tuning_params.clone(),
+            peer_scores: peer_scores.clone(),
+        }));
 
         // Start a loop to handle our fetch queue fetch items.
         {
             let fetch_pool = fetch_pool.clone();
+            let space_metrics = space_metrics.clone();
             let i_s = internal_sender.clone();
             let host = host.clone();
             tokio::task::spawn(async move {
                 loop {
+                    peer_scores.refresh(&space_metrics);
                     let list = fetch_pool.get_items_to_fetch();
 
                     for (key, space, source, context) in list {
@@ -625,12 +692,22 @@ impl KitsuneP2pActor {
             spaces: HashMap::new(),
             config: Arc::new(config),
             bandwidth_throttles,
+            traffic_shapers,
             parallel_notify_permit,
             fetch_pool,
+            space_metrics,
+            mdns,
         })
     }
 }
 
+/// The retry limit of a fetch source with this peer score. A source with a
+/// neutral score gets the configured `limit`, and better or worse sources
+/// proportionally more or fewer attempts, from one up to twice the limit.
+fn source_retry_limit_for_score(limit: u32, score: f32) -> u32 {
+    ((limit as f32 * score / NEUTRAL_PEER_SCORE).round() as u32).clamp(1, limit.saturating_mul(2))
+}
+
 use ghost_actor::dependencies::must_future::MustBoxFuture;
 impl ghost_actor::GhostControlHandler for KitsuneP2pActor {
     fn handle_ghost_actor_shutdown(mut self) -> MustBoxFuture<'static, ()> {
@@ -945,8 +1022,16 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         let host = self.host.clone();
         let config = Arc::clone(&self.config);
         let bandwidth_throttles = self.bandwidth_throttles.clone();
+        let traffic_shapers = self.traffic_shapers.clone();
         let parallel_notify_permit = self.parallel_notify_permit.clone();
         let fetch_pool = self.fetch_pool.clone();
+        let metrics = self
+            .space_metrics
+            .lock()
+            .entry(space.clone())
+            .or_default()
+            .clone();
+        let mdns = self.mdns.clone();
 
         let space_sender = match self.spaces.entry(space.clone()) {
             Entry::Occupied(entry) => entry.into_mut(),
@@ -957,8 +1042,11 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
                     host,
                     config,
                     bandwidth_throttles,
+                    traffic_shapers,
                     parallel_notify_permit,
                     fetch_pool,
+                    metrics,
+                    mdns,
                 )
                 .await
                 .expect("cannot fail to create space");
@@ -1158,6 +1246,24 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         .boxed()
         .into())
     }
+
+    fn handle_report_data_validity(
+        &mut self,
+        space: KSpace,
+        agent: KAgent,
+        valid: bool,
+    ) -> KitsuneP2pHandlerResult<()> {
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return unit_ok_fut(),
+            Some(space) => space.get(),
+        };
+        Ok(async move {
+            let (space_sender, _) = space_sender.await;
+            space_sender.report_data_validity(space, agent, valid).await
+        }
+        .boxed()
+        .into())
+    }
 }
 
 #[cfg(any(test, feature = "test_utils"))]
@@ -1226,3 +1332,17 @@ mockall::mock! {
 impl ghost_actor::GhostHandler<KitsuneP2pEvent> for MockKitsuneP2pEventHandler {}
 #[cfg(any(test, feature = "test_utils"))]
 impl ghost_actor::GhostControlHandler for MockKitsuneP2pEventHandler {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_retry_limit_follows_peer_score() {
+        assert_eq!(source_retry_limit_for_score(4, NEUTRAL_PEER_SCORE), 4);
+        assert_eq!(source_retry_limit_for_score(4, 100.0), 8);
+        assert_eq!(source_retry_limit_for_score(4, 25.0), 2);
+        // Every source gets at least one attempt
+        assert_eq!(source_retry_limit_for_score(4, 0.0), 1);
+    }
+}
@@ -9,6 +9,7 @@
 #![allow(clippy::blocks_in_if_conditions)]
 //! Networking abstraction to handle feature flipping.
 
+use crate::types::traffic::{TrafficClass, TrafficShapers};
 use crate::wire::WireData;
 use crate::*;
 use futures::sink::SinkExt;
@@ -113,22 +114,23 @@ pub type MetaNetEvtRecv = futures::channel::mpsc::Receiver<MetaNetEvt>;
 
 type ResStore = Arc<Mutex<HashMap<u64, tokio::sync::oneshot::Sender<wire::Wire>>>>;
 
+/// A connection, which shapes outgoing messages by the space they're sent in.
 #[derive(Debug, Clone)]
 pub enum MetaNetCon {
     #[cfg(feature = "tx2")]
-    Tx2(Tx2ConHnd<wire::Wire>),
+    Tx2(Tx2ConHnd<wire::Wire>, TrafficShapers),
 
     #[cfg(feature = "tx5")]
-    Tx5(tx5::Ep, tx5::Tx5Url, ResStore),
+    Tx5(tx5::Ep, tx5::Tx5Url, ResStore, TrafficShapers),
 }
 
 impl PartialEq for MetaNetCon {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             #[cfg(feature = "tx2")]
-            (MetaNetCon::Tx2(a), MetaNetCon::Tx2(b)) => a == b,
+            (MetaNetCon::Tx2(a, _), MetaNetCon::Tx2(b, _)) => a == b,
             #[cfg(feature = "tx5")]
-            (MetaNetCon::Tx5(a, _, _), MetaNetCon::Tx5(b, _, _)) => a == b,
+            (MetaNetCon::Tx5(a, _, _, _), MetaNetCon::Tx5(b, _, _, _)) => a == b,
             _ => false,
         }
     }
@@ -140,7 +142,7 @@ impl MetaNetCon {
     pub async fn close(&self, code: u32, reason: &str) {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNetCon::Tx2(con) = self {
+            if let MetaNetCon::Tx2(con, _) = self {
                 con.close(code, reason).await;
                 return;
             }
@@ -152,7 +154,7 @@ impl MetaNetCon {
     pub fn is_closed(&self) -> bool {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNetCon::Tx2(con) = self {
+            if let MetaNetCon::Tx2(con, _) = self {
                 return con.is_closed();
             }
         }
@@ -170,23 +172,31 @@ impl MetaNetCon {
     pub async fn notify(&self, payload: &wire::Wire, timeout: KitsuneTimeout) -> KitsuneResult<()> {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNetCon::Tx2(con) = self {
+            if let MetaNetCon::Tx2(con, traffic) = self {
+                traffic.outgoing(payload).await;
                 return con.notify(payload, timeout).await;
             }
         }
 
         #[cfg(feature = "tx5")]
         {
-            if let MetaNetCon::Tx5(ep, rem_url, _res_store) = self {
+            if let MetaNetCon::Tx5(ep, rem_url, _res_store, traffic) = self {
+                traffic.outgoing(payload).await;
                 let wire = payload.encode_vec().map_err(KitsuneError::other)?;
+                let len = wire.len();
                 let wrap = WireWrap::notify(WireData(wire));
 
                 let mut writer = tx5::Buf::from_writer().map_err(KitsuneError::other)?;
                 wrap.encode(&mut writer).map_err(KitsuneError::other)?;
                 let data = writer.finish();
-                ep.send(rem_url.clone(), data)
-                    .await
-                    .map_err(KitsuneError::other)?;
+                timeout
+                    .mix("tx5 notify", async {
+                        ep.send(rem_url.clone(), data)
+                            .await
+                            .map_err(KitsuneError::other)
+                    })
+                    .await?;
+                crate::types::metrics::count_wire_write(payload.variant_type(), len);
                 return Ok(());
             }
         }
@@ -201,36 +211,43 @@ impl MetaNetCon {
     ) -> KitsuneResult<wire::Wire> {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNetCon::Tx2(con) = self {
+            if let MetaNetCon::Tx2(con, traffic) = self {
+                traffic.outgoing(payload).await;
                 return con.request(payload, timeout).await;
             }
         }
 
         #[cfg(feature = "tx5")]
         {
-            if let MetaNetCon::Tx5(ep, rem_url, res_store) = self {
+            if let MetaNetCon::Tx5(ep, rem_url, res_store, traffic) = self {
+                traffic.outgoing(payload).await;
                 static MSG_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
                 let msg_id = MSG_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                let (s, r) = tokio::sync::oneshot::channel();
-                res_store.lock().insert(msg_id, s);
-
-                let res_store = res_store.clone();
-                tokio::task::spawn(async move {
-                    // TODO - use tuning_params.implicit_timeout
-                    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
-                    res_store.lock().remove(&msg_id);
-                });
-
                 let wire = payload.encode_vec().map_err(KitsuneError::other)?;
+                let len = wire.len();
                 let wrap = WireWrap::request(msg_id, WireData(wire));
 
                 let mut writer = tx5::Buf::from_writer().map_err(KitsuneError::other)?;
                 wrap.encode(&mut writer).map_err(KitsuneError::other)?;
                 let data = writer.finish();
-                ep.send(rem_url.clone(), data)
-                    .await
-                    .map_err(KitsuneError::other)?;
-                return Ok(r.await.map_err(|_| KitsuneError::other("timeout"))?);
+
+                let (s, r) = tokio::sync::oneshot::channel();
+                res_store.lock().insert(msg_id, s);
+
+                let res = timeout
+                    .mix("tx5 request", async {
+                        ep.send(rem_url.clone(), data)
+                            .await
+                            .map_err(KitsuneError::other)?;
+                        crate::types::metrics::count_wire_write(payload.variant_type(), len);
+                        r.await
+                            .map_err(|_| KitsuneError::other("tx5 response channel closed"))
+                    })
+                    .await;
+
+                // Make sure the response slot doesn't leak on errors or timeouts.
+                res_store.lock().remove(&msg_id);
+                return res;
             }
         }
 
@@ -240,14 +257,14 @@ impl MetaNetCon {
     pub fn peer_id(&self) -> Arc<[u8; 32]> {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNetCon::Tx2(con) = self {
+            if let MetaNetCon::Tx2(con, _) = self {
                 return con.peer_cert().into();
             }
         }
 
         #[cfg(feature = "tx5")]
         {
-            if let MetaNetCon::Tx5(_con, rem_url, _res_store) = self {
+            if let MetaNetCon::Tx5(_con, rem_url, _res_store, _) = self {
                 let id = rem_url.id().unwrap();
                 return Arc::new(id.0);
             }
@@ -262,11 +279,11 @@ impl MetaNetCon {
 pub enum MetaNet {
     /// Tx2 Abstraction
     #[cfg(feature = "tx2")]
-    Tx2(Tx2EpHnd<wire::Wire>),
+    Tx2(Tx2EpHnd<wire::Wire>, TrafficShapers),
 
     /// Tx5 Abstraction
     #[cfg(feature = "tx5")]
-    Tx5(tx5::Ep, tx5::Tx5Url, ResStore),
+    Tx5(tx5::Ep, tx5::Tx5Url, ResStore, TrafficShapers),
 }
 
 impl MetaNet {
@@ -276,6 +293,7 @@ impl MetaNet {
         config: KitsuneP2pConfig,
         tls_config: kitsune_p2p_types::tls::TlsConfig,
         metrics: Tx2ApiMetrics,
+        traffic: TrafficShapers,
     ) -> KitsuneP2pResult<(Self, MetaNetEvtRecv)> {
         let tuning_params = config.tuning_params.clone();
         let (mut evt_send, evt_recv) =
@@ -325,10 +343,26 @@ impl MetaNet {
         let f = if !is_mock {
             let mut conf = kitsune_p2p_proxy::tx2::ProxyConfig::default();
             conf.tuning_params = Some(config.tuning_params.clone());
+            let relay = traffic.relay();
+            conf.relay_shaper = Some(Arc::new(move |bytes| {
+                let relay = relay.clone();
+                Box::pin(async move { relay.outgoing_bytes(TrafficClass::Relay, bytes).await })
+            }));
             match tx2_conf.use_proxy {
                 KitsuneP2pTx2ProxyConfig::NoProxy => (),
-                KitsuneP2pTx2ProxyConfig::Specific(proxy_url) => {
+                KitsuneP2pTx2ProxyConfig::Specific {
+                    proxy_url,
+                    auth_token,
+                } => {
                     conf.client_of_remote_proxy = ProxyRemoteType::Specific(proxy_url);
+                    conf.proxy_auth_token = auth_token;
+                }
+                KitsuneP2pTx2ProxyConfig::List {
+                    proxy_urls,
+                    auth_token,
+                } => {
+                    conf.client_of_remote_proxy = ProxyRemoteType::List(proxy_urls);
+                    conf.proxy_auth_token = auth_token;
                 }
                 KitsuneP2pTx2ProxyConfig::Bootstrap {
                     bootstrap_url,
@@ -380,6 +414,7 @@ impl MetaNet {
         // capture endpoint handle
         let ep_hnd = ep.handle().clone();
 
+        let traffic2 = traffic.clone();
         tokio::task::spawn(async move {
             let tuning_params = &tuning_params;
             while let Some(evt) = ep.next().await {
@@ -388,7 +423,7 @@ impl MetaNet {
                         if evt_send
                             .send(MetaNetEvt::Connected {
                                 remote_url: url.to_string(),
-                                con: MetaNetCon::Tx2(con),
+                                con: MetaNetCon::Tx2(con, traffic2.clone()),
                             })
                             .await
                             .is_err()
@@ -400,7 +435,7 @@ impl MetaNet {
                         if evt_send
                             .send(MetaNetEvt::Connected {
                                 remote_url: url.to_string(),
-                                con: MetaNetCon::Tx2(con),
+                                con: MetaNetCon::Tx2(con, traffic2.clone()),
                             })
                             .await
                             .is_err()
@@ -412,7 +447,7 @@ impl MetaNet {
                         if evt_send
                             .send(MetaNetEvt::Disconnected {
                                 remote_url: url.to_string(),
-                                con: MetaNetCon::Tx2(con),
+                                con: MetaNetCon::Tx2(con, traffic2.clone()),
                             })
                             .await
                             .is_err()
@@ -427,13 +462,18 @@ impl MetaNet {
                         respond,
                     }) => {
                         let timeout = tuning_params.implicit_timeout();
+                        // Responses are shaped in the space of the request.
+                        let shaper = traffic2.for_wire(&data);
                         if evt_send
                             .send(MetaNetEvt::Request {
                                 remote_url: url.to_string(),
-                                con: MetaNetCon::Tx2(con),
+                                con: MetaNetCon::Tx2(con, traffic2.clone()),
                                 data,
                                 respond: Box::new(move |data| {
                                     let out: RespondFut = Box::pin(async move {
+                                        if let Some(shaper) = shaper {
+                                            shaper.outgoing(&data).await;
+                                        }
                                         let _ = respond.respond(data, timeout).await;
                                     });
                                     out
@@ -449,7 +489,7 @@ impl MetaNet {
                         if evt_send
                             .send(MetaNetEvt::Notify {
                                 remote_url: url.to_string(),
-                                con: MetaNetCon::Tx2(con),
+                                con: MetaNetCon::Tx2(con, traffic2.clone()),
                                 data,
                             })
                             .await
@@ -463,7 +503,7 @@ impl MetaNet {
             }
         });
 
-        Ok((MetaNet::Tx2(ep_hnd), evt_recv))
+        Ok((MetaNet::Tx2(ep_hnd, traffic), evt_recv))
     }
 
     /// Construct abstraction with tx5 backend.
@@ -471,8 +511,14 @@ impl MetaNet {
     pub async fn new_tx5(
         tuning_params: KitsuneP2pTuningParams,
         host: HostApi,
-        signal_url: String,
+        tx5_config: KitsuneP2pTx5Config,
+        traffic: TrafficShapers,
     ) -> KitsuneP2pResult<(Self, MetaNetEvtRecv)> {
+        let KitsuneP2pTx5Config {
+            signal_urls,
+            ice_servers,
+        } = tx5_config;
+
         let (mut evt_send, evt_recv) =
             futures::channel::mpsc::channel(tuning_params.concurrent_limit_per_thread);
 
@@ -492,21 +538,53 @@ impl MetaNet {
             tx5_config.set_lair_tag(lair_tag);
         }
 
+        if !ice_servers.is_empty() {
+            tx5_config.set_ice_servers(
+                serde_json::to_value(&ice_servers).map_err(KitsuneP2pError::other)?,
+            );
+        }
+
         let (ep_hnd, mut ep_evt) = tx5::Ep::with_config(tx5_config).await?;
 
-        let cli_url = ep_hnd.listen(tx5::Tx5Url::new(&signal_url)?).await?;
+        let mut cli_url = None;
+        for signal_url in signal_urls.iter() {
+            let res = match tx5::Tx5Url::new(signal_url) {
+                Ok(signal_url) => ep_hnd.listen(signal_url).await,
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(url) => {
+                    cli_url = Some(url);
+                    break;
+                }
+                Err(err) => {
+                    tracing::warn!(?err, %signal_url, "could not listen at tx5 signal server");
+                }
+            }
+        }
+        let cli_url = cli_url.ok_or_else(|| {
+            KitsuneP2pError::other(format!(
+                "could not listen at any tx5 signal server: {:?}",
+                signal_urls
+            ))
+        })?;
         tracing::info!(%cli_url, "tx5 listening at url");
 
         let res_store = Arc::new(Mutex::new(HashMap::new()));
 
         let ep_hnd2 = ep_hnd.clone();
         let res_store2 = res_store.clone();
+        let traffic2 = traffic.clone();
         tokio::task::spawn(async move {
             while let Some(evt) = ep_evt.recv().await {
                 let evt = match evt {
                     Ok(evt) => evt,
-                    // TODO - FIXME - handle errors / reconnect?
-                    Err(err) => panic!("{:?}", err),
+                    Err(err) => {
+                        // Errors are per connection or signal message,
+                        // the endpoint itself keeps running.
+                        tracing::warn!(?err, "tx5 endpoint error");
+                        continue;
+                    }
                 };
 
                 match evt {
@@ -518,6 +596,7 @@ impl MetaNet {
                                     ep_hnd2.clone(),
                                     rem_cli_url,
                                     res_store2.clone(),
+                                    traffic2.clone(),
                                 ),
                             })
                             .await
@@ -534,6 +613,7 @@ impl MetaNet {
                                     ep_hnd2.clone(),
                                     rem_cli_url,
                                     res_store2.clone(),
+                                    traffic2.clone(),
                                 ),
                             })
                             .await
@@ -558,6 +638,7 @@ impl MetaNet {
                                                     ep_hnd2.clone(),
                                                     rem_cli_url,
                                                     res_store2.clone(),
+                                                    traffic2.clone(),
                                                 ),
                                                 data,
                                             })
@@ -578,8 +659,13 @@ impl MetaNet {
                                     Ok((_, data)) => {
                                         let ep_hnd = ep_hnd2.clone();
                                         let rem_cli_url2 = rem_cli_url.clone();
+                                        // Responses are shaped in the space of the request.
+                                        let shaper = traffic2.for_wire(&data);
                                         let respond: Respond = Box::new(move |data| {
                                             let out: RespondFut = Box::pin(async move {
+                                                if let Some(shaper) = shaper {
+                                                    shaper.outgoing(&data).await;
+                                                }
                                                 let wire = match data.encode_vec() {
                                                     Ok(wire) => wire,
                                                     Err(_) => return,
@@ -605,6 +691,7 @@ impl MetaNet {
                                                     ep_hnd2.clone(),
                                                     rem_cli_url,
                                                     res_store2.clone(),
+                                                    traffic2.clone(),
                                                 ),
                                                 data,
                                                 respond,
@@ -648,20 +735,20 @@ impl MetaNet {
             }
         });
 
-        Ok((MetaNet::Tx5(ep_hnd, cli_url, res_store), evt_recv))
+        Ok((MetaNet::Tx5(ep_hnd, cli_url, res_store, traffic), evt_recv))
     }
 
     pub fn local_addr(&self) -> KitsuneResult<String> {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNet::Tx2(ep) = self {
+            if let MetaNet::Tx2(ep, _) = self {
                 return ep.local_addr().map(|s| s.to_string());
             }
         }
 
         #[cfg(feature = "tx5")]
         {
-            if let MetaNet::Tx5(_ep, cli_url, _res_store) = self {
+            if let MetaNet::Tx5(_ep, cli_url, _res_store, _) = self {
                 return Ok(cli_url.to_string());
             }
         }
@@ -672,14 +759,14 @@ impl MetaNet {
     pub fn local_id(&self) -> Arc<[u8; 32]> {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNet::Tx2(ep) = self {
+            if let MetaNet::Tx2(ep, _) = self {
                 return ep.local_cert().into();
             }
         }
 
         #[cfg(feature = "tx5")]
         {
-            if let MetaNet::Tx5(_ep, cli_url, _res_store) = self {
+            if let MetaNet::Tx5(_ep, cli_url, _res_store, _) = self {
                 if let Some(id) = cli_url.id() {
                     return Arc::new(id.0);
                 }
@@ -692,7 +779,7 @@ impl MetaNet {
     pub async fn close(&self, code: u32, reason: &str) {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNet::Tx2(ep) = self {
+            if let MetaNet::Tx2(ep, _) = self {
                 ep.close(code, reason).await;
                 return;
             }
@@ -708,19 +795,20 @@ impl MetaNet {
     ) -> KitsuneResult<MetaNetCon> {
         #[cfg(feature = "tx2")]
         {
-            if let MetaNet::Tx2(ep) = self {
+            if let MetaNet::Tx2(ep, traffic) = self {
                 let con = ep.get_connection(remote_url, timeout).await?;
-                return Ok(MetaNetCon::Tx2(con));
+                return Ok(MetaNetCon::Tx2(con, traffic.clone()));
             }
         }
 
         #[cfg(feature = "tx5")]
         {
-            if let MetaNet::Tx5(ep, _cli_url, res_store) = self {
+            if let MetaNet::Tx5(ep, _cli_url, res_store, traffic) = self {
                 return Ok(MetaNetCon::Tx5(
                     ep.clone(),
                     tx5::Tx5Url::new(remote_url).map_err(KitsuneError::other)?,
                     res_store.clone(),
+                    traffic.clone(),
                 ));
             }
         }
@@ -728,3 +816,102 @@ impl MetaNet {
         Err("invalid features".into())
     }
 }
+
+#[cfg(all(test, feature = "tx5_signal_srv"))]
+mod tests {
+    use super::*;
+    use crate::test_util::LocalSignalServer;
+
+    async fn tx5_node(signal_urls: Vec<String>) -> KitsuneP2pResult<(MetaNet, MetaNetEvtRecv)> {
+        MetaNet::new_tx5(
+            KitsuneP2pTuningParams::default(),
+            HostStub::new(),
+            KitsuneP2pTx5Config {
+                signal_urls,
+                ice_servers: Vec::new(),
+            },
+            TrafficShapers::new(KitsuneP2pTuningParams::default()),
+        )
+        .await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tx5_request_and_notify_over_loopback() {
+        let signal = LocalSignalServer::spawn().await.unwrap();
+        let (net1, _evt1) = tx5_node(vec![signal.url().to_string()]).await.unwrap();
+        let (net2, mut evt2) = tx5_node(vec![signal.url().to_string()]).await.unwrap();
+
+        let (notify_send, mut notify_recv) = tokio::sync::mpsc::unbounded_channel();
+        tokio::task::spawn(async move {
+            while let Some(evt) = evt2.next().await {
+                match evt {
+                    // Echo requests back to the sender.
+                    MetaNetEvt::Request { data, respond, .. } => respond(data).await,
+                    MetaNetEvt::Notify { data, .. } => {
+                        let _ = notify_send.send(data);
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        let timeout = KitsuneTimeout::from_millis(30_000);
+        let con = net1
+            .get_connection(net2.local_addr().unwrap(), timeout)
+            .await
+            .unwrap();
+        assert_eq!(*con.peer_id(), *net2.local_id());
+
+        let payload = wire::Wire::failure("echo".to_string());
+        assert_eq!(con.request(&payload, timeout).await.unwrap(), payload);
+
+        con.notify(&payload, timeout).await.unwrap();
+        assert_eq!(notify_recv.recv().await.unwrap(), payload);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tx5_request_times_out_without_response() {
+        let signal = LocalSignalServer::spawn().await.unwrap();
+        let (net1, _evt1) = tx5_node(vec![signal.url().to_string()]).await.unwrap();
+        // Never answer requests.
+        let (net2, _evt2) = tx5_node(vec![signal.url().to_string()]).await.unwrap();
+
+        let con = net1
+            .get_connection(
+                net2.local_addr().unwrap(),
+                KitsuneTimeout::from_millis(30_000),
+            )
+            .await
+            .unwrap();
+        let err = con
+            .request(
+                &wire::Wire::failure("hello".to_string()),
+                KitsuneTimeout::from_millis(500),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind(), KitsuneErrorKind::TimedOut(_)));
+        if let MetaNetCon::Tx5(_, _, res_store, _) = &con {
+            assert!(res_store.lock().is_empty());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tx5_falls_back_to_next_signal_server() {
+        let signal = LocalSignalServer::spawn().await.unwrap();
+
+        // Nothing is listening on port 1.
+        let (net, _evt) = tx5_node(vec![
+            "ws://127.0.0.1:1".to_string(),
+            signal.url().to_string(),
+        ])
+        .await
+        .unwrap();
+        let port = signal.url().rsplit(':').next().unwrap();
+        assert!(net.local_addr().unwrap().contains(port));
+
+        assert!(tx5_node(vec!["ws://127.0.0.1:1".to_string()])
+            .await
+            .is_err());
+    }
+}
@@ -1,15 +1,14 @@
 use super::*;
 use crate::metrics::*;
 use crate::types::gossip::GossipModule;
+use crate::types::traffic::{TrafficShaper, TrafficShapers};
 use ghost_actor::dependencies::tracing;
 use kitsune_p2p_fetch::FetchPool;
 use kitsune_p2p_mdns::*;
 use kitsune_p2p_types::agent_info::AgentInfoSigned;
-use kitsune_p2p_types::codec::{rmp_decode, rmp_encode};
 use kitsune_p2p_types::dht_arc::{DhtArc, DhtArcRange, DhtArcSet};
 use kitsune_p2p_types::tx2::tx2_utils::TxUrl;
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::AtomicBool;
 use url2::Url2;
 
 /// How often to record historical metrics
@@ -19,6 +18,8 @@ const HISTORICAL_METRIC_RECORD_FREQ_MS: u64 = 1000 * 60 * 60;
 mod metric_exchange;
 use metric_exchange::*;
 
+pub(crate) mod mdns;
+
 mod rpc_multi_logic;
 
 type KSpace = Arc<KitsuneSpace>;
@@ -108,8 +109,11 @@ pub(crate) async fn spawn_space(
     host: HostApi,
     config: Arc<KitsuneP2pConfig>,
     bandwidth_throttles: BandwidthThrottles,
+    traffic_shapers: TrafficShapers,
     parallel_notify_permit: Arc<tokio::sync::Semaphore>,
     fetch_pool: FetchPool,
+    metrics: MetricsSync,
+    mdns: Option<MdnsService>,
 ) -> KitsuneP2pResult<(
     ghost_actor::GhostSender<KitsuneP2p>,
     ghost_actor::GhostSender<SpaceInternal>,
@@ -137,8 +141,11 @@ pub(crate) async fn spawn_space(
         ep_hnd,
         config,
         bandwidth_throttles,
+        traffic_shapers,
         parallel_notify_permit,
         fetch_pool,
+        metrics,
+        mdns,
     )));
 
     Ok((sender, i_s, evt_recv))
@@ -172,7 +179,7 @@ impl SpaceInternalHandler for Space {
 
     fn handle_update_agent_info(&mut self) -> SpaceInternalHandlerResult<()> {
         let space = self.space.clone();
-        let mut mdns_handles = self.mdns_handles.clone();
+        let mdns = self.mdns.clone();
         let network_type = self.config.network_type.clone();
         let mut agent_list = Vec::with_capacity(self.local_joined_agents.len());
         for agent in self.local_joined_agents.iter().cloned() {
@@ -202,7 +209,7 @@ impl SpaceInternalHandler for Space {
                     evt_sender: &evt_sender,
                     internal_sender: &internal_sender,
                     network_type: network_type.clone(),
-                    mdns_handles: &mut mdns_handles,
+                    mdns: &mdns,
                     bootstrap_service: &bootstrap_service,
                     dynamic_arcs,
                     single_storage_arc_per_space,
@@ -226,7 +233,7 @@ impl SpaceInternalHandler for Space {
         agent: Arc<KitsuneAgent>,
     ) -> SpaceInternalHandlerResult<()> {
         let space = self.space.clone();
-        let mut mdns_handles = self.mdns_handles.clone();
+        let mdns = self.mdns.clone();
         let network_type = self.config.network_type.clone();
         let ep_hnd = self.ro_inner.ep_hnd.clone();
         let evt_sender = self.evt_sender.clone();
@@ -251,7 +258,7 @@ impl SpaceInternalHandler for Space {
                 evt_sender: &evt_sender,
                 internal_sender: &internal_sender,
                 network_type: network_type.clone(),
-                mdns_handles: &mut mdns_handles,
+                mdns: &mdns,
                 bootstrap_service: &bootstrap_service,
                 dynamic_arcs,
                 single_storage_arc_per_space,
@@ -600,7 +607,7 @@ struct UpdateAgentInfoInput<'borrow> {
     evt_sender: &'borrow futures::channel::mpsc::Sender<KitsuneP2pEvent>,
     internal_sender: &'borrow ghost_actor::GhostSender<SpaceInternal>,
     network_type: NetworkType,
-    mdns_handles: &'borrow mut HashMap<Vec<u8>, Arc<AtomicBool>>,
+    mdns: &'borrow Option<MdnsService>,
     bootstrap_service: &'borrow Option<Url2>,
     dynamic_arcs: bool,
     single_storage_arc_per_space: bool,
@@ -638,7 +645,7 @@ async fn update_single_agent_info(
         evt_sender,
         internal_sender,
         network_type,
-        mdns_handles,
+        mdns,
         bootstrap_service,
         dynamic_arcs,
         single_storage_arc_per_space,
@@ -687,25 +694,9 @@ async fn update_single_agent_info(
     // Push to the network as well
     match network_type {
         NetworkType::QuicMdns => {
-            // Broadcast only valid AgentInfo
-            if !urls.is_empty() {
-                // Kill previous broadcast for this space + agent
-                let key = [space.get_bytes(), agent.get_bytes()].concat();
-                if let Some(current_handle) = mdns_handles.get(&key) {
-                    mdns_kill_thread(current_handle.to_owned());
-                }
-                // Broadcast by using Space as service type and Agent as service name
-                let space_b64 = base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD);
-                let agent_b64 = base64::encode_config(&agent[..], base64::URL_SAFE_NO_PAD);
-                //println!("(MDNS) - Broadcasting of Agent {:?} ({}) in space {:?} ({} ; {})",
-                // agent, agent.get_bytes().len(), space, space.get_bytes().len(), space_b64.len());
-                // Broadcast rmp encoded agent_info_signed
-                let mut buffer = Vec::new();
-                rmp_encode(&mut buffer, &agent_info_signed)?;
-                tracing::trace!(?space_b64, ?agent_b64);
-                let handle = mdns_create_broadcast_thread(space_b64, agent_b64, &buffer);
-                // store handle in self
-                mdns_handles.insert(key, handle);
+            // Only advertise agents which can be reached.
+            if let (Some(mdns), false) = (mdns, urls.is_empty()) {
+                mdns::advertise(mdns, &space, &agent, urls).await?;
             }
         }
         NetworkType::QuicBootstrap => {
@@ -727,6 +718,26 @@ impl ghost_actor::GhostControlHandler for Space {
             // https://doc.rust-lang.org/edition-guide/rust-2021/disjoint-capture-in-closures.html#migration
             let _ = &self;
             self.ro_inner.metric_exchange.write().shutdown();
+            if let Some(discovery) = self.mdns_discovery.take() {
+                discovery.abort();
+            }
+
+            // Save what has changed since the last periodic save.
+            let host = self.host_api.clone();
+            let space = self.space.clone();
+            let records = self.ro_inner.metrics.read().dump_historical();
+            if let Err(err) = host.record_metrics(space.clone(), records).await {
+                tracing::warn!(?err, "failed to record metrics on shutdown");
+            }
+            if self.config.tuning_params.fetch_pool_persist_interval_ms > 0 {
+                let items = self
+                    .ro_inner
+                    .fetch_pool
+                    .items(&[space.clone()].into_iter().collect());
+                if let Err(err) = host.persist_fetch_pool(space, items).await {
+                    tracing::warn!(?err, "failed to persist fetch pool on shutdown");
+                }
+            }
 
             use futures::sink::SinkExt;
             // this is a curtesy, ok if fails
@@ -760,58 +771,15 @@ impl KitsuneP2pHandler for Space {
             self.agent_arcs.insert(agent.clone(), initial_arc);
         }
         self.local_joined_agents.insert(agent.clone());
+        self.traffic_shapers
+            .register(self.space.clone(), self.ro_inner.traffic.clone());
+        if let (Some(mdns), None) = (&self.mdns, &self.mdns_discovery) {
+            self.mdns_discovery = Some(mdns::spawn_discovery(self.ro_inner.clone(), mdns.clone()));
+        }
         for module in self.gossip_mod.values() {
             module.local_agent_join(agent.clone());
         }
         let fut = self.i_s.update_single_agent_info(agent);
-        let evt_sender = self.evt_sender.clone();
-        match self.config.network_type {
-            NetworkType::QuicMdns => {
-                // Listen to MDNS service that has that space as service type
-                let space_b64 = base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD);
-                if !self.mdns_listened_spaces.contains(&space_b64) {
-                    self.mdns_listened_spaces.insert(space_b64.clone());
-                    tokio::task::spawn(async move {
-                        let stream = mdns_listen(space_b64);
-                        tokio::pin!(stream);
-                        while let Some(maybe_response) = stream.next().await {
-                            match maybe_response {
-                                Ok(response) => {
-                                    tracing::trace!(msg = "Peer found via MDNS", ?response);
-                                    // Decode response
-                                    let maybe_agent_info_signed =
-                                        rmp_decode(&mut &*response.buffer);
-                                    if let Err(e) = maybe_agent_info_signed {
-                                        tracing::error!(msg = "Failed to decode MDNS peer", ?e);
-                                        continue;
-                                    }
-                                    if let Ok(remote_agent_info_signed) = maybe_agent_info_signed {
-                                        // Add to local storage
-                                        if let Err(e) = evt_sender
-                                            .put_agent_info_signed(PutAgentInfoSignedEvt {
-                                                space: space.clone(),
-                                                peer_data: vec![remote_agent_info_signed],
-                                            })
-                                            .await
-                                        {
-                                            tracing::error!(msg = "Failed to store MDNS peer", ?e);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::error!(msg = "Failed to get peers from MDNS", ?e);
-                                }
-                            }
-                        }
-                    });
-                }
-            }
-            NetworkType::QuicBootstrap => {
-                // quic bootstrap is managed for the whole space
-                // see the Space::new() constructor
-            }
-        }
-
         Ok(async move { fut.await }.boxed().into())
     }
 
@@ -821,6 +789,12 @@ impl KitsuneP2pHandler for Space {
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
         self.local_joined_agents.remove(&agent);
+        if self.local_joined_agents.is_empty() {
+            self.traffic_shapers.unregister(&self.space);
+            if let Some(discovery) = self.mdns_discovery.take() {
+                discovery.abort();
+            }
+        }
         self.agent_arcs.remove(&agent);
         self.update_metric_exchange_arcset();
         for module in self.gossip_mod.values() {
@@ -1199,10 +1173,17 @@ impl KitsuneP2pHandler for Space {
     ) -> KitsuneP2pHandlerResult<serde_json::Value> {
         let space = self.ro_inner.space.clone();
         let metrics = self.ro_inner.metrics.read().dump();
+        let traffic = self.ro_inner.traffic.dump();
+        let fetch_pool = self
+            .ro_inner
+            .fetch_pool
+            .items(&[space.clone()].into_iter().collect());
         Ok(async move {
             Ok(serde_json::json!({
                 "space": space.to_string(),
                 "metrics": metrics,
+                "traffic": traffic,
+                "fetch_pool": fetch_pool,
             }))
         }
         .boxed()
@@ -1219,6 +1200,22 @@ impl KitsuneP2pHandler for Space {
         };
         Ok(async move { Ok(diagnostics) }.boxed().into())
     }
+
+    fn handle_report_data_validity(
+        &mut self,
+        _space: KSpace,
+        agent: KAgent,
+        valid: bool,
+    ) -> KitsuneP2pHandlerResult<()> {
+        // Responses can come from our own agents, which don't need scoring.
+        if !self.local_joined_agents.contains(&agent) {
+            self.ro_inner
+                .metrics
+                .write()
+                .record_data_validity_event(valid, [&agent]);
+        }
+        unit_ok_fut()
+    }
 }
 
 pub(crate) struct PendingDelegate {
@@ -1243,8 +1240,8 @@ pub(crate) struct SpaceReadOnlyInner {
     pub(crate) metrics: MetricsSync,
     pub(crate) metric_exchange: MetricExchangeSync,
     pub(crate) publish_pending_delegates: parking_lot::Mutex<HashMap<KOpHash, PendingDelegate>>,
-    #[allow(dead_code)]
     pub(crate) fetch_pool: FetchPool,
+    pub(crate) traffic: Arc<TrafficShaper>,
 }
 
 impl SpaceReadOnlyInner {
@@ -1305,8 +1302,10 @@ pub(crate) struct Space {
     pub(crate) local_joined_agents: HashSet<Arc<KitsuneAgent>>,
     pub(crate) agent_arcs: HashMap<Arc<KitsuneAgent>, DhtArc>,
     pub(crate) config: Arc<KitsuneP2pConfig>,
-    mdns_handles: HashMap<Vec<u8>, Arc<AtomicBool>>,
-    mdns_listened_spaces: HashSet<String>,
+    traffic_shapers: TrafficShapers,
+    mdns: Option<MdnsService>,
+    /// Discovers peers over mdns while any local agent is joined.
+    mdns_discovery: Option<tokio::task::JoinHandle<()>>,
     gossip_mod: HashMap<GossipModuleType, GossipModule>,
 }
 
@@ -1321,16 +1320,26 @@ impl Space {
         ep_hnd: MetaNet,
         config: Arc<KitsuneP2pConfig>,
         bandwidth_throttles: BandwidthThrottles,
+        traffic_shapers: TrafficShapers,
         parallel_notify_permit: Arc<tokio::sync::Semaphore>,
         fetch_pool: FetchPool,
+        metrics: MetricsSync,
+        mdns: Option<MdnsService>,
     ) -> Self {
-        let metrics = MetricsSync::default();
+        let traffic = traffic_shapers.new_shaper(&host_api.traffic_weights(space.clone()));
 
         {
             let space = space.clone();
             let metrics = metrics.clone();
             let host = host_api.clone();
             tokio::task::spawn(async move {
+                // Restore the metrics recorded in a previous run so that
+                // peers keep their scores across restarts.
+                match host.query_metrics(space.clone()).await {
+                    Ok(records) => metrics.write().restore_historical(records),
+                    Err(err) => tracing::warn!(?err, "failed to restore historical metrics"),
+                }
+
                 loop {
                     tokio::time::sleep(std::time::Duration::from_millis(
                         HISTORICAL_METRIC_RECORD_FREQ_MS,
@@ -1344,6 +1353,39 @@ impl Space {
             });
         }
 
+        let persist_interval_ms = config.tuning_params.fetch_pool_persist_interval_ms;
+        if persist_interval_ms > 0 {
+            let space = space.clone();
+            let host = host_api.clone();
+            let fetch_pool = fetch_pool.clone();
+            let i_s = i_s.clone();
+            tokio::task::spawn(async move {
+                use ghost_actor::GhostControlSender;
+
+                // Pick up the fetches which were pending when we last stopped.
+                match host.load_fetch_pool(space.clone()).await {
+                    Ok(items) => fetch_pool.restore(items),
+                    Err(err) => tracing::warn!(?err, "failed to restore fetch pool"),
+                }
+
+                let spaces = [space.clone()].into_iter().collect();
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_millis(
+                        persist_interval_ms as u64,
+                    ))
+                    .await;
+                    if !i_s.ghost_actor_is_active() {
+                        break;
+                    }
+
+                    let items = fetch_pool.items(&spaces);
+                    if let Err(err) = host.persist_fetch_pool(space.clone(), items).await {
+                        tracing::warn!(?err, "failed to persist fetch pool");
+                    }
+                }
+            });
+        }
+
         let metric_exchange = MetricExchangeSync::spawn(
             space.clone(),
             config.tuning_params.clone(),
@@ -1492,6 +1534,7 @@ impl Space {
             metric_exchange,
             publish_pending_delegates: parking_lot::Mutex::new(HashMap::new()),
             fetch_pool,
+            traffic,
         });
 
         Self {
@@ -1503,8 +1546,9 @@ impl Space {
             local_joined_agents: HashSet::new(),
             agent_arcs: HashMap::new(),
             config,
-            mdns_handles: HashMap::new(),
-            mdns_listened_spaces: HashSet::new(),
+            traffic_shapers,
+            mdns,
+            mdns_discovery: None,
             gossip_mod,
         }
     }
@@ -1528,6 +1572,7 @@ impl Space {
         let bootstrap_service = self.config.bootstrap_service.clone();
         let expires_after = self.config.tuning_params.agent_info_expires_after_ms as u64;
         let host = self.host_api.clone();
+        let mdns = self.mdns.clone();
 
         Ok(async move {
             let signed_at_ms = crate::spawn::actor::bootstrap::now_once(None).await?;
@@ -1562,13 +1607,20 @@ impl Space {
             // TODO: at some point, we should not remove agents who have left, but rather
             // there should be a flag indicating they have left. The removed agent may just
             // get re-gossiped to another local agent in the same space, defeating the purpose.
-            host.remove_agent_info_signed(GetAgentInfoSignedEvt { space, agent })
-                .await
-                .map_err(KitsuneP2pError::other)?;
+            host.remove_agent_info_signed(GetAgentInfoSignedEvt {
+                space: space.clone(),
+                agent: agent.clone(),
+            })
+            .await
+            .map_err(KitsuneP2pError::other)?;
 
             // Push to the network as well
             match network_type {
-                NetworkType::QuicMdns => tracing::warn!("NOT publishing leaves to mdns"),
+                NetworkType::QuicMdns => {
+                    if let Some(mdns) = &mdns {
+                        mdns::withdraw(mdns, &space, &agent).await?;
+                    }
+                }
                 NetworkType::QuicBootstrap => {
                     crate::spawn::actor::bootstrap::put(
                         bootstrap_service.clone(),
@@ -0,0 +1,164 @@
+//! Peer discovery on the local network for the `QuicMdns` network type.
+//!
+//! Each local agent is advertised with only its urls, under the space as the
+//! service type. Discovered agent infos are fetched over the transport with a
+//! `PeerGet`, so they are never limited by the size of an mdns record.
+
+use super::*;
+use crate::types::KitsuneBinType;
+use kitsune_p2p_types::config::KitsuneP2pTuningParams;
+use std::time::{Duration, Instant};
+
+/// The TXT string prefix for an advertised url.
+const URL_TXT_PREFIX: &str = "url=";
+
+/// Start the mdns service described by the tuning params.
+pub(crate) fn spawn_service(
+    tuning_params: &KitsuneP2pTuningParams,
+) -> KitsuneP2pResult<MdnsService> {
+    let config = MdnsConfig {
+        multicast_addr: tuning_params
+            .mdns_multicast_addr
+            .parse()
+            .map_err(KitsuneP2pError::other)?,
+        interface: tuning_params
+            .mdns_interface
+            .parse()
+            .map_err(KitsuneP2pError::other)?,
+        query_interval: Duration::from_millis(tuning_params.mdns_query_interval_ms as u64),
+    };
+    MdnsService::spawn(config).map_err(KitsuneP2pError::other)
+}
+
+fn service_type(space: &KitsuneSpace) -> String {
+    base64::encode_config(&space[..], base64::URL_SAFE_NO_PAD)
+}
+
+fn service_name(agent: &KitsuneAgent) -> String {
+    base64::encode_config(&agent[..], base64::URL_SAFE_NO_PAD)
+}
+
+/// Advertise a local agent as reachable at these urls.
+pub(crate) async fn advertise(
+    mdns: &MdnsService,
+    space: &KitsuneSpace,
+    agent: &KitsuneAgent,
+    urls: &[TxUrl],
+) -> KitsuneP2pResult<()> {
+    let txt = urls
+        .iter()
+        .map(|url| format!("{}{}", URL_TXT_PREFIX, url))
+        .collect();
+    mdns.advertise(&service_type(space), &service_name(agent), txt)
+        .await
+        .map_err(KitsuneP2pError::other)
+}
+
+/// Stop advertising a local agent.
+pub(crate) async fn withdraw(
+    mdns: &MdnsService,
+    space: &KitsuneSpace,
+    agent: &KitsuneAgent,
+) -> KitsuneP2pResult<()> {
+    mdns.withdraw(&service_type(space), &service_name(agent))
+        .await
+        .map_err(KitsuneP2pError::other)
+}
+
+/// Listen for remote agents in this space and store their agent infos,
+/// until the returned task is aborted or the space is shut down.
+pub(crate) fn spawn_discovery(
+    inner: Arc<SpaceReadOnlyInner>,
+    mdns: MdnsService,
+) -> tokio::task::JoinHandle<()> {
+    // Agents re-advertise whenever they re-sign their agent info,
+    // so there is nothing new to fetch more often than that.
+    let refetch_interval = Duration::from_millis(
+        inner
+            .config
+            .tuning_params
+            .gossip_agent_info_update_interval_ms as u64,
+    );
+    let stream = mdns.listen(&service_type(&inner.space));
+
+    tokio::task::spawn(async move {
+        use ghost_actor::GhostControlSender;
+
+        tokio::pin!(stream);
+        let mut fetched: HashMap<Arc<KitsuneAgent>, Instant> = HashMap::new();
+        while let Some(response) = stream.next().await {
+            if !inner.i_s.ghost_actor_is_active() {
+                break;
+            }
+            if response.withdrawn {
+                continue;
+            }
+            let agent = base64::decode_config(&response.service_name, base64::URL_SAFE_NO_PAD);
+            let agent = match agent {
+                Ok(agent) => Arc::new(KitsuneAgent::new(agent)),
+                Err(err) => {
+                    tracing::debug!(?err, ?response, "Invalid agent advertised over mdns");
+                    continue;
+                }
+            };
+            let url = match response
+                .txt
+                .iter()
+                .find_map(|txt| txt.strip_prefix(URL_TXT_PREFIX))
+            {
+                Some(url) => url.to_string(),
+                None => continue,
+            };
+
+            let now = Instant::now();
+            fetched.retain(|_, at| now.duration_since(*at) < refetch_interval);
+            if fetched.contains_key(&agent) {
+                continue;
+            }
+            if let Ok(true) = inner.i_s.is_agent_local(agent.clone()).await {
+                continue;
+            }
+            fetched.insert(agent.clone(), now);
+
+            tracing::trace!(?agent, %url, "Peer found via mdns");
+            let inner = inner.clone();
+            tokio::task::spawn(async move {
+                if let Err(err) = fetch_agent_info(&inner, url, agent).await {
+                    tracing::debug!(?err, "Failed to fetch agent info of mdns peer");
+                }
+            });
+        }
+        tracing::debug!("mdns discovery loop ending");
+    })
+}
+
+/// Get the full agent info from the node we discovered and store it.
+async fn fetch_agent_info(
+    inner: &SpaceReadOnlyInner,
+    url: String,
+    agent: Arc<KitsuneAgent>,
+) -> KitsuneP2pResult<()> {
+    let timeout = inner.config.tuning_params.implicit_timeout();
+    let con = inner.ep_hnd.get_connection(url, timeout).await?;
+    let payload = wire::Wire::peer_get(inner.space.clone(), agent.clone());
+    let agent_info_signed = match con.request(&payload, timeout).await? {
+        wire::Wire::PeerGetResp(wire::PeerGetResp {
+            agent_info_signed: Some(agent_info_signed),
+        }) => agent_info_signed,
+        wire::Wire::PeerGetResp(wire::PeerGetResp {
+            agent_info_signed: None,
+        }) => return Ok(()),
+        resp => return Err(format!("unexpected peer get response: {:?}", resp).into()),
+    };
+    if agent_info_signed.agent != agent || agent_info_signed.space != inner.space {
+        return Err("peer returned agent info for the wrong agent".into());
+    }
+    inner
+        .evt_sender
+        .put_agent_info_signed(PutAgentInfoSignedEvt {
+            space: inner.space.clone(),
+            peer_data: vec![agent_info_signed],
+        })
+        .await?;
+    Ok(())
+}
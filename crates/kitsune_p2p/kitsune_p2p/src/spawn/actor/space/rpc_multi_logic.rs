@@ -42,6 +42,10 @@ pub(crate) async fn handle_rpc_multi_as_single(
                         .metrics
                         .write()
                         .record_reachability_event(true, [&agent]);
+                    ro_inner
+                        .metrics
+                        .write()
+                        .record_timeout_event(false, [&agent]);
                     ro_inner
                         .metrics
                         .write()
@@ -56,6 +60,14 @@ pub(crate) async fn handle_rpc_multi_as_single(
                         .metrics
                         .write()
                         .record_reachability_event(false, [&agent]);
+                    let timed_out = matches!(
+                        &oth,
+                        Err(err) if matches!(err.kind(), KitsuneErrorKind::TimedOut(_))
+                    );
+                    ro_inner
+                        .metrics
+                        .write()
+                        .record_timeout_event(timed_out, [&agent]);
                     ro_inner
                         .metrics
                         .write()
@@ -105,6 +117,15 @@ pub(crate) async fn handle_rpc_multi_as_single(
                 if let Some(mut infos) = infos {
                     rand::seq::SliceRandom::shuffle(infos.as_mut_slice(), &mut rand::thread_rng());
 
+                    // Try better scoring agents first. The sort is stable
+                    // so agents with similar scores stay in random order.
+                    {
+                        let metrics = ro_inner.metrics.read();
+                        infos.sort_by_cached_key(|info| {
+                            std::cmp::Reverse((metrics.peer_score([info]) / 10.0) as u8)
+                        });
+                    }
+
                     for info in infos {
                         use discover::PeerDiscoverResult;
 
@@ -82,3 +82,8 @@ pub(crate) mod scenario_def_local;
 
 #[cfg(feature = "mock_network")]
 pub mod mock_network;
+
+#[cfg(feature = "tx5_signal_srv")]
+mod tx5_signal;
+#[cfg(feature = "tx5_signal_srv")]
+pub use tx5_signal::*;
@@ -4,6 +4,7 @@ use crate::gossip::sharded_gossip::{BandwidthThrottle, GossipType, ShardedGossip
 use crate::meta_net::*;
 use crate::test_util::spawn_handler;
 use crate::types::gossip::*;
+use crate::types::traffic::TrafficShapers;
 use crate::types::wire;
 use futures::stream::StreamExt;
 use ghost_actor::dependencies::tracing;
@@ -132,11 +133,12 @@ impl Switchboard {
         let (evt_sender, handler_task) = spawn_handler(evt_handler.clone()).await;
 
         let bandwidth = Arc::new(BandwidthThrottle::new(1000.0, 1000.0, 10.0));
+        let traffic = TrafficShapers::new(tuning_params.clone());
 
         let gossip = ShardedGossip::new(
             tuning_params,
             space.clone(),
-            MetaNet::Tx2(ep_hnd.clone()),
+            MetaNet::Tx2(ep_hnd.clone(), traffic.clone()),
             evt_sender,
             host_api,
             self.gossip_type,
@@ -161,7 +163,7 @@ impl Switchboard {
                                 let data: Box<[u8]> = data.into_boxed_slice();
 
                                 gossip_module.incoming_gossip(
-                                    MetaNetCon::Tx2(con),
+                                    MetaNetCon::Tx2(con, traffic.clone()),
                                     url.to_string(),
                                     data,
                                 )?
@@ -0,0 +1,55 @@
+//! A local tx5 signal server, for running kitsune over WebRTC in tests.
+
+use crate::*;
+
+/// A tx5 signal server listening on the loopback interface.
+/// The server is shut down when this is dropped.
+pub struct LocalSignalServer {
+    url: String,
+    driver: tokio::task::JoinHandle<()>,
+}
+
+impl LocalSignalServer {
+    /// Start a signal server on an ephemeral loopback port.
+    pub async fn spawn() -> KitsuneP2pResult<Self> {
+        let mut config = tx5_signal_srv::Config::default();
+        config.interfaces = "127.0.0.1".to_string();
+        config.port = 0;
+        // Loopback connections don't need any STUN or TURN servers.
+        config.ice_servers = serde_json::json!([]);
+        config.demo = false;
+
+        let (driver, addrs) =
+            tx5_signal_srv::exec_tx5_signal_srv(config).map_err(KitsuneP2pError::other)?;
+        let addr = addrs
+            .into_iter()
+            .next()
+            .ok_or_else(|| KitsuneP2pError::other("tx5 signal server did not bind"))?;
+        let driver = tokio::task::spawn(driver);
+
+        Ok(Self {
+            url: format!("ws://{}", addr),
+            driver,
+        })
+    }
+
+    /// The url clients connect to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A WebRTC transport which uses this signal server.
+    pub fn transport_config(&self) -> TransportConfig {
+        TransportConfig::WebRTC {
+            signal_url: self.url.clone(),
+            fallback_signal_urls: Vec::new(),
+            ice_servers: Vec::new(),
+        }
+    }
+}
+
+impl Drop for LocalSignalServer {
+    fn drop(&mut self) {
+        self.driver.abort();
+    }
+}
@@ -102,3 +102,5 @@ pub use kitsune_p2p_types::dht_arc;
 
 #[allow(missing_docs)]
 pub mod metrics;
+
+pub mod traffic;
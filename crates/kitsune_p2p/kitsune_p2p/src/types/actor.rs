@@ -162,5 +162,10 @@ ghost_actor::ghost_chan! {
 
         /// Get data for diagnostics
         fn get_diagnostics(space: KSpace) -> KitsuneDiagnostics;
+
+        /// Report whether data received from a remote agent was valid.
+        /// Agents which send invalid data get lower peer scores, so they are
+        /// less likely to be chosen as gossip partners or authorities.
+        fn report_data_validity(space: KSpace, agent: KAgent, valid: bool) -> ();
     }
 }
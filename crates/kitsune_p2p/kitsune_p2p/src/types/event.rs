@@ -166,6 +166,8 @@ const METRIC_KIND_UNKNOWN: &str = "Unknown";
 const METRIC_KIND_REACHABILITY_QUOTIENT: &str = "ReachabilityQuotient";
 const METRIC_KIND_LATENCY_MICROS: &str = "LatencyMicros";
 const METRIC_KIND_AGG_EXTRAP_COV: &str = "AggExtrapCov";
+const METRIC_KIND_TIMEOUT_RATE: &str = "TimeoutRate";
+const METRIC_KIND_INVALID_DATA_RATE: &str = "InvalidDataRate";
 
 /// The type of metric recorded
 #[derive(Debug)]
@@ -181,6 +183,12 @@ pub enum MetricRecordKind {
 
     /// AggExtrapCov metric kind
     AggExtrapCov,
+
+    /// TimeoutRate metric kind
+    TimeoutRate,
+
+    /// InvalidDataRate metric kind
+    InvalidDataRate,
 }
 
 impl MetricRecordKind {
@@ -192,6 +200,8 @@ impl MetricRecordKind {
             ReachabilityQuotient => METRIC_KIND_REACHABILITY_QUOTIENT,
             LatencyMicros => METRIC_KIND_LATENCY_MICROS,
             AggExtrapCov => METRIC_KIND_AGG_EXTRAP_COV,
+            TimeoutRate => METRIC_KIND_TIMEOUT_RATE,
+            InvalidDataRate => METRIC_KIND_INVALID_DATA_RATE,
         }
     }
 
@@ -204,6 +214,10 @@ impl MetricRecordKind {
             LatencyMicros
         } else if input == METRIC_KIND_AGG_EXTRAP_COV {
             AggExtrapCov
+        } else if input == METRIC_KIND_TIMEOUT_RATE {
+            TimeoutRate
+        } else if input == METRIC_KIND_INVALID_DATA_RATE {
+            InvalidDataRate
         } else {
             Unknown
         }
@@ -14,6 +14,25 @@ observability::metrics!(
     PeerQueryResp
 );
 
+/// Count bytes written to the network for a wire message variant,
+/// by its `Codec::variant_type` name.
+pub fn count_wire_write(variant_type: &'static str, len: usize) {
+    let metric = match variant_type {
+        "Wire::Failure" => KitsuneMetrics::Failure,
+        "Wire::Call" => KitsuneMetrics::Call,
+        "Wire::CallResp" => KitsuneMetrics::CallResp,
+        "Wire::Notify" => KitsuneMetrics::Notify,
+        "Wire::NotifyResp" => KitsuneMetrics::NotifyResp,
+        "Wire::Gossip" => KitsuneMetrics::Gossip,
+        "Wire::PeerGet" => KitsuneMetrics::PeerGet,
+        "Wire::PeerGetResp" => KitsuneMetrics::PeerGetResp,
+        "Wire::PeerQuery" => KitsuneMetrics::PeerQuery,
+        "Wire::PeerQueryResp" => KitsuneMetrics::PeerQueryResp,
+        _ => return,
+    };
+    KitsuneMetrics::count(metric, len);
+}
+
 /// Print all metrics as tracing events
 #[tracing::instrument]
 pub fn print_all_metrics() {
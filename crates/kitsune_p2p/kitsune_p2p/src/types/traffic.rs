@@ -0,0 +1,621 @@
+//! Scheduling of outgoing traffic by class, so that bulk transfers such as
+//! historic gossip don't hold up interactive requests in the same space.
+//!
+//! Each joined space has a [`TrafficShaper`]. If the space has an outbound
+//! target, messages wait for bandwidth in a self-clocked fair queue, so each
+//! class with messages waiting gets a share of the target in proportion to
+//! its weight. Otherwise messages are only counted. The host may choose the
+//! weights of each space, see [`TrafficWeights`].
+//!
+//! Shaping happens in the transport wrapper, so every message sent in a
+//! space is shaped, including responses to requests from other peers.
+//! Messages a proxy forwards for other nodes belong to no space, so they
+//! are shaped by a single relay shaper for the whole node.
+
+use crate::types::actor::BroadcastData;
+use crate::types::wire::{self, Wire};
+use crate::types::*;
+use kitsune_p2p_types::config::KitsuneP2pTuningParams;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// The shortest burst allowed above the target rate.
+const BURST: Duration = Duration::from_millis(100);
+
+/// The smallest burst allowed above the target rate, in bytes.
+const MIN_BURST_BYTES: f64 = 16.0 * 1024.0;
+
+/// Allowance for the parts of a message which aren't counted
+/// by [`approx_len`].
+const MESSAGE_OVERHEAD_BYTES: usize = 64;
+
+/// Allowance for each op hash in a message.
+const OP_HASH_BYTES: usize = 40;
+
+/// Allowance for an agent info in a message.
+const AGENT_INFO_BYTES: usize = 512;
+
+const NUM_CLASSES: usize = 5;
+
+/// A class of outgoing traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficClass {
+    /// Rpc calls and signals, which someone is waiting for.
+    Interactive,
+    /// Publishing ops, and sending ops in response to fetches.
+    Publish,
+    /// Recent gossip.
+    RecentGossip,
+    /// Historic gossip.
+    HistoricGossip,
+    /// Messages forwarded for other nodes when acting as a proxy.
+    /// These are only sent by the relay shaper.
+    Relay,
+}
+
+impl TrafficClass {
+    /// All the classes.
+    pub const ALL: [TrafficClass; NUM_CLASSES] = [
+        TrafficClass::Interactive,
+        TrafficClass::Publish,
+        TrafficClass::RecentGossip,
+        TrafficClass::HistoricGossip,
+        TrafficClass::Relay,
+    ];
+
+    /// The class of an outgoing message.
+    /// Small control messages have no class and are never held back.
+    pub fn of(wire: &Wire) -> Option<Self> {
+        Some(match wire {
+            Wire::Call(_) | Wire::CallResp(_) => TrafficClass::Interactive,
+            Wire::Broadcast(wire::Broadcast { data, .. })
+            | Wire::DelegateBroadcast(wire::DelegateBroadcast { data, .. }) => match data {
+                BroadcastData::User(_) => TrafficClass::Interactive,
+                BroadcastData::AgentInfo(_) | BroadcastData::Publish { .. } => {
+                    TrafficClass::Publish
+                }
+            },
+            Wire::FetchOp(_) | Wire::PushOpData(_) => TrafficClass::Publish,
+            Wire::Gossip(wire::Gossip { module, .. }) => match module {
+                GossipModuleType::ShardedRecent => TrafficClass::RecentGossip,
+                GossipModuleType::ShardedHistorical => TrafficClass::HistoricGossip,
+            },
+            _ => return None,
+        })
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The approximate encoded size of a message,
+/// counting the fields which can be large.
+fn approx_len(wire: &Wire) -> usize {
+    let broadcast_len = |data: &BroadcastData| match data {
+        BroadcastData::User(data) => data.len(),
+        BroadcastData::AgentInfo(_) => AGENT_INFO_BYTES,
+        BroadcastData::Publish { op_hash_list, .. } => op_hash_list.len() * OP_HASH_BYTES,
+    };
+    MESSAGE_OVERHEAD_BYTES
+        + match wire {
+            Wire::Call(wire::Call { data, .. }) | Wire::CallResp(wire::CallResp { data }) => {
+                data.len()
+            }
+            Wire::Broadcast(wire::Broadcast { data, .. })
+            | Wire::DelegateBroadcast(wire::DelegateBroadcast { data, .. }) => broadcast_len(data),
+            Wire::Gossip(wire::Gossip { data, .. }) => data.len(),
+            Wire::FetchOp(wire::FetchOp { fetch_list }) => fetch_list
+                .iter()
+                .map(|(_, keys)| keys.len() * OP_HASH_BYTES)
+                .sum(),
+            Wire::PushOpData(wire::PushOpData { op_data_list }) => op_data_list
+                .iter()
+                .flat_map(|(_, items)| items)
+                .map(|item| item.op_data.size())
+                .sum(),
+            _ => 0,
+        }
+}
+
+/// The space a message belongs to. Fetches and pushes name a space
+/// for each item, but are only ever sent for a single space.
+fn space_of(wire: &Wire) -> Option<&Arc<KitsuneSpace>> {
+    match wire {
+        Wire::Call(wire::Call { space, .. })
+        | Wire::DelegateBroadcast(wire::DelegateBroadcast { space, .. })
+        | Wire::Broadcast(wire::Broadcast { space, .. })
+        | Wire::Gossip(wire::Gossip { space, .. })
+        | Wire::PeerGet(wire::PeerGet { space, .. })
+        | Wire::PeerQuery(wire::PeerQuery { space, .. })
+        | Wire::MetricExchange(wire::MetricExchange { space, .. }) => Some(space),
+        Wire::FetchOp(wire::FetchOp { fetch_list }) => fetch_list.first().map(|(space, _)| space),
+        Wire::PushOpData(wire::PushOpData { op_data_list }) => {
+            op_data_list.first().map(|(space, _)| space)
+        }
+        _ => None,
+    }
+}
+
+/// The weights of the traffic classes in one space.
+/// Unset weights fall back to the tuning params.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TrafficWeights {
+    /// Share of the outbound target for rpc calls and signals.
+    #[serde(default)]
+    pub interactive: Option<u32>,
+    /// Share of the outbound target for publishing and responding
+    /// to op fetches.
+    #[serde(default)]
+    pub publish: Option<u32>,
+    /// Share of the outbound target for recent gossip.
+    #[serde(default)]
+    pub recent_gossip: Option<u32>,
+    /// Share of the outbound target for historic gossip.
+    #[serde(default)]
+    pub historic_gossip: Option<u32>,
+}
+
+impl TrafficWeights {
+    fn resolve(&self, tuning_params: &KitsuneP2pTuningParams) -> [u32; NUM_CLASSES] {
+        [
+            self.interactive
+                .unwrap_or(tuning_params.traffic_weight_interactive),
+            self.publish.unwrap_or(tuning_params.traffic_weight_publish),
+            self.recent_gossip
+                .unwrap_or(tuning_params.traffic_weight_recent_gossip),
+            self.historic_gossip
+                .unwrap_or(tuning_params.traffic_weight_historic_gossip),
+            // Relayed messages are never sent in a space.
+            1,
+        ]
+    }
+}
+
+/// Counts of the outgoing traffic of one class.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TrafficClassStats {
+    /// Messages sent.
+    pub sent_count: u64,
+    /// Approximate bytes sent.
+    pub sent_bytes: u64,
+    /// Messages currently waiting for bandwidth.
+    pub queued_count: u64,
+    /// Total time messages have waited for bandwidth.
+    pub total_wait_ms: u64,
+    /// The longest time a message has waited for bandwidth.
+    pub max_wait_ms: u64,
+}
+
+struct Waiter {
+    tag: f64,
+    seq: u64,
+    class: TrafficClass,
+    bytes: usize,
+    queued_at: Instant,
+    send: tokio::sync::oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.tag
+            .total_cmp(&other.tag)
+            .then(self.seq.cmp(&other.seq))
+    }
+}
+
+struct ShaperInner {
+    tokens: f64,
+    refilled_at: Instant,
+    /// The finish tag of the last message sent from the queue.
+    virtual_time: f64,
+    /// The finish tag of the last message queued in each class.
+    last_tag: [f64; NUM_CLASSES],
+    queue: BinaryHeap<Reverse<Waiter>>,
+    seq: u64,
+    driving: bool,
+    stats: [TrafficClassStats; NUM_CLASSES],
+}
+
+impl ShaperInner {
+    fn refill(&mut self, rate: f64, burst: f64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.refilled_at = now;
+    }
+
+    fn record_sent(&mut self, class: TrafficClass, bytes: usize, waited: Duration) {
+        let stats = &mut self.stats[class.index()];
+        stats.sent_count += 1;
+        stats.sent_bytes += bytes as u64;
+        let waited = waited.as_millis() as u64;
+        stats.total_wait_ms += waited;
+        stats.max_wait_ms = stats.max_wait_ms.max(waited);
+    }
+}
+
+/// Shares the outbound bandwidth of a space between the traffic classes,
+/// or limits the bandwidth of relayed messages.
+pub struct TrafficShaper {
+    /// The rate in bytes per second and the burst in bytes.
+    limit: Option<(f64, f64)>,
+    weights: [f64; NUM_CLASSES],
+    inner: parking_lot::Mutex<ShaperInner>,
+}
+
+impl TrafficShaper {
+    /// Create the shaper for a space from the configuration.
+    pub fn new(tuning_params: &KitsuneP2pTuningParams, weights: &TrafficWeights) -> Self {
+        Self::new_inner(
+            tuning_params.traffic_outbound_target_mbps,
+            weights.resolve(tuning_params),
+        )
+    }
+
+    /// Create the shaper for relayed messages from the configuration.
+    pub fn new_relay(tuning_params: &KitsuneP2pTuningParams) -> Self {
+        Self::new_inner(tuning_params.traffic_relay_target_mbps, [1; NUM_CLASSES])
+    }
+
+    fn new_inner(outbound_mbps: f64, weights: [u32; NUM_CLASSES]) -> Self {
+        let rate = outbound_mbps * 1000.0 * 1000.0 / 8.0;
+        let limit = if rate > 0.0 {
+            Some((rate, (rate * BURST.as_secs_f64()).max(MIN_BURST_BYTES)))
+        } else {
+            None
+        };
+        Self {
+            limit,
+            // A zero weight would never be scheduled.
+            weights: weights.map(|w| w.max(1) as f64),
+            inner: parking_lot::Mutex::new(ShaperInner {
+                tokens: limit.map(|(_, burst)| burst).unwrap_or_default(),
+                refilled_at: Instant::now(),
+                virtual_time: 0.0,
+                last_tag: [0.0; NUM_CLASSES],
+                queue: BinaryHeap::new(),
+                seq: 0,
+                driving: false,
+                stats: Default::default(),
+            }),
+        }
+    }
+
+    /// Wait until there's bandwidth to send this message.
+    pub async fn outgoing(self: &Arc<Self>, wire: &Wire) {
+        if let Some(class) = TrafficClass::of(wire) {
+            self.outgoing_bytes(class, approx_len(wire)).await;
+        }
+    }
+
+    /// Wait until there's bandwidth to send this many bytes of this class.
+    pub async fn outgoing_bytes(self: &Arc<Self>, class: TrafficClass, bytes: usize) {
+        let (rate, burst) = match self.limit {
+            Some(limit) => limit,
+            None => {
+                self.inner.lock().record_sent(class, bytes, Duration::ZERO);
+                return;
+            }
+        };
+
+        let recv = {
+            let mut guard = self.inner.lock();
+            let inner = &mut *guard;
+            inner.refill(rate, burst);
+            if inner.queue.is_empty() && inner.tokens >= bytes as f64 {
+                inner.tokens -= bytes as f64;
+                inner.record_sent(class, bytes, Duration::ZERO);
+                return;
+            }
+
+            let i = class.index();
+            let tag = inner.virtual_time.max(inner.last_tag[i]) + bytes as f64 / self.weights[i];
+            inner.last_tag[i] = tag;
+            let (send, recv) = tokio::sync::oneshot::channel();
+            let seq = inner.seq;
+            inner.seq += 1;
+            inner.queue.push(Reverse(Waiter {
+                tag,
+                seq,
+                class,
+                bytes,
+                queued_at: Instant::now(),
+                send,
+            }));
+            inner.stats[i].queued_count += 1;
+            if !inner.driving {
+                inner.driving = true;
+                tokio::task::spawn(self.clone().drive(rate, burst));
+            }
+            recv
+        };
+        let _ = recv.await;
+    }
+
+    /// Send queued messages as bandwidth becomes available,
+    /// until the queue is empty.
+    async fn drive(self: Arc<Self>, rate: f64, burst: f64) {
+        loop {
+            let wait = {
+                let mut guard = self.inner.lock();
+                let inner = &mut *guard;
+                inner.refill(rate, burst);
+                loop {
+                    let bytes = match inner.queue.peek() {
+                        Some(Reverse(waiter)) => waiter.bytes as f64,
+                        None => {
+                            inner.driving = false;
+                            return;
+                        }
+                    };
+                    // Messages larger than the burst go once the bucket is full.
+                    let needed = bytes.min(burst);
+                    if inner.tokens < needed {
+                        break Duration::from_secs_f64((needed - inner.tokens) / rate);
+                    }
+                    let Reverse(waiter) = inner.queue.pop().expect("peeked");
+                    inner.stats[waiter.class.index()].queued_count -= 1;
+                    // Don't spend bandwidth on messages which are no longer being sent.
+                    if waiter.send.is_closed() {
+                        continue;
+                    }
+                    inner.tokens -= bytes;
+                    inner.virtual_time = waiter.tag;
+                    inner.record_sent(waiter.class, waiter.bytes, waiter.queued_at.elapsed());
+                    let _ = waiter.send.send(());
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// The counts of outgoing traffic for each class.
+    pub fn stats(&self) -> HashMap<TrafficClass, TrafficClassStats> {
+        let inner = self.inner.lock();
+        TrafficClass::ALL
+            .iter()
+            .map(|class| (*class, inner.stats[class.index()].clone()))
+            .collect()
+    }
+
+    /// The configuration and counts of outgoing traffic, for network metrics.
+    pub fn dump(&self) -> serde_json::Value {
+        let inner = self.inner.lock();
+        let classes = TrafficClass::ALL
+            .iter()
+            .map(|class| {
+                (
+                    serde_json::to_value(class)
+                        .ok()
+                        .and_then(|v| v.as_str().map(str::to_string))
+                        .unwrap_or_default(),
+                    serde_json::json!({
+                        "weight": self.weights[class.index()],
+                        "stats": inner.stats[class.index()],
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::json!({
+            "outbound_target_bytes_per_second": self.limit.map(|(rate, _)| rate),
+            "classes": classes,
+        })
+    }
+}
+
+/// The traffic shapers of the joined spaces, and the relay shaper.
+#[derive(Clone)]
+pub(crate) struct TrafficShapers {
+    tuning_params: KitsuneP2pTuningParams,
+    spaces: Arc<parking_lot::Mutex<HashMap<Arc<KitsuneSpace>, Arc<TrafficShaper>>>>,
+    relay: Arc<TrafficShaper>,
+}
+
+impl std::fmt::Debug for TrafficShapers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrafficShapers")
+            .field("spaces", &self.spaces.lock().len())
+            .finish()
+    }
+}
+
+impl TrafficShapers {
+    pub(crate) fn new(tuning_params: KitsuneP2pTuningParams) -> Self {
+        let relay = Arc::new(TrafficShaper::new_relay(&tuning_params));
+        Self {
+            tuning_params,
+            spaces: Default::default(),
+            relay,
+        }
+    }
+
+    /// Create a shaper for a space. It isn't used until it's registered.
+    pub(crate) fn new_shaper(&self, weights: &TrafficWeights) -> Arc<TrafficShaper> {
+        Arc::new(TrafficShaper::new(&self.tuning_params, weights))
+    }
+
+    /// Shape messages sent in a space with this shaper,
+    /// while an agent is joined to it.
+    pub(crate) fn register(&self, space: Arc<KitsuneSpace>, shaper: Arc<TrafficShaper>) {
+        self.spaces.lock().insert(space, shaper);
+    }
+
+    /// Stop shaping messages sent in a space, once every agent has left it.
+    pub(crate) fn unregister(&self, space: &Arc<KitsuneSpace>) {
+        self.spaces.lock().remove(space);
+    }
+
+    /// The shaper for messages forwarded for other nodes.
+    pub(crate) fn relay(&self) -> Arc<TrafficShaper> {
+        self.relay.clone()
+    }
+
+    /// The shaper for the space a message is sent in.
+    /// Messages in spaces which haven't been joined aren't shaped.
+    pub(crate) fn for_wire(&self, wire: &Wire) -> Option<Arc<TrafficShaper>> {
+        let space = space_of(wire)?;
+        self.spaces.lock().get(space).cloned()
+    }
+
+    /// Wait until there's bandwidth to send this message in its space.
+    pub(crate) async fn outgoing(&self, wire: &Wire) {
+        if let Some(shaper) = self.for_wire(wire) {
+            shaper.outgoing(wire).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1 megabit per second is 125 bytes per millisecond.
+    fn shaper(weights: [u32; NUM_CLASSES]) -> Arc<TrafficShaper> {
+        Arc::new(TrafficShaper::new_inner(1.0, weights))
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn unlimited_traffic_is_only_counted() {
+        let shaper = Arc::new(TrafficShaper::new_inner(0.0, [8, 4, 2, 1, 1]));
+        let start = Instant::now();
+        for _ in 0..10 {
+            shaper
+                .outgoing_bytes(TrafficClass::HistoricGossip, 1_000_000)
+                .await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        let stats = &shaper.stats()[&TrafficClass::HistoricGossip];
+        assert_eq!(stats.sent_count, 10);
+        assert_eq!(stats.sent_bytes, 10_000_000);
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn interactive_traffic_jumps_a_gossip_backlog() {
+        let shaper = shaper([8, 4, 2, 1, 1]);
+        let (done_send, mut done_recv) = tokio::sync::mpsc::unbounded_channel();
+
+        for _ in 0..20 {
+            let shaper = shaper.clone();
+            let done_send = done_send.clone();
+            tokio::task::spawn(async move {
+                shaper
+                    .outgoing_bytes(TrafficClass::HistoricGossip, 10_000)
+                    .await;
+                let _ = done_send.send(TrafficClass::HistoricGossip);
+            });
+        }
+        // Let the gossip queue up.
+        tokio::task::yield_now().await;
+
+        let start = Instant::now();
+        shaper
+            .outgoing_bytes(TrafficClass::Interactive, 1_000)
+            .await;
+        // Waited for at most the message in progress, not the whole backlog.
+        assert!(start.elapsed() < Duration::from_millis(200));
+        drop(done_send);
+
+        let mut gossip_done = 0;
+        while done_recv.recv().await.is_some() {
+            gossip_done += 1;
+        }
+        assert_eq!(gossip_done, 20);
+
+        let stats = shaper.stats();
+        assert_eq!(stats[&TrafficClass::Interactive].sent_count, 1);
+        assert_eq!(stats[&TrafficClass::HistoricGossip].sent_count, 20);
+        assert_eq!(stats[&TrafficClass::HistoricGossip].queued_count, 0);
+        assert!(stats[&TrafficClass::HistoricGossip].max_wait_ms > 1000);
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn backlogged_classes_share_by_weight() {
+        let shaper = shaper([8, 4, 2, 1, 1]);
+        let (done_send, mut done_recv) = tokio::sync::mpsc::unbounded_channel();
+
+        for class in [TrafficClass::RecentGossip, TrafficClass::HistoricGossip] {
+            for _ in 0..100 {
+                let shaper = shaper.clone();
+                let done_send = done_send.clone();
+                tokio::task::spawn(async move {
+                    shaper.outgoing_bytes(class, 1_000).await;
+                    let _ = done_send.send(class);
+                });
+            }
+        }
+
+        let mut counts = HashMap::new();
+        for _ in 0..90 {
+            let class = done_recv.recv().await.unwrap();
+            *counts.entry(class).or_insert(0) += 1;
+        }
+        // Recent gossip has twice the weight so gets about 60 of the 90.
+        let recent = counts[&TrafficClass::RecentGossip];
+        assert!((55..=65).contains(&recent), "recent gossip sent {}", recent);
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn messages_are_shaped_in_their_space() {
+        let shapers = TrafficShapers::new(KitsuneP2pTuningParams::default());
+        let space = Arc::new(KitsuneSpace(vec![1; 36]));
+        let other_space = Arc::new(KitsuneSpace(vec![2; 36]));
+        let shaper = shapers.new_shaper(&TrafficWeights::default());
+        shapers.register(space.clone(), shaper.clone());
+
+        let call = |space: &Arc<KitsuneSpace>| {
+            Wire::call(
+                space.clone(),
+                Arc::new(KitsuneAgent(vec![0; 36])),
+                vec![0; 100].into(),
+            )
+        };
+        shapers.outgoing(&call(&space)).await;
+        // A request names its space, so its response is shaped there too.
+        let request = call(&space);
+        shapers
+            .for_wire(&request)
+            .unwrap()
+            .outgoing(&Wire::call_resp(vec![0; 100].into()))
+            .await;
+        // Nothing is shaped in a space which hasn't been joined.
+        shapers.outgoing(&call(&other_space)).await;
+        assert!(shapers.for_wire(&call(&other_space)).is_none());
+
+        assert_eq!(shaper.stats()[&TrafficClass::Interactive].sent_count, 2);
+
+        // Nor once every agent has left the space.
+        shapers.unregister(&space);
+        assert!(shapers.for_wire(&call(&space)).is_none());
+    }
+
+    #[test]
+    fn space_weights_override_the_tuning_params() {
+        let tuning_params = KitsuneP2pTuningParams::default();
+        let weights: TrafficWeights = serde_json::from_str(r#"{"historic_gossip": 6}"#).unwrap();
+        let shaper = TrafficShaper::new(&tuning_params, &weights);
+        let dump = shaper.dump();
+        assert_eq!(dump["classes"]["historic_gossip"]["weight"], 6.0);
+        assert_eq!(
+            dump["classes"]["interactive"]["weight"],
+            tuning_params.traffic_weight_interactive as f64
+        );
+    }
+}
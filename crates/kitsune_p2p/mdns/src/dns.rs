@@ -0,0 +1,323 @@
+//! The small subset of the DNS wire format needed for DNS-SD over mDNS:
+//! PTR queries for a service type, and PTR + TXT answers.
+
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const CLASS_IN: u16 = 1;
+/// mDNS uses the top bit of the class for "unicast response"
+/// in questions and "cache flush" in answers.
+const CLASS_MASK: u16 = 0x7fff;
+const CLASS_CACHE_FLUSH: u16 = 0x8000;
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+
+/// Compression pointers to follow before giving up on a name.
+const MAX_POINTER_JUMPS: usize = 16;
+
+/// Max length of a single label in a name.
+pub(crate) const MAX_LABEL_LEN: usize = 63;
+/// Max length of a single string in a TXT record.
+pub(crate) const MAX_TXT_STRING_LEN: usize = 255;
+
+/// A record in the answer or additional sections of a packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Record {
+    pub name: String,
+    pub ttl: u32,
+    pub data: RecordData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RecordData {
+    Ptr(String),
+    Txt(Vec<String>),
+    /// Any other record type, which we ignore.
+    Other,
+}
+
+/// A decoded packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Packet {
+    pub is_response: bool,
+    /// Names of the PTR questions.
+    pub questions: Vec<String>,
+    /// Records from both the answer and additional sections.
+    pub records: Vec<Record>,
+}
+
+/// Encode a query for the PTR records of these names.
+pub(crate) fn encode_query<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<u8> {
+    let names: Vec<&str> = names.into_iter().collect();
+    let mut out = Vec::new();
+    put_header(&mut out, 0, names.len(), 0);
+    for name in names {
+        put_name(&mut out, name);
+        put_u16(&mut out, TYPE_PTR);
+        put_u16(&mut out, CLASS_IN);
+    }
+    out
+}
+
+/// Encode a response carrying these records.
+pub(crate) fn encode_response(records: &[Record]) -> Vec<u8> {
+    let records: Vec<&Record> = records
+        .iter()
+        .filter(|r| r.data != RecordData::Other)
+        .collect();
+    let mut out = Vec::new();
+    put_header(
+        &mut out,
+        FLAG_RESPONSE | FLAG_AUTHORITATIVE,
+        0,
+        records.len(),
+    );
+    for record in records {
+        put_name(&mut out, &record.name);
+        let (rtype, class) = match &record.data {
+            // Many services share a PTR name, so don't ask for a cache flush.
+            RecordData::Ptr(_) => (TYPE_PTR, CLASS_IN),
+            RecordData::Txt(_) => (TYPE_TXT, CLASS_IN | CLASS_CACHE_FLUSH),
+            RecordData::Other => unreachable!(),
+        };
+        put_u16(&mut out, rtype);
+        put_u16(&mut out, class);
+        out.extend_from_slice(&record.ttl.to_be_bytes());
+        let len_at = out.len();
+        put_u16(&mut out, 0);
+        match &record.data {
+            RecordData::Ptr(target) => put_name(&mut out, target),
+            RecordData::Txt(strings) => {
+                for s in strings {
+                    let s = &s.as_bytes()[..s.len().min(MAX_TXT_STRING_LEN)];
+                    out.push(s.len() as u8);
+                    out.extend_from_slice(s);
+                }
+            }
+            RecordData::Other => unreachable!(),
+        }
+        let len = (out.len() - len_at - 2) as u16;
+        out[len_at..len_at + 2].copy_from_slice(&len.to_be_bytes());
+    }
+    out
+}
+
+/// Decode a packet. Returns `None` if the packet is malformed.
+pub(crate) fn decode(buf: &[u8]) -> Option<Packet> {
+    let mut r = Reader { buf, pos: 0 };
+    let _id = r.u16()?;
+    let flags = r.u16()?;
+    let qd_count = r.u16()?;
+    let an_count = r.u16()?;
+    let ns_count = r.u16()?;
+    let ar_count = r.u16()?;
+
+    let mut packet = Packet {
+        is_response: flags & FLAG_RESPONSE != 0,
+        ..Default::default()
+    };
+
+    for _ in 0..qd_count {
+        let name = r.name()?;
+        let qtype = r.u16()?;
+        let _class = r.u16()? & CLASS_MASK;
+        if qtype == TYPE_PTR {
+            packet.questions.push(name);
+        }
+    }
+
+    for (section, count) in [an_count, ns_count, ar_count].into_iter().enumerate() {
+        for _ in 0..count {
+            let record = r.record()?;
+            // Authority records are only used for probing.
+            if section != 1 {
+                packet.records.push(record);
+            }
+        }
+    }
+
+    Some(packet)
+}
+
+fn put_header(out: &mut Vec<u8>, flags: u16, qd_count: usize, an_count: usize) {
+    // mDNS ids are always zero.
+    put_u16(out, 0);
+    put_u16(out, flags);
+    put_u16(out, qd_count as u16);
+    put_u16(out, an_count as u16);
+    put_u16(out, 0);
+    put_u16(out, 0);
+}
+
+fn put_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        let label = &label.as_bytes()[..label.len().min(MAX_LABEL_LEN)];
+        out.push(label.len() as u8);
+        out.extend_from_slice(label);
+    }
+    out.push(0);
+}
+
+fn put_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let out = self.buf.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(out)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let b = self.bytes(2)?;
+        Some(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let b = self.bytes(4)?;
+        Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read a possibly compressed name, leaving the reader after it.
+    fn name(&mut self) -> Option<String> {
+        let mut labels = Vec::new();
+        // Where to continue reading once the name is done,
+        // if we followed a pointer.
+        let mut resume = None;
+        let mut jumps = 0;
+        loop {
+            let len = self.u8()?;
+            match len & 0xc0 {
+                0x00 if len == 0 => break,
+                0x00 => {
+                    let label = self.bytes(len as usize)?;
+                    labels.push(String::from_utf8_lossy(label).into_owned());
+                }
+                0xc0 => {
+                    let offset = (((len & 0x3f) as usize) << 8) | self.u8()? as usize;
+                    jumps += 1;
+                    if jumps > MAX_POINTER_JUMPS {
+                        return None;
+                    }
+                    resume.get_or_insert(self.pos);
+                    self.pos = offset;
+                }
+                // Extended label types are not used by mDNS.
+                _ => return None,
+            }
+        }
+        if let Some(resume) = resume {
+            self.pos = resume;
+        }
+        Some(labels.join("."))
+    }
+
+    fn record(&mut self) -> Option<Record> {
+        let name = self.name()?;
+        let rtype = self.u16()?;
+        let _class = self.u16()? & CLASS_MASK;
+        let ttl = self.u32()?;
+        let len = self.u16()? as usize;
+        let end = self.pos.checked_add(len)?;
+        if end > self.buf.len() {
+            return None;
+        }
+        let data = match rtype {
+            TYPE_PTR => RecordData::Ptr(self.name()?),
+            TYPE_TXT => {
+                let mut strings = Vec::new();
+                while self.pos < end {
+                    let len = self.u8()? as usize;
+                    let s = self.bytes(len)?;
+                    strings.push(String::from_utf8_lossy(s).into_owned());
+                }
+                RecordData::Txt(strings)
+            }
+            _ => RecordData::Other,
+        };
+        if self.pos > end {
+            return None;
+        }
+        self.pos = end;
+        Some(Record { name, ttl, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_round_trips() {
+        let query = encode_query(["_a._udp.local", "_b._udp.local"]);
+        let packet = decode(&query).unwrap();
+        assert!(!packet.is_response);
+        assert_eq!(packet.questions, vec!["_a._udp.local", "_b._udp.local"]);
+        assert!(packet.records.is_empty());
+    }
+
+    #[test]
+    fn response_round_trips() {
+        let records = vec![
+            Record {
+                name: "_a._udp.local".to_string(),
+                ttl: 120,
+                data: RecordData::Ptr("agent._a._udp.local".to_string()),
+            },
+            Record {
+                name: "agent._a._udp.local".to_string(),
+                ttl: 120,
+                data: RecordData::Txt(vec!["url=kitsune-quic://127.0.0.1:1234".to_string()]),
+            },
+        ];
+        let packet = decode(&encode_response(&records)).unwrap();
+        assert!(packet.is_response);
+        assert_eq!(packet.records, records);
+    }
+
+    #[test]
+    fn follows_compression_pointers() {
+        let mut buf = Vec::new();
+        put_header(&mut buf, FLAG_RESPONSE, 0, 1);
+        // The record name is "_a._udp.local", the target points into it.
+        let name_at = buf.len();
+        put_name(&mut buf, "_a._udp.local");
+        put_u16(&mut buf, TYPE_PTR);
+        put_u16(&mut buf, CLASS_IN);
+        buf.extend_from_slice(&120u32.to_be_bytes());
+        put_u16(&mut buf, 8);
+        buf.extend_from_slice(&[5, b'a', b'g', b'e', b'n', b't']);
+        buf.extend_from_slice(&[0xc0, name_at as u8]);
+
+        let packet = decode(&buf).unwrap();
+        assert_eq!(
+            packet.records[0].data,
+            RecordData::Ptr("agent._a._udp.local".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_packets() {
+        let query = encode_query(["_a._udp.local"]);
+        assert!(decode(&query[..query.len() - 1]).is_none());
+        assert!(decode(&[]).is_none());
+
+        // A pointer to itself.
+        let mut buf = Vec::new();
+        put_header(&mut buf, 0, 1, 0);
+        let at = buf.len() as u8;
+        buf.extend_from_slice(&[0xc0, at]);
+        put_u16(&mut buf, TYPE_PTR);
+        put_u16(&mut buf, CLASS_IN);
+        assert!(decode(&buf).is_none());
+    }
+}
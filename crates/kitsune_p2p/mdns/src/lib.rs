@@ -1,175 +1,515 @@
-///! Crate for discovering Holochain peers over MDNS
-///! Works by broadcasting a service named `HC_SERVICE_NAME`
-///! and adding base64 encoded data in a TXT record
-///!
-///! Uses libmdns crate for broadcasting
-///! Uses mdns crate for discovery
+//! Discovery of peers on the local network over multicast DNS.
+//!
+//! An [`MdnsService`] advertises small records under a service type:
+//! a service name and a few TXT strings, such as the urls a node can be
+//! reached at. It also listens for the records other nodes advertise under
+//! the service types it is interested in. Anything larger than a locator
+//! should be fetched from the discovered node directly.
+//!
+//! This implements only the DNS-SD subset needed for that, on tokio,
+//! and shares the mDNS port with any other responders on the host.
+
 use err_derive::Error;
-use mdns::RecordKind;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
-use tokio_stream::{Stream, StreamExt};
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast;
+use tokio_stream::Stream;
 
-use std::sync::atomic::{AtomicBool, Ordering};
+mod dns;
+use dns::{Record, RecordData};
 
-const HC_SERVICE_PROTOCOL: &str = "._udp";
-const BROADCAST_INTERVAL_SEC: u64 = 8;
-const QUERY_INTERVAL_SEC: u64 = 5;
-const MAX_TXT_SIZE: usize = 192;
+const HC_SERVICE_PROTOCOL: &str = "._udp.local";
+/// How long other nodes may cache our records.
+const RECORD_TTL_SEC: u32 = 120;
+/// The largest packet we expect to receive.
+const MAX_PACKET_SIZE: usize = 9000;
+/// Responses buffered for slow listeners.
+const RESPONSE_CHANNEL_SIZE: usize = 256;
 
+/// Errors from the mdns service.
 #[derive(Debug, Error)]
 pub enum MdnsError {
-    #[error(display = "Regular Mdns error {}", _0)]
-    Mdns(#[error(source)] mdns::Error),
-    #[error(display = "Base64 decoding error {}", _0)]
-    Base64(#[error(source)] base64::DecodeError),
+    /// The multicast socket failed.
+    #[error(display = "Mdns socket error {}", _0)]
+    Io(#[error(source)] std::io::Error),
+    /// A service type or name can't be used as a DNS label.
+    #[error(display = "Invalid mdns service type or name {:?}", _0)]
+    InvalidName(String),
+    /// A TXT string is too long to advertise.
+    #[error(display = "Mdns TXT string of {} bytes is too long", _0)]
+    TxtTooLong(usize),
 }
 
-/// Stop thread created by `mdns_create_broadcast_thread()`
-pub fn mdns_kill_thread(can_run: ::std::sync::Arc<AtomicBool>) {
-    can_run.store(false, Ordering::Relaxed);
+/// Result type for the mdns service.
+pub type MdnsResult<T> = Result<T, MdnsError>;
+
+/// Configuration for an [`MdnsService`].
+#[derive(Debug, Clone)]
+pub struct MdnsConfig {
+    /// The multicast group and port to use.
+    /// [Default: 224.0.0.251:5353]
+    pub multicast_addr: SocketAddrV4,
+
+    /// The local interface to send and receive multicast on.
+    /// `0.0.0.0` lets the OS choose, `127.0.0.1` keeps
+    /// discovery on the loopback interface.
+    /// [Default: 0.0.0.0]
+    pub interface: Ipv4Addr,
+
+    /// How often to query for the service types being listened to.
+    /// [Default: 5 seconds]
+    pub query_interval: Duration,
 }
 
-/// Create a thread that will broadcast a holochain service over mdns
-/// Returns Sender for sending thread termination command
-pub fn mdns_create_broadcast_thread(
-    service_type: String,
-    service_name: String,
-    buffer: &[u8],
-) -> ::std::sync::Arc<AtomicBool> {
-    let svc_type = format!("_{}{}", service_type, HC_SERVICE_PROTOCOL);
-    // Constraints in libmdns
-    assert!(
-        svc_type.len() < 63,
-        "len = {} ({}) ; {}",
-        svc_type.len(),
-        service_type.len(),
-        service_type
-    );
-    assert!(service_name.len() < 63);
-    // Create Termination command variable
-    let can_run = ::std::sync::Arc::new(AtomicBool::new(true));
-    let can_run_clone = can_run.clone();
-    // Change buffer to base64 string
-    let mut b64 = format!(
-        "u{}",
-        base64::encode_config(buffer, base64::URL_SAFE_NO_PAD)
-    );
-    //println!(
-    //    "Broadcasting service type '{}', named '{}' over mdns ({})",
-    //    svc_type,
-    //    service_name,
-    //    b64.len()
-    //);
-    // Create thread
-    let _handle = tokio::task::spawn(async move {
-        // Split buffer to fix TXT max size
-        let mut substrs = Vec::new();
-        while b64.len() > MAX_TXT_SIZE {
-            let start: String = b64.drain(..MAX_TXT_SIZE).collect();
-            substrs.push(start);
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            multicast_addr: SocketAddrV4::new(Ipv4Addr::new(224, 0, 0, 251), 5353),
+            interface: Ipv4Addr::UNSPECIFIED,
+            query_interval: Duration::from_secs(5),
         }
-        substrs.push(b64);
-        let txts: Vec<_> = substrs.iter().map(AsRef::as_ref).collect();
-        //println!("Entering mdns broadcasting thread...");
-        // Create mdns responder
-
-        let responder = libmdns::Responder::new().unwrap();
-        let _svc = responder.register(svc_type, service_name, 0, &txts);
-        // Loop forever unless termination command received
-        loop {
-            tokio::time::sleep(::std::time::Duration::from_secs(BROADCAST_INTERVAL_SEC)).await;
-            if !can_run_clone.load(Ordering::Relaxed) {
-                //println!("Terminating.");
-                break;
-            }
-        }
-    });
-    // Done
-    can_run
+    }
 }
 
-///
-#[derive(Debug, Clone)]
+/// A service discovered over mdns.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MdnsResponse {
-    /// Service type used
+    /// The service type it was advertised under.
     pub service_type: String,
-    /// Service name used
+    /// The name it was advertised with.
     pub service_name: String,
-    /// IP address that responded to the mdns query
-    pub addr: std::net::IpAddr,
-    /// Data contained in the TXT record
-    pub buffer: Vec<u8>,
-}
-
-/// Queries the network for the holochain service.
-/// Returns an iterator over all responses received.
-#[allow(clippy::let_and_return)]
-pub fn mdns_listen(service_type: String) -> impl Stream<Item = Result<MdnsResponse, MdnsError>> {
-    //let service_name = format!("{}.local", HC_SERVICE_TYPE);
-    let svc_type = format!("_{}{}.local", service_type, HC_SERVICE_PROTOCOL);
-    //println!("MDNS query for service type '{}'", svc_type);
-    let query = mdns::discover::all(svc_type, Duration::from_secs(QUERY_INTERVAL_SEC))
-        .expect("mdns Discover failed");
-    // Get Mdns Response stream
-    let response_stream = query.listen();
-    // Change it into a MdnsResponse stream
-    let mdns_stream = response_stream
-        // Filtering out Empty responses
-        .filter(move |res| {
-            match res {
-                Ok(response) => !response.is_empty() && response.ip_addr().is_some(),
-                Err(_) => true, // Keep errors
+    /// The address the response came from.
+    pub addr: SocketAddr,
+    /// The TXT strings it was advertised with.
+    pub txt: Vec<String>,
+    /// True if the service is no longer advertised.
+    pub withdrawn: bool,
+}
+
+/// Advertises services and discovers the services of others.
+/// The background tasks stop when the last clone is dropped.
+#[derive(Clone)]
+pub struct MdnsService(Arc<Inner>);
+
+struct Inner {
+    shared: Arc<Shared>,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+struct Shared {
+    socket: UdpSocket,
+    group: SocketAddr,
+    state: Mutex<State>,
+    responses: broadcast::Sender<MdnsResponse>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Service type -> service name -> TXT strings.
+    advertised: HashMap<String, HashMap<String, Vec<String>>>,
+    /// Service type -> count of listeners.
+    listened: HashMap<String, usize>,
+}
+
+impl MdnsService {
+    /// Bind the multicast socket and start answering queries.
+    /// Must be called from within a tokio runtime.
+    pub fn spawn(config: MdnsConfig) -> MdnsResult<Self> {
+        let socket = bind_multicast(&config)?;
+        let (responses, _) = broadcast::channel(RESPONSE_CHANNEL_SIZE);
+        let shared = Arc::new(Shared {
+            socket,
+            group: config.multicast_addr.into(),
+            state: Mutex::new(State::default()),
+            responses,
+        });
+        let tasks = vec![
+            tokio::task::spawn(recv_loop(shared.clone())),
+            tokio::task::spawn(query_loop(shared.clone(), config.query_interval)),
+        ];
+        Ok(Self(Arc::new(Inner { shared, tasks })))
+    }
+
+    /// Advertise a service, replacing any previous advertisement
+    /// of the same type and name.
+    pub async fn advertise(
+        &self,
+        service_type: &str,
+        service_name: &str,
+        txt: Vec<String>,
+    ) -> MdnsResult<()> {
+        check_label(service_type, dns::MAX_LABEL_LEN - 1)?;
+        check_label(service_name, dns::MAX_LABEL_LEN)?;
+        if let Some(s) = txt.iter().find(|s| s.len() > dns::MAX_TXT_STRING_LEN) {
+            return Err(MdnsError::TxtTooLong(s.len()));
+        }
+        let records = service_records(service_type, service_name, txt.clone(), RECORD_TTL_SEC);
+        self.0
+            .shared
+            .state
+            .lock()
+            .unwrap()
+            .advertised
+            .entry(service_type.to_string())
+            .or_default()
+            .insert(service_name.to_string(), txt);
+        // Announce it so listeners don't have to wait for their next query.
+        self.0.shared.send(&dns::encode_response(&records)).await
+    }
+
+    /// Stop advertising a service, and tell listeners it has gone.
+    pub async fn withdraw(&self, service_type: &str, service_name: &str) -> MdnsResult<()> {
+        let txt = {
+            let mut state = self.0.shared.state.lock().unwrap();
+            let names = match state.advertised.get_mut(service_type) {
+                Some(names) => names,
+                None => return Ok(()),
+            };
+            let txt = names.remove(service_name);
+            if names.is_empty() {
+                state.advertised.remove(service_type);
+            }
+            txt
+        };
+        match txt {
+            Some(txt) => {
+                // A zero ttl tells listeners to forget the records.
+                let records = service_records(service_type, service_name, txt, 0);
+                self.0.shared.send(&dns::encode_response(&records)).await
             }
-        })
-        .map(|maybe_response| {
-            if let Err(e) = maybe_response {
-                return Err(MdnsError::Mdns(e));
+            None => Ok(()),
+        }
+    }
+
+    /// Discover services of this type, including those advertised
+    /// by this service. Services are reported each time they respond
+    /// to a query, so the same service will be seen many times.
+    pub fn listen(&self, service_type: &str) -> impl Stream<Item = MdnsResponse> {
+        let shared = self.0.shared.clone();
+        let service_type = service_type.to_string();
+        let mut recv = shared.responses.subscribe();
+        *shared
+            .state
+            .lock()
+            .unwrap()
+            .listened
+            .entry(service_type.clone())
+            .or_default() += 1;
+        let guard = ListenGuard {
+            shared: Arc::downgrade(&shared),
+            service_type: service_type.clone(),
+        };
+
+        async_stream::stream! {
+            let _guard = guard;
+            // Don't wait for the query loop to find existing services.
+            let query = dns::encode_query([service_type_name(&service_type).as_str()]);
+            let _ = shared.send(&query).await;
+            drop(shared);
+
+            loop {
+                match recv.recv().await {
+                    Ok(response) if response.service_type == service_type => yield response,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => (),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
             }
-            let response = maybe_response.unwrap();
-            // NOTE: if response.ip_addr() is not te right address,
-            // we should give all A/AAA records found in the answers instead
-            let addr = response.ip_addr().unwrap(); // should have already been filtered out
-            let mut buffer = Vec::new();
-            let mut service_name = String::new();
-            let mut service_type = String::new();
-            //println!("Response Answer count = {}", response.answers.len());
-            for answer in response.answers {
-                match answer.kind {
-                    RecordKind::TXT(txts) => {
-                        //println!("TXT count = {}", txts.len());
-                        let mut b64 = String::new();
-                        for txt in txts {
-                            //println!("Response TXT = {:?}", txt);
-                            b64.push_str(&txt);
-                        }
-                        buffer = match base64::decode_config(&b64[1..], base64::URL_SAFE_NO_PAD) {
-                            Err(e) => return Err(MdnsError::Base64(e)),
-                            Ok(s) => s,
-                        };
-                    }
-                    // Retrieve service name stored in PTR record
-                    RecordKind::PTR(ptr) => {
-                        //println!("PTR = {}", ptr);
-                        service_name = ptr
-                            .split('.')
-                            .into_iter()
-                            .next()
-                            .expect("Found service without a name")
-                            .to_string();
-                        let names: Vec<&str> = answer.name.split("._").collect();
-                        //println!("answer.name = {}", answer.name);
-                        service_type = names[0][1..].to_string();
-                    }
-                    _ => {}
+        }
+    }
+}
+
+/// Stops querying for a service type when its last listener is dropped.
+struct ListenGuard {
+    shared: Weak<Shared>,
+    service_type: String,
+}
+
+impl Drop for ListenGuard {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared.upgrade() {
+            let mut state = shared.state.lock().unwrap();
+            if let Some(count) = state.listened.get_mut(&self.service_type) {
+                *count -= 1;
+                if *count == 0 {
+                    state.listened.remove(&self.service_type);
                 }
             }
-            Ok(MdnsResponse {
-                service_type,
-                service_name,
+        }
+    }
+}
+
+impl Shared {
+    async fn send(&self, packet: &[u8]) -> MdnsResult<()> {
+        self.socket.send_to(packet, self.group).await?;
+        Ok(())
+    }
+
+    /// Answer the questions for service types we advertise.
+    async fn handle_query(&self, questions: Vec<String>) {
+        let records: Vec<Record> = {
+            let state = self.state.lock().unwrap();
+            questions
+                .iter()
+                .filter_map(|question| parse_service_type(question))
+                .filter_map(|service_type| {
+                    state
+                        .advertised
+                        .get(service_type)
+                        .map(|names| (service_type, names))
+                })
+                .flat_map(|(service_type, names)| {
+                    names.iter().flat_map(move |(name, txt)| {
+                        service_records(service_type, name, txt.clone(), RECORD_TTL_SEC)
+                    })
+                })
+                .collect()
+        };
+        if !records.is_empty() {
+            let _ = self.send(&dns::encode_response(&records)).await;
+        }
+    }
+
+    /// Pass on the services of the types we listen to.
+    fn handle_response(&self, addr: SocketAddr, records: Vec<Record>) {
+        let mut txts: HashMap<&str, &Vec<String>> = HashMap::new();
+        for record in &records {
+            if let RecordData::Txt(txt) = &record.data {
+                txts.insert(&record.name, txt);
+            }
+        }
+        let state = self.state.lock().unwrap();
+        for record in &records {
+            let instance = match &record.data {
+                RecordData::Ptr(instance) => instance,
+                _ => continue,
+            };
+            let service_type = match parse_service_type(&record.name) {
+                Some(t) if state.listened.contains_key(t) => t,
+                _ => continue,
+            };
+            let service_name = match instance
+                .strip_suffix(record.name.as_str())
+                .and_then(|name| name.strip_suffix('.'))
+            {
+                Some(name) => name,
+                None => continue,
+            };
+            // Nobody may be receiving right now, which is fine.
+            let _ = self.responses.send(MdnsResponse {
+                service_type: service_type.to_string(),
+                service_name: service_name.to_string(),
                 addr,
-                buffer,
-            })
-        });
-    // Done
-    mdns_stream
+                txt: txts
+                    .get(instance.as_str())
+                    .map(|txt| txt.to_vec())
+                    .unwrap_or_default(),
+                withdrawn: record.ttl == 0,
+            });
+        }
+    }
+}
+
+async fn recv_loop(shared: Arc<Shared>) {
+    let mut buf = vec![0; MAX_PACKET_SIZE];
+    loop {
+        let (len, addr) = match shared.socket.recv_from(&mut buf).await {
+            Ok(r) => r,
+            Err(err) => {
+                tracing::debug!(?err, "mdns recv error");
+                continue;
+            }
+        };
+        // Ignore anything we can't understand, it isn't for us.
+        let packet = match dns::decode(&buf[..len]) {
+            Some(packet) => packet,
+            None => continue,
+        };
+        if packet.is_response {
+            shared.handle_response(addr, packet.records);
+        } else {
+            shared.handle_query(packet.questions).await;
+        }
+    }
+}
+
+async fn query_loop(shared: Arc<Shared>, query_interval: Duration) {
+    let mut interval = tokio::time::interval(query_interval);
+    loop {
+        interval.tick().await;
+        let names: Vec<String> = shared
+            .state
+            .lock()
+            .unwrap()
+            .listened
+            .keys()
+            .map(|service_type| service_type_name(service_type))
+            .collect();
+        if !names.is_empty() {
+            if let Err(err) = shared
+                .send(&dns::encode_query(names.iter().map(String::as_str)))
+                .await
+            {
+                tracing::debug!(?err, "mdns query error");
+            }
+        }
+    }
+}
+
+fn bind_multicast(config: &MdnsConfig) -> MdnsResult<UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    // Share the port with any other mdns responders on this host.
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, config.multicast_addr.port()).into())?;
+    socket.join_multicast_v4(config.multicast_addr.ip(), &config.interface)?;
+    socket.set_multicast_if_v4(&config.interface)?;
+    // Other nodes on this host need to see our packets too.
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_nonblocking(true)?;
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+fn check_label(label: &str, max_len: usize) -> MdnsResult<()> {
+    if label.is_empty() || label.len() > max_len || label.contains('.') {
+        return Err(MdnsError::InvalidName(label.to_string()));
+    }
+    Ok(())
+}
+
+/// The DNS name of a service type, e.g. `_bobby._udp.local`.
+fn service_type_name(service_type: &str) -> String {
+    format!("_{}{}", service_type, HC_SERVICE_PROTOCOL)
+}
+
+/// The service type in a DNS name, e.g. `bobby` in `_bobby._udp.local`.
+fn parse_service_type(name: &str) -> Option<&str> {
+    name.strip_suffix(HC_SERVICE_PROTOCOL)?.strip_prefix('_')
+}
+
+/// The PTR and TXT records advertising a service.
+fn service_records(
+    service_type: &str,
+    service_name: &str,
+    txt: Vec<String>,
+    ttl: u32,
+) -> [Record; 2] {
+    let type_name = service_type_name(service_type);
+    let instance = format!("{}.{}", service_name, type_name);
+    [
+        Record {
+            name: type_name,
+            ttl,
+            data: RecordData::Ptr(instance.clone()),
+        },
+        Record {
+            name: instance,
+            ttl,
+            data: RecordData::Txt(txt),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    /// Discovery on loopback, on a group and port no real mdns responder uses.
+    fn loopback_config(port_offset: u16) -> MdnsConfig {
+        MdnsConfig {
+            multicast_addr: SocketAddrV4::new(
+                Ipv4Addr::new(239, 255, 42, 99),
+                40000 + (std::process::id() % 10000) as u16 + port_offset,
+            ),
+            interface: Ipv4Addr::LOCALHOST,
+            query_interval: Duration::from_millis(200),
+        }
+    }
+
+    async fn next(stream: &mut (impl Stream<Item = MdnsResponse> + Unpin)) -> MdnsResponse {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("timed out waiting for mdns response")
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn discover_on_loopback() {
+        let config = loopback_config(0);
+        let alice = MdnsService::spawn(config.clone()).unwrap();
+        let bob = MdnsService::spawn(config).unwrap();
+
+        alice
+            .advertise("space", "alice", vec!["url=test://alice".to_string()])
+            .await
+            .unwrap();
+
+        let mut found = Box::pin(bob.listen("space"));
+        let response = next(&mut found).await;
+        assert_eq!(response.service_type, "space");
+        assert_eq!(response.service_name, "alice");
+        assert_eq!(response.txt, vec!["url=test://alice".to_string()]);
+        assert!(!response.withdrawn);
+
+        alice.withdraw("space", "alice").await.unwrap();
+        let response = loop {
+            let response = next(&mut found).await;
+            if response.withdrawn {
+                break response;
+            }
+        };
+        assert_eq!(response.service_name, "alice");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_listened_service_types_are_reported() {
+        let config = loopback_config(1);
+        let alice = MdnsService::spawn(config.clone()).unwrap();
+        let bob = MdnsService::spawn(config).unwrap();
+
+        alice.advertise("one", "alice", vec![]).await.unwrap();
+        alice.advertise("two", "alice", vec![]).await.unwrap();
+        bob.advertise("two", "bob", vec![]).await.unwrap();
+
+        let mut found = Box::pin(bob.listen("two"));
+        let mut names = std::collections::HashSet::new();
+        while names.len() < 2 {
+            let response = next(&mut found).await;
+            assert_eq!(response.service_type, "two");
+            names.insert(response.service_name);
+        }
+        assert!(names.contains("alice") && names.contains("bob"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejects_invalid_advertisements() {
+        let mdns = MdnsService::spawn(loopback_config(2)).unwrap();
+        assert!(matches!(
+            mdns.advertise("a.b", "name", vec![]).await,
+            Err(MdnsError::InvalidName(_))
+        ));
+        assert!(matches!(
+            mdns.advertise("type", &"x".repeat(64), vec![]).await,
+            Err(MdnsError::InvalidName(_))
+        ));
+        assert!(matches!(
+            mdns.advertise("type", "name", vec!["x".repeat(256)]).await,
+            Err(MdnsError::TxtTooLong(256))
+        ));
+    }
 }
@@ -26,6 +26,20 @@ pub struct Opt {
     /// To which network interface / port should we bind?
     #[structopt(short = "b", long, default_value = "kitsune-quic://0.0.0.0:0")]
     pub bind_to: String,
+
+    /// Only relay for clients with this base64 cert digest,
+    /// or which present the auth token. May be given more than once.
+    #[structopt(long = "allow-cert")]
+    pub allow_certs: Vec<String>,
+
+    /// Only relay for clients which present this token,
+    /// or which have an allowed cert.
+    #[structopt(long)]
+    pub auth_token: Option<String>,
+
+    /// The most bytes to relay for a single client per minute.
+    #[structopt(long)]
+    pub quota_bytes_per_minute: Option<u64>,
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -114,6 +128,16 @@ async fn inner() -> KitsuneResult<()> {
     let mut conf = ProxyConfig::default();
     conf.tuning_params = Some(tuning_params.clone());
     conf.allow_proxy_fwd = true;
+    for cert in opt.allow_certs.iter() {
+        let cert =
+            base64::decode_config(cert, base64::URL_SAFE_NO_PAD).map_err(KitsuneError::other)?;
+        if cert.len() != 32 {
+            return Err(format!("invalid cert digest length: {}", cert.len()).into());
+        }
+        conf.relay_access.allow_certs.insert(cert.into());
+    }
+    conf.relay_access.token = opt.auth_token.clone();
+    conf.relay_quota_bytes_per_minute = opt.quota_bytes_per_minute;
     let f = tx2_proxy(f, conf)?;
 
     let ep = f
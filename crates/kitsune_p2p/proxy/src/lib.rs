@@ -12,3 +12,6 @@ pub mod tx2;
 
 mod proxy_url;
 pub use proxy_url::*;
+
+mod relay;
+pub use relay::*;
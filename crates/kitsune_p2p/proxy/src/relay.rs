@@ -0,0 +1,361 @@
+//! Access control, quotas and stats for nodes which forward data
+//! on behalf of others.
+
+use crate::*;
+use futures::future::BoxFuture;
+use kitsune_p2p_types::dependencies::serde_json;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+
+/// The window over which forwarded bytes are counted against a quota.
+const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+/// Stop tracking quotas for idle clients once this many are tracked.
+const MAX_QUOTA_WINDOWS: usize = 4096;
+
+/// Keep the forwarded byte totals of at most this many clients,
+/// dropping the smallest to make room.
+const MAX_CLIENT_TOTALS: usize = 4096;
+
+/// Accept the token from at most this many connected clients.
+const MAX_AUTHORIZED_CLIENTS: usize = 4096;
+
+/// Callback to wait until there is bandwidth to forward this many bytes.
+pub type RelayShaperCb = Arc<dyn Fn(usize) -> BoxFuture<'static, ()> + 'static + Send + Sync>;
+
+/// Which clients a relay will forward data for.
+/// If neither certs nor a token are set, the relay forwards for anyone.
+#[derive(Debug, Clone, Default)]
+pub struct RelayAccess {
+    /// Clients with these certs may always use the relay.
+    pub allow_certs: HashSet<Tx2Cert>,
+
+    /// Clients which present this token may use the relay.
+    pub token: Option<String>,
+}
+
+impl RelayAccess {
+    /// True if the relay forwards for anyone.
+    pub fn is_open(&self) -> bool {
+        self.allow_certs.is_empty() && self.token.is_none()
+    }
+}
+
+/// Counts of the data a relay has forwarded or refused.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RelayStats {
+    /// Messages forwarded.
+    pub fwd_count: u64,
+
+    /// Bytes forwarded.
+    pub fwd_bytes: u64,
+
+    /// Messages refused because the sender or receiver
+    /// is not allowed to use the relay.
+    pub denied_count: u64,
+
+    /// Messages refused because the sender was over its quota.
+    pub over_quota_count: u64,
+
+    /// Tokens presented which did not match.
+    pub bad_token_count: u64,
+}
+
+/// Why a relay refused to forward a message or accept a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RelayRefusal {
+    Denied,
+    OverQuota,
+    TooManyClients,
+}
+
+impl std::fmt::Display for RelayRefusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelayRefusal::Denied => f.write_str("relay access denied"),
+            RelayRefusal::OverQuota => f.write_str("relay quota exceeded"),
+            RelayRefusal::TooManyClients => f.write_str("relay has too many authorized clients"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RelayInner {
+    /// Connected clients which have presented the token.
+    authorized: HashSet<Tx2Cert>,
+    /// The start of each client's quota window and the bytes counted in it.
+    windows: HashMap<Tx2Cert, (Instant, u64)>,
+    /// Total bytes forwarded for each client, for the largest clients.
+    client_bytes: HashMap<Tx2Cert, u64>,
+    /// The entries of `client_bytes` ordered by their totals,
+    /// so the smallest can be found without a scan.
+    client_bytes_order: BTreeSet<(u64, Tx2Cert)>,
+    /// Total bytes forwarded for clients dropped from `client_bytes`.
+    other_client_bytes: u64,
+    stats: RelayStats,
+}
+
+/// The runtime state of a relay's access checks and quotas.
+pub(crate) struct Relay {
+    access: RelayAccess,
+    quota_bytes_per_minute: Option<u64>,
+    shaper: Option<RelayShaperCb>,
+    inner: parking_lot::Mutex<RelayInner>,
+}
+
+impl Relay {
+    pub fn new(access: RelayAccess, quota_bytes_per_minute: Option<u64>) -> Self {
+        Self {
+            access,
+            quota_bytes_per_minute,
+            shaper: None,
+            inner: parking_lot::Mutex::new(RelayInner::default()),
+        }
+    }
+
+    /// Wait for bandwidth from this shaper before forwarding.
+    pub fn with_shaper(mut self, shaper: Option<RelayShaperCb>) -> Self {
+        self.shaper = shaper;
+        self
+    }
+
+    /// Wait until there is bandwidth to forward a message of `len` bytes.
+    pub async fn shape(&self, len: usize) {
+        if let Some(shaper) = &self.shaper {
+            shaper(len).await;
+        }
+    }
+
+    /// A client presented a token. It is authorized until [`Relay::forget`]
+    /// is called when its connection closes.
+    pub fn present_token(&self, cert: Tx2Cert, token: &[u8]) -> Result<(), RelayRefusal> {
+        let mut inner = self.inner.lock();
+        match &self.access.token {
+            Some(expected) if bool::from(expected.as_bytes().ct_eq(token)) => {
+                if !inner.authorized.contains(&cert)
+                    && inner.authorized.len() >= MAX_AUTHORIZED_CLIENTS
+                {
+                    return Err(RelayRefusal::TooManyClients);
+                }
+                inner.authorized.insert(cert);
+                Ok(())
+            }
+            _ => {
+                inner.stats.bad_token_count += 1;
+                Err(RelayRefusal::Denied)
+            }
+        }
+    }
+
+    /// The connection to a client closed, so it must present
+    /// the token again on its next connection.
+    pub fn forget(&self, cert: &Tx2Cert) {
+        self.inner.lock().authorized.remove(cert);
+    }
+
+    /// Check a message of `len` bytes may be forwarded from the client
+    /// with cert `src` to the client with cert `dest`, and count it if so.
+    pub fn check_fwd(&self, src: &Tx2Cert, dest: &Tx2Cert, len: usize) -> Result<(), RelayRefusal> {
+        let mut guard = self.inner.lock();
+        // Borrow the fields separately through the guard.
+        let inner = &mut *guard;
+
+        if !self.is_allowed(inner, src) || !self.is_allowed(inner, dest) {
+            inner.stats.denied_count += 1;
+            return Err(RelayRefusal::Denied);
+        }
+
+        let len = len as u64;
+        if let Some(quota) = self.quota_bytes_per_minute {
+            let now = Instant::now();
+            if inner.windows.len() >= MAX_QUOTA_WINDOWS {
+                inner
+                    .windows
+                    .retain(|_, (start, _)| now.duration_since(*start) < QUOTA_WINDOW);
+            }
+            let (start, count) = inner.windows.entry(src.clone()).or_insert((now, 0));
+            if now.duration_since(*start) >= QUOTA_WINDOW {
+                *start = now;
+                *count = 0;
+            }
+            if *count + len > quota {
+                inner.stats.over_quota_count += 1;
+                return Err(RelayRefusal::OverQuota);
+            }
+            *count += len;
+        }
+
+        inner.stats.fwd_count += 1;
+        inner.stats.fwd_bytes += len;
+        let total = match inner.client_bytes.get(src) {
+            Some(total) => {
+                inner.client_bytes_order.remove(&(*total, src.clone()));
+                *total
+            }
+            None => {
+                if inner.client_bytes.len() >= MAX_CLIENT_TOTALS {
+                    let smallest = inner.client_bytes_order.iter().next().cloned();
+                    if let Some((bytes, cert)) = smallest {
+                        inner.client_bytes_order.remove(&(bytes, cert.clone()));
+                        inner.client_bytes.remove(&cert);
+                        inner.other_client_bytes += bytes;
+                    }
+                }
+                0
+            }
+        };
+        inner.client_bytes.insert(src.clone(), total + len);
+        inner.client_bytes_order.insert((total + len, src.clone()));
+        Ok(())
+    }
+
+    /// The stats of this relay.
+    pub fn stats(&self) -> RelayStats {
+        self.inner.lock().stats.clone()
+    }
+
+    /// The stats of this relay, with the bytes forwarded for each client.
+    pub fn debug(&self) -> serde_json::Value {
+        let inner = self.inner.lock();
+        let clients = inner
+            .client_bytes
+            .iter()
+            .map(|(cert, bytes)| (cert.as_str().to_string(), serde_json::json!(bytes)))
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::json!({
+            "access": if self.access.is_open() { "open" } else { "restricted" },
+            "quota_bytes_per_minute": self.quota_bytes_per_minute,
+            "authorized_clients": inner.authorized.len(),
+            "stats": inner.stats,
+            "client_bytes": clients,
+            "other_client_bytes": inner.other_client_bytes,
+        })
+    }
+
+    fn is_allowed(&self, inner: &RelayInner, cert: &Tx2Cert) -> bool {
+        self.access.is_open()
+            || self.access.allow_certs.contains(cert)
+            || inner.authorized.contains(cert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cert(b: u8) -> Tx2Cert {
+        vec![b; 32].into()
+    }
+
+    #[test]
+    fn open_relay_forwards_for_anyone() {
+        let relay = Relay::new(RelayAccess::default(), None);
+        relay.check_fwd(&cert(1), &cert(2), 100).unwrap();
+        relay.check_fwd(&cert(2), &cert(1), 50).unwrap();
+        assert_eq!(relay.stats().fwd_count, 2);
+        assert_eq!(relay.stats().fwd_bytes, 150);
+    }
+
+    #[test]
+    fn restricted_relay_needs_allowed_cert_or_token() {
+        let relay = Relay::new(
+            RelayAccess {
+                allow_certs: [cert(1)].into_iter().collect(),
+                token: Some("secret".to_string()),
+            },
+            None,
+        );
+
+        // Both ends must be allowed.
+        assert_eq!(
+            relay.check_fwd(&cert(1), &cert(2), 10),
+            Err(RelayRefusal::Denied)
+        );
+        assert_eq!(
+            relay.present_token(cert(2), b"guess"),
+            Err(RelayRefusal::Denied)
+        );
+        assert_eq!(
+            relay.check_fwd(&cert(2), &cert(1), 10),
+            Err(RelayRefusal::Denied)
+        );
+
+        relay.present_token(cert(2), b"secret").unwrap();
+        relay.check_fwd(&cert(1), &cert(2), 10).unwrap();
+        relay.check_fwd(&cert(2), &cert(1), 10).unwrap();
+
+        // Once its connection closes the client must present the token again.
+        relay.forget(&cert(2));
+        assert_eq!(
+            relay.check_fwd(&cert(2), &cert(1), 10),
+            Err(RelayRefusal::Denied)
+        );
+
+        let stats = relay.stats();
+        assert_eq!(stats.fwd_count, 2);
+        assert_eq!(stats.denied_count, 3);
+        assert_eq!(stats.bad_token_count, 1);
+    }
+
+    #[test]
+    fn quota_is_per_client() {
+        let relay = Relay::new(RelayAccess::default(), Some(100));
+        relay.check_fwd(&cert(1), &cert(2), 60).unwrap();
+        assert_eq!(
+            relay.check_fwd(&cert(1), &cert(2), 60),
+            Err(RelayRefusal::OverQuota)
+        );
+        relay.check_fwd(&cert(1), &cert(2), 40).unwrap();
+        // Another client has its own quota.
+        relay.check_fwd(&cert(2), &cert(1), 100).unwrap();
+        assert_eq!(relay.stats().over_quota_count, 1);
+    }
+
+    #[test]
+    fn client_totals_are_bounded() {
+        let relay = Relay::new(RelayAccess::default(), None);
+        for i in 0..=MAX_CLIENT_TOTALS {
+            let mut src = vec![0; 32];
+            src[..4].copy_from_slice(&(i as u32).to_le_bytes());
+            let src: Tx2Cert = src.into();
+            let len = if i == 0 { 1 } else { 10 };
+            relay.check_fwd(&src, &cert(0), len).unwrap();
+        }
+        let inner = relay.inner.lock();
+        assert_eq!(inner.client_bytes.len(), MAX_CLIENT_TOTALS);
+        assert_eq!(inner.client_bytes_order.len(), MAX_CLIENT_TOTALS);
+        // The smallest client was dropped, but its bytes are still counted.
+        assert_eq!(inner.other_client_bytes, 1);
+    }
+
+    #[test]
+    fn authorized_clients_are_bounded() {
+        let relay = Relay::new(
+            RelayAccess {
+                token: Some("secret".to_string()),
+                ..Default::default()
+            },
+            None,
+        );
+        let client = |i: usize| -> Tx2Cert {
+            let mut c = vec![0; 32];
+            c[..4].copy_from_slice(&(i as u32).to_le_bytes());
+            c.into()
+        };
+        for i in 0..MAX_AUTHORIZED_CLIENTS {
+            relay.present_token(client(i), b"secret").unwrap();
+        }
+        assert_eq!(
+            relay.present_token(client(MAX_AUTHORIZED_CLIENTS), b"secret"),
+            Err(RelayRefusal::TooManyClients)
+        );
+        // Presenting the token again on a new connection is fine.
+        relay.present_token(client(0), b"secret").unwrap();
+
+        relay.forget(&client(0));
+        relay
+            .present_token(client(MAX_AUTHORIZED_CLIENTS), b"secret")
+            .unwrap();
+    }
+}
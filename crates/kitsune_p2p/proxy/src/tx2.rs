@@ -26,6 +26,10 @@ pub enum ProxyRemoteType {
     /// Request proxying through this specific remote proxy address
     Specific(TxUrl),
 
+    /// Request proxying through the first of these remote proxy addresses,
+    /// failing over to the next if we can't connect to it
+    List(Vec<TxUrl>),
+
     /// Fetch / configure proxy through bootstrap service
     /// or optionally fallback on specific proxy address
     Bootstrap {
@@ -52,6 +56,7 @@ impl ProxyRemoteType {
         match self {
             ProxyRemoteType::NoProxy => None,
             ProxyRemoteType::Specific(proxy_url) => Some(proxy_url.clone()),
+            ProxyRemoteType::List(proxy_urls) => proxy_urls.first().cloned(),
             ProxyRemoteType::Bootstrap {
                 bootstrap_url,
                 fallback_proxy_url,
@@ -68,6 +73,22 @@ impl ProxyRemoteType {
             }
         }
     }
+
+    /// Get the proxy_url (or None) to use after we have failed to connect
+    /// to our proxies this many times. A list of proxies is tried in turn,
+    /// other types always give the same proxy.
+    pub async fn get_proxy_url_after_failures(
+        &self,
+        proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+        failures: usize,
+    ) -> Option<TxUrl> {
+        match self {
+            ProxyRemoteType::List(proxy_urls) if !proxy_urls.is_empty() => {
+                Some(proxy_urls[failures % proxy_urls.len()].clone())
+            }
+            _ => self.get_proxy_url(proxy_from_bootstrap_cb).await,
+        }
+    }
 }
 
 /// Callback function signature for fetching dynamic proxy from bootstrap
@@ -99,6 +120,25 @@ pub struct ProxyConfig {
 
     /// Logic for dynamically fetching a proxy url from the bootstrap service
     pub proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+
+    /// If we are a proxy server, only forward messages
+    /// between clients allowed by this.
+    /// Default: forward for anyone.
+    pub relay_access: RelayAccess,
+
+    /// If we are a proxy server, the most bytes we will forward
+    /// from a single client per minute.
+    /// Default: None = no limit.
+    pub relay_quota_bytes_per_minute: Option<u64>,
+
+    /// The token to present to our remote proxy, if it requires one.
+    /// Default: None.
+    pub proxy_auth_token: Option<String>,
+
+    /// If we are a proxy server, wait on this before forwarding
+    /// each message, to limit the bandwidth spent relaying.
+    /// Default: None = forward immediately.
+    pub relay_shaper: Option<RelayShaperCb>,
 }
 
 impl Default for ProxyConfig {
@@ -108,6 +148,10 @@ impl Default for ProxyConfig {
             allow_proxy_fwd: false,
             client_of_remote_proxy: ProxyRemoteType::default(),
             proxy_from_bootstrap_cb: Arc::new(stub_proxy_from_bootstrap_cb),
+            relay_access: RelayAccess::default(),
+            relay_quota_bytes_per_minute: None,
+            proxy_auth_token: None,
+            relay_shaper: None,
         }
     }
 }
@@ -127,6 +171,7 @@ impl ProxyConfig {
             allow_proxy_fwd,
             client_of_remote_proxy,
             proxy_from_bootstrap_cb,
+            ..
         } = self;
 
         let tuning_params = tuning_params.unwrap_or_default();
@@ -151,6 +196,7 @@ const PROXY_TYPE_BYTES: usize = 1;
 const DIGEST_BYTES: usize = 32;
 
 const PROXY_FWD_MSG: u8 = 0x30;
+const PROXY_AUTH_MSG: u8 = 0x31;
 const PROXY_ROUTE_ERR: u8 = 0xc0;
 
 struct ProxyConHnd {
@@ -300,6 +346,12 @@ struct ProxyEpInner {
     direct_to_final_peer_con_map: HashMap<Uniq, HashMap<Tx2Cert, ConHnd>>,
 
     backoff: Backoff,
+
+    // the sub con to our remote proxy which we last sent our auth token on
+    authed_proxy_con: Option<Uniq>,
+
+    // how many times we have switched to another remote proxy
+    proxy_failovers: u64,
 }
 
 impl ProxyEpInner {
@@ -335,6 +387,7 @@ struct ProxyEpHnd {
     logic_hnd: LogicChanHandle<EpEvent>,
     inner: Share<ProxyEpInner>,
     cur_proxy_url: Share<Option<ProxyUrl>>,
+    relay: Relay,
 }
 
 async fn get_con_hnd(
@@ -366,6 +419,7 @@ impl ProxyEpHnd {
         logic_hnd: LogicChanHandle<EpEvent>,
         backoff: Backoff,
         cur_proxy_url: Share<Option<ProxyUrl>>,
+        relay: Relay,
     ) -> KitsuneResult<Arc<ProxyEpHnd>> {
         let local_cert = sub_ep_hnd.local_cert();
         Ok(Arc::new(ProxyEpHnd {
@@ -376,8 +430,11 @@ impl ProxyEpHnd {
                 digest_to_sub_con_map: HashMap::new(),
                 direct_to_final_peer_con_map: HashMap::new(),
                 backoff,
+                authed_proxy_con: None,
+                proxy_failovers: 0,
             }),
             cur_proxy_url,
+            relay,
         }))
     }
 }
@@ -385,6 +442,11 @@ impl ProxyEpHnd {
 impl AsEpHnd for ProxyEpHnd {
     fn debug(&self) -> serde_json::Value {
         let addr = self.local_addr();
+        let remote_proxy = self
+            .cur_proxy_url
+            .share_ref(|r| Ok(r.as_ref().map(|u| u.as_str().to_string())))
+            .ok()
+            .flatten();
         match self.inner.share_mut(|i, _| {
             let proxy_list = i
                 .digest_to_sub_con_map
@@ -397,6 +459,9 @@ impl AsEpHnd for ProxyEpHnd {
                 "addr": addr?,
                 "proxy_count": i.digest_to_sub_con_map.len(),
                 "proxy_list": proxy_list,
+                "remote_proxy": remote_proxy,
+                "remote_proxy_failovers": i.proxy_failovers,
+                "relay": self.relay.debug(),
                 "sub": self.sub_ep_hnd.debug(),
             }))
         }) {
@@ -657,6 +722,12 @@ async fn incoming_evt_handle(
                         let dest = if !allow_proxy_fwd {
                             tracing::error!("received fwd request on, but proxy fwd is disallowed");
                             Err("proxy fwd disallowed".into())
+                        } else if let Err(refusal) =
+                            hnd.relay
+                                .check_fwd(&sub_con.peer_cert(), &dest_cert, data.len())
+                        {
+                            tracing::debug!(?dest_cert, %refusal, "refusing proxy fwd");
+                            Err(refusal.to_string().into())
                         } else {
                             hnd.inner.share_mut(|i, _| {
                                 Ok(i.digest_to_sub_con_map.get(&dest_cert).cloned())
@@ -664,6 +735,7 @@ async fn incoming_evt_handle(
                         };
                         if let Err(e) = match dest {
                             Ok(Some(d_sub_con)) => {
+                                hnd.relay.shape(data.len()).await;
                                 write_to_sub_con(
                                     tuning_params,
                                     &hnd.inner,
@@ -703,6 +775,18 @@ async fn incoming_evt_handle(
                         }
                     }
                 }
+                PROXY_AUTH_MSG => {
+                    if !allow_proxy_fwd {
+                        return;
+                    }
+                    let peer_cert = sub_con.peer_cert();
+                    if let Err(refusal) = hnd
+                        .relay
+                        .present_token(peer_cert.clone(), &data[PROXY_TYPE_BYTES..])
+                    {
+                        tracing::warn!(?peer_cert, %refusal, "proxy auth token refused");
+                    }
+                }
                 PROXY_ROUTE_ERR => {
                     const SRC_START: usize = PROXY_TYPE_BYTES;
                     const SRC_END: usize = SRC_START + DIGEST_BYTES;
@@ -744,6 +828,7 @@ async fn incoming_evt_handle(
         ConnectionClosed(EpConnectionClosed {
             con, code, reason, ..
         }) => {
+            hnd.relay.forget(&con.peer_cert());
             close_connection_inner(&hnd.inner, logic_hnd, con, code, &reason, cur_proxy_url).await;
         }
         Error(e) => {
@@ -845,6 +930,43 @@ async fn close_connection_inner(
     }
 }
 
+/// Connect to our remote proxy, presenting our auth token
+/// each time we get a new connection to it.
+async fn connect_remote_proxy(
+    hnd: &Arc<ProxyEpHnd>,
+    proxy_url: TxUrl,
+    timeout: KitsuneTimeout,
+    proxy_auth_token: Option<&str>,
+) -> KitsuneResult<()> {
+    let base_url: TxUrl = ProxyUrl::from(proxy_url.as_str()).as_base().as_str().into();
+    hnd.get_connection(proxy_url, timeout).await?;
+
+    let proxy_auth_token = match proxy_auth_token {
+        Some(proxy_auth_token) => proxy_auth_token,
+        None => return Ok(()),
+    };
+    // this is the pooled sub con the proxy connection is using
+    let sub_con = hnd.sub_ep_hnd.get_connection(base_url, timeout).await?;
+    let uniq = sub_con.uniq();
+    let is_new = hnd
+        .inner
+        .share_mut(|i, _| Ok(i.authed_proxy_con.replace(uniq) != Some(uniq)))?;
+    if is_new {
+        let mut data = PoolBuf::new();
+        data.extend_from_slice(proxy_auth_token.as_bytes());
+        data.prepend_from_slice(&[PROXY_AUTH_MSG]);
+        if let Err(err) = sub_con.write(0.into(), data, timeout).await {
+            // make sure we try again
+            let _ = hnd.inner.share_mut(|i, _| {
+                i.authed_proxy_con = None;
+                Ok(())
+            });
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
 struct ProxyEp {
     logic_chan: LogicChan<EpEvent>,
     hnd: EpHnd,
@@ -857,6 +979,8 @@ impl ProxyEp {
         allow_proxy_fwd: bool,
         client_of_remote_proxy: ProxyRemoteType,
         proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+        relay: Relay,
+        proxy_auth_token: Option<String>,
     ) -> KitsuneResult<Ep> {
         // this isn't something that needs to be configurable,
         // because it's entirely dependent on the code written here
@@ -876,6 +1000,7 @@ impl ProxyEp {
             logic_hnd.clone(),
             backoff.clone(),
             cur_proxy_url.clone(),
+            relay,
         )?;
 
         let logic = incoming_evt_logic(
@@ -902,30 +1027,61 @@ impl ProxyEp {
                 });
                 let timeout = tuning_params.implicit_timeout();
                 let hnd = hnd.clone();
+                let proxy_auth_token = proxy_auth_token.clone();
                 tokio::task::spawn(async move {
-                    let _ = hnd.get_connection(proxy_url, timeout).await;
+                    let _ =
+                        connect_remote_proxy(&hnd, proxy_url, timeout, proxy_auth_token.as_deref())
+                            .await;
                 });
             }
 
-            // set up the logic loop that keeps us connected to a proxy
+            // set up the logic loop that keeps us connected to a proxy,
+            // failing over to the next one in our list if it goes away
             let hnd = hnd.clone();
             l_hnd
                 .capture_logic(async move {
+                    let mut failures = 0;
                     loop {
                         if backoff.wait().await.is_err() {
                             break;
                         }
 
                         if let Some(proxy_url) = client_of_remote_proxy
-                            .get_proxy_url(proxy_from_bootstrap_cb.clone())
+                            .get_proxy_url_after_failures(proxy_from_bootstrap_cb.clone(), failures)
                             .await
                         {
                             let _ = cur_proxy_url.share_mut(|r, _| {
-                                *r = Some(ProxyUrl::from(proxy_url.as_str()));
+                                let proxy_url = ProxyUrl::from(proxy_url.as_str());
+                                if matches!(r, Some(r) if r.as_base() != proxy_url.as_base()) {
+                                    tracing::info!(%proxy_url, "switching remote proxy");
+                                }
+                                *r = Some(proxy_url);
                                 Ok(())
                             });
                             let timeout = tuning_params.implicit_timeout();
-                            let _ = hnd.get_connection(proxy_url, timeout).await;
+                            match connect_remote_proxy(
+                                &hnd,
+                                proxy_url,
+                                timeout,
+                                proxy_auth_token.as_deref(),
+                            )
+                            .await
+                            {
+                                // stick with a proxy for as long as it works
+                                Ok(()) => (),
+                                Err(err) => {
+                                    tracing::warn!(?err, "could not connect to remote proxy");
+                                    failures += 1;
+                                    if let ProxyRemoteType::List(_) = &client_of_remote_proxy {
+                                        let _ = hnd.inner.share_mut(|i, _| {
+                                            i.proxy_failovers += 1;
+                                            Ok(())
+                                        });
+                                        // try the next proxy straight away
+                                        backoff.reset();
+                                    }
+                                }
+                            }
                         }
                     }
                 })
@@ -961,11 +1117,19 @@ struct ProxyEpFactory {
     allow_proxy_fwd: bool,
     client_of_remote_proxy: ProxyRemoteType,
     proxy_from_bootstrap_cb: ProxyFromBootstrapCb,
+    relay_access: RelayAccess,
+    relay_quota_bytes_per_minute: Option<u64>,
+    relay_shaper: Option<RelayShaperCb>,
+    proxy_auth_token: Option<String>,
     sub_fact: EpFactory,
 }
 
 impl ProxyEpFactory {
     pub fn new(sub_fact: EpFactory, config: ProxyConfig) -> KitsuneResult<EpFactory> {
+        let relay_access = config.relay_access.clone();
+        let relay_quota_bytes_per_minute = config.relay_quota_bytes_per_minute;
+        let relay_shaper = config.relay_shaper.clone();
+        let proxy_auth_token = config.proxy_auth_token.clone();
         let (tuning_params, allow_proxy_fwd, client_of_remote_proxy, proxy_from_bootstrap_cb) =
             config.split()?;
         let fact: EpFactory = Arc::new(ProxyEpFactory {
@@ -973,6 +1137,10 @@ impl ProxyEpFactory {
             allow_proxy_fwd,
             client_of_remote_proxy,
             proxy_from_bootstrap_cb,
+            relay_access,
+            relay_quota_bytes_per_minute,
+            relay_shaper,
+            proxy_auth_token,
             sub_fact,
         });
         Ok(fact)
@@ -990,6 +1158,9 @@ impl AsEpFactory for ProxyEpFactory {
         let allow_proxy_fwd = self.allow_proxy_fwd;
         let client_of_remote_proxy = self.client_of_remote_proxy.clone();
         let proxy_from_bootstrap_cb = self.proxy_from_bootstrap_cb.clone();
+        let relay = Relay::new(self.relay_access.clone(), self.relay_quota_bytes_per_minute)
+            .with_shaper(self.relay_shaper.clone());
+        let proxy_auth_token = self.proxy_auth_token.clone();
         async move {
             let sub_ep = fut.await?;
             ProxyEp::new(
@@ -998,6 +1169,8 @@ impl AsEpFactory for ProxyEpFactory {
                 allow_proxy_fwd,
                 client_of_remote_proxy,
                 proxy_from_bootstrap_cb,
+                relay,
+                proxy_auth_token,
             )
             .await
         }
@@ -1011,16 +1184,24 @@ mod tests {
     use kitsune_p2p_types::tx2::tx2_pool_promote::*;
 
     async fn build_node(
+        s_done: Option<tokio::sync::oneshot::Sender<()>>,
+        expect_err: bool,
+    ) -> (tokio::task::JoinHandle<KitsuneResult<()>>, TxUrl, EpHnd) {
+        let mut conf = super::ProxyConfig::default();
+        conf.allow_proxy_fwd = true;
+        build_node_with_config(s_done, expect_err, conf).await
+    }
+
+    async fn build_node_with_config(
         mut s_done: Option<tokio::sync::oneshot::Sender<()>>,
         expect_err: bool,
+        conf: super::ProxyConfig,
     ) -> (tokio::task::JoinHandle<KitsuneResult<()>>, TxUrl, EpHnd) {
         let t = KitsuneTimeout::from_millis(5000);
 
         let f = tx2_mem_adapter(MemConfig::default()).await.unwrap();
         let f = tx2_pool_promote(f, Default::default());
 
-        let mut conf = super::ProxyConfig::default();
-        conf.allow_proxy_fwd = true;
         let f = tx2_proxy(f, conf).unwrap();
 
         let mut ep = f.bind("none:".into(), t).await.unwrap();
@@ -1165,4 +1346,97 @@ mod tests {
 
         futures::future::try_join_all(all_tasks).await.unwrap();
     }
+
+    fn client_config(proxy: ProxyRemoteType, proxy_auth_token: Option<&str>) -> super::ProxyConfig {
+        let mut conf = super::ProxyConfig::default();
+        conf.client_of_remote_proxy = proxy;
+        conf.proxy_auth_token = proxy_auth_token.map(|t| t.to_string());
+        conf
+    }
+
+    async fn wait_for(mut f: impl FnMut() -> bool) {
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            while !f() {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for condition");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tx2_proxy_auth_token() {
+        observability::test_run().ok();
+        let t = KitsuneTimeout::from_millis(5000);
+
+        let mut conf = super::ProxyConfig::default();
+        conf.allow_proxy_fwd = true;
+        conf.relay_access.token = Some("secret".to_string());
+        let (p_join, p_addr, p_ep) = build_node_with_config(None, false, conf).await;
+
+        let proxy = ProxyRemoteType::Specific(p_addr.clone());
+        let (t_join, t_addr, t_ep) =
+            build_node_with_config(None, false, client_config(proxy.clone(), Some("secret"))).await;
+        let (s_done, r_done) = tokio::sync::oneshot::channel();
+        let (n_join, _n_addr, n_ep) =
+            build_node_with_config(Some(s_done), false, client_config(proxy, Some("secret"))).await;
+
+        // wait for both clients to present their token
+        wait_for(|| p_ep.debug()["relay"]["authorized_clients"] == 2).await;
+
+        let mut data = PoolBuf::new();
+        data.extend_from_slice(b"hello");
+        n_ep.write(proxify_addr(&p_addr, &t_addr), 0.into(), data, t)
+            .await
+            .unwrap();
+        r_done.await.unwrap();
+
+        // a node without the token can't send through the proxy
+        let (s_done, r_done) = tokio::sync::oneshot::channel();
+        let (m_join, _m_addr, m_ep) = build_node(Some(s_done), true).await;
+        let mut data = PoolBuf::new();
+        data.extend_from_slice(b"hello");
+        m_ep.write(proxify_addr(&p_addr, &t_addr), 0.into(), data, t)
+            .await
+            .unwrap();
+        r_done.await.unwrap();
+
+        let stats = &p_ep.debug()["relay"]["stats"];
+        assert_eq!(stats["fwd_count"], 2);
+        assert_eq!(stats["denied_count"], 1);
+
+        for ep in [n_ep, m_ep, t_ep, p_ep] {
+            ep.close(0, "").await;
+        }
+        for join in [n_join, m_join, t_join, p_join] {
+            join.await.unwrap().unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tx2_proxy_failover() {
+        observability::test_run().ok();
+
+        let (p1_join, p1_addr, p1_ep) = build_node(None, false).await;
+        let (p2_join, p2_addr, p2_ep) = build_node(None, false).await;
+
+        let proxy = ProxyRemoteType::List(vec![p1_addr.clone(), p2_addr.clone()]);
+        let (c_join, _c_addr, c_ep) =
+            build_node_with_config(None, false, client_config(proxy, None)).await;
+
+        let base = |addr: TxUrl| ProxyUrl::from(addr.as_str()).as_base().clone();
+        let c_base = || base(c_ep.local_addr().unwrap());
+        assert_eq!(c_base(), base(p1_addr));
+
+        // once the first proxy goes away we move to the next
+        p1_ep.close(0, "").await;
+        p1_join.await.unwrap().unwrap();
+        wait_for(|| c_base() == base(p2_addr.clone())).await;
+        assert!(c_ep.debug()["remote_proxy_failovers"].as_u64().unwrap() >= 1);
+
+        c_ep.close(0, "").await;
+        p2_ep.close(0, "").await;
+        c_join.await.unwrap().unwrap();
+        p2_join.await.unwrap().unwrap();
+    }
 }
@@ -83,6 +83,13 @@ pub trait Codec: Clone + Sized {
 /// - $type_idx   - zero-index type index in message array (usize)
 /// - $type_ty    - type rust type
 ///
+/// A variant may be followed by an `ext { .. }` block of fields which were
+/// added after the variant was first released. These are encoded as a
+/// second message array after the first, which older decoders never read.
+/// If the second array is missing when decoding, every ext field takes its
+/// `Default` value. Ext fields consume the rest of the reader, so they
+/// should only be used on messages which are framed on their own.
+///
 /// E.G.:
 ///
 /// ```ignore
@@ -100,7 +107,9 @@ macro_rules! write_codec_enum {
     ($(#[doc = $codec_doc:expr])* codec $codec_name:ident {$(
         $(#[doc = $var_doc:expr])* $var_name:ident($var_id:literal) {$(
             $(#[doc = $type_doc:expr])* $type_name:ident.$type_idx:literal: $type_ty:ty,
-        )*},
+        )*} $(ext {$(
+            $(#[doc = $ext_doc:expr])* $ext_name:ident.$ext_idx:literal: $ext_ty:ty,
+        )*})?,
     )*}) => {
         $crate::dependencies::paste::item! {
             $(
@@ -110,6 +119,9 @@ macro_rules! write_codec_enum {
                     $(
                         $(#[doc = $type_doc])* pub [< $type_name:snake >]: $type_ty,
                     )*
+                    $($(
+                        $(#[doc = $ext_doc])* pub [< $ext_name:snake >]: $ext_ty,
+                    )*)?
                 }
 
                 impl $crate::codec::Codec for [< $var_name:camel >] {
@@ -134,13 +146,29 @@ macro_rules! write_codec_enum {
                                 _idx += 1;
                                 assert_eq!(_idx, $type_idx, "{}", MSG);
                             )*
+                            $(
+                                _idx = -1;
+                                $(
+                                    _idx += 1;
+                                    assert_eq!(_idx, $ext_idx, "{}", MSG);
+                                )*
+                            )?
                         }
                         let t: (
                             $(&$type_ty,)*
                         ) = (
                             $(&self.[< $type_name:snake >],)*
                         );
-                        $crate::codec::rmp_encode(w, &t)
+                        $crate::codec::rmp_encode(w, &t)?;
+                        $(
+                            let ext: (
+                                $(&$ext_ty,)*
+                            ) = (
+                                $(&self.[< $ext_name:snake >],)*
+                            );
+                            $crate::codec::rmp_encode(w, &ext)?;
+                        )?
+                        Ok(())
                     }
 
                     fn decode<R>(r: &mut R) -> ::std::io::Result<Self>
@@ -152,10 +180,26 @@ macro_rules! write_codec_enum {
                         ): (
                             $($type_ty,)*
                         ) = $crate::codec::rmp_decode(r)?;
+                        $(
+                            let mut rest = Vec::new();
+                            ::std::io::Read::read_to_end(r, &mut rest)?;
+                            let (
+                                $([< $ext_name:snake >],)*
+                            ): (
+                                $($ext_ty,)*
+                            ) = if rest.is_empty() {
+                                Default::default()
+                            } else {
+                                $crate::codec::rmp_decode(&mut rest.as_slice())?
+                            };
+                        )?
                         Ok([< $var_name:camel >] {
                             $(
                                 [< $type_name:snake >],
                             )*
+                            $($(
+                                [< $ext_name:snake >],
+                            )*)?
                         })
                     }
                 }
@@ -175,11 +219,16 @@ macro_rules! write_codec_enum {
                     /// Variant constructor helper function.
                     pub fn [< $var_name:snake >]($(
                         [< $type_name:snake >]: $type_ty,
-                    )*) -> Self {
+                    )* $($(
+                        [< $ext_name:snake >]: $ext_ty,
+                    )*)?) -> Self {
                         Self::[< $var_name:camel >]([< $var_name:camel >] {
                             $(
                                 [< $type_name:snake >],
                             )*
+                            $($(
+                                [< $ext_name:snake >],
+                            )*)?
                         })
                     }
                 )*
@@ -266,4 +315,49 @@ mod tests {
         let res = Bob::decode_ref(&data).unwrap().1;
         assert_eq!(bob, res);
     }
+
+    /// The same codec after a field was added to `BobOne`.
+    mod new {
+        use super::*;
+
+        write_codec_enum! {
+            /// Codec with fields added to an existing variant
+            codec NewBob {
+                /// variant
+                BobOne(0x42) {
+                    /// type 1
+                    yay.0: bool,
+
+                    /// type 2
+                    age.1: u32,
+
+                    /// type 3
+                    sub.2: Arc<Sub>,
+                } ext {
+                    /// ext type 1
+                    name.0: Option<String>,
+                },
+                /// nother variant
+                BobTwo(0x43) {
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_ext_fields_are_compatible() {
+        let sub = Arc::new(Sub(b"test".to_vec()));
+        let new_bob = new::NewBob::bob_one(true, 42, sub.clone(), Some("bob".to_string()));
+        let data = new_bob.encode_vec().unwrap();
+        assert_eq!(new_bob, new::NewBob::decode_ref(&data).unwrap().1);
+
+        // An old decoder ignores the ext fields.
+        let (_, old_bob) = Bob::decode_ref(&data).unwrap();
+        assert_eq!(Bob::bob_one(true, 42, sub.clone()), old_bob);
+
+        // A new decoder defaults the ext fields of an old message.
+        let data = old_bob.encode_vec().unwrap();
+        let (_, new_bob) = new::NewBob::decode_ref(&data).unwrap();
+        assert_eq!(new::NewBob::bob_one(true, 42, sub, None), new_bob);
+    }
 }
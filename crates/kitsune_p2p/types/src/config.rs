@@ -113,6 +113,46 @@ pub mod tuning_params_struct {
         /// seconds to "refill"). [Default: 100.0]
         gossip_burst_ratio: f64 = 100.0,
 
+        /// Rate-limit all outbound interactive, publish and gossip
+        /// traffic in each space to this count megabits per second,
+        /// sharing it between the traffic classes by the weights below,
+        /// unless the host sets other weights for the space.
+        /// If zero, traffic is only counted. [Default: 0.0]
+        traffic_outbound_target_mbps: f64 = 0.0,
+
+        /// Share of the space outbound target for rpc calls and
+        /// signals. [Default: 8]
+        traffic_weight_interactive: u32 = 8,
+
+        /// Share of the space outbound target for publishing and
+        /// responding to op fetches. [Default: 4]
+        traffic_weight_publish: u32 = 4,
+
+        /// Share of the space outbound target for recent gossip.
+        /// [Default: 2]
+        traffic_weight_recent_gossip: u32 = 2,
+
+        /// Share of the space outbound target for historic gossip.
+        /// [Default: 1]
+        traffic_weight_historic_gossip: u32 = 1,
+
+        /// Rate-limit the messages this node forwards for other nodes,
+        /// when acting as a proxy, to this count megabits per second.
+        /// If zero, relayed traffic is only counted. [Default: 0.0]
+        traffic_relay_target_mbps: f64 = 0.0,
+
+        /// How many times to ask a single source for an op before
+        /// giving up on that source. This is the limit for a source with
+        /// a neutral peer score: better scoring sources get up to twice as
+        /// many attempts, and worse ones as few as one.
+        /// 0 means no limit. [Default: 0]
+        fetch_pool_source_retry_limit: u32 = 0,
+
+        /// How often to save the fetch pool of each space to the host,
+        /// so that pending fetches survive a restart.
+        /// 0 disables persistence. [Default: 0]
+        fetch_pool_persist_interval_ms: u32 = 0,
+
         /// How long should we hold off talking to a peer
         /// we've previously spoken successfully to.
         /// [Default: 1 minute]
@@ -154,6 +194,11 @@ pub mod tuning_params_struct {
         /// what you are doing.
         gossip_single_storage_arc_per_space: bool = false,
 
+        /// Offer to reconcile recent op hashes with invertible bloom
+        /// lookup tables rather than bloom filters. [Default: false]
+        /// Peers which don't support this keep using bloom filters.
+        gossip_iblt_reconciliation: bool = false,
+
         /// Default timeout for rpc single. [Default: 60s]
         default_rpc_single_timeout_ms: u32 = 1000 * 60,
 
@@ -226,6 +271,20 @@ pub mod tuning_params_struct {
         /// [Default: 20]
         tx5_max_conn_init_s: u32 = 20,
 
+        /// The multicast group and port used for peer discovery
+        /// when the network type is `QuicMdns`.
+        /// [Default: "224.0.0.251:5353"]
+        mdns_multicast_addr: String = "224.0.0.251:5353".to_string(),
+
+        /// The local interface address used for mdns peer discovery.
+        /// Set this to "127.0.0.1" to only discover peers on this host.
+        /// [Default: "0.0.0.0"]
+        mdns_interface: String = "0.0.0.0".to_string(),
+
+        /// How often to query the local network for peers over mdns.
+        /// [Default: 5 seconds]
+        mdns_query_interval_ms: u32 = 1000 * 5,
+
         /// if you would like to be able to use an external tool
         /// to debug the QUIC messages sent and received by kitsune
         /// you'll need the decryption keys.
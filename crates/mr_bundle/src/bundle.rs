@@ -1,8 +1,10 @@
 use crate::{
     error::{BundleError, MrBundleResult},
+    fetch::{FetchConfig, ResourceHash},
     location::Location,
     manifest::Manifest,
     resource::ResourceBytes,
+    signature::{BundleSignature, PublisherKey, PublisherSecret, SignedContent},
 };
 use holochain_util::ffs;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -46,6 +48,15 @@ where
     //        struct into two versions for each case.
     #[serde(skip)]
     root_dir: Option<PathBuf>,
+
+    /// How to fetch resources located by URL. If not set, the config is read
+    /// from the environment, see [`FetchConfig::from_env`].
+    #[serde(skip)]
+    fetch_config: Option<FetchConfig>,
+
+    /// The publisher's signature over the manifest and resources, if signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<BundleSignature>,
 }
 
 impl<M> Bundle<M>
@@ -104,18 +115,84 @@ where
             manifest,
             resources,
             root_dir,
+            fetch_config: None,
+            signature: None,
         })
     }
 
+    /// Use the given config when fetching resources located by URL
+    pub fn with_fetch_config(mut self, fetch_config: FetchConfig) -> Self {
+        self.fetch_config = Some(fetch_config);
+        self
+    }
+
+    /// The config used when fetching resources located by URL, if set
+    pub fn fetch_config(&self) -> Option<&FetchConfig> {
+        self.fetch_config.as_ref()
+    }
+
     /// Accessor for the Manifest
     pub fn manifest(&self) -> &M {
         &self.manifest
     }
 
+    /// Sign the bundle as its publisher, replacing any existing signature
+    pub fn signed(mut self, secret: &PublisherSecret) -> MrBundleResult<Self> {
+        self.signature = Some(secret.sign(&self.signed_content())?);
+        Ok(self)
+    }
+
+    /// Accessor for the publisher's signature, if the bundle is signed
+    pub fn signature(&self) -> Option<&BundleSignature> {
+        self.signature.as_ref()
+    }
+
+    /// Locations outside the bundle whose content is not pinned by a hash in
+    /// the manifest, so is not covered by the publisher's signature
+    pub fn unpinned_locations(&self) -> Vec<Location> {
+        self.manifest
+            .locations()
+            .into_iter()
+            .filter(|location| {
+                !matches!(location, Location::Bundled(_))
+                    && self.manifest.resource_hash(location).is_none()
+            })
+            .collect()
+    }
+
+    /// Check the publisher's signature, returning the publisher's key if the
+    /// bundle is signed, or None if it is not.
+    /// A bundle whose signature doesn't match its contents is an error.
+    pub fn verify_signature(&self) -> MrBundleResult<Option<PublisherKey>> {
+        match &self.signature {
+            Some(signature) => {
+                signature.verify(&self.signed_content())?;
+                Ok(Some(signature.publisher))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn signed_content(&self) -> SignedContent<'_, M> {
+        SignedContent {
+            manifest: &self.manifest,
+            resources: self
+                .resources
+                .iter()
+                .map(|(path, bytes)| (path, ResourceHash::of(bytes.inner())))
+                .collect(),
+        }
+    }
+
     /// Return a new Bundle with an updated manifest, subject to the same
     /// validation constraints as creating a new Bundle from scratch.
+    ///
+    /// The new Bundle is unsigned, since its contents have changed.
     pub fn update_manifest(self, manifest: M) -> MrBundleResult<Self> {
-        Self::from_parts(manifest, self.resources, self.root_dir)
+        let fetch_config = self.fetch_config;
+        let mut bundle = Self::from_parts(manifest, self.resources, self.root_dir)?;
+        bundle.fetch_config = fetch_config;
+        Ok(bundle)
     }
 
     /// Load a Bundle into memory from a file
@@ -129,7 +206,8 @@ where
     }
 
     /// Retrieve the bytes for a resource at a Location, downloading it if
-    /// necessary
+    /// necessary. Resources located by URL or local path are checked against
+    /// the hash pinned by the manifest, if any.
     pub async fn resolve(&self, location: &Location) -> MrBundleResult<Cow<'_, ResourceBytes>> {
         let bytes = match &location.normalize(self.root_dir.as_ref())? {
             Location::Bundled(path) => Cow::Borrowed(
@@ -137,8 +215,24 @@ where
                     .get(path)
                     .ok_or_else(|| BundleError::BundledResourceMissing(path.clone()))?,
             ),
-            Location::Path(path) => Cow::Owned(crate::location::resolve_local(path).await?),
-            Location::Url(url) => Cow::Owned(crate::location::resolve_remote(url).await?),
+            Location::Path(path) => {
+                let bytes = crate::location::resolve_local(path).await?;
+                let expected = self.manifest.resource_hash(location);
+                crate::fetch::verify(
+                    &path.display().to_string(),
+                    expected.as_ref(),
+                    bytes.inner(),
+                )?;
+                Cow::Owned(bytes)
+            }
+            Location::Url(url) => {
+                let fetch_config = self
+                    .fetch_config
+                    .clone()
+                    .unwrap_or_else(FetchConfig::from_env);
+                let expected = self.manifest.resource_hash(location);
+                Cow::Owned(crate::fetch::resolve_url(url, expected.as_ref(), &fetch_config).await?)
+            }
         };
         Ok(bytes)
     }
@@ -269,4 +363,37 @@ mod tests {
             Err(MrBundleError::BundleError(BundleError::BundledPathNotInManifest(path))) if path == PathBuf::from("3.thing")
         );
     }
+
+    #[test]
+    fn bundle_signature() {
+        let manifest = TestManifest(vec![
+            Location::Bundled("1.thing".into()),
+            Location::Bundled("2.thing".into()),
+        ]);
+        let bundle = Bundle::new_unchecked(
+            manifest.clone(),
+            vec![
+                ("1.thing".into(), vec![1].into()),
+                ("2.thing".into(), vec![2].into()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(bundle.verify_signature().unwrap(), None);
+
+        let secret = PublisherSecret::generate().unwrap();
+        let signed = bundle.signed(&secret).unwrap();
+        let decoded = Bundle::<TestManifest>::decode(&signed.encode().unwrap()).unwrap();
+        assert_eq!(
+            decoded.verify_signature().unwrap(),
+            Some(secret.public_key())
+        );
+
+        // Swapping out a resource invalidates the signature
+        let mut tampered = decoded;
+        tampered.resources.insert("2.thing".into(), vec![3].into());
+        matches::assert_matches!(
+            tampered.verify_signature(),
+            Err(MrBundleError::BundleError(BundleError::InvalidSignature(key))) if key == secret.public_key()
+        );
+    }
 }
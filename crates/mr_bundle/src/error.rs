@@ -52,6 +52,34 @@ pub enum BundleError {
         "Cannot use relative paths for local locations. The following local path is relative: {0}"
     )]
     RelativeLocalPath(std::path::PathBuf),
+
+    #[error("Not a valid resource URL: {0}")]
+    BadUrl(String),
+
+    #[error("Not a valid SHA-256 resource hash: {0}")]
+    BadResourceHash(String),
+
+    #[error("The resource at '{url}' has hash {actual}, but the manifest expects {expected}")]
+    ResourceHashMismatch {
+        url: String,
+        expected: crate::ResourceHash,
+        actual: crate::ResourceHash,
+    },
+
+    #[error("Cannot fetch '{0}' in offline mode, and it is not in the resource cache")]
+    Offline(String),
+
+    #[error("Cannot fetch '{0}' in offline mode, because it is not pinned by a sha256 hash")]
+    UnpinnedOffline(String),
+
+    #[error("Not a valid publisher key: {0}")]
+    BadPublisherKey(String),
+
+    #[error("Not a valid publisher secret key file")]
+    BadPublisherSecret,
+
+    #[error("The bundle's signature by publisher {0} does not match its contents")]
+    InvalidSignature(crate::PublisherKey),
 }
 pub type BundleResult<T> = Result<T, BundleError>;
 
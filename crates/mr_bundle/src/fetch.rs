@@ -0,0 +1,294 @@
+//! Fetching of resources located by URL.
+//!
+//! A URL location may be pinned to the [`ResourceHash`] of the resource it
+//! points to. Pinned resources are verified before use and stored in a local
+//! cache directory named by their hash, so that once fetched they can be
+//! resolved again without the network, e.g. in offline mode.
+
+use crate::{
+    error::{BundleError, MrBundleResult},
+    ResourceBytes,
+};
+use holochain_util::ffs;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// Environment variable naming the directory in which fetched resources are cached
+pub const CACHE_DIR_ENV: &str = "HC_BUNDLE_CACHE_DIR";
+
+/// Environment variable which, when set to "1" or "true", prevents resources
+/// being fetched over the network
+pub const OFFLINE_ENV: &str = "HC_BUNDLE_OFFLINE";
+
+/// The SHA-256 hash of a resource's bytes.
+///
+/// This is written in manifests as a hex string, as produced by `sha256sum`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ResourceHash([u8; 32]);
+
+impl ResourceHash {
+    /// Compute the hash of some bytes
+    pub fn of(bytes: &[u8]) -> Self {
+        Self(Sha256::digest(bytes).into())
+    }
+}
+
+impl std::fmt::Display for ResourceHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl std::str::FromStr for ResourceHash {
+    type Err = BundleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(s, &mut bytes)
+            .map_err(|_| BundleError::BadResourceHash(s.to_string()))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl TryFrom<String> for ResourceHash {
+    type Error = BundleError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ResourceHash> for String {
+    fn from(hash: ResourceHash) -> Self {
+        hash.to_string()
+    }
+}
+
+/// How resources located by URL are fetched
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct FetchConfig {
+    /// Where to cache pinned resources once they have been fetched and verified.
+    /// Resources are not cached if this is not set.
+    pub cache_dir: Option<PathBuf>,
+
+    /// Never use the network. Only `file://` URLs and pinned resources which
+    /// are already cached can be resolved, so any other URL which is not
+    /// pinned is an error.
+    pub offline: bool,
+}
+
+impl FetchConfig {
+    /// Read the config from the [`CACHE_DIR_ENV`] and [`OFFLINE_ENV`]
+    /// environment variables.
+    pub fn from_env() -> Self {
+        Self {
+            cache_dir: std::env::var_os(CACHE_DIR_ENV).map(PathBuf::from),
+            offline: matches!(std::env::var(OFFLINE_ENV).as_deref(), Ok("1") | Ok("true")),
+        }
+    }
+
+    fn cache_path(&self, hash: &ResourceHash) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(hash.to_string()))
+    }
+}
+
+/// Get the bytes at a URL, checking them against the `expected` hash if given.
+///
+/// `file://` URLs are read from the local filesystem. Other URLs are fetched
+/// over the network unless the resource is pinned and already cached.
+pub async fn resolve_url(
+    url: &str,
+    expected: Option<&ResourceHash>,
+    config: &FetchConfig,
+) -> MrBundleResult<ResourceBytes> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| BundleError::BadUrl(url.to_string()))?;
+
+    if parsed.scheme() == "file" {
+        let path = parsed
+            .to_file_path()
+            .map_err(|_| BundleError::BadUrl(url.to_string()))?;
+        let bytes = ffs::read(&path).await?;
+        verify(url, expected, &bytes)?;
+        return Ok(bytes.into());
+    }
+
+    let cache_path = expected.and_then(|hash| config.cache_path(hash));
+    if let Some(path) = &cache_path {
+        if let Ok(bytes) = ffs::read(path).await {
+            // A cached file which no longer matches its name is refetched.
+            if expected == Some(&ResourceHash::of(&bytes)) {
+                return Ok(bytes.into());
+            }
+        }
+    }
+
+    if config.offline {
+        return Err(match expected {
+            Some(_) => BundleError::Offline(url.to_string()),
+            None => BundleError::UnpinnedOffline(url.to_string()),
+        }
+        .into());
+    }
+
+    let bytes = reqwest::get(parsed)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec();
+    verify(url, expected, &bytes)?;
+
+    if let Some(path) = cache_path {
+        if let Some(dir) = path.parent() {
+            ffs::create_dir_all(dir).await?;
+        }
+        ffs::write(&path, &bytes).await?;
+    }
+
+    Ok(bytes.into())
+}
+
+pub(crate) fn verify(
+    url: &str,
+    expected: Option<&ResourceHash>,
+    bytes: &[u8],
+) -> MrBundleResult<()> {
+    if let Some(expected) = expected {
+        let actual = ResourceHash::of(bytes);
+        if &actual != expected {
+            return Err(BundleError::ResourceHashMismatch {
+                url: url.to_string(),
+                expected: expected.clone(),
+                actual,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MrBundleError;
+    use matches::assert_matches;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve `body` over HTTP on localhost, returning the URL and a count of
+    /// the requests made.
+    async fn serve(body: &'static [u8]) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/thing.wasm", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let count = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                count.fetch_add(1, Ordering::SeqCst);
+                let mut buf = [0; 1024];
+                let _ = socket.read(&mut buf).await;
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(body).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn resource_hash_roundtrip() {
+        let hash = ResourceHash::of(b"abc");
+        assert_eq!(
+            hash.to_string(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(hash.to_string().parse::<ResourceHash>().unwrap(), hash);
+        assert_matches!(
+            "not hex".parse::<ResourceHash>(),
+            Err(BundleError::BadResourceHash(_))
+        );
+    }
+
+    #[tokio::test]
+    async fn file_url_is_verified() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("thing.wasm");
+        std::fs::write(&path, b"thing").unwrap();
+        let url = format!("file://{}", path.display());
+        let config = FetchConfig {
+            offline: true,
+            ..Default::default()
+        };
+
+        let bytes = resolve_url(&url, Some(&ResourceHash::of(b"thing")), &config)
+            .await
+            .unwrap();
+        assert_eq!(bytes.inner(), b"thing");
+
+        assert_matches!(
+            resolve_url(&url, Some(&ResourceHash::of(b"other")), &config).await,
+            Err(MrBundleError::BundleError(
+                BundleError::ResourceHashMismatch { .. }
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_resource_is_cached_for_offline_use() {
+        let (url, requests) = serve(b"thing").await;
+        let cache = tempfile::tempdir().unwrap();
+        let hash = ResourceHash::of(b"thing");
+        let mut config = FetchConfig {
+            cache_dir: Some(cache.path().to_owned()),
+            offline: false,
+        };
+
+        let bytes = resolve_url(&url, Some(&hash), &config).await.unwrap();
+        assert_eq!(bytes.inner(), b"thing");
+        assert!(cache.path().join(hash.to_string()).exists());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        config.offline = true;
+        let bytes = resolve_url(&url, Some(&hash), &config).await.unwrap();
+        assert_eq!(bytes.inner(), b"thing");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Unpinned resources can't be looked up in the cache
+        assert_matches!(
+            resolve_url(&url, None, &config).await,
+            Err(MrBundleError::BundleError(BundleError::UnpinnedOffline(_)))
+        );
+    }
+
+    #[tokio::test]
+    async fn mismatched_resource_is_not_cached() {
+        let (url, _) = serve(b"tampered").await;
+        let cache = tempfile::tempdir().unwrap();
+        let hash = ResourceHash::of(b"thing");
+        let config = FetchConfig {
+            cache_dir: Some(cache.path().to_owned()),
+            offline: false,
+        };
+
+        assert_matches!(
+            resolve_url(&url, Some(&hash), &config).await,
+            Err(MrBundleError::BundleError(
+                BundleError::ResourceHashMismatch { .. }
+            ))
+        );
+        assert!(!cache.path().join(hash.to_string()).exists());
+    }
+}
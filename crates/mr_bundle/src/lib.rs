@@ -17,9 +17,11 @@
 mod bundle;
 mod encoding;
 pub mod error;
+pub mod fetch;
 mod location;
 mod manifest;
 mod resource;
+pub mod signature;
 pub(crate) mod util;
 
 #[cfg(feature = "packing")]
@@ -27,6 +29,8 @@ mod packing;
 
 pub use bundle::{Bundle, RawBundle};
 pub use encoding::{decode, encode};
+pub use fetch::{FetchConfig, ResourceHash};
 pub use location::Location;
 pub use manifest::Manifest;
 pub use resource::ResourceBytes;
+pub use signature::{BundleSignature, PublisherKey, PublisherSecret};
@@ -21,7 +21,8 @@ pub enum Location {
     /// Get file from local filesystem (not bundled)
     Path(PathBuf),
 
-    /// Get file from URL
+    /// Get file from URL. The manifest may pin the expected
+    /// [`ResourceHash`](crate::ResourceHash) of the file.
     Url(String),
 }
 
@@ -48,16 +49,6 @@ pub(crate) async fn resolve_local(path: &Path) -> MrBundleResult<ResourceBytes>
     Ok(ffs::read(path).await?.into())
 }
 
-pub(crate) async fn resolve_remote(url: &str) -> MrBundleResult<ResourceBytes> {
-    Ok(reqwest::get(url)
-        .await?
-        .bytes()
-        .await?
-        .into_iter()
-        .collect::<Vec<_>>()
-        .into())
-}
-
 #[cfg(test)]
 mod tests {
 
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::location::Location;
+use crate::{fetch::ResourceHash, location::Location};
 
 /// A Manifest describes the resources in a [`Bundle`](crate::Bundle) and how
 /// to pack and unpack them.
@@ -18,6 +18,13 @@ pub trait Manifest:
     /// correctly implemented to enable resource resolution.
     fn locations(&self) -> Vec<Location>;
 
+    /// The hash which the resource at this Location is expected to have, if
+    /// the manifest pins one. This is checked for resources located by URL
+    /// or local path.
+    fn resource_hash(&self, _location: &Location) -> Option<ResourceHash> {
+        None
+    }
+
     /// When unpacking the bundle into a directory structure, this becomes
     /// the relative path of the manifest file.
     #[cfg(feature = "packing")]
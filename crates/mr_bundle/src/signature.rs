@@ -0,0 +1,167 @@
+//! Publisher signatures over bundles.
+//!
+//! A bundle may carry a detached ed25519 signature by its publisher over the
+//! manifest and the [`ResourceHash`] of each bundled resource. Resources
+//! located by URL are covered through the hashes pinned in the manifest.
+
+use crate::{
+    error::{BundleError, MrBundleResult},
+    fetch::ResourceHash,
+};
+use ed25519_dalek::{Signer, Verifier};
+use sha2::{Digest, Sha256};
+
+/// The public key of a bundle publisher, written as a hex string
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PublisherKey([u8; 32]);
+
+impl std::fmt::Display for PublisherKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl std::str::FromStr for PublisherKey {
+    type Err = BundleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(s.trim(), &mut bytes)
+            .map_err(|_| BundleError::BadPublisherKey(s.to_string()))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl TryFrom<String> for PublisherKey {
+    type Error = BundleError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PublisherKey> for String {
+    fn from(key: PublisherKey) -> Self {
+        key.to_string()
+    }
+}
+
+/// The secret key with which a publisher signs bundles.
+///
+/// This is stored as the hex encoding of the 32 byte ed25519 seed.
+pub struct PublisherSecret(ed25519_dalek::Keypair);
+
+impl PublisherSecret {
+    /// Generate a new random key
+    pub fn generate() -> MrBundleResult<Self> {
+        let mut seed = [0; 32];
+        getrandom::getrandom(&mut seed)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        Ok(Self::from_seed(&seed))
+    }
+
+    fn from_seed(seed: &[u8; 32]) -> Self {
+        let secret = ed25519_dalek::SecretKey::from_bytes(seed)
+            .expect("any 32 bytes are a valid ed25519 seed");
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        Self(ed25519_dalek::Keypair { secret, public })
+    }
+
+    /// The public key corresponding to this secret
+    pub fn public_key(&self) -> PublisherKey {
+        PublisherKey(self.0.public.to_bytes())
+    }
+
+    /// The hex encoding of the seed, for writing to a key file
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0.secret.as_bytes())
+    }
+
+    /// Parse the contents of a key file written with [`to_hex`](Self::to_hex)
+    pub fn from_hex(s: &str) -> MrBundleResult<Self> {
+        let mut seed = [0; 32];
+        hex::decode_to_slice(s.trim(), &mut seed).map_err(|_| BundleError::BadPublisherSecret)?;
+        Ok(Self::from_seed(&seed))
+    }
+
+    pub(crate) fn sign<M: serde::Serialize>(
+        &self,
+        content: &SignedContent<M>,
+    ) -> MrBundleResult<BundleSignature> {
+        Ok(BundleSignature {
+            publisher: self.public_key(),
+            signature: self.0.sign(&content.digest()?).to_bytes().to_vec(),
+        })
+    }
+}
+
+impl std::fmt::Debug for PublisherSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PublisherSecret")
+            .field(&self.public_key())
+            .finish()
+    }
+}
+
+/// A publisher's detached signature over a bundle
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct BundleSignature {
+    /// Who signed the bundle
+    pub publisher: PublisherKey,
+    /// The ed25519 signature
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+impl BundleSignature {
+    pub(crate) fn verify<M: serde::Serialize>(
+        &self,
+        content: &SignedContent<M>,
+    ) -> MrBundleResult<()> {
+        let invalid = || BundleError::InvalidSignature(self.publisher);
+        let public =
+            ed25519_dalek::PublicKey::from_bytes(&self.publisher.0).map_err(|_| invalid())?;
+        let signature =
+            ed25519_dalek::Signature::try_from(self.signature.as_slice()).map_err(|_| invalid())?;
+        public
+            .verify(&content.digest()?, &signature)
+            .map_err(|_| invalid())?;
+        Ok(())
+    }
+}
+
+/// What a publisher signs: the manifest, and the hash of each bundled resource
+#[derive(serde::Serialize)]
+pub(crate) struct SignedContent<'a, M: serde::Serialize> {
+    pub manifest: &'a M,
+    pub resources: std::collections::BTreeMap<&'a std::path::PathBuf, ResourceHash>,
+}
+
+impl<'a, M: serde::Serialize> SignedContent<'a, M> {
+    fn digest(&self) -> MrBundleResult<Vec<u8>> {
+        Ok(Sha256::digest(rmp_serde::to_vec_named(self)?).to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publisher_secret_roundtrip() {
+        let secret = PublisherSecret::generate().unwrap();
+        let restored = PublisherSecret::from_hex(&secret.to_hex()).unwrap();
+        assert_eq!(secret.public_key(), restored.public_key());
+        assert_eq!(
+            secret
+                .public_key()
+                .to_string()
+                .parse::<PublisherKey>()
+                .unwrap(),
+            secret.public_key()
+        );
+    }
+}
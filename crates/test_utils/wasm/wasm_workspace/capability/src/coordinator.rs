@@ -114,3 +114,23 @@ fn send_assigned_cap_claim(agent: AgentPubKey) -> ExternResult<()> {
     )?;
     Ok(())
 }
+
+#[hdk_extern]
+fn list_cap_grants(query: CapGrantQuery) -> ExternResult<Vec<CapGrantInfo>> {
+    capability_grants(query)
+}
+
+#[hdk_extern]
+fn list_cap_claims(query: CapClaimQuery) -> ExternResult<Vec<CapClaim>> {
+    capability_claims(query)
+}
+
+#[hdk_extern]
+fn cap_info(_: ()) -> ExternResult<CapabilityInfo> {
+    capability_info()
+}
+
+#[hdk_extern]
+fn revoke_grant(secret: CapSecret) -> ExternResult<Option<ActionHash>> {
+    revoke_cap_grant(secret)
+}
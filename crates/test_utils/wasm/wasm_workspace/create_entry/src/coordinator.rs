@@ -69,6 +69,22 @@ fn get_post(hash: ActionHash) -> ExternResult<Option<Record>> {
     get(hash, GetOptions::content())
 }
 
+#[hdk_extern]
+fn get_entry_meta(_: ()) -> ExternResult<Option<MetadataSet>> {
+    get_meta(
+        hash_entry(&post())?,
+        MetadataRequest {
+            entry_dht_status: true,
+            ..Default::default()
+        },
+    )
+}
+
+#[hdk_extern]
+fn get_post_meta(hash: ActionHash) -> ExternResult<Option<MetadataSet>> {
+    get_meta(hash, MetadataRequest::default())
+}
+
 #[hdk_extern]
 fn create_msg(_: ()) -> ExternResult<ActionHash> {
     use EntryTypes::*;
@@ -88,6 +88,22 @@ fn who_are_they_role(role_name: RoleName) -> ExternResult<AgentInfo> {
     }
 }
 
+#[hdk_extern]
+fn who_are_they_dependency(dependency_name: String) -> ExternResult<AgentInfo> {
+    let zome_call_response: ZomeCallResponse = call(
+        CallTargetCell::Dependency(dependency_name),
+        zome_info()?.name,
+        "whoami".to_string().into(),
+        None,
+        &(),
+    )?;
+    match zome_call_response {
+        ZomeCallResponse::Ok(v) => Ok(v.decode().map_err(|e| wasm_error!(e))?),
+        // This should be handled in real code.
+        _ => unreachable!(),
+    }
+}
+
 /// Call the create entry zome from this zome.
 /// The cell id must point to a cell which includes
 /// the "create_entry" zome.
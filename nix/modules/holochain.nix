@@ -127,7 +127,7 @@
         (holochainTestsNextestArgs // {
           pname = "holochain-nextest-tx5";
           cargoExtraArgs = holochainTestsNextestArgs.cargoExtraArgs + '' \
-            --features tx5 \
+            --features tx5_tests \
           '';
 
           nativeBuildInputs = holochainTestsNextestArgs.nativeBuildInputs ++ [